@@ -0,0 +1,479 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction};
+use sqlx::types::Json as SqlJson;
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+use shared::geo::distance_metres;
+use shared::models::{
+    AgentAssignment,
+    CreateAgentAssignment,
+    AssignmentListQuery,
+    EndAssignmentQuery,
+    FarmVisit,
+    CreateFarmVisit,
+    VisitFilter,
+    CoverageQuery,
+    AgentCoverage,
+    OverdueQuery,
+    OverdueFarm
+};
+use tracing::error;
+
+const MAX_PHOTOS: usize = 10;
+const MAX_INTERVAL_DAYS: i64 = 3650;
+// Allowance for device clocks running ahead of the server
+const CLOCK_SKEW_MINUTES: i64 = 5;
+
+const ASSIGNMENT_QUERY: &str = r#"
+    SELECT a.id, a."createdAt", a."agentId", a."farmerId", u."firstName", u."lastName", a."startedOn", a."endedOn"
+    FROM "AgentAssignment" a
+    JOIN "User" u ON u.id = a."farmerId"
+"#;
+
+#[derive(Debug, Clone)]
+pub struct FieldVisitConfig {
+    // Check-ins further than this from the edge of the farm are not verified
+    pub checkin_radius_metres: f64,
+    // Farms not visited for this long are overdue
+    pub visit_interval_days: i64,
+}
+
+impl Default for FieldVisitConfig {
+    fn default() -> Self {
+        FieldVisitConfig {
+            checkin_radius_metres: 250.0,
+            visit_interval_days: 30,
+        }
+    }
+}
+
+impl FieldVisitConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|value| value.parse().ok())
+        }
+        let default = FieldVisitConfig::default();
+        FieldVisitConfig {
+            checkin_radius_metres: var("FIELD_VISIT_CHECKIN_RADIUS_M").unwrap_or(default.checkin_radius_metres),
+            visit_interval_days: var("FIELD_VISIT_INTERVAL_DAYS").unwrap_or(default.visit_interval_days),
+        }
+    }
+
+    /**
+     * Farms only have a single recorded point, so the check-in radius is widened
+     * by the radius of a circular farm of the same area
+     **/
+    pub fn allowed_distance_metres(&self, acreage_hectares: f64) -> f64 {
+        self.checkin_radius_metres + (acreage_hectares.max(0.0) * 10_000.0 / std::f64::consts::PI).sqrt()
+    }
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Field agent query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    let config = web::Data::new(FieldVisitConfig::from_env());
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/visits")
+                    .app_data(config.clone())
+                    .route("", web::get().to(get_farm_visits))
+                    .route("", web::post().to(create_visit))
+    );
+    cfg.service(web::scope("/v0.1/agents")
+                    .app_data(config)
+                    .route("/coverage", web::get().to(get_coverage))
+                    .route("/overdue", web::get().to(get_overdue_farms))
+                    .route("/{agent_id}/farmers", web::get().to(get_all_assignments))
+                    .route("/{agent_id}/farmers", web::post().to(create_assignment))
+                    .route("/{agent_id}/farmers/{farmer_id}", web::delete().to(end_assignment))
+                    .route("/{agent_id}/visits", web::get().to(get_agent_visits))
+    );
+}
+
+async fn user_exists(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<bool, AppError> {
+    let exists = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+        .bind(id)
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(exists)
+}
+
+async fn fetch_assignment(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<AgentAssignment, AppError> {
+    sqlx::query_as::<_, AgentAssignment>(&format!("{} WHERE a.id = $1", ASSIGNMENT_QUERY))
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Assignment".to_string()))
+}
+
+/**
+ * Get Agent Portfolio
+ * Current assignments by default; `history=true` also returns ended ones
+ **/
+async fn get_all_assignments(pool: web::Data<PgPool>, agent_id: web::Path<Uuid>, query: web::Query<AssignmentListQuery>) -> Result<HttpResponse, AppError> {
+    let assignments = sqlx::query_as::<_, AgentAssignment>(
+        &format!(
+            r#"{} WHERE a."agentId" = $1 AND ($2 OR a."endedOn" IS NULL) ORDER BY u."lastName", u."firstName", a."startedOn""#,
+            ASSIGNMENT_QUERY
+        ),
+    )
+        .bind(agent_id.into_inner())
+        .bind(query.history)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(assignments))
+}
+
+async fn create_assignment(pool: web::Data<PgPool>, agent_id: web::Path<Uuid>, assignment: Json<CreateAgentAssignment>) -> Result<HttpResponse, AppError> {
+    let agent_id = agent_id.into_inner();
+    let assignment = assignment.into_inner();
+    let started_on = assignment.started_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    if agent_id == assignment.farmer_id {
+        return Err(AppError::GenericError("An agent cannot be assigned to themselves".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    if !user_exists(&mut tx, agent_id).await? {
+        return Err(AppError::NotFound("Agent".to_string()));
+    }
+    // Serialises concurrent assignments of the same farmer
+    let farmer = sqlx::query(r#"SELECT id FROM "User" WHERE id = $1 FOR UPDATE"#)
+        .bind(assignment.farmer_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if farmer.is_none() {
+        return Err(AppError::NotFound("Farmer".to_string()));
+    }
+
+    let current: Option<Uuid> = sqlx::query_scalar(
+        r#"SELECT "agentId" FROM "AgentAssignment" WHERE "farmerId" = $1 AND "endedOn" IS NULL"#,
+    )
+        .bind(assignment.farmer_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    match current {
+        Some(current) if current == agent_id => {
+            return Err(AppError::GenericError("Farmer is already assigned to this agent".to_string()));
+        }
+        Some(_) => {
+            return Err(AppError::GenericError("Farmer is assigned to another agent; end that assignment first".to_string()));
+        }
+        None => {}
+    }
+
+    let last_ended: Option<NaiveDate> = sqlx::query_scalar(
+        r#"SELECT MAX("endedOn") FROM "AgentAssignment" WHERE "farmerId" = $1"#,
+    )
+        .bind(assignment.farmer_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if last_ended.is_some_and(|ended_on| started_on < ended_on) {
+        return Err(AppError::GenericError("startedOn must not be before the end of the farmer's previous assignment".to_string()));
+    }
+
+    let id: Uuid = sqlx::query_scalar(
+        r#"INSERT INTO "AgentAssignment" ("agentId", "farmerId", "startedOn") VALUES ($1, $2, $3) RETURNING id"#,
+    )
+        .bind(agent_id)
+        .bind(assignment.farmer_id)
+        .bind(started_on)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let assignment = fetch_assignment(&mut tx, id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(assignment))
+}
+
+/**
+ * End Assignment
+ * Ends the assignment on `endedOn` (today by default); the record is kept as history
+ **/
+async fn end_assignment(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, query: web::Query<EndAssignmentQuery>) -> Result<HttpResponse, AppError> {
+    let (agent_id, farmer_id) = path.into_inner();
+    let ended_on = query.ended_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut tx = pool.begin().await?;
+    let current: Option<(Uuid, NaiveDate)> = sqlx::query_as(
+        r#"
+        SELECT id, "startedOn"
+        FROM "AgentAssignment"
+        WHERE "agentId" = $1 AND "farmerId" = $2 AND "endedOn" IS NULL
+        FOR UPDATE
+        "#,
+    )
+        .bind(agent_id)
+        .bind(farmer_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let (id, started_on) = current.ok_or_else(|| AppError::NotFound("Assignment".to_string()))?;
+    if ended_on < started_on {
+        return Err(AppError::GenericError("endedOn must not be before startedOn".to_string()));
+    }
+
+    sqlx::query(r#"UPDATE "AgentAssignment" SET "endedOn" = $2 WHERE id = $1"#)
+        .bind(id)
+        .bind(ended_on)
+        .execute(&mut *tx)
+        .await?;
+
+    let assignment = fetch_assignment(&mut tx, id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(assignment))
+}
+
+async fn find_visits(pool: &PgPool, farm_id: Option<Uuid>, filter: VisitFilter) -> Result<Vec<FarmVisit>, AppError> {
+    let visits = sqlx::query_as::<_, FarmVisit>(
+        r#"
+        SELECT *
+        FROM "FarmVisit"
+        WHERE ($1::uuid IS NULL OR "farmId" = $1)
+          AND ($2::uuid IS NULL OR "agentId" = $2)
+          AND ($3::date IS NULL OR "visitedAt" >= $3)
+          AND ($4::date IS NULL OR "visitedAt" < $4 + 1)
+        ORDER BY "visitedAt" DESC
+        "#,
+    )
+        .bind(farm_id)
+        .bind(filter.agent_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .fetch_all(pool)
+        .await?;
+    Ok(visits)
+}
+
+async fn get_farm_visits(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, filter: web::Query<VisitFilter>) -> Result<HttpResponse, AppError> {
+    let visits = find_visits(pool.get_ref(), Some(farm_id.into_inner()), filter.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(visits))
+}
+
+async fn get_agent_visits(pool: web::Data<PgPool>, agent_id: web::Path<Uuid>, filter: web::Query<VisitFilter>) -> Result<HttpResponse, AppError> {
+    let filter = VisitFilter { agent_id: Some(agent_id.into_inner()), ..filter.into_inner() };
+    let visits = find_visits(pool.get_ref(), None, filter).await?;
+    Ok(HttpResponse::Ok().json(visits))
+}
+
+/**
+ * Log a Visit
+ * The agent must be assigned to the farmer on the visit date. The GPS check-in is compared with
+ * the farm's recorded location; visits outside the allowed distance are kept but not verified.
+ **/
+async fn create_visit(pool: web::Data<PgPool>, config: web::Data<FieldVisitConfig>, farm_id: web::Path<Uuid>, visit: Json<CreateFarmVisit>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let visit = visit.into_inner();
+    let visited_at = visit.visited_at.unwrap_or_else(Utc::now);
+
+    if !(-90.0..=90.0).contains(&visit.latitude) || !(-180.0..=180.0).contains(&visit.longitude) {
+        return Err(AppError::GenericError("Invalid coordinates".to_string()));
+    }
+    if visit.accuracy_metres.is_some_and(|accuracy| !accuracy.is_finite() || accuracy < 0.0) {
+        return Err(AppError::GenericError("accuracyMetres must not be negative".to_string()));
+    }
+    if visited_at > Utc::now() + Duration::minutes(CLOCK_SKEW_MINUTES) {
+        return Err(AppError::GenericError("visitedAt must not be in the future".to_string()));
+    }
+    if visit.photo_urls.len() > MAX_PHOTOS {
+        return Err(AppError::GenericError(format!("At most {} photos can be attached", MAX_PHOTOS)));
+    }
+    if visit.photo_urls.iter().any(|url| !(url.starts_with("https://") || url.starts_with("http://"))) {
+        return Err(AppError::GenericError("Photo URLs must be http(s) links".to_string()));
+    }
+    let mut questions = HashSet::new();
+    for item in &visit.checklist {
+        let question = item.question.trim();
+        if question.is_empty() {
+            return Err(AppError::GenericError("Checklist questions must not be empty".to_string()));
+        }
+        if !questions.insert(question.to_lowercase()) {
+            return Err(AppError::GenericError(format!("Checklist question '{}' is answered more than once", question)));
+        }
+    }
+
+    let farm: Option<(f64, f64, f64, Uuid)> = sqlx::query_as(
+        r#"SELECT latitude, longitude, acreage, "farmerId" FROM "Farm" WHERE id = $1"#,
+    )
+        .bind(farm_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+    let (farm_latitude, farm_longitude, acreage, farmer_id) = farm.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+
+    let assigned: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM "AgentAssignment"
+            WHERE "agentId" = $1 AND "farmerId" = $2
+              AND "startedOn" <= $3::date
+              AND ("endedOn" IS NULL OR "endedOn" >= $3::date)
+        )
+        "#,
+    )
+        .bind(visit.agent_id)
+        .bind(farmer_id)
+        .bind(visited_at.date_naive())
+        .fetch_one(pool.get_ref())
+        .await?;
+    if !assigned {
+        return Err(AppError::GenericError("Agent is not assigned to this farmer on the visit date".to_string()));
+    }
+
+    let distance = distance_metres([visit.longitude, visit.latitude], [farm_longitude, farm_latitude]);
+    let location_verified = distance <= config.allowed_distance_metres(acreage);
+
+    let checklist = visit.checklist.into_iter()
+        .map(|mut item| {
+            item.question = item.question.trim().to_string();
+            item
+        })
+        .collect::<Vec<_>>();
+
+    let visit = sqlx::query_as::<_, FarmVisit>(
+        r#"
+        INSERT INTO "FarmVisit" ("farmId", "agentId", "visitedAt", latitude, longitude, "accuracyMetres", "distanceMetres", "locationVerified", notes, "photoUrls", checklist)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(visit.agent_id)
+        .bind(visited_at)
+        .bind(visit.latitude)
+        .bind(visit.longitude)
+        .bind(visit.accuracy_metres)
+        .bind(distance)
+        .bind(location_verified)
+        .bind(visit.notes)
+        .bind(visit.photo_urls)
+        .bind(SqlJson(checklist))
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(visit))
+}
+
+/**
+ * Get Visit Coverage
+ * For each agent, the share of farms in their current portfolio with a verified visit in the period
+ **/
+async fn get_coverage(pool: web::Data<PgPool>, config: web::Data<FieldVisitConfig>, query: web::Query<CoverageQuery>) -> Result<HttpResponse, AppError> {
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query.from.unwrap_or(to - Duration::days(config.visit_interval_days));
+    if from > to {
+        return Err(AppError::GenericError("from must not be after to".to_string()));
+    }
+
+    let coverage = sqlx::query_as::<_, AgentCoverage>(
+        r#"
+        WITH portfolio AS (
+            SELECT "agentId", "farmerId" FROM "AgentAssignment" WHERE "endedOn" IS NULL
+        ), portfolio_farm AS (
+            SELECT p."agentId", f.id AS "farmId"
+            FROM portfolio p
+            JOIN "Farm" f ON f."farmerId" = p."farmerId"
+        ), visit AS (
+            SELECT "agentId", "farmId", "locationVerified"
+            FROM "FarmVisit"
+            WHERE "visitedAt" >= $1::date AND "visitedAt" < $2::date + 1
+        ), agent AS (
+            SELECT u.id, u."firstName", u."lastName",
+                   (SELECT COUNT(*) FROM portfolio p WHERE p."agentId" = u.id) AS "farmerCount",
+                   (SELECT COUNT(*) FROM portfolio_farm pf WHERE pf."agentId" = u.id) AS "farmCount",
+                   (SELECT COUNT(DISTINCT v."farmId")
+                    FROM visit v
+                    JOIN portfolio_farm pf ON pf."farmId" = v."farmId" AND pf."agentId" = v."agentId"
+                    WHERE v."agentId" = u.id AND v."locationVerified") AS "visitedFarmCount",
+                   (SELECT COUNT(*) FROM visit v WHERE v."agentId" = u.id) AS "visitCount",
+                   (SELECT COUNT(*) FROM visit v WHERE v."agentId" = u.id AND NOT v."locationVerified") AS "unverifiedVisitCount"
+            FROM "User" u
+            WHERE u.id IN (SELECT "agentId" FROM portfolio UNION SELECT "agentId" FROM visit)
+        )
+        SELECT id AS "agentId", "firstName", "lastName", "farmerCount", "farmCount", "visitedFarmCount", "visitCount", "unverifiedVisitCount",
+               COALESCE("visitedFarmCount"::double precision / NULLIF("farmCount", 0), 0) AS coverage
+        FROM agent
+        ORDER BY coverage, "lastName", "firstName"
+        "#,
+    )
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(coverage))
+}
+
+/**
+ * Get Overdue Farms
+ * Farms of assigned farmers without a verified visit within the interval, never-visited farms first.
+ * A new assignment gets a full interval before its farms fall overdue.
+ **/
+async fn get_overdue_farms(pool: web::Data<PgPool>, config: web::Data<FieldVisitConfig>, query: web::Query<OverdueQuery>) -> Result<HttpResponse, AppError> {
+    let interval_days = query.interval_days.unwrap_or(config.visit_interval_days);
+    if !(1..=MAX_INTERVAL_DAYS).contains(&interval_days) {
+        return Err(AppError::GenericError(format!("intervalDays must be between 1 and {}", MAX_INTERVAL_DAYS)));
+    }
+
+    let farms = sqlx::query_as::<_, OverdueFarm>(
+        r#"
+        SELECT f.id AS "farmId", f.farm_name AS "farmName", f."farmerId", a."agentId",
+               lv."lastVisitedAt",
+               (current_date - lv."lastVisitedAt"::date) AS "daysSinceVisit"
+        FROM "AgentAssignment" a
+        JOIN "Farm" f ON f."farmerId" = a."farmerId"
+        LEFT JOIN LATERAL (
+            SELECT MAX(v."visitedAt") AS "lastVisitedAt"
+            FROM "FarmVisit" v
+            WHERE v."farmId" = f.id AND v."locationVerified"
+        ) lv ON true
+        WHERE a."endedOn" IS NULL
+          AND ($1::uuid IS NULL OR a."agentId" = $1)
+          AND COALESCE(lv."lastVisitedAt"::date, a."startedOn") < current_date - $2::int
+        ORDER BY lv."lastVisitedAt" NULLS FIRST, a."startedOn", f.id
+        "#,
+    )
+        .bind(query.agent_id)
+        .bind(interval_days as i32)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(farms))
+}
@@ -0,0 +1,190 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use shared::models::{
+    YieldFilter,
+    YieldGroupBy,
+    YieldSummary,
+    UnderperformingFarm
+};
+use tracing::error;
+
+const DEFAULT_UNDERPERFORMANCE_THRESHOLD: f64 = 0.7;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/analytics")
+                    .route("/yield", web::get().to(get_yield))
+                    .route("/yield/underperforming", web::get().to(get_underperforming_farms))
+    );
+}
+
+// One row per farm, crop and season: net harvest over the area planted with the crop,
+// benchmarked against the planted variety (or the crop's average variety)
+const FARM_YIELD_CTE: &str = r#"
+    WITH crop_benchmark AS (
+        SELECT "cropId", AVG("expectedYieldKgPerHectare") AS benchmark
+        FROM "CropVariety"
+        GROUP BY "cropId"
+    ),
+    planting_harvest AS (
+        SELECT "plantingId", SUM("quantityKg" - "postHarvestLossKg") AS net_kg
+        FROM "Harvest"
+        GROUP BY "plantingId"
+    ),
+    farm_yield AS (
+        SELECT p."farmId",
+               p."cropId",
+               p.season,
+               p."seasonYear",
+               SUM(ph.net_kg) AS net_kg,
+               SUM(p."plantedArea") AS hectares,
+               AVG(COALESCE(v."expectedYieldKgPerHectare", cb.benchmark)) AS benchmark
+        FROM planting_harvest ph
+        JOIN "Planting" p ON p.id = ph."plantingId"
+        LEFT JOIN "CropVariety" v ON v.id = p."varietyId"
+        LEFT JOIN crop_benchmark cb ON cb."cropId" = p."cropId"
+        WHERE ($1::uuid IS NULL OR p."cropId" = $1)
+          AND ($2::varchar IS NULL OR p.season = $2)
+          AND ($3::int IS NULL OR p."seasonYear" = $3)
+        GROUP BY p."farmId", p."cropId", p.season, p."seasonYear"
+    ),
+    rows AS (
+        SELECT fy.*,
+               f.farm_name,
+               COALESCE(s.name, f.state) AS state_name,
+               c.name AS crop_name
+        FROM farm_yield fy
+        JOIN "Farm" f ON f.id = fy."farmId"
+        JOIN "Crop" c ON c.id = fy."cropId"
+        LEFT JOIN "State" s ON s.id = f."stateId"
+    )
+"#;
+
+fn group_expressions(group_by: YieldGroupBy) -> (&'static str, &'static str) {
+    match group_by {
+        YieldGroupBy::Farm => (r#""farmId"::text"#, "MAX(farm_name)"),
+        YieldGroupBy::Crop => (r#""cropId"::text"#, "MAX(crop_name)"),
+        YieldGroupBy::State => ("COALESCE(state_name, 'UNKNOWN')", "NULL::text"),
+        YieldGroupBy::Season => (r#""seasonYear" || '-' || season"#, "NULL::text"),
+    }
+}
+
+/**
+ * Yield per hectare grouped by farm, crop, state or season
+ **/
+async fn get_yield(pool: web::Data<PgPool>, filter: web::Query<YieldFilter>) -> Result<HttpResponse, AppError> {
+    let filter = filter.into_inner();
+    let (key, label) = group_expressions(filter.group_by);
+
+    let query = format!(
+        r#"
+        {}
+        SELECT {} AS key,
+               {} AS label,
+               COUNT(DISTINCT "farmId") AS "farmCount",
+               SUM(net_kg) AS "harvestedKg",
+               SUM(hectares) AS hectares,
+               SUM(net_kg) / NULLIF(SUM(hectares), 0) AS "yieldKgPerHectare",
+               SUM(benchmark * hectares) / NULLIF(SUM(hectares) FILTER (WHERE benchmark IS NOT NULL), 0) AS "benchmarkKgPerHectare",
+               (SUM(net_kg) / NULLIF(SUM(hectares), 0))
+                   / NULLIF(SUM(benchmark * hectares) / NULLIF(SUM(hectares) FILTER (WHERE benchmark IS NOT NULL), 0), 0) AS "performanceRatio"
+        FROM rows
+        GROUP BY 1
+        ORDER BY 1
+        "#,
+        FARM_YIELD_CTE, key, label
+    );
+
+    let summary_result = sqlx::query_as::<_, YieldSummary>(&query)
+        .bind(filter.crop_id)
+        .bind(filter.season)
+        .bind(filter.season_year)
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match summary_result {
+        Ok(summary) => Ok(HttpResponse::Ok().json(summary)),
+        Err(e) => {
+            error!("Error computing yield: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+/**
+ * Farm, crop and season combinations yielding below `threshold` of the benchmark
+ **/
+async fn get_underperforming_farms(pool: web::Data<PgPool>, filter: web::Query<YieldFilter>) -> Result<HttpResponse, AppError> {
+    let filter = filter.into_inner();
+    let threshold = filter.threshold.unwrap_or(DEFAULT_UNDERPERFORMANCE_THRESHOLD);
+
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(AppError::GenericError("Threshold must be between 0 and 1".to_string()));
+    }
+
+    let query = format!(
+        r#"
+        {}
+        SELECT "farmId",
+               farm_name AS "farmName",
+               "cropId",
+               crop_name AS crop,
+               season,
+               "seasonYear",
+               net_kg / hectares AS "yieldKgPerHectare",
+               benchmark AS "benchmarkKgPerHectare",
+               (net_kg / hectares) / benchmark AS "performanceRatio"
+        FROM rows
+        WHERE benchmark > 0
+          AND (net_kg / hectares) / benchmark < $4
+        ORDER BY "performanceRatio"
+        "#,
+        FARM_YIELD_CTE
+    );
+
+    let farms_result = sqlx::query_as::<_, UnderperformingFarm>(&query)
+        .bind(filter.crop_id)
+        .bind(filter.season)
+        .bind(filter.season_year)
+        .bind(threshold)
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match farms_result {
+        Ok(farms) => Ok(HttpResponse::Ok().json(farms)),
+        Err(e) => {
+            error!("Error finding underperforming farms: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
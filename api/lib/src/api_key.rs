@@ -0,0 +1,229 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpRequest, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, types::Json as SqlxJson};
+use actix_web::http::{header, StatusCode};
+use ring::{constant_time, digest};
+use uuid::Uuid;
+use shared::models::{
+    Pagination,
+    ApiKey,
+    CreatedApiKey,
+    CreateApiKey
+};
+use tracing::error;
+
+// Length of the key start kept in clear to tell keys apart
+const KEY_PREFIX_LENGTH: usize = 12;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+    Unauthorized(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+            AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        let mut response = HttpResponse::build(status);
+        if status == StatusCode::UNAUTHORIZED {
+            response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        response.json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Api key query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+/**
+ * Api key management settings. Keys are managed with the API_KEY_ADMIN_TOKEN bearer token;
+ * management is turned off while it is unset.
+ **/
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyConfig {
+    pub admin_token: Option<String>,
+}
+
+impl ApiKeyConfig {
+    pub fn from_env() -> Self {
+        ApiKeyConfig {
+            admin_token: std::env::var("API_KEY_ADMIN_TOKEN").ok().filter(|token| !token.trim().is_empty()),
+        }
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/api-keys")
+                    .app_data(web::Data::new(ApiKeyConfig::from_env()))
+                    .route("", web::get().to(get_all_api_keys))
+                    .route("", web::post().to(create_api_key))
+                    .route("/{id}", web::delete().to(revoke_api_key))
+    );
+}
+
+pub(crate) fn bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+fn require_admin(req: &HttpRequest, config: &ApiKeyConfig) -> Result<(), AppError> {
+    let Some(admin_token) = &config.admin_token else {
+        return Err(AppError::Unauthorized("Api key management is turned off; set API_KEY_ADMIN_TOKEN".to_string()));
+    };
+    let token = bearer_token(req).ok_or_else(|| AppError::Unauthorized("The admin token is required".to_string()))?;
+    constant_time::verify_slices_are_equal(token.as_bytes(), admin_token.as_bytes())
+        .map_err(|_| AppError::Unauthorized("Invalid admin token".to_string()))
+}
+
+fn hash(key: &str) -> String {
+    hex::encode(digest::digest(&digest::SHA256, key.as_bytes()).as_ref())
+}
+
+/**
+ * The unrevoked key matching `key`, noting that it was used
+ **/
+pub async fn authenticate(pool: &PgPool, key: &str) -> Result<Option<ApiKey>, SqlxError> {
+    sqlx::query_as::<_, ApiKey>(
+        r#"
+        UPDATE "ApiKey"
+        SET "lastUsedAt" = current_timestamp
+        WHERE "keyHash" = $1 AND "revokedAt" IS NULL
+        RETURNING *
+        "#,
+    )
+        .bind(hash(key))
+        .fetch_optional(pool)
+        .await
+}
+
+async fn get_all_api_keys(req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<ApiKeyConfig>, pagination: web::Query<Pagination>) -> Result<HttpResponse, AppError> {
+    require_admin(&req, config.get_ref())?;
+    let Pagination { limit, offset } = pagination.into_inner();
+    let api_keys = sqlx::query_as::<_, ApiKey>(
+        r#"SELECT * FROM "ApiKey" ORDER BY "createdAt" LIMIT $1 OFFSET $2"#,
+    )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(api_keys))
+}
+
+/**
+ * Create an Api Key
+ * The key may see the listed entity types, in the listed states or in every state when
+ * none are given. It is only shown in this response. Needs the admin token.
+ **/
+async fn create_api_key(req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<ApiKeyConfig>, api_key: Json<CreateApiKey>) -> Result<HttpResponse, AppError> {
+    require_admin(&req, config.get_ref())?;
+    let api_key = api_key.into_inner();
+    if api_key.name.trim().is_empty() {
+        return Err(AppError::GenericError("name is required".to_string()));
+    }
+    if api_key.entity_types.is_empty() {
+        return Err(AppError::GenericError("entityTypes must list at least one entity type".to_string()));
+    }
+    if !api_key.state_ids.is_empty() {
+        let known: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "State" WHERE id = ANY($1)"#)
+            .bind(&api_key.state_ids)
+            .fetch_one(pool.get_ref())
+            .await?;
+        if known != api_key.state_ids.len() as i64 {
+            return Err(AppError::NotFound("State".to_string()));
+        }
+    }
+
+    let key = format!("pk_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let created = sqlx::query_as::<_, ApiKey>(
+        r#"
+        INSERT INTO "ApiKey" (name, "keyPrefix", "keyHash", "entityTypes", "stateIds")
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+        .bind(api_key.name.trim())
+        .bind(&key[..KEY_PREFIX_LENGTH])
+        .bind(hash(&key))
+        .bind(SqlxJson(api_key.entity_types))
+        .bind(&api_key.state_ids)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(CreatedApiKey { api_key: created, key }))
+}
+
+/**
+ * Revoke an Api Key
+ * Open event streams using the key are closed at their next keep-alive
+ **/
+async fn revoke_api_key(req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<ApiKeyConfig>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    require_admin(&req, config.get_ref())?;
+    let result = sqlx::query(r#"UPDATE "ApiKey" SET "revokedAt" = COALESCE("revokedAt", current_timestamp) WHERE id = $1"#)
+        .bind(id.into_inner())
+        .execute(pool.get_ref())
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Api key".to_string()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn request(authorization: Option<&str>) -> HttpRequest {
+        let request = TestRequest::default();
+        match authorization {
+            Some(value) => request.insert_header((header::AUTHORIZATION, value)).to_http_request(),
+            None => request.to_http_request(),
+        }
+    }
+
+    #[test]
+    fn require_admin_checks_the_bearer_token() {
+        let config = ApiKeyConfig { admin_token: Some("admin-secret".to_string()) };
+        assert!(require_admin(&request(Some("Bearer admin-secret")), &config).is_ok());
+        assert!(require_admin(&request(Some("Bearer admin-secre")), &config).is_err());
+        assert!(require_admin(&request(Some("admin-secret")), &config).is_err());
+        assert!(require_admin(&request(None), &config).is_err());
+    }
+
+    #[test]
+    fn require_admin_refuses_everyone_without_a_configured_token() {
+        let config = ApiKeyConfig::default();
+        assert!(require_admin(&request(Some("Bearer ")), &config).is_err());
+        assert!(require_admin(&request(None), &config).is_err());
+    }
+}
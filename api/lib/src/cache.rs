@@ -0,0 +1,67 @@
+use deadpool_redis::{redis::AsyncCommands, Pool};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+/**
+ * Read a JSON value from Redis.
+ * Any Redis or decoding failure is treated as a cache miss.
+ **/
+pub async fn get_json<T: DeserializeOwned>(redis: &Pool, key: &str) -> Option<T> {
+    let mut conn = match redis.get().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            warn!("Redis unavailable, skipping cache read for {}: {:?}", key, e);
+            return None;
+        }
+    };
+
+    let cached: Option<String> = match conn.get(key).await {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Error reading cache key {}: {:?}", key, e);
+            return None;
+        }
+    };
+
+    cached.and_then(|value| serde_json::from_str(&value).ok())
+}
+
+/**
+ * Write a JSON value to Redis with an expiry.
+ * Failures are logged and otherwise ignored.
+ **/
+pub async fn set_json<T: Serialize>(redis: &Pool, key: &str, value: &T, ttl_seconds: usize) {
+    let payload = match serde_json::to_string(value) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Error serializing cache value for {}: {:?}", key, e);
+            return;
+        }
+    };
+
+    match redis.get().await {
+        Ok(mut conn) => {
+            let result: Result<(), _> = conn.set_ex(key, payload, ttl_seconds).await;
+            if let Err(e) = result {
+                warn!("Error writing cache key {}: {:?}", key, e);
+            }
+        }
+        Err(e) => warn!("Redis unavailable, skipping cache write for {}: {:?}", key, e),
+    }
+}
+
+/**
+ * Remove a cached value so the next read goes to the database.
+ * Failures are logged and otherwise ignored.
+ **/
+pub async fn delete(redis: &Pool, key: &str) {
+    match redis.get().await {
+        Ok(mut conn) => {
+            let result: Result<(), _> = conn.del(key).await;
+            if let Err(e) = result {
+                warn!("Error deleting cache key {}: {:?}", key, e);
+            }
+        }
+        Err(e) => warn!("Redis unavailable, skipping cache delete for {}: {:?}", key, e),
+    }
+}
@@ -0,0 +1,503 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpRequest, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction};
+use actix_web::http::StatusCode;
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+use shared::models::{
+    AreaUnitQuery,
+    Cooperative,
+    CreateCooperative,
+    CooperativeFilter,
+    CooperativeMember,
+    AddCooperativeMember,
+    ChangeMemberRole,
+    MemberListQuery,
+    EndMembershipQuery,
+    MemberRole,
+    CooperativeSummary,
+    CooperativeSummaryQuery,
+    CropShare
+};
+use tracing::error;
+use crate::farm::requested_area_unit;
+
+const MEMBER_QUERY: &str = r#"
+    SELECT m.id AS "membershipId", m."userId", u."firstName", u."lastName", m.role, m."joinedOn", m."leftOn"
+    FROM "CooperativeMembership" m
+    JOIN "User" u ON u.id = m."userId"
+"#;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Cooperative query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/cooperatives")
+                    .route("", web::get().to(get_all_cooperatives))
+                    .route("", web::post().to(create_cooperative))
+                    .route("/{id}", web::get().to(get_cooperative))
+                    .route("/{id}", web::put().to(update_cooperative))
+                    .route("/{id}/members", web::get().to(get_all_members))
+                    .route("/{id}/members", web::post().to(add_member))
+                    .route("/{id}/members/{user_id}", web::put().to(change_member_role))
+                    .route("/{id}/members/{user_id}", web::delete().to(remove_member))
+                    .route("/{id}/summary", web::get().to(get_summary))
+    );
+}
+
+fn role_name(role: MemberRole) -> &'static str {
+    match role {
+        MemberRole::Chair => "chair",
+        MemberRole::Secretary => "secretary",
+        MemberRole::Member => "member",
+    }
+}
+
+async fn validate_cooperative(tx: &mut Transaction<'_, Postgres>, id: Option<Uuid>, cooperative: &CreateCooperative) -> Result<(), AppError> {
+    if cooperative.name.trim().is_empty() {
+        return Err(AppError::GenericError("Cooperative name is required".to_string()));
+    }
+
+    let name_taken: bool = sqlx::query_scalar(
+        r#"SELECT EXISTS (SELECT 1 FROM "Cooperative" WHERE lower(name) = lower($1) AND ($2::uuid IS NULL OR id <> $2))"#,
+    )
+        .bind(cooperative.name.trim())
+        .bind(id)
+        .fetch_one(&mut **tx)
+        .await?;
+    if name_taken {
+        return Err(AppError::GenericError(format!("Cooperative {} already exists", cooperative.name.trim())));
+    }
+
+    if let Some(registration_number) = &cooperative.registration_number {
+        let number_taken: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (SELECT 1 FROM "Cooperative" WHERE "registrationNumber" = $1 AND ($2::uuid IS NULL OR id <> $2))"#,
+        )
+            .bind(registration_number)
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await?;
+        if number_taken {
+            return Err(AppError::GenericError(format!("Registration number {} is already in use", registration_number)));
+        }
+    }
+
+    if let Some(lga_id) = cooperative.lga_id {
+        let lga_state: Option<Uuid> = sqlx::query_scalar(r#"SELECT "stateId" FROM "Lga" WHERE id = $1"#)
+            .bind(lga_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+        match lga_state {
+            None => return Err(AppError::NotFound("Lga".to_string())),
+            Some(state_id) if cooperative.state_id.is_some_and(|id| id != state_id) => {
+                return Err(AppError::GenericError("Lga does not belong to the given state".to_string()));
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+// Locks the cooperative so membership checks and the write that follows see the same rows
+async fn lock_cooperative(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<(), AppError> {
+    sqlx::query(r#"SELECT id FROM "Cooperative" WHERE id = $1 FOR UPDATE"#)
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Cooperative".to_string()))?;
+    Ok(())
+}
+
+async fn current_membership(tx: &mut Transaction<'_, Postgres>, id: Uuid, user_id: Uuid) -> Result<Option<CooperativeMember>, AppError> {
+    let member = sqlx::query_as::<_, CooperativeMember>(
+        &format!(r#"{} WHERE m."cooperativeId" = $1 AND m."userId" = $2 AND m."leftOn" IS NULL"#, MEMBER_QUERY),
+    )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    Ok(member)
+}
+
+// Chair and secretary are held by one sitting member at a time
+async fn ensure_office_vacant(tx: &mut Transaction<'_, Postgres>, id: Uuid, role: MemberRole) -> Result<(), AppError> {
+    if role == MemberRole::Member {
+        return Ok(());
+    }
+
+    let held: bool = sqlx::query_scalar(
+        r#"SELECT EXISTS (SELECT 1 FROM "CooperativeMembership" WHERE "cooperativeId" = $1 AND role = $2 AND "leftOn" IS NULL)"#,
+    )
+        .bind(id)
+        .bind(role)
+        .fetch_one(&mut **tx)
+        .await?;
+    if held {
+        return Err(AppError::GenericError(format!("The cooperative already has a sitting {}", role_name(role))));
+    }
+    Ok(())
+}
+
+async fn get_all_cooperatives(pool: web::Data<PgPool>, filter: web::Query<CooperativeFilter>) -> Result<HttpResponse, AppError> {
+    let CooperativeFilter { kind, state_id, user_id } = filter.into_inner();
+    let cooperatives = sqlx::query_as::<_, Cooperative>(
+        r#"
+        SELECT c.*
+        FROM "Cooperative" c
+        WHERE ($1::varchar IS NULL OR c.kind = $1)
+          AND ($2::uuid IS NULL OR c."stateId" = $2)
+          AND ($3::uuid IS NULL OR EXISTS (
+                SELECT 1 FROM "CooperativeMembership" m
+                WHERE m."cooperativeId" = c.id AND m."userId" = $3 AND m."leftOn" IS NULL
+              ))
+        ORDER BY c.name
+        "#,
+    )
+        .bind(kind)
+        .bind(state_id)
+        .bind(user_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(cooperatives))
+}
+
+async fn get_cooperative(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let cooperative = sqlx::query_as::<_, Cooperative>(r#"SELECT * FROM "Cooperative" WHERE id = $1"#)
+        .bind(id.into_inner())
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match cooperative {
+        Some(cooperative) => Ok(HttpResponse::Ok().json(cooperative)),
+        None => Err(AppError::NotFound("Cooperative".to_string())),
+    }
+}
+
+async fn create_cooperative(pool: web::Data<PgPool>, cooperative: Json<CreateCooperative>) -> Result<HttpResponse, AppError> {
+    let cooperative = cooperative.into_inner();
+
+    let mut tx = pool.begin().await?;
+    validate_cooperative(&mut tx, None, &cooperative).await?;
+
+    let cooperative = sqlx::query_as::<_, Cooperative>(
+        r#"
+        INSERT INTO "Cooperative" (name, kind, "registrationNumber", "stateId", "lgaId", description)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+        .bind(cooperative.name.trim())
+        .bind(cooperative.kind)
+        .bind(cooperative.registration_number)
+        .bind(cooperative.state_id)
+        .bind(cooperative.lga_id)
+        .bind(cooperative.description)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(cooperative))
+}
+
+async fn update_cooperative(pool: web::Data<PgPool>, id: web::Path<Uuid>, cooperative: Json<CreateCooperative>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let cooperative = cooperative.into_inner();
+
+    let mut tx = pool.begin().await?;
+    lock_cooperative(&mut tx, id).await?;
+    validate_cooperative(&mut tx, Some(id), &cooperative).await?;
+
+    let cooperative = sqlx::query_as::<_, Cooperative>(
+        r#"
+        UPDATE "Cooperative"
+        SET name = $2,
+            kind = $3,
+            "registrationNumber" = $4,
+            "stateId" = $5,
+            "lgaId" = $6,
+            description = $7,
+            "updatedAt" = current_timestamp
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(cooperative.name.trim())
+        .bind(cooperative.kind)
+        .bind(cooperative.registration_number)
+        .bind(cooperative.state_id)
+        .bind(cooperative.lga_id)
+        .bind(cooperative.description)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(cooperative))
+}
+
+/**
+ * Get Members
+ * Current members by default; `history=true` also returns past memberships and roles
+ **/
+async fn get_all_members(pool: web::Data<PgPool>, id: web::Path<Uuid>, query: web::Query<MemberListQuery>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+
+    let exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Cooperative" WHERE id = $1)"#)
+        .bind(id)
+        .fetch_one(pool.get_ref())
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound("Cooperative".to_string()));
+    }
+
+    let members = sqlx::query_as::<_, CooperativeMember>(
+        &format!(
+            r#"{} WHERE m."cooperativeId" = $1 AND ($2 OR m."leftOn" IS NULL) ORDER BY u."lastName", u."firstName", m."joinedOn""#,
+            MEMBER_QUERY
+        ),
+    )
+        .bind(id)
+        .bind(query.history)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(members))
+}
+
+async fn add_member(pool: web::Data<PgPool>, id: web::Path<Uuid>, member: Json<AddCooperativeMember>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let member = member.into_inner();
+    let joined_on = member.joined_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut tx = pool.begin().await?;
+    lock_cooperative(&mut tx, id).await?;
+
+    let user_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+        .bind(member.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !user_exists {
+        return Err(AppError::NotFound("User".to_string()));
+    }
+    if current_membership(&mut tx, id, member.user_id).await?.is_some() {
+        return Err(AppError::GenericError("User is already a member of this cooperative".to_string()));
+    }
+
+    // Rejoining must not overlap an earlier membership
+    let last_left: Option<NaiveDate> = sqlx::query_scalar(
+        r#"SELECT MAX("leftOn") FROM "CooperativeMembership" WHERE "cooperativeId" = $1 AND "userId" = $2"#,
+    )
+        .bind(id)
+        .bind(member.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if last_left.is_some_and(|left_on| joined_on < left_on) {
+        return Err(AppError::GenericError("joinedOn must not be before the end of the previous membership".to_string()));
+    }
+
+    ensure_office_vacant(&mut tx, id, member.role).await?;
+
+    sqlx::query(
+        r#"INSERT INTO "CooperativeMembership" ("cooperativeId", "userId", role, "joinedOn") VALUES ($1, $2, $3, $4)"#,
+    )
+        .bind(id)
+        .bind(member.user_id)
+        .bind(member.role)
+        .bind(joined_on)
+        .execute(&mut *tx)
+        .await?;
+
+    let member = current_membership(&mut tx, id, member.user_id).await?
+        .ok_or_else(|| AppError::NotFound("Member".to_string()))?;
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(member))
+}
+
+/**
+ * Change Member Role
+ * The current membership is closed on the effective date and a new one opened with the new role,
+ * so the membership list with `history=true` shows who held each office and when
+ **/
+async fn change_member_role(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, change: Json<ChangeMemberRole>) -> Result<HttpResponse, AppError> {
+    let (id, user_id) = path.into_inner();
+    let change = change.into_inner();
+    let effective_on = change.effective_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut tx = pool.begin().await?;
+    lock_cooperative(&mut tx, id).await?;
+
+    let current = current_membership(&mut tx, id, user_id).await?
+        .ok_or_else(|| AppError::NotFound("Member".to_string()))?;
+    if current.role == change.role {
+        return Err(AppError::GenericError(format!("Member is already the {}", role_name(change.role))));
+    }
+    if effective_on < current.joined_on {
+        return Err(AppError::GenericError("effectiveOn must not be before the current role started".to_string()));
+    }
+    ensure_office_vacant(&mut tx, id, change.role).await?;
+
+    sqlx::query(r#"UPDATE "CooperativeMembership" SET "leftOn" = $2 WHERE id = $1"#)
+        .bind(current.membership_id)
+        .bind(effective_on)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"INSERT INTO "CooperativeMembership" ("cooperativeId", "userId", role, "joinedOn") VALUES ($1, $2, $3, $4)"#,
+    )
+        .bind(id)
+        .bind(user_id)
+        .bind(change.role)
+        .bind(effective_on)
+        .execute(&mut *tx)
+        .await?;
+
+    let member = current_membership(&mut tx, id, user_id).await?
+        .ok_or_else(|| AppError::NotFound("Member".to_string()))?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(member))
+}
+
+/**
+ * Remove Member
+ * Ends the membership on `leftOn` (today by default); the record is kept as history
+ **/
+async fn remove_member(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, query: web::Query<EndMembershipQuery>) -> Result<HttpResponse, AppError> {
+    let (id, user_id) = path.into_inner();
+    let left_on = query.left_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut tx = pool.begin().await?;
+    lock_cooperative(&mut tx, id).await?;
+
+    let current = current_membership(&mut tx, id, user_id).await?
+        .ok_or_else(|| AppError::NotFound("Member".to_string()))?;
+    if left_on < current.joined_on {
+        return Err(AppError::GenericError("leftOn must not be before joinedOn".to_string()));
+    }
+
+    let member = sqlx::query_as::<_, CooperativeMember>(
+        r#"
+        WITH ended AS (
+            UPDATE "CooperativeMembership" SET "leftOn" = $2 WHERE id = $1
+            RETURNING *
+        )
+        SELECT m.id AS "membershipId", m."userId", u."firstName", u."lastName", m.role, m."joinedOn", m."leftOn"
+        FROM ended m
+        JOIN "User" u ON u.id = m."userId"
+        "#,
+    )
+        .bind(current.membership_id)
+        .bind(left_on)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(member))
+}
+
+/**
+ * Get Cooperative Summary
+ * Totals across the farms of current members, with the crop mix of their plantings
+ * optionally narrowed to a season
+ **/
+async fn get_summary(req: HttpRequest, pool: web::Data<PgPool>, id: web::Path<Uuid>, query: web::Query<CooperativeSummaryQuery>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let CooperativeSummaryQuery { unit, season, season_year } = query.into_inner();
+    let unit = requested_area_unit(&req, &AreaUnitQuery { unit });
+
+    let exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Cooperative" WHERE id = $1)"#)
+        .bind(id)
+        .fetch_one(pool.get_ref())
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound("Cooperative".to_string()));
+    }
+
+    let (member_count, farm_count, total_hectares): (i64, i64, f64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(DISTINCT m."userId"),
+               COUNT(f.id),
+               COALESCE(SUM(f.acreage), 0)::double precision
+        FROM "CooperativeMembership" m
+        LEFT JOIN "Farm" f ON f."farmerId" = m."userId"
+        WHERE m."cooperativeId" = $1 AND m."leftOn" IS NULL
+        "#,
+    )
+        .bind(id)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    let mut crop_mix = sqlx::query_as::<_, CropShare>(
+        r#"
+        SELECT c.id AS "cropId",
+               c.name AS crop,
+               COUNT(p.id) AS "plantingCount",
+               SUM(p."plantedArea") AS "plantedArea",
+               (SUM(p."plantedArea") / SUM(SUM(p."plantedArea")) OVER ()) AS share
+        FROM "CooperativeMembership" m
+        JOIN "Farm" f ON f."farmerId" = m."userId"
+        JOIN "Planting" p ON p."farmId" = f.id
+        JOIN "Crop" c ON c.id = p."cropId"
+        WHERE m."cooperativeId" = $1 AND m."leftOn" IS NULL
+          AND ($2::varchar IS NULL OR p.season = $2)
+          AND ($3::int IS NULL OR p."seasonYear" = $3)
+        GROUP BY c.id, c.name
+        ORDER BY "plantedArea" DESC, c.name
+        "#,
+    )
+        .bind(id)
+        .bind(season)
+        .bind(season_year)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    for share in &mut crop_mix {
+        share.planted_area = unit.from_hectares(share.planted_area);
+    }
+
+    Ok(HttpResponse::Ok().json(CooperativeSummary {
+        cooperative_id: id,
+        member_count,
+        farm_count,
+        total_acreage: unit.from_hectares(total_hectares),
+        area_unit: unit,
+        crop_mix,
+    }))
+}
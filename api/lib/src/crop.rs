@@ -0,0 +1,225 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use uuid::Uuid;
+use shared::models::{
+    Crop,
+    CreateCrop,
+    CropVariety,
+    CreateCropVariety
+};
+use tracing::error;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/crops")
+                    .route("", web::get().to(get_all_crops))
+                    .route("", web::post().to(create_crop))
+                    .route("/{id}/varieties", web::get().to(get_crop_varieties))
+                    .route("/{id}/varieties", web::post().to(create_crop_variety))
+    );
+}
+
+async fn get_all_crops(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let crops_result = sqlx::query_as::<_, Crop>(
+        r#"
+        SELECT *
+        FROM "Crop"
+        ORDER BY name
+        "#,
+    )
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match crops_result {
+        Ok(crops) => Ok(HttpResponse::Ok().json(crops)),
+        Err(e) => {
+            error!("Error getting crops: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+fn validate_crop(crop: &CreateCrop) -> Result<(), AppError> {
+    if crop.name.trim().is_empty() {
+        return Err(AppError::GenericError("Crop name is required".to_string()));
+    }
+    if crop.water_requirement_mm_per_day.is_some_and(|mm| !mm.is_finite() || mm < 0.0) {
+        return Err(AppError::GenericError("Water requirement must not be negative".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_variety(variety: &CreateCropVariety) -> Result<(), AppError> {
+    if variety.cycle_days <= 0 {
+        return Err(AppError::GenericError("Cycle length must be positive".to_string()));
+    }
+    if !variety.expected_yield_kg_per_hectare.is_finite() || variety.expected_yield_kg_per_hectare < 0.0 {
+        return Err(AppError::GenericError("Expected yield must not be negative".to_string()));
+    }
+    Ok(())
+}
+
+async fn create_crop(pool: web::Data<PgPool>, crop: Json<CreateCrop>) -> Result<HttpResponse, AppError> {
+    let crop = crop.into_inner();
+    validate_crop(&crop)?;
+
+    let crop_result = sqlx::query_as::<_, Crop>(
+        r#"
+        INSERT INTO "Crop" (name, "scientificName", "waterRequirementMmPerDay")
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+        .bind(crop.name.trim())
+        .bind(crop.scientific_name)
+        .bind(crop.water_requirement_mm_per_day)
+        .fetch_one(pool.get_ref())
+        .await;
+
+    match crop_result {
+        Ok(crop) => Ok(HttpResponse::Created().json(crop)),
+        Err(e) => {
+            error!("Error creating crop: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+async fn get_crop_varieties(pool: web::Data<PgPool>, crop_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let varieties_result = sqlx::query_as::<_, CropVariety>(
+        r#"
+        SELECT *
+        FROM "CropVariety"
+        WHERE "cropId" = $1
+        ORDER BY name
+        "#,
+    )
+        .bind(crop_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match varieties_result {
+        Ok(varieties) => Ok(HttpResponse::Ok().json(varieties)),
+        Err(e) => {
+            error!("Error getting crop varieties: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+async fn create_crop_variety(pool: web::Data<PgPool>, crop_id: web::Path<Uuid>, variety: Json<CreateCropVariety>) -> Result<HttpResponse, AppError> {
+    let crop_id = crop_id.into_inner();
+    let variety = variety.into_inner();
+    validate_variety(&variety)?;
+
+    let crop_result = sqlx::query_scalar::<_, Uuid>(r#"SELECT id FROM "Crop" WHERE id = $1"#)
+        .bind(crop_id)
+        .fetch_optional(pool.get_ref())
+        .await;
+    match crop_result {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(AppError::NotFound("Crop".to_string())),
+        Err(e) => {
+            error!("Error getting crop: {:?}", e);
+            return Err(AppError::SqlError(e));
+        }
+    }
+
+    let variety_result = sqlx::query_as::<_, CropVariety>(
+        r#"
+        INSERT INTO "CropVariety" ("cropId", name, "cycleDays", "expectedYieldKgPerHectare")
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+        .bind(crop_id)
+        .bind(variety.name)
+        .bind(variety.cycle_days)
+        .bind(variety.expected_yield_kg_per_hectare)
+        .fetch_one(pool.get_ref())
+        .await;
+
+    match variety_result {
+        Ok(variety) => Ok(HttpResponse::Created().json(variety)),
+        Err(e) => {
+            error!("Error creating crop variety: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crop(name: &str, water_requirement_mm_per_day: Option<f64>) -> CreateCrop {
+        CreateCrop { name: name.to_string(), scientific_name: None, water_requirement_mm_per_day }
+    }
+
+    fn variety(cycle_days: i32, expected_yield_kg_per_hectare: f64) -> CreateCropVariety {
+        CreateCropVariety { name: "TME 419".to_string(), cycle_days, expected_yield_kg_per_hectare }
+    }
+
+    #[test]
+    fn accepts_a_named_crop() {
+        assert!(validate_crop(&crop("Cassava", None)).is_ok());
+        assert!(validate_crop(&crop("Cassava", Some(0.0))).is_ok());
+        assert!(validate_crop(&crop("Cassava", Some(4.5))).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_blank_name() {
+        assert!(validate_crop(&crop("", None)).is_err());
+        assert!(validate_crop(&crop("   ", Some(4.5))).is_err());
+    }
+
+    #[test]
+    fn rejects_a_negative_or_non_finite_water_requirement() {
+        assert!(validate_crop(&crop("Cassava", Some(-1.0))).is_err());
+        assert!(validate_crop(&crop("Cassava", Some(f64::NAN))).is_err());
+        assert!(validate_crop(&crop("Cassava", Some(f64::INFINITY))).is_err());
+    }
+
+    #[test]
+    fn validates_the_variety_cycle_and_yield() {
+        assert!(validate_variety(&variety(365, 25_000.0)).is_ok());
+        assert!(validate_variety(&variety(365, 0.0)).is_ok());
+        assert!(validate_variety(&variety(0, 25_000.0)).is_err());
+        assert!(validate_variety(&variety(-30, 25_000.0)).is_err());
+        assert!(validate_variety(&variety(365, -1.0)).is_err());
+        assert!(validate_variety(&variety(365, f64::NAN)).is_err());
+    }
+}
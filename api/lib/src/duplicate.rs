@@ -0,0 +1,673 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction};
+use sqlx::types::Json as SqlJson;
+use actix_web::http::StatusCode;
+use uuid::Uuid;
+use shared::geo::distance_metres;
+use shared::models::{
+    Pagination,
+    DuplicateCandidate,
+    DuplicateFilter,
+    DuplicateScan,
+    DuplicateScanQuery,
+    DuplicateSignal,
+    DuplicateSignalKind,
+    DuplicateStatus,
+    DismissDuplicate,
+    MergeDuplicate,
+    UserMerge,
+    UserMergeFilter,
+    PhoneLineType,
+    DomainEventType
+};
+use tracing::error;
+use crate::outbox;
+
+// Score contributed by each signal; a pair's score is their sum, capped at 1
+const BVN_WEIGHT: f64 = 0.6;
+const PHONE_WEIGHT: f64 = 0.35;
+// Scaled by the trigram similarity of the names
+const NAME_WEIGHT: f64 = 0.35;
+const FARM_WEIGHT: f64 = 0.25;
+
+const CANDIDATE_QUERY: &str = r#"
+    SELECT d.id, d."createdAt", d."updatedAt", d."userId", u."firstName", u."lastName",
+           d."otherUserId", o."firstName" AS "otherFirstName", o."lastName" AS "otherLastName",
+           d.score, d.signals, d.status, d."reviewerId", d."reviewedAt", d.reason
+    FROM "DuplicateCandidate" d
+    JOIN "User" u ON u.id = d."userId"
+    JOIN "User" o ON o.id = d."otherUserId"
+"#;
+
+// Pairs sharing a BVN or phone number, or with similar names in either order.
+// Phone numbers are compared on their last ten digits so 080... and +23480... match.
+const PAIR_QUERY: &str = r#"
+    WITH person AS (
+        SELECT u.id,
+               lower(u."firstName" || ' ' || u."lastName") AS name,
+               lower(u."lastName" || ' ' || u."firstName") AS "reversedName",
+               NULLIF(trim(p.bvn), '') AS bvn,
+               CASE WHEN length(regexp_replace(coalesce(p."phoneNumber", ''), '\D', '', 'g')) >= 10
+                    THEN right(regexp_replace(p."phoneNumber", '\D', '', 'g'), 10)
+               END AS phone
+        FROM "User" u
+        LEFT JOIN "Profile" p ON p."userId" = u.id
+    )
+    SELECT a.id AS "userId",
+           b.id AS "otherUserId",
+           GREATEST(similarity(a.name, b.name), similarity(a.name, b."reversedName"))::float8 AS "nameSimilarity",
+           coalesce(a.bvn = b.bvn, false) AS "sameBvn",
+           coalesce(a.phone = b.phone, false) AS "samePhone"
+    FROM person a
+    JOIN person b ON a.id < b.id
+    WHERE ($1::uuid IS NULL OR a.id = $1 OR b.id = $1)
+      AND (similarity(a.name, b.name) >= $2
+           OR similarity(a.name, b."reversedName") >= $2
+           OR a.bvn = b.bvn
+           OR a.phone = b.phone)
+"#;
+
+// References to a user that move to the survivor unchanged. Rows with uniqueness rules are
+// reconciled in `merge_users` before this runs.
+const USER_REFERENCES: [(&str, &str); 16] = [
+    ("Farm", "farmerId"),
+    ("FarmTitle", "ownerId"),
+    ("FarmTransfer", "fromOwnerId"),
+    ("FarmTransfer", "toOwnerId"),
+    ("InputTransaction", "farmerId"),
+    ("PestIncident", "reportedBy"),
+    ("CooperativeMembership", "userId"),
+    ("AgentAssignment", "agentId"),
+    ("AgentAssignment", "farmerId"),
+    ("FarmVisit", "agentId"),
+    ("LeaseApplication", "applicantId"),
+    ("Lease", "landlordId"),
+    ("Lease", "tenantId"),
+    ("KycEvent", "actorId"),
+    ("DuplicateCandidate", "reviewerId"),
+    ("UserMerge", "survivorId"),
+];
+
+#[derive(Debug, Clone)]
+pub struct DuplicateConfig {
+    // Pairs scoring below this are not queued
+    pub min_score: f64,
+    // Trigram similarity at which two names make a pair worth scoring
+    pub name_similarity: f64,
+    // Farms closer than this count as the same plot of land
+    pub farm_radius_metres: f64,
+}
+
+impl Default for DuplicateConfig {
+    fn default() -> Self {
+        DuplicateConfig {
+            min_score: 0.5,
+            name_similarity: 0.45,
+            farm_radius_metres: 200.0,
+        }
+    }
+}
+
+impl DuplicateConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|value| value.parse().ok())
+        }
+        let default = DuplicateConfig::default();
+        DuplicateConfig {
+            min_score: var("DUPLICATE_MIN_SCORE").unwrap_or(default.min_score),
+            name_similarity: var("DUPLICATE_NAME_SIMILARITY").unwrap_or(default.name_similarity),
+            farm_radius_metres: var("DUPLICATE_FARM_RADIUS_M").unwrap_or(default.farm_radius_metres),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Duplicate query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/duplicates")
+                    .app_data(web::Data::new(DuplicateConfig::from_env()))
+                    .route("", web::get().to(get_all_candidates))
+                    .route("/scan", web::post().to(scan_duplicates))
+                    .route("/merges", web::get().to(get_all_merges))
+                    .route("/{id}", web::get().to(get_candidate))
+                    .route("/{id}/dismiss", web::post().to(dismiss_candidate))
+                    .route("/{id}/merge", web::post().to(merge_candidate))
+    );
+}
+
+#[derive(sqlx::FromRow)]
+struct Pair {
+    #[sqlx(rename = "userId")]
+    user_id: Uuid,
+    #[sqlx(rename = "otherUserId")]
+    other_user_id: Uuid,
+    #[sqlx(rename = "nameSimilarity")]
+    name_similarity: f64,
+    #[sqlx(rename = "sameBvn")]
+    same_bvn: bool,
+    #[sqlx(rename = "samePhone")]
+    same_phone: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct FarmPoint {
+    #[sqlx(rename = "farmerId")]
+    farmer_id: Uuid,
+    latitude: f64,
+    longitude: f64,
+}
+
+/**
+ * Signals for one pair and their total score. `nearest_farm_metres` is the distance between
+ * the closest of the two users' farms, if both have any.
+ **/
+fn score_pair(pair: &Pair, nearest_farm_metres: Option<f64>, config: &DuplicateConfig) -> (f64, Vec<DuplicateSignal>) {
+    let mut signals = Vec::new();
+
+    if pair.same_bvn {
+        signals.push(DuplicateSignal {
+            signal: DuplicateSignalKind::Bvn,
+            weight: BVN_WEIGHT,
+            detail: "Same BVN".to_string(),
+        });
+    }
+    if pair.same_phone {
+        signals.push(DuplicateSignal {
+            signal: DuplicateSignalKind::Phone,
+            weight: PHONE_WEIGHT,
+            detail: "Same phone number".to_string(),
+        });
+    }
+    if pair.name_similarity >= config.name_similarity {
+        signals.push(DuplicateSignal {
+            signal: DuplicateSignalKind::Name,
+            weight: NAME_WEIGHT * pair.name_similarity,
+            detail: format!("Names are {:.0}% similar", pair.name_similarity * 100.0),
+        });
+    }
+    if let Some(metres) = nearest_farm_metres.filter(|metres| *metres <= config.farm_radius_metres) {
+        signals.push(DuplicateSignal {
+            signal: DuplicateSignalKind::FarmProximity,
+            weight: FARM_WEIGHT,
+            detail: format!("Farms are {:.0} m apart", metres),
+        });
+    }
+
+    let score = signals.iter().map(|signal| signal.weight).sum::<f64>().min(1.0);
+    // Stored rounded so rescans of unchanged data do not churn the queue
+    ((score * 1000.0).round() / 1000.0, signals)
+}
+
+fn nearest_farm_metres(farms: &HashMap<Uuid, Vec<[f64; 2]>>, user_id: Uuid, other_user_id: Uuid) -> Option<f64> {
+    let (first, second) = (farms.get(&user_id)?, farms.get(&other_user_id)?);
+    first.iter()
+        .flat_map(|a| second.iter().map(move |b| distance_metres(*a, *b)))
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+async fn fetch_candidate(pool: &PgPool, id: Uuid) -> Result<DuplicateCandidate, AppError> {
+    sqlx::query_as::<_, DuplicateCandidate>(&format!("{} WHERE d.id = $1", CANDIDATE_QUERY))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Duplicate candidate".to_string()))
+}
+
+async fn user_exists(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+    let exists = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    Ok(exists)
+}
+
+/**
+ * Get Duplicate Queue
+ * Suspected duplicates, highest score first; pending pairs by default
+ **/
+async fn get_all_candidates(pool: web::Data<PgPool>, pagination: web::Query<Pagination>, filter: web::Query<DuplicateFilter>) -> Result<HttpResponse, AppError> {
+    let Pagination { limit, offset } = pagination.into_inner();
+    let filter = filter.into_inner();
+    let candidates = sqlx::query_as::<_, DuplicateCandidate>(
+        &format!(
+            r#"{}
+            WHERE d.status = $1
+              AND ($2::float8 IS NULL OR d.score >= $2)
+              AND ($3::uuid IS NULL OR d."userId" = $3 OR d."otherUserId" = $3)
+            ORDER BY d.score DESC, d."createdAt"
+            LIMIT $4 OFFSET $5"#,
+            CANDIDATE_QUERY
+        ),
+    )
+        .bind(filter.status.unwrap_or(DuplicateStatus::Pending))
+        .bind(filter.min_score)
+        .bind(filter.user_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(candidates))
+}
+
+async fn get_candidate(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let candidate = fetch_candidate(pool.get_ref(), id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(candidate))
+}
+
+/**
+ * Scan for Duplicates
+ * Scores every pair sharing a BVN or phone number or with similar names, adding farm
+ * proximity, and queues those at or above the threshold. Pending pairs are rescored;
+ * dismissed pairs are left alone. Pass `userId` to check a single farmer, e.g. after registration.
+ **/
+async fn scan_duplicates(pool: web::Data<PgPool>, config: web::Data<DuplicateConfig>, query: web::Query<DuplicateScanQuery>) -> Result<HttpResponse, AppError> {
+    let user_id = query.into_inner().user_id;
+    if let Some(user_id) = user_id {
+        if !user_exists(pool.get_ref(), user_id).await? {
+            return Err(AppError::NotFound("User".to_string()));
+        }
+    }
+
+    let pairs = sqlx::query_as::<_, Pair>(PAIR_QUERY)
+        .bind(user_id)
+        .bind(config.name_similarity)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    let mut user_ids: Vec<Uuid> = pairs.iter().flat_map(|pair| [pair.user_id, pair.other_user_id]).collect();
+    user_ids.sort();
+    user_ids.dedup();
+    let points = sqlx::query_as::<_, FarmPoint>(r#"SELECT "farmerId", latitude, longitude FROM "Farm" WHERE "farmerId" = ANY($1)"#)
+        .bind(&user_ids)
+        .fetch_all(pool.get_ref())
+        .await?;
+    let mut farms: HashMap<Uuid, Vec<[f64; 2]>> = HashMap::new();
+    for point in points {
+        farms.entry(point.farmer_id).or_default().push([point.longitude, point.latitude]);
+    }
+
+    let mut candidates = 0;
+    let mut tx = pool.begin().await?;
+    for pair in &pairs {
+        let nearest = nearest_farm_metres(&farms, pair.user_id, pair.other_user_id);
+        let (score, signals) = score_pair(pair, nearest, &config);
+        if score < config.min_score {
+            continue;
+        }
+        let result = sqlx::query(
+            r#"
+            INSERT INTO "DuplicateCandidate" ("userId", "otherUserId", score, signals)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT ("userId", "otherUserId") DO UPDATE
+            SET score = EXCLUDED.score, signals = EXCLUDED.signals, "updatedAt" = current_timestamp
+            WHERE "DuplicateCandidate".status = 'PENDING'
+            "#,
+        )
+            .bind(pair.user_id)
+            .bind(pair.other_user_id)
+            .bind(score)
+            .bind(SqlJson(&signals))
+            .execute(&mut *tx)
+            .await?;
+        candidates += result.rows_affected() as usize;
+    }
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(DuplicateScan { pairs_scored: pairs.len(), candidates }))
+}
+
+/**
+ * Dismiss a Duplicate
+ * Marks the pair as distinct people; later scans will not queue it again
+ **/
+async fn dismiss_candidate(pool: web::Data<PgPool>, id: web::Path<Uuid>, dismissal: Json<DismissDuplicate>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let dismissal = dismissal.into_inner();
+    if !user_exists(pool.get_ref(), dismissal.reviewer_id).await? {
+        return Err(AppError::NotFound("Reviewer".to_string()));
+    }
+    let reason = dismissal.reason.map(|reason| reason.trim().to_string()).filter(|reason| !reason.is_empty());
+
+    let mut tx = pool.begin().await?;
+    let status: DuplicateStatus = sqlx::query_scalar(r#"SELECT status FROM "DuplicateCandidate" WHERE id = $1 FOR UPDATE"#)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Duplicate candidate".to_string()))?;
+    if status != DuplicateStatus::Pending {
+        return Err(AppError::GenericError("Only pending pairs can be dismissed".to_string()));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE "DuplicateCandidate"
+        SET status = 'DISMISSED', "reviewerId" = $2, "reviewedAt" = current_timestamp, reason = $3, "updatedAt" = current_timestamp
+        WHERE id = $1
+        "#,
+    )
+        .bind(id)
+        .bind(dismissal.reviewer_id)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    let candidate = fetch_candidate(pool.get_ref(), id).await?;
+    Ok(HttpResponse::Ok().json(candidate))
+}
+
+// Transfers, leases, agent assignments or lease applications between the two mean they are
+// different people, and would violate the tables' own checks once merged
+async fn users_are_linked(tx: &mut Transaction<'_, Postgres>, user_id: Uuid, other_user_id: Uuid) -> Result<bool, AppError> {
+    let linked = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM "FarmTransfer"
+            WHERE ("fromOwnerId" = $1 AND "toOwnerId" = $2) OR ("fromOwnerId" = $2 AND "toOwnerId" = $1)
+        ) OR EXISTS (
+            SELECT 1 FROM "Lease"
+            WHERE ("landlordId" = $1 AND "tenantId" = $2) OR ("landlordId" = $2 AND "tenantId" = $1)
+        ) OR EXISTS (
+            SELECT 1 FROM "AgentAssignment"
+            WHERE ("agentId" = $1 AND "farmerId" = $2) OR ("agentId" = $2 AND "farmerId" = $1)
+        ) OR EXISTS (
+            SELECT 1
+            FROM "LeaseApplication" a
+            JOIN "LandListing" l ON l.id = a."listingId"
+            JOIN "Farm" f ON f.id = l."farmId"
+            WHERE a.status = 'PENDING'
+              AND ((a."applicantId" = $1 AND f."farmerId" = $2) OR (a."applicantId" = $2 AND f."farmerId" = $1))
+        )
+        "#,
+    )
+        .bind(user_id)
+        .bind(other_user_id)
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(linked)
+}
+
+/**
+ * Keeps the survivor's profile, filling its blanks from the merged user's and moving the
+ * merged profile's KYC history across. A merged user's profile moves over whole if the
+ * survivor has none.
+ **/
+async fn merge_profiles(tx: &mut Transaction<'_, Postgres>, survivor_id: Uuid, merged_id: Uuid, moved: &mut BTreeMap<String, i64>) -> Result<(), AppError> {
+    let profile_of = |user_id: Uuid| {
+        sqlx::query_scalar::<_, Uuid>(r#"SELECT id FROM "Profile" WHERE "userId" = $1 FOR UPDATE"#).bind(user_id)
+    };
+    let survivor_profile = profile_of(survivor_id).fetch_optional(&mut **tx).await?;
+    let merged_profile = profile_of(merged_id).fetch_optional(&mut **tx).await?;
+
+    match (survivor_profile, merged_profile) {
+        (Some(survivor_profile), Some(merged_profile)) => {
+            outbox::record_current(tx, DomainEventType::ProfileDeleted, merged_profile).await?;
+            // Phone numbers are unique, so the merged profile gives its number up before the
+            // survivor can take it
+            let (phone_number, phone_carrier, phone_line_type): (Option<String>, Option<String>, Option<PhoneLineType>) = sqlx::query_as(
+                r#"
+                UPDATE "Profile" p
+                SET "phoneNumber" = NULL, "phoneCarrier" = NULL, "phoneLineType" = NULL
+                FROM (SELECT "phoneNumber", "phoneCarrier", "phoneLineType" FROM "Profile" WHERE id = $1) old
+                WHERE p.id = $1
+                RETURNING old."phoneNumber", old."phoneCarrier", old."phoneLineType"
+                "#,
+            )
+                .bind(merged_profile)
+                .fetch_one(&mut **tx)
+                .await?;
+            sqlx::query(
+                r#"
+                UPDATE "Profile" s
+                SET bio = coalesce(s.bio, m.bio),
+                    "accountNumber" = coalesce(s."accountNumber", m."accountNumber"),
+                    "identityNumber" = coalesce(s."identityNumber", m."identityNumber"),
+                    "phoneNumber" = coalesce(s."phoneNumber", $3),
+                    "phoneCarrier" = CASE WHEN s."phoneNumber" IS NULL THEN $4 ELSE s."phoneCarrier" END,
+                    "phoneLineType" = CASE WHEN s."phoneNumber" IS NULL THEN $5 ELSE s."phoneLineType" END,
+                    "updatedAt" = current_timestamp
+                FROM "Profile" m
+                WHERE s.id = $1 AND m.id = $2
+                "#,
+            )
+                .bind(survivor_profile)
+                .bind(merged_profile)
+                .bind(phone_number)
+                .bind(phone_carrier)
+                .bind(phone_line_type)
+                .execute(&mut **tx)
+                .await?;
+            outbox::record_current(tx, DomainEventType::ProfileUpdated, survivor_profile).await?;
+            let events = sqlx::query(r#"UPDATE "KycEvent" SET "profileId" = $1 WHERE "profileId" = $2"#)
+                .bind(survivor_profile)
+                .bind(merged_profile)
+                .execute(&mut **tx)
+                .await?;
+            sqlx::query(r#"DELETE FROM "Profile" WHERE id = $1"#)
+                .bind(merged_profile)
+                .execute(&mut **tx)
+                .await?;
+            moved.insert("Profile.userId".to_string(), 1);
+            if events.rows_affected() > 0 {
+                moved.insert("KycEvent.profileId".to_string(), events.rows_affected() as i64);
+            }
+        }
+        (None, Some(merged_profile)) => {
+            sqlx::query(r#"UPDATE "Profile" SET "userId" = $1, "updatedAt" = current_timestamp WHERE id = $2"#)
+                .bind(survivor_id)
+                .bind(merged_profile)
+                .execute(&mut **tx)
+                .await?;
+            outbox::record_current(tx, DomainEventType::ProfileUpdated, merged_profile).await?;
+            moved.insert("Profile.userId".to_string(), 1);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/**
+ * Moves everything the merged user owns onto the survivor and deletes the merged user.
+ * Where both users hold a slot that only one may (current membership of a group, current
+ * agent, pending application to a listing) the survivor's is kept and the merged user's is closed.
+ **/
+async fn merge_users(tx: &mut Transaction<'_, Postgres>, survivor_id: Uuid, merged_id: Uuid) -> Result<BTreeMap<String, i64>, AppError> {
+    let mut moved = BTreeMap::new();
+    merge_profiles(tx, survivor_id, merged_id, &mut moved).await?;
+
+    sqlx::query(
+        r#"
+        UPDATE "CooperativeMembership" m
+        SET "leftOn" = GREATEST(m."joinedOn", current_date)
+        WHERE m."userId" = $2 AND m."leftOn" IS NULL
+          AND EXISTS (
+              SELECT 1 FROM "CooperativeMembership" s
+              WHERE s."cooperativeId" = m."cooperativeId" AND s."userId" = $1 AND s."leftOn" IS NULL
+          )
+        "#,
+    )
+        .bind(survivor_id)
+        .bind(merged_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(
+        r#"
+        UPDATE "AgentAssignment"
+        SET "endedOn" = GREATEST("startedOn", current_date)
+        WHERE "farmerId" = $2 AND "endedOn" IS NULL
+          AND EXISTS (SELECT 1 FROM "AgentAssignment" WHERE "farmerId" = $1 AND "endedOn" IS NULL)
+        "#,
+    )
+        .bind(survivor_id)
+        .bind(merged_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(
+        r#"
+        UPDATE "LeaseApplication" m
+        SET status = 'WITHDRAWN', "decisionReason" = 'Applicant merged into a duplicate record', "updatedAt" = current_timestamp
+        WHERE m."applicantId" = $2 AND m.status = 'PENDING'
+          AND EXISTS (
+              SELECT 1 FROM "LeaseApplication" s
+              WHERE s."listingId" = m."listingId" AND s."applicantId" = $1 AND s.status = 'PENDING'
+          )
+        "#,
+    )
+        .bind(survivor_id)
+        .bind(merged_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let moved_farms: Vec<Uuid> = sqlx::query_scalar(r#"SELECT id FROM "Farm" WHERE "farmerId" = $1 ORDER BY id"#)
+        .bind(merged_id)
+        .fetch_all(&mut **tx)
+        .await?;
+    for (table, column) in USER_REFERENCES {
+        let result = sqlx::query(&format!(r#"UPDATE "{}" SET "{}" = $1 WHERE "{}" = $2"#, table, column, column))
+            .bind(survivor_id)
+            .bind(merged_id)
+            .execute(&mut **tx)
+            .await?;
+        if result.rows_affected() > 0 {
+            moved.insert(format!("{}.{}", table, column), result.rows_affected() as i64);
+        }
+    }
+
+    for farm_id in moved_farms {
+        outbox::record_current(tx, DomainEventType::FarmUpdated, farm_id).await?;
+    }
+
+    // Other pairs involving the merged user go with it; the next scan scores them against the survivor
+    outbox::record_current(tx, DomainEventType::UserDeleted, merged_id).await?;
+    sqlx::query(r#"DELETE FROM "User" WHERE id = $1"#)
+        .bind(merged_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(moved)
+}
+
+/**
+ * Merge a Duplicate
+ * Moves the other user's profile, farms and history onto the survivor and deletes the
+ * other user, all in one transaction. The merge is recorded in the merge log.
+ **/
+async fn merge_candidate(pool: web::Data<PgPool>, id: web::Path<Uuid>, merge: Json<MergeDuplicate>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let merge = merge.into_inner();
+    if !user_exists(pool.get_ref(), merge.reviewer_id).await? {
+        return Err(AppError::NotFound("Reviewer".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(r#"SELECT id FROM "DuplicateCandidate" WHERE id = $1 FOR UPDATE"#)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Duplicate candidate".to_string()))?;
+    let candidate = sqlx::query_as::<_, DuplicateCandidate>(&format!("{} WHERE d.id = $1", CANDIDATE_QUERY))
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if candidate.status != DuplicateStatus::Pending {
+        return Err(AppError::GenericError("Only pending pairs can be merged".to_string()));
+    }
+    let (merged_id, merged_first_name, merged_last_name) = if merge.survivor_id == candidate.user_id {
+        (candidate.other_user_id, candidate.other_first_name.clone(), candidate.other_last_name.clone())
+    } else if merge.survivor_id == candidate.other_user_id {
+        (candidate.user_id, candidate.first_name.clone(), candidate.last_name.clone())
+    } else {
+        return Err(AppError::GenericError("Survivor must be one of the pair".to_string()));
+    };
+    if merge.reviewer_id == candidate.user_id || merge.reviewer_id == candidate.other_user_id {
+        return Err(AppError::GenericError("Reviewers cannot merge their own records".to_string()));
+    }
+
+    sqlx::query(r#"SELECT id FROM "User" WHERE id = ANY($1) ORDER BY id FOR UPDATE"#)
+        .bind(vec![candidate.user_id, candidate.other_user_id])
+        .fetch_all(&mut *tx)
+        .await?;
+    if users_are_linked(&mut tx, candidate.user_id, candidate.other_user_id).await? {
+        return Err(AppError::GenericError(
+            "The two users are parties to a farm transfer, lease, lease application or agent assignment with each other and cannot be merged".to_string(),
+        ));
+    }
+
+    let moved = merge_users(&mut tx, merge.survivor_id, merged_id).await?;
+    let record = sqlx::query_as::<_, UserMerge>(
+        r#"
+        INSERT INTO "UserMerge" ("survivorId", "mergedUserId", "mergedFirstName", "mergedLastName", score, signals, moved, "reviewerId")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+        .bind(merge.survivor_id)
+        .bind(merged_id)
+        .bind(merged_first_name)
+        .bind(merged_last_name)
+        .bind(candidate.score)
+        .bind(&candidate.signals)
+        .bind(SqlJson(&moved))
+        .bind(merge.reviewer_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Created().json(record))
+}
+
+/**
+ * Get Merge Log
+ * Completed merges, newest first
+ **/
+async fn get_all_merges(pool: web::Data<PgPool>, filter: web::Query<UserMergeFilter>) -> Result<HttpResponse, AppError> {
+    let merges = sqlx::query_as::<_, UserMerge>(
+        r#"SELECT * FROM "UserMerge" WHERE ($1::uuid IS NULL OR "survivorId" = $1) ORDER BY "createdAt" DESC"#,
+    )
+        .bind(filter.survivor_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(merges))
+}
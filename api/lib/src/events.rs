@@ -0,0 +1,448 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+use actix_web::{web::{self, ServiceConfig, Bytes}, HttpRequest, HttpResponse};
+use actix_web::http::{header, StatusCode};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use deadpool_redis::{redis, Connection, Pool};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+use shared::models::{
+    DomainEntityType,
+    EventStreamFilter
+};
+use tracing::{error, warn};
+use crate::api_key;
+use crate::outbox::{self, OutboxConfig, StreamEntry};
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    Unauthorized(String),
+    Forbidden(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        let mut response = HttpResponse::build(status);
+        if status == StatusCode::UNAUTHORIZED {
+            response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        response.json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Event stream query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+/**
+ * Live event stream settings, read from EVENTS_KEEP_ALIVE_SECS, EVENTS_BUFFER, EVENTS_PAGE_SIZE
+ * and EVENTS_RETRY_MS. Events are read from OUTBOX_STREAM.
+ **/
+#[derive(Debug, Clone)]
+pub struct EventsConfig {
+    pub stream: String,
+    // Quiet connections get a comment this often so proxies keep them open
+    pub keep_alive: Duration,
+    // Events held for slow clients before they fall back to reading Redis
+    pub buffer: usize,
+    // Entries read from Redis at a time when a client catches up
+    pub page_size: i64,
+    // Reconnection delay suggested to clients
+    pub retry: Duration,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        EventsConfig {
+            stream: OutboxConfig::default().stream,
+            keep_alive: Duration::from_secs(15),
+            buffer: 1024,
+            page_size: 100,
+            retry: Duration::from_secs(5),
+        }
+    }
+}
+
+impl EventsConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|value| value.parse().ok())
+        }
+        let default = EventsConfig::default();
+        EventsConfig {
+            stream: OutboxConfig::from_env().stream,
+            keep_alive: var("EVENTS_KEEP_ALIVE_SECS").map(Duration::from_secs).unwrap_or(default.keep_alive),
+            buffer: var("EVENTS_BUFFER").unwrap_or(default.buffer),
+            page_size: var("EVENTS_PAGE_SIZE").unwrap_or(default.page_size),
+            retry: var("EVENTS_RETRY_MS").map(Duration::from_millis).unwrap_or(default.retry),
+        }
+    }
+}
+
+/**
+ * Hands the events `run` reads from Redis to every open stream of this instance, so clients
+ * share one Redis connection instead of each blocking one of the pool's.
+ **/
+#[derive(Clone)]
+pub struct EventHub {
+    sender: broadcast::Sender<Arc<StreamEntry>>,
+    config: EventsConfig,
+}
+
+impl EventHub {
+    pub fn new(config: EventsConfig) -> Self {
+        let (sender, _) = broadcast::channel(config.buffer.max(1));
+        EventHub { sender, config }
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/events")
+                    .route("/stream", web::get().to(stream_events))
+    );
+}
+
+// Stream entry ids are `<milliseconds>-<sequence>`
+fn parse_entry_id(id: &str) -> Option<(u64, u64)> {
+    let (millis, sequence) = id.split_once('-')?;
+    Some((millis.parse().ok()?, sequence.parse().ok()?))
+}
+
+// Id of the newest entry of the stream, so reading after it only returns new ones
+async fn latest_entry_id(conn: &mut Connection, stream: &str) -> Result<String, redis::RedisError> {
+    // [[id, [field, value, ...]]], or empty for an empty stream
+    let newest: Vec<Vec<redis::Value>> = redis::cmd("XREVRANGE")
+        .arg(stream)
+        .arg("+")
+        .arg("-")
+        .arg("COUNT")
+        .arg(1)
+        .query_async(conn)
+        .await?;
+    match newest.first().and_then(|entry| entry.first()) {
+        Some(id) => redis::from_redis_value(id),
+        None => Ok("0-0".to_string()),
+    }
+}
+
+/**
+ * Tail the event stream and pass each entry to the open streams. After a Redis error the tail
+ * resumes from the last entry read, so none are skipped.
+ **/
+pub async fn run(redis: Pool, hub: EventHub) {
+    let mut last_id: Option<String> = None;
+    loop {
+        let mut conn = match redis.get().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("Redis unavailable for the event stream: {:?}", e);
+                actix_web::rt::time::sleep(hub.config.retry).await;
+                continue;
+            }
+        };
+        loop {
+            let after = match &last_id {
+                Some(id) => id.clone(),
+                None => match latest_entry_id(&mut conn, &hub.config.stream).await {
+                    Ok(id) => id,
+                    Err(e) => {
+                        warn!("Error reading the event stream: {:?}", e);
+                        break;
+                    }
+                },
+            };
+            last_id = Some(after.clone());
+            match outbox::read_stream(&mut conn, &hub.config.stream, &after, hub.config.page_size, Some(hub.config.keep_alive)).await {
+                Ok(entries) => {
+                    for entry in entries {
+                        last_id = Some(entry.id.clone());
+                        // Fails only when no stream is open
+                        let _ = hub.sender.send(Arc::new(entry));
+                    }
+                }
+                Err(e) => {
+                    warn!("Error reading the event stream: {:?}", e);
+                    break;
+                }
+            }
+        }
+        actix_web::rt::time::sleep(hub.config.retry).await;
+    }
+}
+
+// One open stream: what its key and filters let through and how far it has read
+struct Subscriber {
+    pool: PgPool,
+    redis: Pool,
+    config: EventsConfig,
+    receiver: broadcast::Receiver<Arc<StreamEntry>>,
+    key_id: Uuid,
+    entity_types: Vec<DomainEntityType>,
+    // Empty for every state
+    allowed_state_ids: Vec<Uuid>,
+    state_id: Option<Uuid>,
+    last_id: String,
+    last_position: (u64, u64),
+    // Last id the client was sent
+    announced_id: String,
+    // Entries read but not yet written
+    pending: VecDeque<StreamEntry>,
+    // Set while entries after `last_id` may be in Redis and not in `pending` or the channel
+    catching_up: bool,
+    started: bool,
+}
+
+impl Subscriber {
+    fn visible(&self, entry: &StreamEntry) -> bool {
+        let Some(event) = &entry.event else { return false };
+        if !self.entity_types.contains(&event.entity_type) {
+            return false;
+        }
+        if !self.allowed_state_ids.is_empty()
+            && !event.state_ids.iter().any(|state_id| self.allowed_state_ids.contains(state_id)) {
+            return false;
+        }
+        match self.state_id {
+            Some(state_id) => event.state_ids.contains(&state_id),
+            None => true,
+        }
+    }
+
+    fn frame(&self, entry: &StreamEntry) -> Option<String> {
+        let event = entry.event.as_ref()?;
+        let envelope = serde_json::to_value(event).ok()?;
+        Some(format!(
+            "id: {}\nevent: {}\ndata: {}\n\n",
+            entry.id,
+            envelope["eventType"].as_str().unwrap_or_default(),
+            envelope,
+        ))
+    }
+
+    async fn key_active(&self) -> bool {
+        let active = sqlx::query_scalar::<_, bool>(r#"SELECT "revokedAt" IS NULL FROM "ApiKey" WHERE id = $1"#)
+            .bind(self.key_id)
+            .fetch_optional(&self.pool)
+            .await;
+        match active {
+            Ok(active) => active.unwrap_or(false),
+            Err(e) => {
+                error!("Event stream query failed: {:?}", e);
+                false
+            }
+        }
+    }
+
+    async fn read_missed(&self) -> Result<Vec<StreamEntry>, String> {
+        let mut conn = self.redis.get().await.map_err(|e| e.to_string())?;
+        outbox::read_stream(&mut conn, &self.config.stream, &self.last_id, self.config.page_size, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /**
+     * The next chunk of the response, or None to close it. Entries come from Redis while
+     * catching up and from the hub after that; ids at or before `last_id` are dropped so an
+     * entry seen both ways is only written once.
+     **/
+    async fn next_frame(&mut self) -> Option<String> {
+        if !self.started {
+            self.started = true;
+            return Some(format!("retry: {}\n\n", self.config.retry.as_millis()));
+        }
+        loop {
+            if let Some(entry) = self.pending.pop_front() {
+                let Some(position) = parse_entry_id(&entry.id) else { continue };
+                if position <= self.last_position {
+                    continue;
+                }
+                self.last_position = position;
+                self.last_id = entry.id.clone();
+                if self.visible(&entry) {
+                    if let Some(frame) = self.frame(&entry) {
+                        self.announced_id = entry.id;
+                        return Some(frame);
+                    }
+                }
+                continue;
+            }
+
+            if self.catching_up {
+                match self.read_missed().await {
+                    Ok(entries) => {
+                        self.catching_up = entries.len() as i64 >= self.config.page_size;
+                        self.pending.extend(entries);
+                        continue;
+                    }
+                    Err(e) => {
+                        // The client reconnects with its Last-Event-ID and misses nothing
+                        warn!("Error reading missed events: {}", e);
+                        return None;
+                    }
+                }
+            }
+
+            match actix_web::rt::time::timeout(self.config.keep_alive, self.receiver.recv()).await {
+                Ok(Ok(entry)) => self.pending.push_back(entry.as_ref().clone()),
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!("Event stream client fell {} events behind, reading them from Redis", skipped);
+                    self.catching_up = true;
+                }
+                Ok(Err(RecvError::Closed)) => return None,
+                Err(_) => {
+                    if !self.key_active().await {
+                        return None;
+                    }
+                    if self.announced_id == self.last_id {
+                        return Some(": keep-alive\n\n".to_string());
+                    }
+                    // An event without data only moves the client's last event id past the
+                    // entries it was not shown, so a reconnect does not read them again
+                    self.announced_id = self.last_id.clone();
+                    return Some(format!(": keep-alive\nid: {}\n\n", self.last_id));
+                }
+            }
+        }
+    }
+}
+
+fn parse_entity_types(entity_types: &str) -> Result<Vec<DomainEntityType>, AppError> {
+    entity_types.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            serde_json::from_value(serde_json::Value::String(name.to_uppercase()))
+                .map_err(|_| AppError::GenericError(format!("Unknown entity type {}", name)))
+        })
+        .collect()
+}
+
+/**
+ * Stream Events
+ * Server-sent events for user, profile and farm changes, named after the event type with the
+ * event as data. Authenticate with an api key as a Bearer token, or as `access_token` where
+ * headers cannot be set, e.g. EventSource; query strings end up in access logs. Only the
+ * entity types and states the key allows are sent, narrowed by `entityTypes` and `stateId`.
+ * A client reconnecting with Last-Event-ID (or `lastEventId`) first gets what it missed, as
+ * far back as the Redis stream is kept.
+ **/
+async fn stream_events(req: HttpRequest, pool: web::Data<PgPool>, redis: web::Data<Pool>, hub: web::Data<EventHub>, filter: web::Query<EventStreamFilter>) -> Result<HttpResponse, AppError> {
+    let filter = filter.into_inner();
+    let header_token = api_key::bearer_token(&req);
+    let from_query = header_token.is_none() && filter.access_token.is_some();
+    let token = header_token
+        .or(filter.access_token)
+        .ok_or_else(|| AppError::Unauthorized("An api key is required".to_string()))?;
+    let key = api_key::authenticate(pool.get_ref(), &token)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid or revoked api key".to_string()))?;
+    if from_query {
+        warn!("Api key {} was sent as access_token, so it is in the access logs; send it as a Bearer token where the client allows", key.key_prefix);
+    }
+
+    let entity_types = match filter.entity_types.as_deref() {
+        Some(requested) => {
+            let requested = parse_entity_types(requested)?;
+            if let Some(denied) = requested.iter().find(|entity_type| !key.entity_types.contains(entity_type)) {
+                return Err(AppError::Forbidden(format!("The api key may not read {:?} events", denied)));
+            }
+            requested
+        }
+        None => key.entity_types.0.clone(),
+    };
+    if let Some(state_id) = filter.state_id {
+        if !key.state_ids.is_empty() && !key.state_ids.contains(&state_id) {
+            return Err(AppError::Forbidden("The api key may not read events of this state".to_string()));
+        }
+    }
+
+    let last_event_id = req.headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or(filter.last_event_id);
+    if let Some(last_event_id) = &last_event_id {
+        if parse_entry_id(last_event_id).is_none() {
+            return Err(AppError::GenericError("Last-Event-ID must be an id sent by this stream".to_string()));
+        }
+    }
+
+    // Subscribe before looking at Redis so nothing added in between is lost
+    let receiver = hub.sender.subscribe();
+    let (last_id, catching_up) = match last_event_id {
+        Some(last_event_id) => (last_event_id, true),
+        None => {
+            let latest = match redis.get().await {
+                Ok(mut conn) => latest_entry_id(&mut conn, &hub.config.stream).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match latest {
+                Ok(latest) => (latest, false),
+                Err(e) => {
+                    error!("Event stream unavailable: {}", e);
+                    return Ok(HttpResponse::ServiceUnavailable().json("Event stream unavailable"));
+                }
+            }
+        }
+    };
+
+    let subscriber = Subscriber {
+        pool: pool.get_ref().clone(),
+        redis: redis.get_ref().clone(),
+        config: hub.config.clone(),
+        receiver,
+        key_id: key.id,
+        entity_types,
+        allowed_state_ids: key.state_ids,
+        state_id: filter.state_id,
+        last_position: parse_entry_id(&last_id).unwrap_or_default(),
+        announced_id: last_id.clone(),
+        last_id,
+        pending: VecDeque::new(),
+        catching_up,
+        started: false,
+    };
+    let body = futures_util::stream::unfold(subscriber, |mut subscriber| async move {
+        subscriber.next_frame()
+            .await
+            .map(|frame| (Ok::<_, Infallible>(Bytes::from(frame)), subscriber))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Keeps the compression middleware and proxies from holding events back
+        .insert_header((header::CONTENT_ENCODING, "identity"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body))
+}
@@ -1,25 +1,39 @@
 use std::fmt::{Display, Formatter};
-use actix_web::{web::{self, ServiceConfig}, HttpResponse,web::Json};
-use actix_web::error::BlockingError;
-use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::{web::{self, ServiceConfig}, HttpRequest, HttpResponse,web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, types::Json as SqlxJson};
 use actix_web::http::StatusCode;
 use uuid::Uuid;
+use shared::geo::Polygon;
 use shared::models::{
     Pagination,
+    AsOfQuery,
+    AreaUnit,
+    AreaUnitQuery,
     Farm,
+    FarmFilter,
+    FarmValuation,
     CreateFarm,
-    UpdateFarm
+    UpdateFarm,
+    DomainEventType
 };
 use tracing::error;
+use crate::{history, outbox, plot, reference};
 
 #[derive(Debug)]
 pub enum AppError {
     SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
     OtherError,
 }
 impl Display for AppError {
-    fn fmt(&self, _f: &mut Formatter<'_>) -> std::fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+            AppError::OtherError => write!(f, "An unknown error occurred"),
+        }
     }
 }
 
@@ -27,9 +41,44 @@ impl actix_web::error::ResponseError for AppError {
     fn status_code(&self) -> StatusCode {
         match *self {
             AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::OtherError => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Farm query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+/**
+ * Unit to report areas in: `?unit=` first, then the Accept-Language region, falling back
+ * to acres like bare numbers sent in
+ **/
+pub fn requested_area_unit(req: &HttpRequest, query: &AreaUnitQuery) -> AreaUnit {
+    if let Some(unit) = query.unit {
+        return unit;
+    }
+
+    let language = req.headers()
+        .get("Accept-Language")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|tag| tag.split(';').next().unwrap_or("").trim().to_ascii_lowercase());
+
+    match language.as_deref() {
+        None | Some("") | Some("*") | Some("en-us") | Some("en-lr") | Some("my") | Some("my-mm") => AreaUnit::Acre,
+        _ => AreaUnit::Hectare,
+    }
 }
 
 pub fn service(cfg: &mut ServiceConfig) {
@@ -39,27 +88,39 @@ pub fn service(cfg: &mut ServiceConfig) {
                     .route("/farm", web::post().to(create_farm))
                     .route("/farm/{id}", web::put().to(update_farm))
                     .route("/farm/{id}", web::delete().to(delete_farm))
+                    .route("/farm/{id}/valuations", web::get().to(get_farm_valuations))
     );
 }
 
-async fn get_all_farms(pool: web::Data<PgPool>, pagination: web::Query<Pagination>) -> Result<HttpResponse, AppError> {
+async fn get_all_farms(req: HttpRequest, pool: web::Data<PgPool>, pagination: web::Query<Pagination>, filter: web::Query<FarmFilter>, unit: web::Query<AreaUnitQuery>) -> Result<HttpResponse, AppError> {
+    let unit = requested_area_unit(&req, &unit);
     let Pagination { limit, offset } = pagination.into_inner();
+    let FarmFilter { country_id, state_id, lga_id } = filter.into_inner();
 
     let farms_result = sqlx::query_as::<_, Farm>(
         r#"
         SELECT *
         FROM "Farm"
+        WHERE ($3::uuid IS NULL OR "countryId" = $3)
+          AND ($4::uuid IS NULL OR "stateId" = $4)
+          AND ($5::uuid IS NULL OR "lgaId" = $5)
         ORDER BY "createdAt"
         LIMIT $1 OFFSET $2
         "#,
     )
         .bind(limit)
         .bind(offset)
+        .bind(country_id)
+        .bind(state_id)
+        .bind(lga_id)
         .fetch_all(pool.get_ref())
         .await;
 
     match farms_result {
-        Ok(farms) => Ok(HttpResponse::Ok().json(farms)),
+        Ok(farms) => {
+            let farms: Vec<Farm> = farms.into_iter().map(|farm| farm.in_unit(unit)).collect();
+            Ok(HttpResponse::Ok().json(farms))
+        }
         Err(e) => {
             error!("Error getting all farms: {:?}", e);
             Err(AppError::SqlError(e))
@@ -67,22 +128,35 @@ async fn get_all_farms(pool: web::Data<PgPool>, pagination: web::Query<Paginatio
     }
 }
 
-async fn get_farm(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+/**
+ * Get Farm
+ * `?as_of=` returns the farm as it was at that time
+ **/
+async fn get_farm(req: HttpRequest, pool: web::Data<PgPool>, id: web::Path<Uuid>, unit: web::Query<AreaUnitQuery>, as_of: web::Query<AsOfQuery>) -> Result<HttpResponse, AppError> {
+    let unit = requested_area_unit(&req, &unit);
     let id = id.into_inner();
+    let source = match as_of.as_of {
+        Some(_) => history::as_of_source("Farm", 2, Some(1)),
+        None => r#""Farm""#.to_string(),
+    };
 
-    let farm_result = sqlx::query_as::<_, Farm>(
+    let sql = format!(
         r#"
         SELECT *
-        FROM "Farm"
+        FROM {}
         WHERE id = $1
         "#,
-    )
-        .bind(id)
-        .fetch_one(pool.get_ref())
-        .await;
+        source
+    );
+    let mut farm_query = sqlx::query_as::<_, Farm>(&sql).bind(id);
+    if let Some(as_of) = as_of.as_of {
+        farm_query = farm_query.bind(as_of);
+    }
+    let farm_result = farm_query.fetch_optional(pool.get_ref()).await;
 
     match farm_result {
-        Ok(farm) => Ok(HttpResponse::Ok().json(farm)),
+        Ok(Some(farm)) => Ok(HttpResponse::Ok().json(farm.in_unit(unit))),
+        Ok(None) => Err(AppError::NotFound("Farm".to_string())),
         Err(e) => {
             error!("Error getting farm: {:?}", e);
             Err(AppError::SqlError(e))
@@ -93,35 +167,52 @@ async fn get_farm(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpRe
 /**
  * Create Farm
  **/
-async fn create_farm(pool: web::Data<PgPool>, farm: Json<CreateFarm>) -> Result<HttpResponse, BlockingError> {
+async fn create_farm(req: HttpRequest, pool: web::Data<PgPool>, farm: Json<CreateFarm>, unit: web::Query<AreaUnitQuery>) -> Result<HttpResponse, AppError> {
+    let unit = requested_area_unit(&req, &unit);
     let farm = farm.into_inner();
 
+    if let Some(Err(e)) = farm.boundary.as_ref().map(|boundary| boundary.validate()) {
+        return Ok(HttpResponse::BadRequest().json(format!("Invalid boundary: {}", e)));
+    }
+    if let Some(mismatch) = reference::region_mismatch(pool.get_ref(), farm.country_id, farm.state_id, farm.lga_id).await? {
+        return Err(AppError::GenericError(mismatch));
+    }
+
+    let mut tx = pool.begin().await?;
     let farm_result = sqlx::query_as::<_, Farm>(
         r#"
-        INSERT INTO "Farm" ("farmName", acreage, state, locality, "hasDrainageTile", "landValue", "isIrrigated", ownership, "availablePortion", country, "farmerId", latitude, longitude, "farmSite")
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        INSERT INTO "Farm" ("farmName", acreage, state, locality, "hasDrainageTile", "landValue", "isIrrigated", ownership, "availablePortion", country, "farmerId", latitude, longitude, "farmSite", "countryId", "stateId", "lgaId", boundary)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
         RETURNING *
         "#,
     )
         .bind(farm.farm_name)
-        .bind(farm.acreage)
+        .bind(farm.acreage.to_hectares())
         .bind(farm.state)
         .bind(farm.locality)
         .bind(farm.has_drainage_tile)
         .bind(farm.land_value)
         .bind(farm.is_irrigated)
         .bind(farm.ownership)
-        .bind(farm.available_portion)
+        .bind(farm.available_portion.to_hectares())
         .bind(farm.country)
         .bind(farm.farmer_id)
         .bind(farm.latitude)
         .bind(farm.longitude)
         .bind(farm.farm_site)
-        .fetch_one(pool.get_ref())
+        .bind(farm.country_id)
+        .bind(farm.state_id)
+        .bind(farm.lga_id)
+        .bind(farm.boundary.map(SqlxJson))
+        .fetch_one(&mut *tx)
         .await;
 
     match farm_result {
-        Ok(farm) => Ok(HttpResponse::Created().json(farm)),
+        Ok(farm) => {
+            outbox::record(&mut tx, DomainEventType::FarmCreated, farm.id, &farm).await?;
+            tx.commit().await?;
+            Ok(HttpResponse::Created().json(farm.in_unit(unit)))
+        }
         Err(e) => {
             error!("Error creating farm: {:?}", e);
             Ok(HttpResponse::InternalServerError().json("Internal server error"))
@@ -131,10 +222,40 @@ async fn create_farm(pool: web::Data<PgPool>, farm: Json<CreateFarm>) -> Result<
 
 /**
  * Update Farm
+ * The owner is changed with a transfer, see `title::create_transfer`. A new acreage or
+ * boundary must still hold the farm's plots.
  **/
-async fn update_farm(pool: web::Data<PgPool>, farm: Json<UpdateFarm>) -> Result<HttpResponse, BlockingError> {
+async fn update_farm(req: HttpRequest, pool: web::Data<PgPool>, farm: Json<UpdateFarm>, unit: web::Query<AreaUnitQuery>) -> Result<HttpResponse, AppError> {
+    let unit = requested_area_unit(&req, &unit);
     let farm = farm.into_inner();
 
+    if let Some(Err(e)) = farm.boundary.as_ref().map(|boundary| boundary.validate()) {
+        return Ok(HttpResponse::BadRequest().json(format!("Invalid boundary: {}", e)));
+    }
+    let mut tx = pool.begin().await?;
+    let current: Option<(f64, Option<SqlxJson<Polygon>>)> = sqlx::query_as(
+        r#"
+        SELECT acreage, boundary
+        FROM "Farm"
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+        .bind(farm.id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let (acreage, boundary) = current.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+    if let Some(mismatch) = reference::region_mismatch(&mut *tx, farm.country_id, farm.state_id, farm.lga_id).await? {
+        return Err(AppError::GenericError(mismatch));
+    }
+
+    let new_acreage = farm.acreage.map(|area| area.to_hectares()).unwrap_or(acreage);
+    if new_acreage != acreage || farm.boundary.as_ref() != boundary.as_ref().map(|SqlxJson(boundary)| boundary) {
+        if let Some(misfit) = plot::plots_misfit(&mut tx, farm.id, new_acreage, farm.boundary.as_ref()).await? {
+            return Err(AppError::GenericError(misfit));
+        }
+    }
+
     let farm_result = sqlx::query_as::<_, Farm>(
         r#"
         UPDATE "Farm"
@@ -148,34 +269,44 @@ async fn update_farm(pool: web::Data<PgPool>, farm: Json<UpdateFarm>) -> Result<
             ownership = $8,
             "availablePortion" = $9,
             country = $10,
-            "farmerId" = $11,
-            latitude = $12,
-            longitude = $13,
-            "farmSite" = $14
-        WHERE id = $15
+            latitude = $11,
+            longitude = $12,
+            "farmSite" = $13,
+            "countryId" = $14,
+            "stateId" = $15,
+            "lgaId" = $16,
+            boundary = $17
+        WHERE id = $18
         RETURNING *
         "#,
     )
         .bind(farm.farm_name)
-        .bind(farm.acreage)
+        .bind(farm.acreage.map(|area| area.to_hectares()))
         .bind(farm.state)
         .bind(farm.locality)
         .bind(farm.has_drainage_tile)
         .bind(farm.land_value)
         .bind(farm.is_irrigated)
         .bind(farm.ownership)
-        .bind(farm.available_portion)
+        .bind(farm.available_portion.map(|area| area.to_hectares()))
         .bind(farm.country)
-        .bind(farm.farmer_id)
         .bind(farm.latitude)
         .bind(farm.longitude)
         .bind(farm.farm_site)
+        .bind(farm.country_id)
+        .bind(farm.state_id)
+        .bind(farm.lga_id)
+        .bind(farm.boundary.map(SqlxJson))
         .bind(farm.id)
-        .fetch_one(pool.get_ref())
+        .fetch_one(&mut *tx)
         .await;
 
     match farm_result {
-        Ok(farm) => Ok(HttpResponse::Ok().json(farm)),
+        Ok(farm) => {
+            outbox::record(&mut tx, DomainEventType::FarmUpdated, farm.id, &farm).await?;
+            tx.commit().await?;
+            Ok(HttpResponse::Ok().json(farm.in_unit(unit)))
+        }
         Err(e) => {
             error!("Error updating farm: {:?}", e);
             Ok(HttpResponse::InternalServerError().json("Internal server error"))
@@ -186,9 +317,11 @@ async fn update_farm(pool: web::Data<PgPool>, farm: Json<UpdateFarm>) -> Result<
 /**
  * Delete Farm
  **/
-async fn delete_farm(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+async fn delete_farm(req: HttpRequest, pool: web::Data<PgPool>, id: web::Path<Uuid>, unit: web::Query<AreaUnitQuery>) -> Result<HttpResponse, AppError> {
+    let unit = requested_area_unit(&req, &unit);
     let id = id.into_inner();
 
+    let mut tx = pool.begin().await?;
     let farm_result = sqlx::query_as::<_, Farm>(
         r#"
         DELETE FROM "Farm"
@@ -197,14 +330,70 @@ async fn delete_farm(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<Htt
         "#,
     )
         .bind(id)
-        .fetch_one(pool.get_ref())
+        .fetch_one(&mut *tx)
         .await;
 
     match farm_result {
-        Ok(farm) => Ok(HttpResponse::Ok().json(farm)),
+        Ok(farm) => {
+            outbox::record(&mut tx, DomainEventType::FarmDeleted, farm.id, &farm).await?;
+            tx.commit().await?;
+            Ok(HttpResponse::Ok().json(farm.in_unit(unit)))
+        }
         Err(e) => {
             error!("Error deleting farm: {:?}", e);
             Err(AppError::SqlError(e))
         }
     }
-}
\ No newline at end of file
+}
+
+/**
+ * Land value history, oldest first
+ **/
+async fn get_farm_valuations(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+
+    let valuations_result = sqlx::query_as::<_, FarmValuation>(
+        r#"
+        SELECT *
+        FROM "FarmValuation"
+        WHERE "farmId" = $1
+        ORDER BY "createdAt"
+        "#,
+    )
+        .bind(id)
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match valuations_result {
+        Ok(valuations) => Ok(HttpResponse::Ok().json(valuations)),
+        Err(e) => {
+            error!("Error getting farm valuations: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn unit(accept_language: Option<&str>, query: Option<AreaUnit>) -> AreaUnit {
+        let request = match accept_language {
+            Some(language) => TestRequest::default().insert_header(("Accept-Language", language)),
+            None => TestRequest::default(),
+        };
+        requested_area_unit(&request.to_http_request(), &AreaUnitQuery { unit: query })
+    }
+
+    #[test]
+    fn requested_area_unit_defaults_to_acres() {
+        assert_eq!(unit(None, None), AreaUnit::Acre);
+        assert_eq!(unit(Some("*"), None), AreaUnit::Acre);
+        assert_eq!(unit(Some("en-US,en;q=0.9"), None), AreaUnit::Acre);
+        assert_eq!(unit(Some("en-NG"), None), AreaUnit::Hectare);
+        assert_eq!(unit(Some("fr-FR;q=0.8"), None), AreaUnit::Hectare);
+        assert_eq!(unit(Some("en-US"), Some(AreaUnit::Plot)), AreaUnit::Plot);
+        assert_eq!(unit(None, Some(AreaUnit::Hectare)), AreaUnit::Hectare);
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use uuid::Uuid;
+use shared::models::{
+    Harvest,
+    CreateHarvest
+};
+use tracing::error;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/harvests")
+                    .route("", web::get().to(get_all_harvests))
+                    .route("", web::post().to(create_harvest))
+    );
+}
+
+async fn get_all_harvests(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let harvests_result = sqlx::query_as::<_, Harvest>(
+        r#"
+        SELECT *
+        FROM "Harvest"
+        WHERE "farmId" = $1
+        ORDER BY "harvestDate" DESC
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match harvests_result {
+        Ok(harvests) => Ok(HttpResponse::Ok().json(harvests)),
+        Err(e) => {
+            error!("Error getting harvests: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+/**
+ * Create Harvest
+ **/
+async fn create_harvest(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, harvest: Json<CreateHarvest>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let harvest = harvest.into_inner();
+
+    if !harvest.quantity.is_finite() || harvest.quantity < 0.0 {
+        return Err(AppError::GenericError("Quantity must not be negative".to_string()));
+    }
+    if !harvest.post_harvest_loss.is_finite() || harvest.post_harvest_loss < 0.0 || harvest.post_harvest_loss > harvest.quantity {
+        return Err(AppError::GenericError("Post-harvest loss must be between zero and the harvested quantity".to_string()));
+    }
+
+    // The planting must belong to the farm in the path
+    let planting_exists = sqlx::query_scalar::<_, bool>(
+        r#"SELECT EXISTS (SELECT 1 FROM "Planting" WHERE id = $1 AND "farmId" = $2)"#,
+    )
+        .bind(harvest.planting_id)
+        .bind(farm_id)
+        .fetch_one(pool.get_ref())
+        .await;
+
+    match planting_exists {
+        Ok(true) => {}
+        Ok(false) => return Err(AppError::NotFound("Planting".to_string())),
+        Err(e) => {
+            error!("Error checking planting: {:?}", e);
+            return Err(AppError::SqlError(e));
+        }
+    }
+
+    let harvest_result = sqlx::query_as::<_, Harvest>(
+        r#"
+        INSERT INTO "Harvest" ("farmId", "plantingId", quantity, unit, "quantityKg", "postHarvestLossKg", "qualityGrade", "harvestDate")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(harvest.planting_id)
+        .bind(harvest.quantity)
+        .bind(harvest.unit)
+        .bind(harvest.unit.to_kilograms(harvest.quantity))
+        .bind(harvest.unit.to_kilograms(harvest.post_harvest_loss))
+        .bind(harvest.quality_grade)
+        .bind(harvest.harvest_date)
+        .fetch_one(pool.get_ref())
+        .await;
+
+    match harvest_result {
+        Ok(harvest) => Ok(HttpResponse::Created().json(harvest)),
+        Err(e) => {
+            error!("Error creating harvest: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
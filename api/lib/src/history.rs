@@ -0,0 +1,159 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpRequest, HttpResponse};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use shared::models::{
+    AreaUnitQuery,
+    Farm,
+    FarmVersion,
+    FieldChange,
+    VersionOperation
+};
+use tracing::error;
+use crate::farm::requested_area_unit;
+
+// Bookkeeping fields left out of diffs
+const IGNORED_FIELDS: [&str; 2] = ["updated_at", "area_unit"];
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("History query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/history")
+                    .route("", web::get().to(get_farm_history))
+    );
+}
+
+/**
+ * Stands in for `"<table>"` in a FROM clause, yielding rows as they were at the time bound
+ * to `$as_of`. Pass `id` to narrow to one row's history by its bound placeholder, which
+ * lets the lookup use the history index.
+ **/
+pub fn as_of_source(table: &str, as_of: usize, id: Option<usize>) -> String {
+    let mut key = table.to_string();
+    key[..1].make_ascii_lowercase();
+    let id_filter = id.map(|id| format!(r#"AND h."{}Id" = ${}"#, key, id)).unwrap_or_default();
+    format!(
+        r#"(
+            SELECT r.*
+            FROM "{table}History" h
+            CROSS JOIN LATERAL jsonb_populate_record(NULL::"{table}", h.data) r
+            WHERE h."validFrom" <= ${as_of} AND (h."validTo" IS NULL OR h."validTo" > ${as_of})
+              AND h.operation <> 'DELETE' {id_filter}
+        ) AS "{table}""#,
+        table = table,
+        as_of = as_of,
+        id_filter = id_filter,
+    )
+}
+
+/**
+ * Fields of `current` that differ from `previous`, both being serialised records. Every set
+ * field counts as a change for the first version.
+ **/
+pub fn diff(previous: Option<&Value>, current: &Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = previous.and_then(Value::as_object).unwrap_or(&empty);
+    let after = current.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields.into_iter()
+        .filter(|field| !IGNORED_FIELDS.contains(&field.as_str()))
+        .filter_map(|field| {
+            let from = before.get(field).cloned().unwrap_or(Value::Null);
+            let to = after.get(field).cloned().unwrap_or(Value::Null);
+            (from != to).then(|| FieldChange { field: field.clone(), from, to })
+        })
+        .collect()
+}
+
+#[derive(sqlx::FromRow)]
+struct FarmVersionRow {
+    version: i32,
+    operation: VersionOperation,
+    #[sqlx(rename = "validFrom")]
+    valid_from: DateTime<Utc>,
+    #[sqlx(rename = "validTo")]
+    valid_to: Option<DateTime<Utc>>,
+    #[sqlx(flatten)]
+    farm: Farm,
+}
+
+/**
+ * Get Farm History
+ * Every version of the farm, oldest first, with the fields that changed in each.
+ * Areas are reported in the requested unit.
+ **/
+async fn get_farm_history(req: HttpRequest, pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, unit: web::Query<AreaUnitQuery>) -> Result<HttpResponse, AppError> {
+    let unit = requested_area_unit(&req, &unit);
+    let rows = sqlx::query_as::<_, FarmVersionRow>(
+        r#"
+        SELECT h.version, h.operation, h."validFrom", h."validTo", f.*
+        FROM "FarmHistory" h
+        CROSS JOIN LATERAL jsonb_populate_record(NULL::"Farm", h.data) f
+        WHERE h."farmId" = $1
+        ORDER BY h.version
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+    if rows.is_empty() {
+        return Err(AppError::NotFound("Farm".to_string()));
+    }
+
+    let mut previous: Option<Value> = None;
+    let mut versions = Vec::with_capacity(rows.len());
+    for row in rows {
+        let current = serde_json::to_value(row.farm.in_unit(unit)).unwrap_or(Value::Null);
+        versions.push(FarmVersion {
+            version: row.version,
+            operation: row.operation,
+            valid_from: row.valid_from,
+            valid_to: row.valid_to,
+            changes: diff(previous.as_ref(), &current),
+        });
+        previous = Some(current);
+    }
+
+    Ok(HttpResponse::Ok().json(versions))
+}
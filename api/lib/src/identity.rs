@@ -0,0 +1,163 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use shared::models::IdentityOutcome;
+use uuid::Uuid;
+
+/**
+ * Identity details sent to a verification provider
+ **/
+#[derive(Debug, Clone)]
+pub struct IdentityCheck {
+    pub bvn: String,
+    pub identity_number: Option<String>,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct IdentityResult {
+    pub outcome: IdentityOutcome,
+    // Provider's reference for the lookup, kept for audits and disputes
+    pub reference: String,
+    pub message: Option<String>,
+}
+
+/**
+ * A BVN/NIN verification service. Implementations are registered as
+ * `web::Data<dyn IdentityProvider>`; see `provider_from_env`.
+ **/
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Err means the provider could not be reached or answered; the profile is left unchanged
+    async fn verify(&self, check: &IdentityCheck) -> Result<IdentityResult, String>;
+}
+
+/**
+ * A BVN is 11 digits. The mock treats the last digit as a Luhn check digit
+ * over the first ten, so typos are caught without a network call.
+ **/
+pub fn bvn_is_well_formed(bvn: &str) -> bool {
+    if bvn.len() != 11 || !bvn.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let sum: u32 = bvn.chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockResult {
+    Match,
+    Mismatch,
+    // Simulates a provider outage
+    Error,
+}
+
+/**
+ * Local stand-in for a real provider: rejects malformed BVNs and answers
+ * every well-formed one with the configured result
+ **/
+#[derive(Debug, Clone)]
+pub struct MockIdentityProvider {
+    pub result: MockResult,
+}
+
+impl MockIdentityProvider {
+    pub fn from_env() -> Self {
+        let result = match std::env::var("KYC_MOCK_RESULT").ok().as_deref().map(str::to_ascii_uppercase).as_deref() {
+            Some("MISMATCH") => MockResult::Mismatch,
+            Some("ERROR") => MockResult::Error,
+            _ => MockResult::Match,
+        };
+        MockIdentityProvider { result }
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for MockIdentityProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn verify(&self, check: &IdentityCheck) -> Result<IdentityResult, String> {
+        let reference = format!("mock-{}", Uuid::new_v4());
+
+        if !bvn_is_well_formed(&check.bvn) {
+            return Ok(IdentityResult {
+                outcome: IdentityOutcome::Invalid,
+                reference,
+                message: Some("BVN must be 11 digits with a valid check digit".to_string()),
+            });
+        }
+
+        match self.result {
+            MockResult::Match => Ok(IdentityResult {
+                outcome: IdentityOutcome::Match,
+                reference,
+                message: None,
+            }),
+            MockResult::Mismatch => Ok(IdentityResult {
+                outcome: IdentityOutcome::Mismatch,
+                reference,
+                message: Some(format!("BVN is not registered to {} {}", check.first_name, check.last_name)),
+            }),
+            MockResult::Error => Err("Mock identity provider is configured to fail".to_string()),
+        }
+    }
+}
+
+/**
+ * Provider named by `KYC_PROVIDER`; only the mock is bundled
+ **/
+pub fn provider_from_env() -> Arc<dyn IdentityProvider> {
+    match std::env::var("KYC_PROVIDER").ok().as_deref() {
+        None | Some("mock") => Arc::new(MockIdentityProvider::from_env()),
+        Some(other) => panic!("Unknown KYC_PROVIDER: {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_a_bvn_with_a_valid_check_digit() {
+        assert!(bvn_is_well_formed("22345678902"));
+        assert!(bvn_is_well_formed("10000000009"));
+    }
+
+    #[test]
+    fn rejects_a_bvn_with_a_bad_check_digit() {
+        assert!(!bvn_is_well_formed("22345678901"));
+        assert!(!bvn_is_well_formed("22345678903"));
+        // Transposed digits
+        assert!(!bvn_is_well_formed("23245678902"));
+    }
+
+    #[test]
+    fn rejects_a_bvn_of_the_wrong_length() {
+        assert!(!bvn_is_well_formed(""));
+        assert!(!bvn_is_well_formed("2234567890"));
+        assert!(!bvn_is_well_formed("223456789025"));
+    }
+
+    #[test]
+    fn rejects_a_bvn_with_non_digits() {
+        assert!(!bvn_is_well_formed("2234567890a"));
+        assert!(!bvn_is_well_formed("2234 678902"));
+    }
+}
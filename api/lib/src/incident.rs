@@ -0,0 +1,155 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use uuid::Uuid;
+use shared::models::{
+    PestIncident,
+    CreatePestIncident
+};
+use tracing::error;
+
+const MAX_PHOTOS: usize = 10;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Incident query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/incidents")
+                    .route("", web::get().to(get_all_incidents))
+                    .route("", web::post().to(create_incident))
+    );
+}
+
+async fn get_all_incidents(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let incidents = sqlx::query_as::<_, PestIncident>(
+        r#"
+        SELECT *
+        FROM "PestIncident"
+        WHERE "farmId" = $1
+        ORDER BY "observedOn" DESC
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(incidents))
+}
+
+/**
+ * Report a pest or disease sighting.
+ * The location defaults to the farm's; outbreak detection picks it up on its next scan.
+ **/
+async fn create_incident(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, incident: Json<CreatePestIncident>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let incident = incident.into_inner();
+
+    let name = incident.name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(AppError::GenericError("Pest or disease name is required".to_string()));
+    }
+    if incident.photo_urls.len() > MAX_PHOTOS {
+        return Err(AppError::GenericError(format!("At most {} photos can be attached", MAX_PHOTOS)));
+    }
+    if incident.photo_urls.iter().any(|url| !(url.starts_with("https://") || url.starts_with("http://"))) {
+        return Err(AppError::GenericError("Photo URLs must be http(s) links".to_string()));
+    }
+
+    let farm: Option<(f64, f64, f64)> = sqlx::query_as(r#"SELECT latitude, longitude, acreage FROM "Farm" WHERE id = $1"#)
+        .bind(farm_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+    let (farm_latitude, farm_longitude, acreage) = farm.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+
+    let latitude = incident.latitude.unwrap_or(farm_latitude);
+    let longitude = incident.longitude.unwrap_or(farm_longitude);
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(AppError::GenericError("Invalid coordinates".to_string()));
+    }
+
+    let affected_area = incident.affected_area.map(|area| area.to_hectares());
+    if affected_area.is_some_and(|hectares| hectares > acreage) {
+        return Err(AppError::GenericError("Affected area exceeds the farm's acreage".to_string()));
+    }
+
+    if let Some(plot_id) = incident.plot_id {
+        let plot_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Plot" WHERE id = $1 AND "farmId" = $2)"#)
+            .bind(plot_id)
+            .bind(farm_id)
+            .fetch_one(pool.get_ref())
+            .await?;
+        if !plot_exists {
+            return Err(AppError::NotFound("Plot".to_string()));
+        }
+    }
+    if let Some(user_id) = incident.reported_by {
+        let user_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+            .bind(user_id)
+            .fetch_one(pool.get_ref())
+            .await?;
+        if !user_exists {
+            return Err(AppError::NotFound("User".to_string()));
+        }
+    }
+
+    let incident = sqlx::query_as::<_, PestIncident>(
+        r#"
+        INSERT INTO "PestIncident" ("farmId", "plotId", "reportedBy", category, name, severity, "affectedArea", "observedOn", latitude, longitude, "photoUrls", notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(incident.plot_id)
+        .bind(incident.reported_by)
+        .bind(incident.category)
+        .bind(name)
+        .bind(incident.severity)
+        .bind(affected_area)
+        .bind(incident.observed_on)
+        .bind(latitude)
+        .bind(longitude)
+        .bind(incident.photo_urls)
+        .bind(incident.notes)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(incident))
+}
@@ -0,0 +1,435 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+use shared::models::{
+    InputProduct,
+    CreateInputProduct,
+    InputProductFilter,
+    InputTransaction,
+    CreateInputTransaction,
+    InputTransactionFilter,
+    InputTransactionKind,
+    InputBalance
+};
+use tracing::error;
+
+// Without a maximum, applications may exceed the recommended rate by this factor
+const RATE_TOLERANCE: f64 = 1.2;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Input query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/inputs")
+                    .route("", web::get().to(get_all_transactions))
+                    .route("", web::post().to(create_transaction))
+                    .route("/balance", web::get().to(get_balance))
+    );
+    cfg.service(web::scope("/v0.1/inputs/products")
+                    .route("", web::get().to(get_all_products))
+                    .route("", web::post().to(create_product))
+                    .route("/{id}", web::put().to(update_product))
+    );
+}
+
+// Stock per product across all of a farmer's farms
+const BALANCE_QUERY: &str = r#"
+    SELECT p.id AS "productId",
+           p.name AS product,
+           p.unit,
+           COALESCE(SUM(t.quantity) FILTER (WHERE t.kind <> 'APPLICATION'), 0) AS received,
+           COALESCE(SUM(t.quantity) FILTER (WHERE t.kind = 'APPLICATION'), 0) AS applied,
+           COALESCE(SUM(CASE WHEN t.kind = 'APPLICATION' THEN -t.quantity ELSE t.quantity END), 0) AS balance
+    FROM "InputTransaction" t
+    JOIN "InputProduct" p ON p.id = t."productId"
+    WHERE t."farmerId" = $1
+      AND ($2::uuid IS NULL OR p.id = $2)
+    GROUP BY p.id
+    ORDER BY p.name
+"#;
+
+fn validate_product(product: &CreateInputProduct) -> Result<(), AppError> {
+    if product.name.trim().is_empty() {
+        return Err(AppError::GenericError("Product name is required".to_string()));
+    }
+    let rates = [product.recommended_rate_per_hectare, product.max_rate_per_hectare];
+    if rates.iter().flatten().any(|rate| !rate.is_finite() || *rate <= 0.0) {
+        return Err(AppError::GenericError("Application rates must be positive".to_string()));
+    }
+    if let (Some(recommended), Some(max)) = (product.recommended_rate_per_hectare, product.max_rate_per_hectare) {
+        if max < recommended {
+            return Err(AppError::GenericError("maxRatePerHectare must not be below the recommended rate".to_string()));
+        }
+    }
+    Ok(())
+}
+
+async fn get_all_products(pool: web::Data<PgPool>, filter: web::Query<InputProductFilter>) -> Result<HttpResponse, AppError> {
+    let products = sqlx::query_as::<_, InputProduct>(
+        r#"
+        SELECT *
+        FROM "InputProduct"
+        WHERE ($1::varchar IS NULL OR category = $1)
+        ORDER BY name
+        "#,
+    )
+        .bind(filter.into_inner().category)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(products))
+}
+
+async fn create_product(pool: web::Data<PgPool>, product: Json<CreateInputProduct>) -> Result<HttpResponse, AppError> {
+    let product = product.into_inner();
+    validate_product(&product)?;
+
+    let product = sqlx::query_as::<_, InputProduct>(
+        r#"
+        INSERT INTO "InputProduct" (name, category, unit, manufacturer, "cropId", "recommendedRatePerHectare", "maxRatePerHectare")
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+        .bind(product.name.trim())
+        .bind(product.category)
+        .bind(product.unit)
+        .bind(product.manufacturer)
+        .bind(product.crop_id)
+        .bind(product.recommended_rate_per_hectare)
+        .bind(product.max_rate_per_hectare)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(product))
+}
+
+async fn update_product(pool: web::Data<PgPool>, id: web::Path<Uuid>, product: Json<CreateInputProduct>) -> Result<HttpResponse, AppError> {
+    let product = product.into_inner();
+    validate_product(&product)?;
+
+    let product = sqlx::query_as::<_, InputProduct>(
+        r#"
+        UPDATE "InputProduct"
+        SET name = $2,
+            category = $3,
+            unit = $4,
+            manufacturer = $5,
+            "cropId" = $6,
+            "recommendedRatePerHectare" = $7,
+            "maxRatePerHectare" = $8,
+            "updatedAt" = current_timestamp
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(id.into_inner())
+        .bind(product.name.trim())
+        .bind(product.category)
+        .bind(product.unit)
+        .bind(product.manufacturer)
+        .bind(product.crop_id)
+        .bind(product.recommended_rate_per_hectare)
+        .bind(product.max_rate_per_hectare)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match product {
+        Some(product) => Ok(HttpResponse::Ok().json(product)),
+        None => Err(AppError::NotFound("Input product".to_string())),
+    }
+}
+
+async fn get_all_transactions(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, filter: web::Query<InputTransactionFilter>) -> Result<HttpResponse, AppError> {
+    let filter = filter.into_inner();
+    let transactions = sqlx::query_as::<_, InputTransaction>(
+        r#"
+        SELECT *
+        FROM "InputTransaction"
+        WHERE "farmId" = $1
+          AND ($2::uuid IS NULL OR "productId" = $2)
+          AND ($3::varchar IS NULL OR kind = $3)
+          AND ($4::date IS NULL OR "transactionDate" >= $4)
+          AND ($5::date IS NULL OR "transactionDate" <= $5)
+        ORDER BY "transactionDate" DESC, "createdAt" DESC
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .bind(filter.product_id)
+        .bind(filter.kind)
+        .bind(filter.from)
+        .bind(filter.to)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(transactions))
+}
+
+/**
+ * Hectares an application covers: the requested area, else the plot's, else the farm's.
+ * It must fit within the plot or farm and keep to the product's maximum rate.
+ **/
+fn check_application(product: &InputProduct, quantity: f64, requested_area: Option<f64>, plot_area: Option<f64>, acreage: f64) -> Result<f64, AppError> {
+    let hectares = requested_area.or(plot_area).unwrap_or(acreage);
+    if !hectares.is_finite() || hectares <= 0.0 {
+        return Err(AppError::GenericError("Applied area must be positive".to_string()));
+    }
+    if hectares > plot_area.unwrap_or(acreage) {
+        return Err(AppError::GenericError("Applied area exceeds the plot or farm area".to_string()));
+    }
+
+    let rate = quantity / hectares;
+    let max_rate = product.max_rate_per_hectare
+        .or(product.recommended_rate_per_hectare.map(|rate| rate * RATE_TOLERANCE));
+    if let Some(max_rate) = max_rate {
+        if rate > max_rate {
+            return Err(AppError::GenericError(format!(
+                "Application rate of {:.2} per hectare exceeds the limit of {:.2} for {}",
+                rate, max_rate, product.name
+            )));
+        }
+    }
+    Ok(hectares)
+}
+
+// `stock` is the product's BALANCE_QUERY balance: received less applied
+fn check_stock(product: &InputProduct, quantity: f64, stock: f64) -> Result<(), AppError> {
+    if quantity > stock {
+        return Err(AppError::GenericError(format!(
+            "Insufficient stock of {}: {:.2} available",
+            product.name, stock
+        )));
+    }
+    Ok(())
+}
+
+/**
+ * Record an allocation, purchase or application.
+ * Applications must be covered by the farmer's stock and stay within the product's rate.
+ **/
+async fn create_transaction(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, transaction: Json<CreateInputTransaction>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let transaction = transaction.into_inner();
+
+    if !transaction.quantity.is_finite() || transaction.quantity <= 0.0 {
+        return Err(AppError::GenericError("Quantity must be positive".to_string()));
+    }
+    if transaction.unit_cost.as_ref().is_some_and(|cost| cost.amount < Decimal::ZERO) {
+        return Err(AppError::GenericError("Unit cost must not be negative".to_string()));
+    }
+    let is_application = transaction.kind == InputTransactionKind::Application;
+    if !is_application && transaction.applied_area.is_some() {
+        return Err(AppError::GenericError("appliedArea is only recorded for applications".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let farm: Option<(Uuid, f64)> = sqlx::query_as(r#"SELECT "farmerId", acreage FROM "Farm" WHERE id = $1"#)
+        .bind(farm_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let (farmer_id, acreage) = farm.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+
+    // Serialises ledger writes per farmer so the stock check holds
+    sqlx::query(r#"SELECT id FROM "User" WHERE id = $1 FOR UPDATE"#)
+        .bind(farmer_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let product = sqlx::query_as::<_, InputProduct>(r#"SELECT * FROM "InputProduct" WHERE id = $1"#)
+        .bind(transaction.product_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Input product".to_string()))?;
+
+    let plot_area = match transaction.plot_id {
+        Some(plot_id) => {
+            let area: Option<f64> = sqlx::query_scalar(r#"SELECT area FROM "Plot" WHERE id = $1 AND "farmId" = $2"#)
+                .bind(plot_id)
+                .bind(farm_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            Some(area.ok_or_else(|| AppError::NotFound("Plot".to_string()))?)
+        }
+        None => None,
+    };
+
+    let applied_area = if is_application {
+        let requested_area = transaction.applied_area.map(|area| area.to_hectares());
+        let hectares = check_application(&product, transaction.quantity, requested_area, plot_area, acreage)?;
+
+        let stock = sqlx::query_as::<_, InputBalance>(BALANCE_QUERY)
+            .bind(farmer_id)
+            .bind(product.id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .map(|stock| stock.balance)
+            .unwrap_or(0.0);
+        check_stock(&product, transaction.quantity, stock)?;
+        Some(hectares)
+    } else {
+        None
+    };
+
+    let transaction = sqlx::query_as::<_, InputTransaction>(
+        r#"
+        INSERT INTO "InputTransaction" ("farmId", "farmerId", "productId", "plotId", kind, quantity, "unitCost", "appliedArea", "transactionDate", supplier, notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(farmer_id)
+        .bind(product.id)
+        .bind(transaction.plot_id)
+        .bind(transaction.kind)
+        .bind(transaction.quantity)
+        .bind(transaction.unit_cost)
+        .bind(applied_area)
+        .bind(transaction.transaction_date)
+        .bind(transaction.supplier)
+        .bind(transaction.notes)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(transaction))
+}
+
+/**
+ * Stock held by the farm's farmer, across all of their farms
+ **/
+async fn get_balance(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let farmer_id: Option<Uuid> = sqlx::query_scalar(r#"SELECT "farmerId" FROM "Farm" WHERE id = $1"#)
+        .bind(farm_id.into_inner())
+        .fetch_optional(pool.get_ref())
+        .await?;
+    let farmer_id = farmer_id.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+
+    let balances = sqlx::query_as::<_, InputBalance>(BALANCE_QUERY)
+        .bind(farmer_id)
+        .bind(None::<Uuid>)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(balances))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use shared::models::{InputCategory, InputUnit};
+
+    fn product(recommended_rate_per_hectare: Option<f64>, max_rate_per_hectare: Option<f64>) -> InputProduct {
+        InputProduct {
+            id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            name: "NPK 15-15-15".to_string(),
+            category: InputCategory::Fertilizer,
+            unit: InputUnit::Kg,
+            manufacturer: None,
+            crop_id: None,
+            recommended_rate_per_hectare,
+            max_rate_per_hectare,
+        }
+    }
+
+    fn message(result: Result<impl std::fmt::Debug, AppError>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn applied_area_defaults_to_the_plot_then_the_farm() {
+        let product = product(None, None);
+        assert_eq!(check_application(&product, 100.0, Some(1.5), Some(2.0), 10.0).unwrap(), 1.5);
+        assert_eq!(check_application(&product, 100.0, None, Some(2.0), 10.0).unwrap(), 2.0);
+        assert_eq!(check_application(&product, 100.0, None, None, 10.0).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn applied_area_must_be_positive_and_fit() {
+        let product = product(None, None);
+        assert_eq!(message(check_application(&product, 100.0, Some(0.0), None, 10.0)), "Error: Applied area must be positive");
+        assert_eq!(message(check_application(&product, 100.0, Some(f64::NAN), None, 10.0)), "Error: Applied area must be positive");
+        assert_eq!(message(check_application(&product, 100.0, None, None, 0.0)), "Error: Applied area must be positive");
+        assert_eq!(
+            message(check_application(&product, 100.0, Some(3.0), Some(2.0), 10.0)),
+            "Error: Applied area exceeds the plot or farm area",
+        );
+        assert_eq!(
+            message(check_application(&product, 100.0, Some(11.0), None, 10.0)),
+            "Error: Applied area exceeds the plot or farm area",
+        );
+    }
+
+    #[test]
+    fn rate_is_limited_by_the_maximum() {
+        let product = product(Some(100.0), Some(150.0));
+        assert!(check_application(&product, 300.0, Some(2.0), None, 10.0).is_ok());
+        assert_eq!(
+            message(check_application(&product, 301.0, Some(2.0), None, 10.0)),
+            "Error: Application rate of 150.50 per hectare exceeds the limit of 150.00 for NPK 15-15-15",
+        );
+    }
+
+    #[test]
+    fn rate_falls_back_to_the_recommended_rate_with_tolerance() {
+        let product = product(Some(100.0), None);
+        assert!(check_application(&product, 240.0, Some(2.0), None, 10.0).is_ok());
+        assert!(check_application(&product, 241.0, Some(2.0), None, 10.0).is_err());
+    }
+
+    #[test]
+    fn rate_is_unlimited_without_rates() {
+        assert!(check_application(&product(None, None), 1e6, Some(1.0), None, 10.0).is_ok());
+    }
+
+    #[test]
+    fn stock_must_cover_the_application() {
+        let product = product(None, None);
+        assert!(check_stock(&product, 50.0, 50.0).is_ok());
+        assert_eq!(message(check_stock(&product, 50.5, 50.0)), "Error: Insufficient stock of NPK 15-15-15: 50.00 available");
+        assert!(check_stock(&product, 1.0, 0.0).is_err());
+    }
+}
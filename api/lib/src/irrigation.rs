@@ -0,0 +1,608 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+use shared::models::{
+    IrrigationSystem,
+    CreateIrrigationSystem,
+    IrrigationEvent,
+    CreateIrrigationEvent,
+    CompleteIrrigationEvent,
+    IrrigationEventFilter,
+    IrrigationStatus,
+    WaterBalanceQuery,
+    WaterBalance,
+    FarmWeatherQuery
+};
+use tracing::error;
+use crate::weather;
+
+// Used for crops without a recorded water requirement
+const DEFAULT_WATER_REQUIREMENT_MM_PER_DAY: f64 = 5.0;
+const DEFAULT_ALLOWABLE_DEPLETION_MM: f64 = 30.0;
+// More than any root zone holds
+const MAX_ALLOWABLE_DEPLETION_MM: f64 = 1000.0;
+const DEFAULT_WINDOW_DAYS: i64 = 14;
+const MAX_WINDOW_DAYS: i64 = 90;
+// Share of rainfall that reaches the root zone
+const EFFECTIVE_RAINFALL_FACTOR: f64 = 0.8;
+// 1 mm of water over one hectare
+const LITRES_PER_MM_HECTARE: f64 = 10_000.0;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Irrigation query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+impl From<weather::AppError> for AppError {
+    fn from(e: weather::AppError) -> Self {
+        match e {
+            weather::AppError::SqlError(e) => AppError::SqlError(e),
+            weather::AppError::GenericError(msg) => AppError::GenericError(msg),
+            weather::AppError::NotFound(what) => AppError::NotFound(what),
+        }
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/irrigation")
+                    .route("/systems", web::get().to(get_all_systems))
+                    .route("/systems", web::post().to(create_system))
+                    .route("/systems/{id}", web::put().to(update_system))
+                    .route("/systems/{id}", web::delete().to(delete_system))
+                    .route("/events", web::get().to(get_all_events))
+                    .route("/events", web::post().to(create_event))
+                    .route("/events/{id}/complete", web::post().to(complete_event))
+                    .route("/events/{id}/cancel", web::post().to(cancel_event))
+                    .route("/balance", web::get().to(get_water_balance))
+    );
+}
+
+// Planting area and water use, for working out demand on a given day
+#[derive(Debug, sqlx::FromRow)]
+struct ActivePlanting {
+    #[sqlx(rename = "plantedArea")]
+    planted_area: f64,
+    #[sqlx(rename = "plantingDate")]
+    planting_date: NaiveDate,
+    #[sqlx(rename = "expectedHarvestDate")]
+    expected_harvest_date: Option<NaiveDate>,
+    #[sqlx(rename = "waterRequirementMmPerDay")]
+    water_requirement_mm_per_day: f64,
+}
+
+impl ActivePlanting {
+    fn is_growing(&self, date: NaiveDate) -> bool {
+        self.planting_date <= date && self.expected_harvest_date.is_none_or(|harvest| date <= harvest)
+    }
+}
+
+// Planted hectares and area-weighted crop demand (mm/day) on a date
+fn crop_demand(plantings: &[ActivePlanting], date: NaiveDate) -> (f64, f64) {
+    let (hectares, weighted) = plantings.iter()
+        .filter(|p| p.is_growing(date))
+        .fold((0.0, 0.0), |(hectares, weighted), p| {
+            (hectares + p.planted_area, weighted + p.planted_area * p.water_requirement_mm_per_day)
+        });
+    if hectares > 0.0 { (hectares, weighted / hectares) } else { (0.0, 0.0) }
+}
+
+// A day's demand adds to the deficit, effective rainfall and irrigation reduce it down to field capacity
+fn next_deficit(deficit: f64, demand: f64, rain_mm: f64, irrigated_mm: f64) -> f64 {
+    f64::max(0.0, deficit + demand - rain_mm * EFFECTIVE_RAINFALL_FACTOR - irrigated_mm)
+}
+
+// The day the deficit reaches `allowable_depletion`, assuming no rain until then
+fn next_irrigation_date(date: NaiveDate, hectares: f64, demand: f64, deficit: f64, allowable_depletion: f64) -> Option<NaiveDate> {
+    if hectares <= 0.0 {
+        None
+    } else if deficit >= allowable_depletion {
+        Some(date)
+    } else if demand > 0.0 {
+        let days = ((allowable_depletion - deficit) / demand).ceil() as i64;
+        date.checked_add_signed(Duration::try_days(days)?)
+    } else {
+        None
+    }
+}
+
+/**
+ * Irrigation is only tracked for farms marked as irrigated
+ **/
+async fn ensure_irrigated(pool: &PgPool, farm_id: Uuid) -> Result<(), AppError> {
+    let is_irrigated: Option<Option<bool>> = sqlx::query_scalar(r#"SELECT is_irrigated FROM "Farm" WHERE id = $1"#)
+        .bind(farm_id)
+        .fetch_optional(pool)
+        .await?;
+
+    match is_irrigated {
+        None => Err(AppError::NotFound("Farm".to_string())),
+        Some(Some(true)) => Ok(()),
+        Some(_) => Err(AppError::GenericError("Farm is not irrigated".to_string())),
+    }
+}
+
+async fn get_all_systems(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let systems = sqlx::query_as::<_, IrrigationSystem>(
+        r#"
+        SELECT *
+        FROM "IrrigationSystem"
+        WHERE "farmId" = $1
+        ORDER BY "createdAt"
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(systems))
+}
+
+fn validate_system(system: &CreateIrrigationSystem) -> Result<f64, AppError> {
+    if !system.capacity_litres_per_hour.is_finite() || system.capacity_litres_per_hour <= 0.0 {
+        return Err(AppError::GenericError("Capacity must be positive".to_string()));
+    }
+    let efficiency = system.efficiency.unwrap_or_else(|| system.system_type.default_efficiency());
+    if !(efficiency > 0.0 && efficiency <= 1.0) {
+        return Err(AppError::GenericError("Efficiency must be between 0 and 1".to_string()));
+    }
+    Ok(efficiency)
+}
+
+/**
+ * Create Irrigation System
+ **/
+async fn create_system(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, system: Json<CreateIrrigationSystem>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let system = system.into_inner();
+    let efficiency = validate_system(&system)?;
+    ensure_irrigated(pool.get_ref(), farm_id).await?;
+
+    let system = sqlx::query_as::<_, IrrigationSystem>(
+        r#"
+        INSERT INTO "IrrigationSystem" ("farmId", name, "systemType", "waterSource", "capacityLitresPerHour", efficiency, "installedOn")
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(system.name)
+        .bind(system.system_type)
+        .bind(system.water_source)
+        .bind(system.capacity_litres_per_hour)
+        .bind(efficiency)
+        .bind(system.installed_on)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(system))
+}
+
+async fn update_system(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, system: Json<CreateIrrigationSystem>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let system = system.into_inner();
+    let efficiency = validate_system(&system)?;
+
+    let system = sqlx::query_as::<_, IrrigationSystem>(
+        r#"
+        UPDATE "IrrigationSystem"
+        SET name = $3,
+            "systemType" = $4,
+            "waterSource" = $5,
+            "capacityLitresPerHour" = $6,
+            efficiency = $7,
+            "installedOn" = $8,
+            "updatedAt" = current_timestamp
+        WHERE id = $1 AND "farmId" = $2
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(farm_id)
+        .bind(system.name)
+        .bind(system.system_type)
+        .bind(system.water_source)
+        .bind(system.capacity_litres_per_hour)
+        .bind(efficiency)
+        .bind(system.installed_on)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match system {
+        Some(system) => Ok(HttpResponse::Ok().json(system)),
+        None => Err(AppError::NotFound("Irrigation system".to_string())),
+    }
+}
+
+async fn delete_system(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let result = sqlx::query(r#"DELETE FROM "IrrigationSystem" WHERE id = $1 AND "farmId" = $2"#)
+        .bind(id)
+        .bind(farm_id)
+        .execute(pool.get_ref())
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Irrigation system".to_string()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn get_all_events(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, filter: web::Query<IrrigationEventFilter>) -> Result<HttpResponse, AppError> {
+    let filter = filter.into_inner();
+    let events = sqlx::query_as::<_, IrrigationEvent>(
+        r#"
+        SELECT *
+        FROM "IrrigationEvent"
+        WHERE "farmId" = $1
+          AND ($2::varchar IS NULL OR status = $2)
+          AND ($3::date IS NULL OR COALESCE("actualDate", "scheduledDate") >= $3)
+          AND ($4::date IS NULL OR COALESCE("actualDate", "scheduledDate") <= $4)
+        ORDER BY COALESCE("actualDate", "scheduledDate") DESC
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .bind(filter.status)
+        .bind(filter.from)
+        .bind(filter.to)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(events))
+}
+
+fn validate_volume(volume: Option<f64>, duration: Option<i32>) -> Result<(), AppError> {
+    if volume.is_some_and(|litres| !litres.is_finite() || litres < 0.0) {
+        return Err(AppError::GenericError("Volume must not be negative".to_string()));
+    }
+    if duration.is_some_and(|minutes| minutes < 0) {
+        return Err(AppError::GenericError("Duration must not be negative".to_string()));
+    }
+    Ok(())
+}
+
+/**
+ * Schedule an irrigation, or record one that already happened
+ * by giving `actualDate` and `actualVolumeLitres`
+ **/
+async fn create_event(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, event: Json<CreateIrrigationEvent>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let event = event.into_inner();
+
+    validate_volume(event.scheduled_volume_litres, None)?;
+    validate_volume(event.actual_volume_litres, event.duration_minutes)?;
+    let status = match (event.actual_date, event.actual_volume_litres, event.scheduled_date) {
+        (Some(_), Some(_), _) => IrrigationStatus::Completed,
+        (None, None, Some(_)) => IrrigationStatus::Scheduled,
+        (None, None, None) => return Err(AppError::GenericError("scheduledDate or actualDate is required".to_string())),
+        _ => return Err(AppError::GenericError("actualDate and actualVolumeLitres must be given together".to_string())),
+    };
+
+    ensure_irrigated(pool.get_ref(), farm_id).await?;
+
+    if let Some(system_id) = event.system_id {
+        let system_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "IrrigationSystem" WHERE id = $1 AND "farmId" = $2)"#)
+            .bind(system_id)
+            .bind(farm_id)
+            .fetch_one(pool.get_ref())
+            .await?;
+        if !system_exists {
+            return Err(AppError::NotFound("Irrigation system".to_string()));
+        }
+    }
+    if let Some(plot_id) = event.plot_id {
+        let plot_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Plot" WHERE id = $1 AND "farmId" = $2)"#)
+            .bind(plot_id)
+            .bind(farm_id)
+            .fetch_one(pool.get_ref())
+            .await?;
+        if !plot_exists {
+            return Err(AppError::NotFound("Plot".to_string()));
+        }
+    }
+
+    let event = sqlx::query_as::<_, IrrigationEvent>(
+        r#"
+        INSERT INTO "IrrigationEvent" ("farmId", "systemId", "plotId", status, "scheduledDate", "scheduledVolumeLitres", "actualDate", "actualVolumeLitres", "durationMinutes", notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(event.system_id)
+        .bind(event.plot_id)
+        .bind(status)
+        .bind(event.scheduled_date)
+        .bind(event.scheduled_volume_litres)
+        .bind(event.actual_date)
+        .bind(event.actual_volume_litres)
+        .bind(event.duration_minutes)
+        .bind(event.notes)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(event))
+}
+
+/**
+ * Record the actual irrigation for a scheduled event
+ **/
+async fn complete_event(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, completion: Json<CompleteIrrigationEvent>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let completion = completion.into_inner();
+    validate_volume(Some(completion.actual_volume_litres), completion.duration_minutes)?;
+
+    let event = sqlx::query_as::<_, IrrigationEvent>(
+        r#"
+        UPDATE "IrrigationEvent"
+        SET status = 'COMPLETED',
+            "actualDate" = $3,
+            "actualVolumeLitres" = $4,
+            "durationMinutes" = $5,
+            notes = COALESCE($6, notes),
+            "updatedAt" = current_timestamp
+        WHERE id = $1 AND "farmId" = $2 AND status = 'SCHEDULED'
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(farm_id)
+        .bind(completion.actual_date)
+        .bind(completion.actual_volume_litres)
+        .bind(completion.duration_minutes)
+        .bind(completion.notes)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match event {
+        Some(event) => Ok(HttpResponse::Ok().json(event)),
+        None => Err(AppError::NotFound("Scheduled irrigation event".to_string())),
+    }
+}
+
+async fn cancel_event(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let event = sqlx::query_as::<_, IrrigationEvent>(
+        r#"
+        UPDATE "IrrigationEvent"
+        SET status = 'CANCELLED',
+            "updatedAt" = current_timestamp
+        WHERE id = $1 AND "farmId" = $2 AND status = 'SCHEDULED'
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(farm_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match event {
+        Some(event) => Ok(HttpResponse::Ok().json(event)),
+        None => Err(AppError::NotFound("Scheduled irrigation event".to_string())),
+    }
+}
+
+/**
+ * Daily soil water balance over the last `windowDays`, starting from field capacity:
+ * crop demand adds to the deficit, effective rainfall and completed irrigations reduce it.
+ * Irrigation is due once the deficit reaches `allowableDepletionMm`.
+ **/
+async fn get_water_balance(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, query: web::Query<WaterBalanceQuery>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let query = query.into_inner();
+    let date = query.date.unwrap_or_else(|| Utc::now().date_naive());
+    let window_days = query.window_days.unwrap_or(DEFAULT_WINDOW_DAYS);
+    let allowable_depletion = query.allowable_depletion_mm.unwrap_or(DEFAULT_ALLOWABLE_DEPLETION_MM);
+
+    if !(1..=MAX_WINDOW_DAYS).contains(&window_days) {
+        return Err(AppError::GenericError(format!("windowDays must be between 1 and {}", MAX_WINDOW_DAYS)));
+    }
+    if !allowable_depletion.is_finite() || allowable_depletion <= 0.0 || allowable_depletion > MAX_ALLOWABLE_DEPLETION_MM {
+        return Err(AppError::GenericError(format!("allowableDepletionMm must be positive and at most {}", MAX_ALLOWABLE_DEPLETION_MM)));
+    }
+    let from = date.checked_sub_signed(Duration::days(window_days - 1))
+        .ok_or_else(|| AppError::GenericError("date is out of range".to_string()))?;
+    ensure_irrigated(pool.get_ref(), farm_id).await?;
+
+    // The requested system, or the farm's largest
+    let system = sqlx::query_as::<_, IrrigationSystem>(
+        r#"
+        SELECT *
+        FROM "IrrigationSystem"
+        WHERE "farmId" = $1 AND ($2::uuid IS NULL OR id = $2)
+        ORDER BY "capacityLitresPerHour" DESC
+        LIMIT 1
+        "#,
+    )
+        .bind(farm_id)
+        .bind(query.system_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+    if query.system_id.is_some() && system.is_none() {
+        return Err(AppError::NotFound("Irrigation system".to_string()));
+    }
+
+    let plantings = sqlx::query_as::<_, ActivePlanting>(
+        r#"
+        SELECT p."plantedArea",
+               p."plantingDate",
+               p."expectedHarvestDate",
+               COALESCE(c."waterRequirementMmPerDay", $4) AS "waterRequirementMmPerDay"
+        FROM "Planting" p
+        JOIN "Crop" c ON c.id = p."cropId"
+        WHERE p."farmId" = $1
+          AND p."plantingDate" <= $3
+          AND (p."expectedHarvestDate" IS NULL OR p."expectedHarvestDate" >= $2)
+        "#,
+    )
+        .bind(farm_id)
+        .bind(from)
+        .bind(date)
+        .bind(DEFAULT_WATER_REQUIREMENT_MM_PER_DAY)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    // Net litres reaching the crop per day, after each system's losses
+    let applied: HashMap<NaiveDate, f64> = sqlx::query_as::<_, (NaiveDate, f64)>(
+        r#"
+        SELECT e."actualDate", SUM(e."actualVolumeLitres" * COALESCE(s.efficiency, 1.0))
+        FROM "IrrigationEvent" e
+        LEFT JOIN "IrrigationSystem" s ON s.id = e."systemId"
+        WHERE e."farmId" = $1
+          AND e.status = 'COMPLETED'
+          AND e."actualDate" BETWEEN $2 AND $3
+        GROUP BY e."actualDate"
+        "#,
+    )
+        .bind(farm_id)
+        .bind(from)
+        .bind(date)
+        .fetch_all(pool.get_ref())
+        .await?
+        .into_iter()
+        .collect();
+
+    let weather = weather::farm_weather(pool.get_ref(), farm_id, &FarmWeatherQuery {
+        from,
+        to: date,
+        base_temp: None,
+        stations: None,
+        radius_km: None,
+    }).await?;
+
+    let (mut deficit, mut rainfall, mut effective_rainfall, mut irrigation) = (0.0, 0.0, 0.0, 0.0);
+    for day in &weather.days {
+        let (hectares, demand) = crop_demand(&plantings, day.date);
+        let rain = day.rainfall_mm.unwrap_or(0.0);
+        let irrigated_mm = match applied.get(&day.date) {
+            Some(litres) if hectares > 0.0 => litres / (hectares * LITRES_PER_MM_HECTARE),
+            _ => 0.0,
+        };
+        rainfall += rain;
+        effective_rainfall += rain * EFFECTIVE_RAINFALL_FACTOR;
+        irrigation += irrigated_mm;
+        deficit = next_deficit(deficit, demand, rain, irrigated_mm);
+    }
+
+    let (hectares, demand) = crop_demand(&plantings, date);
+    let efficiency = system.as_ref().map(|s| s.efficiency).unwrap_or(1.0);
+
+    let next_irrigation_date = next_irrigation_date(date, hectares, demand, deficit, allowable_depletion);
+    let recommended_volume = next_irrigation_date.map(|_| {
+        deficit.max(allowable_depletion) * hectares * LITRES_PER_MM_HECTARE / efficiency
+    });
+    let estimated_duration = recommended_volume
+        .zip(system.as_ref())
+        .map(|(litres, system)| litres / system.capacity_litres_per_hour * 60.0);
+
+    Ok(HttpResponse::Ok().json(WaterBalance {
+        farm_id,
+        date,
+        system_id: system.map(|s| s.id),
+        planted_hectares: hectares,
+        crop_demand_mm_per_day: demand,
+        rainfall_mm: rainfall,
+        effective_rainfall_mm: effective_rainfall,
+        irrigation_applied_mm: irrigation,
+        soil_water_deficit_mm: deficit,
+        allowable_depletion_mm: allowable_depletion,
+        next_irrigation_date,
+        recommended_volume_litres: recommended_volume,
+        estimated_duration_minutes: estimated_duration,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    fn planting(planted_area: f64, planting_date: NaiveDate, expected_harvest_date: Option<NaiveDate>, water_requirement_mm_per_day: f64) -> ActivePlanting {
+        ActivePlanting { planted_area, planting_date, expected_harvest_date, water_requirement_mm_per_day }
+    }
+
+    #[test]
+    fn crop_demand_is_weighted_by_area() {
+        let plantings = [
+            planting(1.0, date(1), None, 4.0),
+            planting(3.0, date(1), Some(date(20)), 8.0),
+        ];
+        assert_eq!(crop_demand(&plantings, date(10)), (4.0, 7.0));
+    }
+
+    #[test]
+    fn crop_demand_only_counts_growing_plantings() {
+        let plantings = [
+            planting(1.0, date(5), None, 4.0),
+            planting(3.0, date(1), Some(date(10)), 8.0),
+        ];
+        assert_eq!(crop_demand(&plantings, date(1)), (3.0, 8.0));
+        assert_eq!(crop_demand(&plantings, date(10)), (4.0, 7.0));
+        assert_eq!(crop_demand(&plantings, date(11)), (1.0, 4.0));
+        assert_eq!(crop_demand(&plantings[..1], date(4)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn deficit_grows_with_demand_and_stops_at_field_capacity() {
+        assert_eq!(next_deficit(10.0, 5.0, 0.0, 0.0), 15.0);
+        assert_eq!(next_deficit(10.0, 5.0, 5.0, 0.0), 11.0);
+        assert_eq!(next_deficit(10.0, 5.0, 0.0, 12.0), 3.0);
+        assert_eq!(next_deficit(10.0, 5.0, 50.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn irrigation_is_due_once_the_deficit_reaches_the_allowable_depletion() {
+        assert_eq!(next_irrigation_date(date(1), 2.0, 5.0, 30.0, 30.0), Some(date(1)));
+        assert_eq!(next_irrigation_date(date(1), 2.0, 5.0, 20.0, 30.0), Some(date(3)));
+        assert_eq!(next_irrigation_date(date(1), 2.0, 4.0, 20.0, 30.0), Some(date(4)));
+        assert_eq!(next_irrigation_date(date(1), 0.0, 5.0, 40.0, 30.0), None);
+        assert_eq!(next_irrigation_date(date(1), 2.0, 0.0, 20.0, 30.0), None);
+    }
+
+    #[test]
+    fn next_irrigation_date_is_none_when_it_is_out_of_range() {
+        assert_eq!(next_irrigation_date(date(1), 2.0, 1e-300, 0.0, MAX_ALLOWABLE_DEPLETION_MM), None);
+        assert_eq!(next_irrigation_date(date(1), 2.0, 1e-9, 0.0, MAX_ALLOWABLE_DEPLETION_MM), None);
+        assert_eq!(next_irrigation_date(NaiveDate::MAX, 2.0, 5.0, 20.0, 30.0), None);
+    }
+}
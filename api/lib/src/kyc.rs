@@ -0,0 +1,302 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction};
+use actix_web::http::StatusCode;
+use uuid::Uuid;
+use shared::models::{
+    IdentityOutcome,
+    KycEvent,
+    KycFilter,
+    KycProfile,
+    KycStatus,
+    ReviewKyc,
+    SubmitKyc,
+    DomainEventType
+};
+use tracing::error;
+use crate::identity::{IdentityCheck, IdentityProvider, IdentityResult};
+use crate::outbox;
+
+const KYC_PROFILE_QUERY: &str = r#"
+    SELECT p.id AS "profileId", p."userId", u."firstName", u."lastName", p."kycStatus" AS status, p."kycReason" AS reason, p."updatedAt"
+    FROM "Profile" p
+    JOIN "User" u ON u.id = p."userId"
+"#;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+    ProviderError(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+            AppError::ProviderError(msg) => write!(f, "Identity provider error: {}", msg),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::ProviderError(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("KYC query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/kyc")
+                    .route("/profiles", web::get().to(get_all_kyc_profiles))
+                    .route("/profiles/{id}", web::get().to(get_kyc_profile))
+                    .route("/profiles/{id}/submit", web::post().to(submit_profile))
+                    .route("/profiles/{id}/approve", web::post().to(approve_profile))
+                    .route("/profiles/{id}/reject", web::post().to(reject_profile))
+    );
+}
+
+#[derive(sqlx::FromRow)]
+struct Submission {
+    #[sqlx(rename = "kycStatus")]
+    status: KycStatus,
+    bvn: Option<String>,
+    #[sqlx(rename = "identityNumber")]
+    identity_number: Option<String>,
+    #[sqlx(rename = "firstName")]
+    first_name: String,
+    #[sqlx(rename = "lastName")]
+    last_name: String,
+}
+
+fn status_name(status: KycStatus) -> &'static str {
+    match status {
+        KycStatus::Unverified => "unverified",
+        KycStatus::Pending => "pending",
+        KycStatus::Verified => "verified",
+        KycStatus::Rejected => "rejected",
+    }
+}
+
+async fn fetch_kyc_profile(pool: &PgPool, id: Uuid) -> Result<KycProfile, AppError> {
+    let mut profile = sqlx::query_as::<_, KycProfile>(&format!("{} WHERE p.id = $1", KYC_PROFILE_QUERY))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Profile".to_string()))?;
+
+    profile.events = sqlx::query_as::<_, KycEvent>(
+        r#"SELECT * FROM "KycEvent" WHERE "profileId" = $1 ORDER BY "createdAt""#,
+    )
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+    Ok(profile)
+}
+
+// Locks the profile and checks it can move to `next`
+async fn lock_for_transition(tx: &mut Transaction<'_, Postgres>, id: Uuid, next: KycStatus) -> Result<KycStatus, AppError> {
+    let status: KycStatus = sqlx::query_scalar(r#"SELECT "kycStatus" FROM "Profile" WHERE id = $1 FOR UPDATE"#)
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Profile".to_string()))?;
+
+    if !status.can_become(next) {
+        return Err(AppError::GenericError(format!("A {} profile cannot become {}", status_name(status), status_name(next))));
+    }
+    Ok(status)
+}
+
+async fn transition(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    from: KycStatus,
+    to: KycStatus,
+    actor_id: Option<Uuid>,
+    reason: Option<&str>,
+    provider: Option<(&str, &IdentityResult)>,
+) -> Result<(), AppError> {
+    sqlx::query(r#"UPDATE "Profile" SET "kycStatus" = $2, "kycReason" = $3, "updatedAt" = current_timestamp WHERE id = $1"#)
+        .bind(id)
+        .bind(to)
+        .bind(if to == KycStatus::Rejected { reason } else { None })
+        .execute(&mut **tx)
+        .await?;
+    outbox::record_current(tx, DomainEventType::ProfileUpdated, id).await?;
+
+    // clock_timestamp keeps events written in one transaction in order
+    sqlx::query(
+        r#"
+        INSERT INTO "KycEvent" ("createdAt", "profileId", "fromStatus", "toStatus", "actorId", reason, provider, "providerReference", "providerOutcome")
+        VALUES (clock_timestamp(), $1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+        .bind(id)
+        .bind(from)
+        .bind(to)
+        .bind(actor_id)
+        .bind(reason)
+        .bind(provider.map(|(name, _)| name))
+        .bind(provider.map(|(_, result)| result.reference.as_str()))
+        .bind(provider.map(|(_, result)| result.outcome))
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+async fn user_exists(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+    let exists = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    Ok(exists)
+}
+
+/**
+ * Get KYC Queue
+ * Profiles in the given KYC status, oldest first; pending ones by default
+ **/
+async fn get_all_kyc_profiles(pool: web::Data<PgPool>, filter: web::Query<KycFilter>) -> Result<HttpResponse, AppError> {
+    let status = filter.status.unwrap_or(KycStatus::Pending);
+    let profiles = sqlx::query_as::<_, KycProfile>(
+        &format!(r#"{} WHERE p."kycStatus" = $1 ORDER BY p."updatedAt""#, KYC_PROFILE_QUERY),
+    )
+        .bind(status)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(profiles))
+}
+
+async fn get_kyc_profile(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let profile = fetch_kyc_profile(pool.get_ref(), id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+/**
+ * Submit a Profile for KYC
+ * The identity provider is asked first. Malformed or mismatched identities are rejected straight
+ * away; matches wait for a reviewer. Provider failures leave the profile untouched.
+ **/
+async fn submit_profile(pool: web::Data<PgPool>, provider: web::Data<dyn IdentityProvider>, id: web::Path<Uuid>, submission: Json<SubmitKyc>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let submission = submission.into_inner();
+
+    if !user_exists(pool.get_ref(), submission.submitted_by).await? {
+        return Err(AppError::NotFound("Submitting agent".to_string()));
+    }
+
+    let details = sqlx::query_as::<_, Submission>(
+        r#"
+        SELECT p."kycStatus", p.bvn, p."identityNumber", u."firstName", u."lastName"
+        FROM "Profile" p
+        JOIN "User" u ON u.id = p."userId"
+        WHERE p.id = $1
+        "#,
+    )
+        .bind(id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+    let Submission { status, bvn, identity_number, first_name, last_name } = details.ok_or_else(|| AppError::NotFound("Profile".to_string()))?;
+
+    if !status.can_become(KycStatus::Pending) {
+        return Err(AppError::GenericError(format!("A {} profile cannot be submitted", status_name(status))));
+    }
+    let bvn = bvn.map(|bvn| bvn.trim().to_string()).filter(|bvn| !bvn.is_empty())
+        .ok_or_else(|| AppError::GenericError("Profile has no BVN".to_string()))?;
+
+    // Called outside the transaction so a slow provider does not hold the profile lock
+    let check = IdentityCheck { bvn, identity_number, first_name, last_name };
+    let result = provider.verify(&check).await.map_err(|e| {
+        error!("Identity provider {} failed: {}", provider.name(), e);
+        AppError::ProviderError(e)
+    })?;
+
+    let mut tx = pool.begin().await?;
+    let current = lock_for_transition(&mut tx, id, KycStatus::Pending).await?;
+    let stored_bvn: Option<String> = sqlx::query_scalar(r#"SELECT bvn FROM "Profile" WHERE id = $1"#)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if stored_bvn.as_deref().map(str::trim) != Some(check.bvn.as_str()) {
+        return Err(AppError::GenericError("Profile changed during verification; submit again".to_string()));
+    }
+
+    transition(&mut tx, id, current, KycStatus::Pending, Some(submission.submitted_by), None, Some((provider.name(), &result))).await?;
+    if result.outcome != IdentityOutcome::Match {
+        let reason = result.message.clone().unwrap_or_else(|| "Identity could not be confirmed".to_string());
+        transition(&mut tx, id, KycStatus::Pending, KycStatus::Rejected, None, Some(&reason), Some((provider.name(), &result))).await?;
+    }
+    tx.commit().await?;
+
+    let profile = fetch_kyc_profile(pool.get_ref(), id).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+// The agent who submitted a profile cannot also review it
+async fn ensure_independent_reviewer(tx: &mut Transaction<'_, Postgres>, id: Uuid, reviewer_id: Uuid) -> Result<(), AppError> {
+    let submitted_by: Option<Option<Uuid>> = sqlx::query_scalar(
+        r#"
+        SELECT "actorId"
+        FROM "KycEvent"
+        WHERE "profileId" = $1 AND "toStatus" = 'PENDING'
+        ORDER BY "createdAt" DESC, id DESC
+        LIMIT 1
+        "#,
+    )
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    if submitted_by.flatten() == Some(reviewer_id) {
+        return Err(AppError::GenericError("Reviewers cannot decide on profiles they submitted".to_string()));
+    }
+    Ok(())
+}
+
+async fn review(pool: &PgPool, id: Uuid, review: ReviewKyc, decision: KycStatus) -> Result<KycProfile, AppError> {
+    if !user_exists(pool, review.reviewer_id).await? {
+        return Err(AppError::NotFound("Reviewer".to_string()));
+    }
+    let reason = review.reason.map(|reason| reason.trim().to_string()).filter(|reason| !reason.is_empty());
+    if decision == KycStatus::Rejected && reason.is_none() {
+        return Err(AppError::GenericError("A reason is required to reject a profile".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    let current = lock_for_transition(&mut tx, id, decision).await?;
+    ensure_independent_reviewer(&mut tx, id, review.reviewer_id).await?;
+    transition(&mut tx, id, current, decision, Some(review.reviewer_id), reason.as_deref(), None).await?;
+    tx.commit().await?;
+
+    fetch_kyc_profile(pool, id).await
+}
+
+async fn approve_profile(pool: web::Data<PgPool>, id: web::Path<Uuid>, decision: Json<ReviewKyc>) -> Result<HttpResponse, AppError> {
+    let profile = review(pool.get_ref(), id.into_inner(), decision.into_inner(), KycStatus::Verified).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+async fn reject_profile(pool: web::Data<PgPool>, id: web::Path<Uuid>, decision: Json<ReviewKyc>) -> Result<HttpResponse, AppError> {
+    let profile = review(pool.get_ref(), id.into_inner(), decision.into_inner(), KycStatus::Rejected).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
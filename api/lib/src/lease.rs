@@ -0,0 +1,619 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction};
+use actix_web::http::StatusCode;
+use chrono::{Datelike, Months, Utc};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use uuid::Uuid;
+use shared::models::{
+    Money,
+    LandListing,
+    CreateLandListing,
+    ListingFilter,
+    ListingStatus,
+    LeaseApplication,
+    CreateLeaseApplication,
+    ApplicationDecision,
+    ApplicationStatus,
+    Lease,
+    LeaseFilter,
+    EndLease,
+    DomainEventType
+};
+use tracing::error;
+use crate::outbox;
+
+const AREA_EPSILON_HECTARES: f64 = 1e-6;
+const MAX_TERM_MONTHS: i32 = 99 * 12;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Lease query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/listings")
+                    .route("", web::get().to(get_farm_listings))
+                    .route("", web::post().to(create_listing))
+    );
+    cfg.service(web::scope("/v0.1/land")
+                    .route("/listings", web::get().to(get_all_listings))
+                    .route("/listings/{id}", web::get().to(get_listing))
+                    .route("/listings/{id}/withdraw", web::post().to(withdraw_listing))
+                    .route("/listings/{id}/applications", web::get().to(get_all_applications))
+                    .route("/listings/{id}/applications", web::post().to(create_application))
+                    .route("/applications/{id}/accept", web::post().to(accept_application))
+                    .route("/applications/{id}/reject", web::post().to(reject_application))
+                    .route("/applications/{id}/withdraw", web::post().to(withdraw_application))
+                    .route("/leases", web::get().to(get_all_leases))
+                    .route("/leases/{id}", web::get().to(get_lease))
+                    .route("/leases/{id}/end", web::post().to(end_lease))
+    );
+}
+
+// Yearly rent per hectare over the leased area and term
+fn total_rent(rent_per_hectare: &Money, area: f64, term_months: i32) -> Result<Money, AppError> {
+    let area = Decimal::from_f64(area)
+        .ok_or_else(|| AppError::GenericError("Invalid area".to_string()))?;
+    let amount = rent_per_hectare.amount * area * Decimal::from(term_months) / Decimal::from(12);
+    Money::new(amount, &rent_per_hectare.currency).map_err(AppError::GenericError)
+}
+
+async fn lock_listing(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<LandListing, AppError> {
+    sqlx::query_as::<_, LandListing>(r#"SELECT * FROM "LandListing" WHERE id = $1 FOR UPDATE"#)
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Listing".to_string()))
+}
+
+async fn lock_application(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<LeaseApplication, AppError> {
+    sqlx::query_as::<_, LeaseApplication>(r#"SELECT * FROM "LeaseApplication" WHERE id = $1 FOR UPDATE"#)
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Application".to_string()))
+}
+
+async fn set_application_status(tx: &mut Transaction<'_, Postgres>, id: Uuid, status: ApplicationStatus, reason: Option<String>) -> Result<LeaseApplication, AppError> {
+    let application = sqlx::query_as::<_, LeaseApplication>(
+        r#"
+        UPDATE "LeaseApplication"
+        SET status = $2, "decisionReason" = $3, "updatedAt" = current_timestamp
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(status)
+        .bind(reason)
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(application)
+}
+
+// Pending applications that can no longer be met are turned down with the listing
+async fn reject_pending(tx: &mut Transaction<'_, Postgres>, listing_id: Uuid, reason: &str) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE "LeaseApplication"
+        SET status = 'REJECTED', "decisionReason" = $2, "updatedAt" = current_timestamp
+        WHERE "listingId" = $1 AND status = 'PENDING'
+        "#,
+    )
+        .bind(listing_id)
+        .bind(reason)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+async fn get_farm_listings(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let listings = sqlx::query_as::<_, LandListing>(
+        r#"SELECT * FROM "LandListing" WHERE "farmId" = $1 ORDER BY "createdAt" DESC"#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(listings))
+}
+
+/**
+ * Publish a Listing
+ * Only owned or inherited land can be offered, and no more than the farm's available portion.
+ * The portion is only taken when an application is accepted.
+ **/
+async fn create_listing(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, listing: Json<CreateLandListing>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let listing = listing.into_inner();
+    let area = listing.area.to_hectares();
+
+    if area <= 0.0 {
+        return Err(AppError::GenericError("Listed area must be positive".to_string()));
+    }
+    if listing.rent_per_hectare.amount <= Decimal::ZERO {
+        return Err(AppError::GenericError("rentPerHectare must be positive".to_string()));
+    }
+    if listing.min_term_months < 1 || listing.max_term_months > MAX_TERM_MONTHS || listing.min_term_months > listing.max_term_months {
+        return Err(AppError::GenericError(format!(
+            "Lease terms must satisfy 1 <= minTermMonths <= maxTermMonths <= {}",
+            MAX_TERM_MONTHS
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let farm: Option<(Option<String>, Option<f64>)> = sqlx::query_as(
+        r#"SELECT ownership, available_portion FROM "Farm" WHERE id = $1"#,
+    )
+        .bind(farm_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let (ownership, available_portion) = farm.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+
+    if !matches!(ownership.as_deref(), Some("OWNER") | Some("INHERIT")) {
+        return Err(AppError::GenericError("Only owned or inherited land can be leased out".to_string()));
+    }
+    let available_portion = available_portion.unwrap_or(0.0);
+    if area > available_portion + AREA_EPSILON_HECTARES {
+        return Err(AppError::GenericError(format!(
+            "Listed area of {:.4} ha exceeds the farm's available portion ({:.4} ha)",
+            area, available_portion
+        )));
+    }
+
+    if let Some(plot_id) = listing.plot_id {
+        let plot_area: Option<f64> = sqlx::query_scalar(r#"SELECT area FROM "Plot" WHERE id = $1 AND "farmId" = $2"#)
+            .bind(plot_id)
+            .bind(farm_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        match plot_area {
+            None => return Err(AppError::NotFound("Plot".to_string())),
+            Some(plot_area) if area > plot_area + AREA_EPSILON_HECTARES => {
+                return Err(AppError::GenericError(format!(
+                    "Listed area of {:.4} ha exceeds the plot's area ({:.4} ha)",
+                    area, plot_area
+                )));
+            }
+            Some(_) => {}
+        }
+    }
+
+    let listing = sqlx::query_as::<_, LandListing>(
+        r#"
+        INSERT INTO "LandListing" ("farmId", "plotId", area, "rentPerHectare", "availableFrom", "minTermMonths", "maxTermMonths", description)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(listing.plot_id)
+        .bind(area)
+        .bind(listing.rent_per_hectare)
+        .bind(listing.available_from)
+        .bind(listing.min_term_months)
+        .bind(listing.max_term_months)
+        .bind(listing.description)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(listing))
+}
+
+/**
+ * Get Listings
+ * The marketplace view: open listings by default, narrowed by location and size
+ **/
+async fn get_all_listings(pool: web::Data<PgPool>, filter: web::Query<ListingFilter>) -> Result<HttpResponse, AppError> {
+    let ListingFilter { status, state_id, lga_id, min_area } = filter.into_inner();
+    let listings = sqlx::query_as::<_, LandListing>(
+        r#"
+        SELECT l.*
+        FROM "LandListing" l
+        JOIN "Farm" f ON f.id = l."farmId"
+        WHERE l.status = $1
+          AND ($2::uuid IS NULL OR f."stateId" = $2)
+          AND ($3::uuid IS NULL OR f."lgaId" = $3)
+          AND ($4::double precision IS NULL OR l.area >= $4)
+        ORDER BY l."createdAt" DESC
+        "#,
+    )
+        .bind(status.unwrap_or(ListingStatus::Open))
+        .bind(state_id)
+        .bind(lga_id)
+        .bind(min_area)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(listings))
+}
+
+async fn get_listing(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let listing = sqlx::query_as::<_, LandListing>(r#"SELECT * FROM "LandListing" WHERE id = $1"#)
+        .bind(id.into_inner())
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match listing {
+        Some(listing) => Ok(HttpResponse::Ok().json(listing)),
+        None => Err(AppError::NotFound("Listing".to_string())),
+    }
+}
+
+async fn withdraw_listing(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+
+    let mut tx = pool.begin().await?;
+    let listing = lock_listing(&mut tx, id).await?;
+    if listing.status != ListingStatus::Open {
+        return Err(AppError::GenericError("Only open listings can be withdrawn".to_string()));
+    }
+
+    let listing = sqlx::query_as::<_, LandListing>(
+        r#"UPDATE "LandListing" SET status = 'WITHDRAWN', "updatedAt" = current_timestamp WHERE id = $1 RETURNING *"#,
+    )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+    reject_pending(&mut tx, id, "Listing withdrawn").await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(listing))
+}
+
+async fn get_all_applications(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let applications = sqlx::query_as::<_, LeaseApplication>(
+        r#"SELECT * FROM "LeaseApplication" WHERE "listingId" = $1 ORDER BY "createdAt""#,
+    )
+        .bind(id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(applications))
+}
+
+async fn create_application(pool: web::Data<PgPool>, id: web::Path<Uuid>, application: Json<CreateLeaseApplication>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let application = application.into_inner();
+
+    let mut tx = pool.begin().await?;
+    let listing = lock_listing(&mut tx, id).await?;
+    if listing.status != ListingStatus::Open {
+        return Err(AppError::GenericError("Listing is not open for applications".to_string()));
+    }
+
+    let area = application.area.map(|area| area.to_hectares()).unwrap_or(listing.area);
+    if area <= 0.0 {
+        return Err(AppError::GenericError("Requested area must be positive".to_string()));
+    }
+    if area > listing.area + AREA_EPSILON_HECTARES {
+        return Err(AppError::GenericError(format!(
+            "Requested area of {:.4} ha exceeds the {:.4} ha on offer",
+            area, listing.area
+        )));
+    }
+    if application.term_months < listing.min_term_months || application.term_months > listing.max_term_months {
+        return Err(AppError::GenericError(format!(
+            "termMonths must be between {} and {}",
+            listing.min_term_months, listing.max_term_months
+        )));
+    }
+    if application.start_date < listing.available_from {
+        return Err(AppError::GenericError(format!("The land is available from {}", listing.available_from)));
+    }
+
+    let landlord_id: Uuid = sqlx::query_scalar(r#"SELECT "farmerId" FROM "Farm" WHERE id = $1"#)
+        .bind(listing.farm_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if landlord_id == application.applicant_id {
+        return Err(AppError::GenericError("Owners cannot apply for their own land".to_string()));
+    }
+    let applicant_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+        .bind(application.applicant_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !applicant_exists {
+        return Err(AppError::NotFound("Applicant".to_string()));
+    }
+    let pending: bool = sqlx::query_scalar(
+        r#"SELECT EXISTS (SELECT 1 FROM "LeaseApplication" WHERE "listingId" = $1 AND "applicantId" = $2 AND status = 'PENDING')"#,
+    )
+        .bind(id)
+        .bind(application.applicant_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if pending {
+        return Err(AppError::GenericError("Applicant already has a pending application for this listing".to_string()));
+    }
+
+    let application = sqlx::query_as::<_, LeaseApplication>(
+        r#"
+        INSERT INTO "LeaseApplication" ("listingId", "applicantId", area, "startDate", "termMonths", message)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(application.applicant_id)
+        .bind(area)
+        .bind(application.start_date)
+        .bind(application.term_months)
+        .bind(application.message)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(application))
+}
+
+/**
+ * Accept an Application
+ * Creates the lease and takes its area out of the farm's available portion in one transaction.
+ * The farm row is locked, so concurrent acceptances against the same land cannot both succeed,
+ * and land already planted for the lease's seasons cannot be leased out from under the crop.
+ **/
+async fn accept_application(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+
+    let mut tx = pool.begin().await?;
+    // Lock order: listing, application, farm
+    let listing_id: Uuid = sqlx::query_scalar(r#"SELECT "listingId" FROM "LeaseApplication" WHERE id = $1"#)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Application".to_string()))?;
+    let listing = lock_listing(&mut tx, listing_id).await?;
+    let application = lock_application(&mut tx, id).await?;
+
+    if application.status != ApplicationStatus::Pending {
+        return Err(AppError::GenericError("Only pending applications can be accepted".to_string()));
+    }
+    if listing.status != ListingStatus::Open {
+        return Err(AppError::GenericError("Listing is no longer open".to_string()));
+    }
+    if application.area > listing.area + AREA_EPSILON_HECTARES {
+        return Err(AppError::GenericError(format!(
+            "Requested area of {:.4} ha exceeds the {:.4} ha still on offer",
+            application.area, listing.area
+        )));
+    }
+
+    let (landlord_id, available_portion): (Uuid, Option<f64>) = sqlx::query_as(
+        r#"SELECT "farmerId", available_portion FROM "Farm" WHERE id = $1 FOR UPDATE"#,
+    )
+        .bind(listing.farm_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let available_portion = available_portion.unwrap_or(0.0);
+
+    let planted: f64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(MAX(planted), 0)
+        FROM (
+            SELECT SUM("plantedArea") AS planted
+            FROM "Planting"
+            WHERE "farmId" = $1 AND "seasonYear" >= $2
+            GROUP BY season, "seasonYear"
+        ) s
+        "#,
+    )
+        .bind(listing.farm_id)
+        .bind(application.start_date.year())
+        .fetch_one(&mut *tx)
+        .await?;
+    if application.area > available_portion - planted + AREA_EPSILON_HECTARES {
+        return Err(AppError::GenericError(format!(
+            "Only {:.4} ha of the farm's available portion is free ({:.4} ha available, {:.4} ha planted)",
+            (available_portion - planted).max(0.0),
+            available_portion,
+            planted
+        )));
+    }
+
+    let end_date = application.start_date
+        .checked_add_months(Months::new(application.term_months as u32))
+        .ok_or_else(|| AppError::GenericError("Lease term is out of range".to_string()))?;
+    let rent = total_rent(&listing.rent_per_hectare, application.area, application.term_months)?;
+
+    sqlx::query(
+        r#"
+        UPDATE "Farm"
+        SET available_portion = GREATEST(available_portion - $2, 0), "updatedAt" = current_timestamp
+        WHERE id = $1
+        "#,
+    )
+        .bind(listing.farm_id)
+        .bind(application.area)
+        .execute(&mut *tx)
+        .await?;
+    outbox::record_current(&mut tx, DomainEventType::FarmUpdated, listing.farm_id).await?;
+
+    let lease = sqlx::query_as::<_, Lease>(
+        r#"
+        INSERT INTO "Lease" ("farmId", "plotId", "listingId", "applicationId", "landlordId", "tenantId", area, "startDate", "endDate", rent)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING *
+        "#,
+    )
+        .bind(listing.farm_id)
+        .bind(listing.plot_id)
+        .bind(listing.id)
+        .bind(application.id)
+        .bind(landlord_id)
+        .bind(application.applicant_id)
+        .bind(application.area)
+        .bind(application.start_date)
+        .bind(end_date)
+        .bind(rent)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    set_application_status(&mut tx, application.id, ApplicationStatus::Accepted, None).await?;
+
+    // Whatever is left of the listing stays on offer
+    let remaining = (listing.area - application.area).max(0.0);
+    if remaining <= AREA_EPSILON_HECTARES {
+        sqlx::query(r#"UPDATE "LandListing" SET area = 0, status = 'LEASED', "updatedAt" = current_timestamp WHERE id = $1"#)
+            .bind(listing.id)
+            .execute(&mut *tx)
+            .await?;
+        reject_pending(&mut tx, listing.id, "Listing fully leased").await?;
+    } else {
+        sqlx::query(r#"UPDATE "LandListing" SET area = $2, "updatedAt" = current_timestamp WHERE id = $1"#)
+            .bind(listing.id)
+            .bind(remaining)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(lease))
+}
+
+async fn reject_application(pool: web::Data<PgPool>, id: web::Path<Uuid>, decision: Json<ApplicationDecision>) -> Result<HttpResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let application = lock_application(&mut tx, id.into_inner()).await?;
+    if application.status != ApplicationStatus::Pending {
+        return Err(AppError::GenericError("Only pending applications can be rejected".to_string()));
+    }
+
+    let application = set_application_status(&mut tx, application.id, ApplicationStatus::Rejected, decision.into_inner().reason).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(application))
+}
+
+async fn withdraw_application(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let application = lock_application(&mut tx, id.into_inner()).await?;
+    if application.status != ApplicationStatus::Pending {
+        return Err(AppError::GenericError("Only pending applications can be withdrawn".to_string()));
+    }
+
+    let application = set_application_status(&mut tx, application.id, ApplicationStatus::Withdrawn, None).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(application))
+}
+
+async fn get_all_leases(pool: web::Data<PgPool>, filter: web::Query<LeaseFilter>) -> Result<HttpResponse, AppError> {
+    let LeaseFilter { farm_id, landlord_id, tenant_id, status } = filter.into_inner();
+    let leases = sqlx::query_as::<_, Lease>(
+        r#"
+        SELECT *
+        FROM "Lease"
+        WHERE ($1::uuid IS NULL OR "farmId" = $1)
+          AND ($2::uuid IS NULL OR "landlordId" = $2)
+          AND ($3::uuid IS NULL OR "tenantId" = $3)
+          AND ($4::varchar IS NULL OR status = $4)
+        ORDER BY "startDate" DESC
+        "#,
+    )
+        .bind(farm_id)
+        .bind(landlord_id)
+        .bind(tenant_id)
+        .bind(status)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(leases))
+}
+
+async fn get_lease(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let lease = sqlx::query_as::<_, Lease>(r#"SELECT * FROM "Lease" WHERE id = $1"#)
+        .bind(id.into_inner())
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match lease {
+        Some(lease) => Ok(HttpResponse::Ok().json(lease)),
+        None => Err(AppError::NotFound("Lease".to_string())),
+    }
+}
+
+/**
+ * End a Lease
+ * Returns the leased area to the farm's available portion
+ **/
+async fn end_lease(pool: web::Data<PgPool>, id: web::Path<Uuid>, end: Json<EndLease>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let ended_on = end.ended_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut tx = pool.begin().await?;
+    let lease = sqlx::query_as::<_, Lease>(r#"SELECT * FROM "Lease" WHERE id = $1 FOR UPDATE"#)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Lease".to_string()))?;
+    if lease.ended_on.is_some() {
+        return Err(AppError::GenericError("Lease has already ended".to_string()));
+    }
+    if ended_on < lease.start_date {
+        return Err(AppError::GenericError("endedOn must not be before the lease starts".to_string()));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE "Farm"
+        SET available_portion = LEAST(COALESCE(available_portion, 0) + $2, acreage), "updatedAt" = current_timestamp
+        WHERE id = $1
+        "#,
+    )
+        .bind(lease.farm_id)
+        .bind(lease.area)
+        .execute(&mut *tx)
+        .await?;
+    outbox::record_current(&mut tx, DomainEventType::FarmUpdated, lease.farm_id).await?;
+
+    let lease = sqlx::query_as::<_, Lease>(
+        r#"
+        UPDATE "Lease"
+        SET status = 'ENDED', "endedOn" = $2, "updatedAt" = current_timestamp
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(ended_on)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(lease))
+}
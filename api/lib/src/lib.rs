@@ -1,3 +1,34 @@
 pub mod user;
 pub mod profile;
-pub mod farm;
\ No newline at end of file
+pub mod farm;
+pub mod cache;
+pub mod reference;
+pub mod money;
+pub mod plot;
+pub mod crop;
+pub mod planting;
+pub mod harvest;
+pub mod analytics;
+pub mod soil;
+pub mod weather;
+pub mod weather_import;
+pub mod irrigation;
+pub mod incident;
+pub mod outbreak;
+pub mod input;
+pub mod livestock;
+pub mod cooperative;
+pub mod agent;
+pub mod lease;
+pub mod title;
+pub mod identity;
+pub mod kyc;
+pub mod duplicate;
+pub mod search;
+pub mod phone;
+pub mod history;
+pub mod outbox;
+pub mod webhook;
+pub mod api_key;
+pub mod events;
+pub mod sync;
@@ -0,0 +1,592 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction};
+use actix_web::http::StatusCode;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+use shared::models::{
+    Herd,
+    CreateHerd,
+    UpdateHerd,
+    Animal,
+    AnimalStatus,
+    CreateAnimal,
+    Sex,
+    LivestockEvent,
+    LivestockEventKind,
+    CreateLivestockEvent,
+    LivestockEventFilter,
+    HerdSizeInterval,
+    HerdSizeQuery,
+    HerdSizePoint,
+    LivestockSummary,
+    LivestockSummaryQuery
+};
+use tracing::error;
+
+const MAX_SERIES_POINTS: i64 = 1000;
+
+// Effect of an event on the herd's headcount
+const SIGNED_COUNT: &str = r#"
+    CASE
+        WHEN e.kind IN ('OPENING', 'PURCHASE', 'BIRTH') THEN e.count
+        WHEN e.kind IN ('DEATH', 'SALE') THEN -e.count
+        ELSE 0
+    END
+"#;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Livestock query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/livestock")
+                    .route("/herds", web::get().to(get_all_herds))
+                    .route("/herds", web::post().to(create_herd))
+                    .route("/herds/{id}", web::get().to(get_herd))
+                    .route("/herds/{id}", web::put().to(update_herd))
+                    .route("/herds/{id}/animals", web::get().to(get_all_animals))
+                    .route("/herds/{id}/animals", web::post().to(create_animal))
+                    .route("/herds/{id}/events", web::get().to(get_all_events))
+                    .route("/herds/{id}/events", web::post().to(create_event))
+                    .route("/size", web::get().to(get_herd_size))
+                    .route("/summary", web::get().to(get_summary))
+    );
+}
+
+fn herd_query(condition: &str) -> String {
+    format!(
+        r#"
+        SELECT h.*,
+               COALESCE((SELECT SUM({}) FROM "LivestockEvent" e WHERE e."herdId" = h.id), 0)::bigint AS headcount
+        FROM "Herd" h
+        WHERE {}
+        ORDER BY h.name
+        "#,
+        SIGNED_COUNT, condition
+    )
+}
+
+async fn fetch_herd(tx: &mut Transaction<'_, Postgres>, farm_id: Uuid, id: Uuid) -> Result<Herd, AppError> {
+    sqlx::query_as::<_, Herd>(&herd_query(r#"h.id = $1 AND h."farmId" = $2"#))
+        .bind(id)
+        .bind(farm_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Herd".to_string()))
+}
+
+// Locks the herd so headcount checks and the write that follows see the same events
+async fn lock_herd(tx: &mut Transaction<'_, Postgres>, farm_id: Uuid, id: Uuid) -> Result<Herd, AppError> {
+    sqlx::query(r#"SELECT id FROM "Herd" WHERE id = $1 AND "farmId" = $2 FOR UPDATE"#)
+        .bind(id)
+        .bind(farm_id)
+        .execute(&mut **tx)
+        .await?;
+    fetch_herd(tx, farm_id, id).await
+}
+
+async fn tagged_alive(tx: &mut Transaction<'_, Postgres>, herd_id: Uuid) -> Result<i64, AppError> {
+    let count = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Animal" WHERE "herdId" = $1 AND status = 'ALIVE'"#)
+        .bind(herd_id)
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(count)
+}
+
+async fn get_all_herds(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let herds = sqlx::query_as::<_, Herd>(&herd_query(r#"h."farmId" = $1"#))
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(herds))
+}
+
+async fn get_herd(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let herd = sqlx::query_as::<_, Herd>(&herd_query(r#"h.id = $1 AND h."farmId" = $2"#))
+        .bind(id)
+        .bind(farm_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match herd {
+        Some(herd) => Ok(HttpResponse::Ok().json(herd)),
+        None => Err(AppError::NotFound("Herd".to_string())),
+    }
+}
+
+/**
+ * Create Herd
+ * Animals already on hand are recorded as an opening event on the start date
+ **/
+async fn create_herd(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, herd: Json<CreateHerd>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let herd = herd.into_inner();
+
+    if herd.name.trim().is_empty() {
+        return Err(AppError::GenericError("Herd name is required".to_string()));
+    }
+    if herd.opening_count < 0 {
+        return Err(AppError::GenericError("openingCount must not be negative".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let farm_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Farm" WHERE id = $1)"#)
+        .bind(farm_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !farm_exists {
+        return Err(AppError::NotFound("Farm".to_string()));
+    }
+
+    let id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO "Herd" ("farmId", name, species, breed, "startedOn")
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+        .bind(farm_id)
+        .bind(herd.name.trim())
+        .bind(herd.species)
+        .bind(herd.breed)
+        .bind(herd.started_on)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if herd.opening_count > 0 {
+        sqlx::query(r#"INSERT INTO "LivestockEvent" ("herdId", kind, count, "eventDate") VALUES ($1, 'OPENING', $2, $3)"#)
+            .bind(id)
+            .bind(herd.opening_count)
+            .bind(herd.started_on)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let herd = fetch_herd(&mut tx, farm_id, id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(herd))
+}
+
+async fn update_herd(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, herd: Json<UpdateHerd>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let herd = herd.into_inner();
+
+    if herd.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+        return Err(AppError::GenericError("Herd name is required".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query(
+        r#"
+        UPDATE "Herd"
+        SET name = COALESCE($3, name),
+            breed = COALESCE($4, breed),
+            "updatedAt" = current_timestamp
+        WHERE id = $1 AND "farmId" = $2
+        "#,
+    )
+        .bind(id)
+        .bind(farm_id)
+        .bind(herd.name.map(|name| name.trim().to_string()))
+        .bind(herd.breed)
+        .execute(&mut *tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Herd".to_string()));
+    }
+    let herd = fetch_herd(&mut tx, farm_id, id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(herd))
+}
+
+async fn get_all_animals(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let animals = sqlx::query_as::<_, Animal>(
+        r#"
+        SELECT a.*
+        FROM "Animal" a
+        JOIN "Herd" h ON h.id = a."herdId"
+        WHERE a."herdId" = $1 AND h."farmId" = $2
+        ORDER BY a."tagId"
+        "#,
+    )
+        .bind(id)
+        .bind(farm_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(animals))
+}
+
+/**
+ * Register a tagged animal.
+ * With an acquisition (BIRTH or PURCHASE) the animal is added to the headcount;
+ * otherwise it must be one of the herd's untagged animals.
+ **/
+async fn create_animal(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, animal: Json<CreateAnimal>) -> Result<HttpResponse, AppError> {
+    let (farm_id, herd_id) = path.into_inner();
+    let animal = animal.into_inner();
+
+    let tag_id = animal.tag_id.trim();
+    if tag_id.is_empty() {
+        return Err(AppError::GenericError("tagId is required".to_string()));
+    }
+    if let Some(kind) = animal.acquisition {
+        if kind != LivestockEventKind::Birth && kind != LivestockEventKind::Purchase {
+            return Err(AppError::GenericError("acquisition must be BIRTH or PURCHASE".to_string()));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let herd = lock_herd(&mut tx, farm_id, herd_id).await?;
+
+    let tag_taken: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Animal" WHERE "tagId" = $1)"#)
+        .bind(tag_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if tag_taken {
+        return Err(AppError::GenericError(format!("Tag {} is already registered", tag_id)));
+    }
+
+    // Parents must be on the same farm and of the right sex
+    for (parent_id, sex, role) in [(animal.dam_id, Sex::Female, "Dam"), (animal.sire_id, Sex::Male, "Sire")] {
+        let expected = if sex == Sex::Female { "female" } else { "male" };
+        let Some(parent_id) = parent_id else { continue };
+        let parent_sex: Option<Sex> = sqlx::query_scalar(
+            r#"
+            SELECT a.sex
+            FROM "Animal" a
+            JOIN "Herd" h ON h.id = a."herdId"
+            WHERE a.id = $1 AND h."farmId" = $2
+            "#,
+        )
+            .bind(parent_id)
+            .bind(farm_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        match parent_sex {
+            None => return Err(AppError::NotFound(role.to_string())),
+            Some(parent_sex) if parent_sex != sex => {
+                return Err(AppError::GenericError(format!("{} must be {}", role, expected)));
+            }
+            Some(_) => {}
+        }
+    }
+
+    let acquired_on = animal.acquired_on
+        .or(animal.birth_date.filter(|_| animal.acquisition == Some(LivestockEventKind::Birth)))
+        .unwrap_or_else(|| Utc::now().date_naive());
+    if animal.acquisition.is_some() && acquired_on < herd.started_on {
+        return Err(AppError::GenericError("Acquisition date is before the herd was started".to_string()));
+    }
+
+    let created = sqlx::query_as::<_, Animal>(
+        r#"
+        INSERT INTO "Animal" ("herdId", "tagId", sex, breed, "birthDate", "damId", "sireId", notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+        .bind(herd_id)
+        .bind(tag_id)
+        .bind(animal.sex)
+        .bind(animal.breed.or(herd.breed))
+        .bind(animal.birth_date)
+        .bind(animal.dam_id)
+        .bind(animal.sire_id)
+        .bind(animal.notes)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let headcount = match animal.acquisition {
+        Some(kind) => {
+            sqlx::query(r#"INSERT INTO "LivestockEvent" ("herdId", "animalId", kind, count, "eventDate") VALUES ($1, $2, $3, 1, $4)"#)
+                .bind(herd_id)
+                .bind(created.id)
+                .bind(kind)
+                .bind(acquired_on)
+                .execute(&mut *tx)
+                .await?;
+            herd.headcount + 1
+        }
+        None => herd.headcount,
+    };
+    if tagged_alive(&mut tx, herd_id).await? > headcount {
+        return Err(AppError::GenericError(
+            "All animals in the herd are already tagged; give an acquisition for new animals".to_string()
+        ));
+    }
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(created))
+}
+
+async fn get_all_events(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, filter: web::Query<LivestockEventFilter>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let filter = filter.into_inner();
+    let events = sqlx::query_as::<_, LivestockEvent>(
+        r#"
+        SELECT e.*
+        FROM "LivestockEvent" e
+        JOIN "Herd" h ON h.id = e."herdId"
+        WHERE e."herdId" = $1 AND h."farmId" = $2
+          AND ($3::varchar IS NULL OR e.kind = $3)
+          AND ($4::uuid IS NULL OR e."animalId" = $4)
+        ORDER BY e."eventDate" DESC, e."createdAt" DESC
+        "#,
+    )
+        .bind(id)
+        .bind(farm_id)
+        .bind(filter.kind)
+        .bind(filter.animal_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(events))
+}
+
+/**
+ * Record a birth, purchase, death, sale, vaccination or treatment
+ * for the whole herd, a number of animals, or one tagged animal
+ **/
+async fn create_event(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, event: Json<CreateLivestockEvent>) -> Result<HttpResponse, AppError> {
+    let (farm_id, herd_id) = path.into_inner();
+    let event = event.into_inner();
+    let kind = event.kind;
+
+    if kind == LivestockEventKind::Opening {
+        return Err(AppError::GenericError("Opening counts are set when the herd is created".to_string()));
+    }
+    if event.animal_id.is_some() && matches!(kind, LivestockEventKind::Birth | LivestockEventKind::Purchase) {
+        return Err(AppError::GenericError("Register tagged animals through the animals endpoint".to_string()));
+    }
+    let count = match (event.animal_id, event.count) {
+        (Some(_), Some(count)) if count != 1 => {
+            return Err(AppError::GenericError("count must be 1 for a single animal".to_string()));
+        }
+        (_, Some(count)) if count < 1 => return Err(AppError::GenericError("count must be positive".to_string())),
+        (_, count) => count.unwrap_or(1),
+    };
+    if matches!(kind, LivestockEventKind::Vaccination | LivestockEventKind::Treatment)
+        && event.product.as_deref().is_none_or(|product| product.trim().is_empty()) {
+        return Err(AppError::GenericError("product is required for vaccinations and treatments".to_string()));
+    }
+    if let Some(price) = &event.sale_price {
+        if kind != LivestockEventKind::Sale {
+            return Err(AppError::GenericError("salePrice is only recorded for sales".to_string()));
+        }
+        if price.amount < Decimal::ZERO {
+            return Err(AppError::GenericError("salePrice must not be negative".to_string()));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let herd = lock_herd(&mut tx, farm_id, herd_id).await?;
+
+    if event.event_date < herd.started_on {
+        return Err(AppError::GenericError("Event date is before the herd was started".to_string()));
+    }
+
+    if let Some(animal_id) = event.animal_id {
+        let status: Option<AnimalStatus> = sqlx::query_scalar(r#"SELECT status FROM "Animal" WHERE id = $1 AND "herdId" = $2"#)
+            .bind(animal_id)
+            .bind(herd_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        match status {
+            None => return Err(AppError::NotFound("Animal".to_string())),
+            Some(AnimalStatus::Alive) => {}
+            Some(_) => return Err(AppError::GenericError("Animal is no longer in the herd".to_string())),
+        }
+    }
+
+    if kind.headcount_sign() < 0 {
+        // Untagged removals must leave the tagged animals in place
+        let available = match event.animal_id {
+            Some(_) => herd.headcount,
+            None => herd.headcount - tagged_alive(&mut tx, herd_id).await?,
+        };
+        if i64::from(count) > available {
+            return Err(AppError::GenericError(format!(
+                "Only {} {}animals in the herd",
+                available,
+                if event.animal_id.is_some() { "" } else { "untagged " }
+            )));
+        }
+    }
+
+    let created = sqlx::query_as::<_, LivestockEvent>(
+        r#"
+        INSERT INTO "LivestockEvent" ("herdId", "animalId", kind, count, "eventDate", "salePrice", cause, product, dose, "administeredBy", notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING *
+        "#,
+    )
+        .bind(herd_id)
+        .bind(event.animal_id)
+        .bind(kind)
+        .bind(count)
+        .bind(event.event_date)
+        .bind(event.sale_price)
+        .bind(event.cause)
+        .bind(event.product)
+        .bind(event.dose)
+        .bind(event.administered_by)
+        .bind(event.notes)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let new_status = match kind {
+        LivestockEventKind::Death => Some(AnimalStatus::Dead),
+        LivestockEventKind::Sale => Some(AnimalStatus::Sold),
+        _ => None,
+    };
+    if let (Some(animal_id), Some(status)) = (event.animal_id, new_status) {
+        sqlx::query(r#"UPDATE "Animal" SET status = $2, "updatedAt" = current_timestamp WHERE id = $1"#)
+            .bind(animal_id)
+            .bind(status)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(created))
+}
+
+/**
+ * Headcount at each day, week or month start between `from` and `to`,
+ * for one herd or all of a farm's herds of a species
+ **/
+async fn get_herd_size(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, query: web::Query<HerdSizeQuery>) -> Result<HttpResponse, AppError> {
+    let query = query.into_inner();
+    if query.to < query.from {
+        return Err(AppError::GenericError("to must not be before from".to_string()));
+    }
+    let (step, days_per_step) = match query.interval {
+        HerdSizeInterval::Day => ("1 day", 1),
+        HerdSizeInterval::Week => ("1 week", 7),
+        HerdSizeInterval::Month => ("1 month", 28),
+    };
+    if (query.to - query.from).num_days() / days_per_step > MAX_SERIES_POINTS {
+        return Err(AppError::GenericError(format!("At most {} points can be requested", MAX_SERIES_POINTS)));
+    }
+
+    let series_query = format!(
+        r#"
+        SELECT d::date AS date,
+               COALESCE((
+                   SELECT SUM({})
+                   FROM "LivestockEvent" e
+                   JOIN "Herd" h ON h.id = e."herdId"
+                   WHERE h."farmId" = $1
+                     AND ($2::uuid IS NULL OR h.id = $2)
+                     AND ($3::varchar IS NULL OR h.species = $3)
+                     AND e."eventDate" <= d::date
+               ), 0)::bigint AS headcount
+        FROM generate_series($4::date, $5::date, $6::interval) d
+        "#,
+        SIGNED_COUNT
+    );
+
+    let points = sqlx::query_as::<_, HerdSizePoint>(&series_query)
+        .bind(farm_id.into_inner())
+        .bind(query.herd_id)
+        .bind(query.species)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(step)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(points))
+}
+
+/**
+ * Per-herd movements over a period: opening and closing headcount,
+ * purchases, births, deaths, sales and health events
+ **/
+async fn get_summary(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, query: web::Query<LivestockSummaryQuery>) -> Result<HttpResponse, AppError> {
+    let query = query.into_inner();
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    if query.from.is_some_and(|from| from > to) {
+        return Err(AppError::GenericError("from must not be after to".to_string()));
+    }
+
+    let summary_query = format!(
+        r#"
+        WITH events AS (
+            SELECT e.*,
+                   {} AS signed,
+                   ($2::date IS NULL OR e."eventDate" >= $2) AND e."eventDate" <= $3 AS in_period
+            FROM "LivestockEvent" e
+        )
+        SELECT h.id AS "herdId",
+               h.name,
+               h.species,
+               COALESCE(SUM(e.signed) FILTER (WHERE e."eventDate" < $2), 0)::bigint AS "openingHeadcount",
+               COALESCE(SUM(e.count) FILTER (WHERE e.in_period AND e.kind = 'PURCHASE'), 0)::bigint AS purchases,
+               COALESCE(SUM(e.count) FILTER (WHERE e.in_period AND e.kind = 'BIRTH'), 0)::bigint AS births,
+               COALESCE(SUM(e.count) FILTER (WHERE e.in_period AND e.kind = 'DEATH'), 0)::bigint AS deaths,
+               COALESCE(SUM(e.count) FILTER (WHERE e.in_period AND e.kind = 'SALE'), 0)::bigint AS sales,
+               COALESCE(SUM(e.signed) FILTER (WHERE e."eventDate" <= $3), 0)::bigint AS "closingHeadcount",
+               COUNT(e.id) FILTER (WHERE e.in_period AND e.kind = 'VACCINATION') AS vaccinations,
+               COUNT(e.id) FILTER (WHERE e.in_period AND e.kind = 'TREATMENT') AS treatments
+        FROM "Herd" h
+        LEFT JOIN events e ON e."herdId" = h.id
+        WHERE h."farmId" = $1
+        GROUP BY h.id
+        ORDER BY h.name
+        "#,
+        SIGNED_COUNT
+    );
+
+    let summary = sqlx::query_as::<_, LivestockSummary>(&summary_query)
+        .bind(farm_id.into_inner())
+        .bind(query.from)
+        .bind(to)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(summary))
+}
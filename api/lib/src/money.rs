@@ -0,0 +1,193 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use shared::models::{
+    Money,
+    ExchangeRate,
+    CreateExchangeRate,
+    ConvertMoney
+};
+use tracing::error;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    MissingRate(String, String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::MissingRate(from, to) => write!(f, "No exchange rate from {} to {}", from, to),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::MissingRate(_, _) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/exchange-rates")
+                    .route("", web::get().to(get_all_exchange_rates))
+                    .route("", web::post().to(create_exchange_rate))
+                    .route("/convert", web::get().to(convert_money))
+    );
+}
+
+/**
+ * Latest rate for `from -> to` effective on `date`.
+ * Falls back to the inverse of `to -> from` when only that pair is recorded.
+ **/
+pub async fn exchange_rate(pool: &PgPool, from: &str, to: &str, date: NaiveDate) -> Result<Decimal, AppError> {
+    if from == to {
+        return Ok(Decimal::ONE);
+    }
+
+    let rate: Option<(String, Decimal)> = sqlx::query_as(
+        r#"
+        SELECT "baseCurrency", rate
+        FROM "ExchangeRate"
+        WHERE (("baseCurrency" = $1 AND "quoteCurrency" = $2)
+            OR ("baseCurrency" = $2 AND "quoteCurrency" = $1))
+          AND "effectiveDate" <= $3
+        ORDER BY "effectiveDate" DESC, ("baseCurrency" = $1) DESC
+        LIMIT 1
+        "#,
+    )
+        .bind(from)
+        .bind(to)
+        .bind(date)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            error!("Error getting exchange rate: {:?}", e);
+            AppError::SqlError(e)
+        })?;
+
+    rate.map(|(base, rate)| oriented_rate(from, &base, rate))
+        .ok_or_else(|| AppError::MissingRate(from.to_string(), to.to_string()))
+}
+
+// A rate recorded for the opposite pair is turned around
+fn oriented_rate(from: &str, base: &str, rate: Decimal) -> Decimal {
+    if base == from {
+        rate
+    } else {
+        Decimal::ONE / rate
+    }
+}
+
+/**
+ * Convert money into another currency at the rate effective on `date`
+ **/
+pub async fn convert(pool: &PgPool, money: &Money, to: &str, date: NaiveDate) -> Result<Money, AppError> {
+    let currency = Money::new(Decimal::ZERO, to).map_err(AppError::GenericError)?.currency;
+    let rate = exchange_rate(pool, &money.currency, &currency, date).await?;
+    // Rounded by `Money::new` like any other amount
+    Money::new(money.amount * rate, &currency).map_err(AppError::GenericError)
+}
+
+async fn get_all_exchange_rates(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let rates_result = sqlx::query_as::<_, ExchangeRate>(
+        r#"
+        SELECT *
+        FROM "ExchangeRate"
+        ORDER BY "effectiveDate" DESC, "baseCurrency", "quoteCurrency"
+        "#,
+    )
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match rates_result {
+        Ok(rates) => Ok(HttpResponse::Ok().json(rates)),
+        Err(e) => {
+            error!("Error getting exchange rates: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+async fn create_exchange_rate(pool: web::Data<PgPool>, rate: Json<CreateExchangeRate>) -> Result<HttpResponse, AppError> {
+    let rate = rate.into_inner();
+
+    let base = Money::new(Decimal::ZERO, &rate.base_currency).map_err(AppError::GenericError)?.currency;
+    let quote = Money::new(Decimal::ZERO, &rate.quote_currency).map_err(AppError::GenericError)?.currency;
+    if base == quote {
+        return Err(AppError::GenericError("Base and quote currency must differ".to_string()));
+    }
+    if rate.rate <= Decimal::ZERO {
+        return Err(AppError::GenericError("Rate must be positive".to_string()));
+    }
+
+    let rate_result = sqlx::query_as::<_, ExchangeRate>(
+        r#"
+        INSERT INTO "ExchangeRate" ("baseCurrency", "quoteCurrency", rate, "effectiveDate")
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT ("baseCurrency", "quoteCurrency", "effectiveDate")
+        DO UPDATE SET rate = EXCLUDED.rate
+        RETURNING *
+        "#,
+    )
+        .bind(base)
+        .bind(quote)
+        .bind(rate.rate)
+        .bind(rate.effective_date)
+        .fetch_one(pool.get_ref())
+        .await;
+
+    match rate_result {
+        Ok(rate) => Ok(HttpResponse::Created().json(rate)),
+        Err(e) => {
+            error!("Error creating exchange rate: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+async fn convert_money(pool: web::Data<PgPool>, query: web::Query<ConvertMoney>) -> Result<HttpResponse, AppError> {
+    let query = query.into_inner();
+    let money = Money::new(query.amount, &query.from).map_err(AppError::GenericError)?;
+    let date = query.date.unwrap_or_else(|| Utc::now().date_naive());
+
+    let converted = convert(pool.get_ref(), &money, &query.to, date).await?;
+    Ok(HttpResponse::Ok().json(converted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn oriented_rate_inverts_the_opposite_pair() {
+        assert_eq!(oriented_rate("USD", "USD", decimal("1500")), decimal("1500"));
+        assert_eq!(oriented_rate("NGN", "USD", decimal("1500")), Decimal::ONE / decimal("1500"));
+        assert_eq!(oriented_rate("NGN", "USD", decimal("0.5")), decimal("2"));
+
+        // 1234 NGN at the inverse of 1500 NGN per USD
+        let rate = oriented_rate("NGN", "USD", decimal("1500"));
+        let converted = Money::new(decimal("1234") * rate, "USD").unwrap();
+        assert_eq!(converted.amount, decimal("0.82"));
+    }
+}
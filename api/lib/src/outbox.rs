@@ -0,0 +1,274 @@
+use serde::Serialize;
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction, types::Json};
+use deadpool_redis::{redis, Connection, Pool};
+use uuid::Uuid;
+use shared::models::{
+    DomainEntityType,
+    DomainEventType,
+    Farm,
+    OutboxEvent,
+    Profile,
+    User
+};
+use tracing::{error, info, warn};
+
+// Keeps relays in several API instances from publishing the same events out of order
+const RELAY_LOCK_KEY: i64 = 0x0B7B_0E57;
+
+/**
+ * Outbox relay settings, read from
+ * OUTBOX_STREAM, OUTBOX_STREAM_MAXLEN, OUTBOX_BATCH_SIZE, OUTBOX_POLL_INTERVAL_MS and OUTBOX_RETENTION_DAYS
+ **/
+#[derive(Debug, Clone)]
+pub struct OutboxConfig {
+    // Redis stream every event is added to
+    pub stream: String,
+    // The stream is trimmed to roughly this many entries
+    pub max_len: usize,
+    pub batch_size: i64,
+    pub poll_interval: std::time::Duration,
+    // Published events are kept this long for replays and audits
+    pub retention_days: i32,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        OutboxConfig {
+            stream: "events:domain".to_string(),
+            max_len: 1_000_000,
+            batch_size: 100,
+            poll_interval: std::time::Duration::from_secs(1),
+            retention_days: 7,
+        }
+    }
+}
+
+impl OutboxConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|value| value.parse().ok())
+        }
+        let default = OutboxConfig::default();
+        OutboxConfig {
+            stream: var("OUTBOX_STREAM").unwrap_or(default.stream),
+            max_len: var("OUTBOX_STREAM_MAXLEN").unwrap_or(default.max_len),
+            batch_size: var("OUTBOX_BATCH_SIZE").unwrap_or(default.batch_size),
+            poll_interval: var("OUTBOX_POLL_INTERVAL_MS")
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(default.poll_interval),
+            retention_days: var("OUTBOX_RETENTION_DAYS").unwrap_or(default.retention_days),
+        }
+    }
+}
+
+/**
+ * Queue an event about `entity` in the transaction that changed it, so the event is relayed
+ * if and only if the change commits. Call it after the change, while the row is still locked.
+ * The event is tagged with the farm's state, or for users and profiles the states of the
+ * user's farms.
+ **/
+pub async fn record<T: Serialize + Sync>(tx: &mut Transaction<'_, Postgres>, event_type: DomainEventType, entity_id: Uuid, entity: &T) -> Result<(), SqlxError> {
+    sqlx::query(
+        r#"
+        INSERT INTO "OutboxEvent" ("eventType", "entityType", "entityId", payload, "stateIds")
+        SELECT $1, $2, $3, $4, ARRAY(
+            SELECT DISTINCT state_id
+            FROM (
+                SELECT ($4::jsonb->>'state_id')::uuid AS state_id WHERE $2 = 'FARM'
+                UNION ALL
+                SELECT f."stateId"
+                FROM "Farm" f
+                WHERE $2 <> 'FARM'
+                  AND f."farmerId" = CASE WHEN $2 = 'PROFILE' THEN ($4::jsonb->>'user_id')::uuid ELSE $3 END
+            ) states
+            WHERE state_id IS NOT NULL
+        )
+        "#,
+    )
+        .bind(event_type)
+        .bind(event_type.entity_type())
+        .bind(entity_id)
+        .bind(Json(entity))
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/**
+ * Same as `record`, reading the entity as it now stands in the transaction. For changes made
+ * without `RETURNING *`; deletes must call it before the row goes.
+ **/
+pub async fn record_current(tx: &mut Transaction<'_, Postgres>, event_type: DomainEventType, entity_id: Uuid) -> Result<(), SqlxError> {
+    match event_type.entity_type() {
+        DomainEntityType::User => {
+            let user = sqlx::query_as::<_, User>(r#"SELECT * FROM "User" WHERE id = $1"#)
+                .bind(entity_id)
+                .fetch_one(&mut **tx)
+                .await?;
+            record(tx, event_type, entity_id, &user).await
+        }
+        DomainEntityType::Profile => {
+            let profile = sqlx::query_as::<_, Profile>(r#"SELECT * FROM "Profile" WHERE id = $1"#)
+                .bind(entity_id)
+                .fetch_one(&mut **tx)
+                .await?;
+            record(tx, event_type, entity_id, &profile).await
+        }
+        DomainEntityType::Farm => {
+            let farm = sqlx::query_as::<_, Farm>(r#"SELECT * FROM "Farm" WHERE id = $1"#)
+                .bind(entity_id)
+                .fetch_one(&mut **tx)
+                .await?;
+            record(tx, event_type, entity_id, &farm).await
+        }
+    }
+}
+
+pub async fn run(pool: PgPool, redis: Pool, config: OutboxConfig) {
+    let mut ticker = actix_web::rt::time::interval(config.poll_interval);
+    loop {
+        ticker.tick().await;
+        // A full batch means more are waiting
+        loop {
+            match relay(&pool, &redis, &config).await {
+                Ok(published) if published as i64 == config.batch_size => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    error!("Outbox relay failed: {:?}", e);
+                    break;
+                }
+            }
+        }
+        match prune(&pool, config.retention_days).await {
+            Ok(0) => {}
+            Ok(pruned) => info!("Pruned {} published outbox events", pruned),
+            Err(e) => error!("Outbox prune failed: {:?}", e),
+        }
+    }
+}
+
+/**
+ * An entry read back from the event stream; `event` is None when it cannot be decoded
+ **/
+#[derive(Debug, Clone)]
+pub struct StreamEntry {
+    pub id: String,
+    pub event: Option<OutboxEvent>,
+}
+
+/**
+ * Up to `count` entries of `stream` after the entry id `after`, waiting up to `block` for one
+ * to arrive when given.
+ **/
+pub async fn read_stream(conn: &mut Connection, stream: &str, after: &str, count: i64, block: Option<std::time::Duration>) -> Result<Vec<StreamEntry>, redis::RedisError> {
+    let mut command = redis::cmd("XREAD");
+    command.arg("COUNT").arg(count);
+    if let Some(block) = block {
+        command.arg("BLOCK").arg(block.as_millis() as u64);
+    }
+    let reply: redis::Value = command.arg("STREAMS").arg(stream).arg(after).query_async(conn).await?;
+
+    // [[stream, [[id, [field, value, ...]], ...]]], or nil when nothing arrived
+    let streams: Option<Vec<Vec<redis::Value>>> = redis::from_redis_value(&reply)?;
+    let mut entries = Vec::new();
+    for reply in streams.into_iter().flatten() {
+        let Some(stream_entries) = reply.get(1) else { continue };
+        for entry in redis::from_redis_value::<Vec<redis::Value>>(stream_entries)? {
+            let (id, fields): (String, Vec<String>) = redis::from_redis_value(&entry)?;
+            let event = fields.chunks(2)
+                .find(|pair| pair[0] == "event")
+                .and_then(|pair| pair.get(1))
+                .and_then(|event| serde_json::from_str(event).ok());
+            entries.push(StreamEntry { id, event });
+        }
+    }
+    Ok(entries)
+}
+
+async fn publish(conn: &mut Connection, config: &OutboxConfig, event: &OutboxEvent) -> Result<(), redis::RedisError> {
+    let envelope = serde_json::to_value(event).unwrap_or_default();
+    redis::cmd("XADD")
+        .arg(&config.stream)
+        .arg("MAXLEN")
+        .arg("~")
+        .arg(config.max_len)
+        .arg("*")
+        .arg("eventType")
+        .arg(envelope["eventType"].as_str().unwrap_or_default())
+        .arg("entityId")
+        .arg(event.entity_id.to_string())
+        .arg("event")
+        .arg(envelope.to_string())
+        .query_async::<_, String>(conn)
+        .await?;
+    Ok(())
+}
+
+/**
+ * Publish the next batch of events in sequence order. Events are marked published only after
+ * Redis has them, so a crash in between publishes them again: consumers should skip event ids
+ * they have seen. A failure stops the batch so no event overtakes an earlier one.
+ **/
+pub async fn relay(pool: &PgPool, redis: &Pool, config: &OutboxConfig) -> Result<usize, SqlxError> {
+    let mut tx = pool.begin().await?;
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+        .bind(RELAY_LOCK_KEY)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !locked {
+        return Ok(0);
+    }
+
+    let pending = sqlx::query_as::<_, OutboxEvent>(
+        r#"
+        SELECT *
+        FROM "OutboxEvent"
+        WHERE "publishedAt" IS NULL
+        ORDER BY "sequence"
+        LIMIT $1
+        "#,
+    )
+        .bind(config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let mut conn = match redis.get().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            warn!("Redis unavailable, {} outbox events not published: {:?}", pending.len(), e);
+            return Ok(0);
+        }
+    };
+
+    let mut published = Vec::with_capacity(pending.len());
+    for event in &pending {
+        if let Err(e) = publish(&mut conn, config, event).await {
+            warn!("Error publishing outbox event {}: {:?}", event.id, e);
+            sqlx::query(r#"UPDATE "OutboxEvent" SET attempts = attempts + 1, "lastError" = $2 WHERE id = $1"#)
+                .bind(event.id)
+                .bind(e.to_string())
+                .execute(&mut *tx)
+                .await?;
+            break;
+        }
+        published.push(event.id);
+    }
+
+    sqlx::query(r#"UPDATE "OutboxEvent" SET "publishedAt" = current_timestamp, attempts = attempts + 1 WHERE id = ANY($1)"#)
+        .bind(&published)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(published.len())
+}
+
+async fn prune(pool: &PgPool, retention_days: i32) -> Result<u64, SqlxError> {
+    let result = sqlx::query(r#"DELETE FROM "OutboxEvent" WHERE "publishedAt" < current_timestamp - make_interval(days => $1)"#)
+        .bind(retention_days)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
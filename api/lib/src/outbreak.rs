@@ -0,0 +1,595 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDate, Utc};
+use deadpool_redis::{redis::AsyncCommands, Pool};
+use uuid::Uuid;
+use shared::geo::distance_metres;
+use shared::models::{
+    PestCategory,
+    PestIncident,
+    Severity,
+    OutbreakAlert,
+    OutbreakFilter,
+    OutbreakScan
+};
+use tracing::{error, info, warn};
+
+// Redis pub/sub channel new and escalated alerts are published on
+pub const OUTBREAK_CHANNEL: &str = "notifications:outbreaks";
+// Keeps scans from several API instances from racing each other
+const SCAN_LOCK_KEY: i64 = 0x0B7B_EA4C;
+const MAX_WINDOW_DAYS: i64 = 365;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Outbreak query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+/**
+ * Outbreak detection settings, read from
+ * OUTBREAK_RADIUS_KM, OUTBREAK_WINDOW_DAYS, OUTBREAK_MIN_FARMS and OUTBREAK_SCAN_INTERVAL_SECS
+ **/
+#[derive(Debug, Clone)]
+pub struct OutbreakConfig {
+    // Incidents closer than this are linked into the same cluster
+    pub radius_km: f64,
+    // Incidents further apart in time are not linked; alerts resolve after this many quiet days
+    pub window_days: i64,
+    // Distinct farms a cluster needs before it becomes an alert
+    pub min_farms: usize,
+    pub scan_interval: std::time::Duration,
+}
+
+impl Default for OutbreakConfig {
+    fn default() -> Self {
+        OutbreakConfig {
+            radius_km: 10.0,
+            window_days: 14,
+            min_farms: 3,
+            scan_interval: std::time::Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+impl OutbreakConfig {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    // Unparseable or out-of-range values fall back to the default
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        fn var<T: std::str::FromStr>(lookup: &impl Fn(&str) -> Option<String>, name: &str, valid: impl Fn(&T) -> bool) -> Option<T> {
+            let value = lookup(name)?;
+            let parsed = value.parse().ok().filter(valid);
+            if parsed.is_none() {
+                warn!("Ignoring invalid {}={:?}", name, value);
+            }
+            parsed
+        }
+        let default = OutbreakConfig::default();
+        OutbreakConfig {
+            radius_km: var(&lookup, "OUTBREAK_RADIUS_KM", |km: &f64| km.is_finite() && *km > 0.0)
+                .unwrap_or(default.radius_km),
+            window_days: var(&lookup, "OUTBREAK_WINDOW_DAYS", |days| (1..=MAX_WINDOW_DAYS).contains(days))
+                .unwrap_or(default.window_days),
+            min_farms: var(&lookup, "OUTBREAK_MIN_FARMS", |farms| *farms > 0)
+                .unwrap_or(default.min_farms),
+            scan_interval: var(&lookup, "OUTBREAK_SCAN_INTERVAL_SECS", |secs| *secs > 0)
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(default.scan_interval),
+        }
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/outbreaks")
+                    .app_data(web::Data::new(OutbreakConfig::from_env()))
+                    .route("", web::get().to(get_all_outbreaks))
+                    .route("/scan", web::post().to(run_scan))
+                    .route("/{id}", web::get().to(get_outbreak))
+                    .route("/{id}/incidents", web::get().to(get_outbreak_incidents))
+                    .route("/{id}/resolve", web::post().to(resolve_outbreak))
+    );
+}
+
+/**
+ * Single-linkage clustering of `([lon, lat], observed on)` points:
+ * two points are linked when they are within `radius_metres` and `window_days` of each other.
+ * Returns the indices of each cluster.
+ **/
+pub fn cluster(points: &[([f64; 2], NaiveDate)], radius_metres: f64, window_days: i64) -> Vec<Vec<usize>> {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent: Vec<usize> = (0..points.len()).collect();
+    for i in 0..points.len() {
+        for j in (i + 1)..points.len() {
+            let (a, a_date) = points[i];
+            let (b, b_date) = points[j];
+            if (a_date - b_date).num_days().abs() <= window_days && distance_metres(a, b) <= radius_metres {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..points.len() {
+        let r = root(&mut parent, i);
+        clusters.entry(r).or_default().push(i);
+    }
+    let mut clusters: Vec<Vec<usize>> = clusters.into_values().collect();
+    clusters.sort();
+    clusters
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RecentIncident {
+    id: Uuid,
+    #[sqlx(rename = "farmId")]
+    farm_id: Uuid,
+    #[sqlx(rename = "outbreakId")]
+    outbreak_id: Option<Uuid>,
+    category: PestCategory,
+    name: String,
+    severity: Severity,
+    latitude: f64,
+    longitude: f64,
+    #[sqlx(rename = "observedOn")]
+    observed_on: NaiveDate,
+}
+
+/**
+ * Periodically scan for outbreaks. Spawned once at startup.
+ **/
+pub async fn run(pool: PgPool, redis: Pool, config: OutbreakConfig) {
+    let mut ticker = actix_web::rt::time::interval(config.scan_interval);
+    loop {
+        ticker.tick().await;
+        match scan(&pool, &redis, &config, Utc::now().date_naive()).await {
+            Ok(summary) => info!("Outbreak scan finished: {:?}", summary),
+            Err(e) => error!("Outbreak scan failed: {}", e),
+        }
+    }
+}
+
+/**
+ * Cluster recent incidents of the same pest or disease and raise, grow or resolve alerts.
+ * Incidents already attached to a resolved alert are left out so it is not raised again.
+ **/
+pub async fn scan(pool: &PgPool, redis: &Pool, config: &OutbreakConfig, today: NaiveDate) -> Result<OutbreakScan, AppError> {
+    let mut summary = OutbreakScan::default();
+    let since = today - Duration::days(config.window_days);
+    let mut tx = pool.begin().await?;
+
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+        .bind(SCAN_LOCK_KEY)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !locked {
+        return Ok(summary);
+    }
+
+    let incidents = sqlx::query_as::<_, RecentIncident>(
+        r#"
+        SELECT i.id, i."farmId", i."outbreakId", i.category, i.name, i.severity, i.latitude, i.longitude, i."observedOn"
+        FROM "PestIncident" i
+        LEFT JOIN "OutbreakAlert" a ON a.id = i."outbreakId"
+        WHERE i."observedOn" BETWEEN $1 AND $2
+          AND (a.id IS NULL OR a.status = 'ACTIVE')
+        ORDER BY i."observedOn", i.id
+        "#,
+    )
+        .bind(since)
+        .bind(today)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    let active: HashMap<Uuid, OutbreakAlert> = sqlx::query_as::<_, OutbreakAlert>(r#"SELECT * FROM "OutbreakAlert" WHERE status = 'ACTIVE'"#)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|alert| (alert.id, alert))
+        .collect();
+
+    let mut by_issue: HashMap<(PestCategory, &str), Vec<&RecentIncident>> = HashMap::new();
+    for incident in &incidents {
+        by_issue.entry((incident.category, &incident.name)).or_default().push(incident);
+    }
+
+    for ((category, name), reports) in by_issue {
+        let points: Vec<([f64; 2], NaiveDate)> = reports.iter()
+            .map(|i| ([i.longitude, i.latitude], i.observed_on))
+            .collect();
+
+        for indices in cluster(&points, config.radius_km * 1000.0, config.window_days) {
+            let members: Vec<&RecentIncident> = indices.iter().map(|&i| reports[i]).collect();
+            let farms: HashSet<Uuid> = members.iter().map(|i| i.farm_id).collect();
+            if farms.len() < config.min_farms {
+                continue;
+            }
+
+            let count = members.len() as f64;
+            let latitude = members.iter().map(|i| i.latitude).sum::<f64>() / count;
+            let longitude = members.iter().map(|i| i.longitude).sum::<f64>() / count;
+            let radius_km = members.iter()
+                .map(|i| distance_metres([longitude, latitude], [i.longitude, i.latitude]) / 1000.0)
+                .fold(0.0, f64::max);
+            let severity = members.iter().map(|i| i.severity).max().unwrap_or(Severity::Low);
+            let first_observed = members.iter().map(|i| i.observed_on).min().unwrap_or(today);
+            let last_observed = members.iter().map(|i| i.observed_on).max().unwrap_or(today);
+
+            // Oldest alert among the members survives; any others were merged into it
+            let mut existing: Vec<&OutbreakAlert> = members.iter()
+                .filter_map(|i| i.outbreak_id)
+                .collect::<HashSet<Uuid>>()
+                .iter()
+                .filter_map(|id| active.get(id))
+                .collect();
+            existing.sort_by_key(|alert| alert.created_at);
+
+            let alert_id = match existing.split_first() {
+                Some((alert, merged)) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE "OutbreakAlert"
+                        SET severity = $2,
+                            latitude = $3,
+                            longitude = $4,
+                            "radiusKm" = $5,
+                            "incidentCount" = $6,
+                            "farmCount" = $7,
+                            "firstObservedOn" = $8,
+                            "lastObservedOn" = $9,
+                            "notifiedAt" = CASE WHEN $10 THEN NULL ELSE "notifiedAt" END,
+                            "updatedAt" = current_timestamp
+                        WHERE id = $1
+                        "#,
+                    )
+                        .bind(alert.id)
+                        .bind(severity)
+                        .bind(latitude)
+                        .bind(longitude)
+                        .bind(radius_km)
+                        .bind(members.len() as i32)
+                        .bind(farms.len() as i32)
+                        .bind(first_observed)
+                        .bind(last_observed)
+                        // Escalations are announced again
+                        .bind(severity > alert.severity)
+                        .execute(&mut *tx)
+                        .await?;
+
+                    if !merged.is_empty() {
+                        let merged: Vec<Uuid> = merged.iter().map(|alert| alert.id).collect();
+                        let result = sqlx::query(
+                            r#"
+                            UPDATE "OutbreakAlert"
+                            SET status = 'RESOLVED', "resolvedAt" = current_timestamp, "updatedAt" = current_timestamp
+                            WHERE id = ANY($1)
+                            "#,
+                        )
+                            .bind(&merged)
+                            .execute(&mut *tx)
+                            .await?;
+                        summary.resolved += result.rows_affected();
+                    }
+                    summary.updated += 1;
+                    alert.id
+                }
+                None => {
+                    summary.created += 1;
+                    sqlx::query_scalar(
+                        r#"
+                        INSERT INTO "OutbreakAlert" (category, name, severity, latitude, longitude, "radiusKm", "incidentCount", "farmCount", "firstObservedOn", "lastObservedOn")
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                        RETURNING id
+                        "#,
+                    )
+                        .bind(category)
+                        .bind(name)
+                        .bind(severity)
+                        .bind(latitude)
+                        .bind(longitude)
+                        .bind(radius_km)
+                        .bind(members.len() as i32)
+                        .bind(farms.len() as i32)
+                        .bind(first_observed)
+                        .bind(last_observed)
+                        .fetch_one(&mut *tx)
+                        .await?
+                }
+            };
+
+            let ids: Vec<Uuid> = members.iter().map(|i| i.id).collect();
+            sqlx::query(r#"UPDATE "PestIncident" SET "outbreakId" = $1 WHERE id = ANY($2)"#)
+                .bind(alert_id)
+                .bind(&ids)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE "OutbreakAlert"
+        SET status = 'RESOLVED', "resolvedAt" = current_timestamp, "updatedAt" = current_timestamp
+        WHERE status = 'ACTIVE' AND "lastObservedOn" < $1
+        "#,
+    )
+        .bind(since)
+        .execute(&mut *tx)
+        .await?;
+    summary.resolved += result.rows_affected();
+
+    tx.commit().await?;
+
+    summary.notified = notify_pending(pool, redis).await?;
+    Ok(summary)
+}
+
+/**
+ * Publish active alerts that have not been announced yet.
+ * Unpublished alerts are retried on the next scan.
+ **/
+async fn notify_pending(pool: &PgPool, redis: &Pool) -> Result<usize, AppError> {
+    let pending = sqlx::query_as::<_, OutbreakAlert>(
+        r#"
+        SELECT *
+        FROM "OutbreakAlert"
+        WHERE status = 'ACTIVE' AND "notifiedAt" IS NULL
+        ORDER BY "createdAt"
+        "#,
+    )
+        .fetch_all(pool)
+        .await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let mut conn = match redis.get().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            warn!("Redis unavailable, {} outbreak alerts not published: {:?}", pending.len(), e);
+            return Ok(0);
+        }
+    };
+
+    let mut notified = 0;
+    for alert in pending {
+        let payload = match serde_json::to_string(&alert) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Error serializing outbreak alert {}: {:?}", alert.id, e);
+                continue;
+            }
+        };
+        let published: Result<i64, _> = conn.publish(OUTBREAK_CHANNEL, payload).await;
+        if let Err(e) = published {
+            warn!("Error publishing outbreak alert {}: {:?}", alert.id, e);
+            break;
+        }
+        sqlx::query(r#"UPDATE "OutbreakAlert" SET "notifiedAt" = current_timestamp WHERE id = $1"#)
+            .bind(alert.id)
+            .execute(pool)
+            .await?;
+        notified += 1;
+    }
+    Ok(notified)
+}
+
+async fn get_all_outbreaks(pool: web::Data<PgPool>, filter: web::Query<OutbreakFilter>) -> Result<HttpResponse, AppError> {
+    let filter = filter.into_inner();
+    let alerts = sqlx::query_as::<_, OutbreakAlert>(
+        r#"
+        SELECT *
+        FROM "OutbreakAlert"
+        WHERE ($1::varchar IS NULL OR status = $1)
+          AND ($2::varchar IS NULL OR category = $2)
+          AND ($3::varchar IS NULL OR name = $3)
+        ORDER BY "lastObservedOn" DESC
+        "#,
+    )
+        .bind(filter.status)
+        .bind(filter.category)
+        .bind(filter.name.map(|name| name.trim().to_lowercase()))
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(alerts))
+}
+
+async fn get_outbreak(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let alert = sqlx::query_as::<_, OutbreakAlert>(r#"SELECT * FROM "OutbreakAlert" WHERE id = $1"#)
+        .bind(id.into_inner())
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match alert {
+        Some(alert) => Ok(HttpResponse::Ok().json(alert)),
+        None => Err(AppError::NotFound("Outbreak".to_string())),
+    }
+}
+
+async fn get_outbreak_incidents(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let incidents = sqlx::query_as::<_, PestIncident>(
+        r#"
+        SELECT *
+        FROM "PestIncident"
+        WHERE "outbreakId" = $1
+        ORDER BY "observedOn"
+        "#,
+    )
+        .bind(id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(incidents))
+}
+
+async fn resolve_outbreak(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let alert = sqlx::query_as::<_, OutbreakAlert>(
+        r#"
+        UPDATE "OutbreakAlert"
+        SET status = 'RESOLVED', "resolvedAt" = current_timestamp, "updatedAt" = current_timestamp
+        WHERE id = $1 AND status = 'ACTIVE'
+        RETURNING *
+        "#,
+    )
+        .bind(id.into_inner())
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match alert {
+        Some(alert) => Ok(HttpResponse::Ok().json(alert)),
+        None => Err(AppError::NotFound("Active outbreak".to_string())),
+    }
+}
+
+/**
+ * Run a scan now instead of waiting for the background job
+ **/
+async fn run_scan(pool: web::Data<PgPool>, redis: web::Data<Pool>, config: web::Data<OutbreakConfig>) -> Result<HttpResponse, AppError> {
+    let summary = scan(pool.get_ref(), redis.get_ref(), config.get_ref(), Utc::now().date_naive()).await?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Kilometres spanned by one degree of latitude
+    const KM_PER_DEGREE: f64 = 111.195;
+
+    fn day(offset: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 7, 1).unwrap() + chrono::Duration::days(offset)
+    }
+
+    // A point `km` north of a fixed origin
+    fn north(km: f64) -> [f64; 2] {
+        [7.7, 11.1 + km / KM_PER_DEGREE]
+    }
+
+    fn config(vars: &[(&str, &str)]) -> OutbreakConfig {
+        OutbreakConfig::from_lookup(|name| vars.iter().find(|(n, _)| *n == name).map(|(_, v)| v.to_string()))
+    }
+
+    #[test]
+    fn config_reads_valid_values() {
+        let config = config(&[
+            ("OUTBREAK_RADIUS_KM", "5.5"),
+            ("OUTBREAK_WINDOW_DAYS", "30"),
+            ("OUTBREAK_MIN_FARMS", "2"),
+            ("OUTBREAK_SCAN_INTERVAL_SECS", "60"),
+        ]);
+        assert_eq!(config.radius_km, 5.5);
+        assert_eq!(config.window_days, 30);
+        assert_eq!(config.min_farms, 2);
+        assert_eq!(config.scan_interval, std::time::Duration::from_secs(60));
+    }
+
+    #[test]
+    fn config_falls_back_on_invalid_values() {
+        let default = OutbreakConfig::default();
+        for vars in [
+            [("OUTBREAK_RADIUS_KM", "NaN"), ("OUTBREAK_WINDOW_DAYS", "0"), ("OUTBREAK_MIN_FARMS", "0"), ("OUTBREAK_SCAN_INTERVAL_SECS", "0")],
+            [("OUTBREAK_RADIUS_KM", "-1"), ("OUTBREAK_WINDOW_DAYS", "9223372036854775807"), ("OUTBREAK_MIN_FARMS", "-3"), ("OUTBREAK_SCAN_INTERVAL_SECS", "soon")],
+        ] {
+            let config = config(&vars);
+            assert_eq!(config.radius_km, default.radius_km);
+            assert_eq!(config.window_days, default.window_days);
+            assert_eq!(config.min_farms, default.min_farms);
+            assert_eq!(config.scan_interval, default.scan_interval);
+        }
+    }
+
+    #[test]
+    fn no_points_no_clusters() {
+        assert!(cluster(&[], 10_000.0, 14).is_empty());
+    }
+
+    #[test]
+    fn links_chain_through_space() {
+        // The ends are 16 km apart, but each is 8 km from the middle point
+        let points = [(north(0.0), day(0)), (north(8.0), day(0)), (north(16.0), day(0))];
+        assert_eq!(cluster(&points, 10_000.0, 14), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn links_chain_through_time() {
+        let points = [(north(0.0), day(0)), (north(0.0), day(10)), (north(0.0), day(20))];
+        assert_eq!(cluster(&points, 10_000.0, 14), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn chains_join_regardless_of_point_order() {
+        // The first two points only become linked through the last one
+        let points = [(north(0.0), day(0)), (north(16.0), day(0)), (north(8.0), day(0)), (north(50.0), day(0))];
+        assert_eq!(cluster(&points, 10_000.0, 14), vec![vec![0, 1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn distance_boundary_is_inclusive() {
+        let points = [(north(0.0), day(0)), (north(5.0), day(0))];
+        let distance = distance_metres(points[0].0, points[1].0);
+
+        assert_eq!(cluster(&points, distance, 14), vec![vec![0, 1]]);
+        assert_eq!(cluster(&points, distance - 1.0, 14), vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn window_boundary_is_inclusive() {
+        let linked = [(north(0.0), day(0)), (north(1.0), day(14))];
+        assert_eq!(cluster(&linked, 10_000.0, 14), vec![vec![0, 1]]);
+
+        let apart = [(north(0.0), day(0)), (north(1.0), day(15))];
+        assert_eq!(cluster(&apart, 10_000.0, 14), vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn points_must_be_close_in_both_space_and_time() {
+        let points = [(north(0.0), day(0)), (north(1.0), day(30)), (north(40.0), day(1))];
+        assert_eq!(cluster(&points, 10_000.0, 14), vec![vec![0], vec![1], vec![2]]);
+    }
+}
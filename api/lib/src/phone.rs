@@ -0,0 +1,203 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+use phonenumber::{country, Mode, Type};
+use serde::Deserialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+use shared::models::PhoneLineType;
+use tracing::{error, info, warn};
+
+// Mobile prefixes by carrier, longest prefix wins. Numbers keep their carrier when ported,
+// so this names the carrier that issued the number.
+const CARRIER_DATA: &str = include_str!("../data/phone_carriers.csv");
+const BACKFILL_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Clone)]
+pub struct PhoneConfig {
+    // Region assumed for numbers written without a country code
+    pub default_region: country::Id,
+}
+
+impl Default for PhoneConfig {
+    fn default() -> Self {
+        PhoneConfig {
+            default_region: country::Id::NG,
+        }
+    }
+}
+
+impl PhoneConfig {
+    pub fn from_env() -> Self {
+        match std::env::var("PHONE_DEFAULT_REGION").ok() {
+            None => PhoneConfig::default(),
+            Some(region) => PhoneConfig {
+                default_region: country::Id::from_str(&region.to_ascii_uppercase())
+                    .unwrap_or_else(|_| panic!("Unknown PHONE_DEFAULT_REGION: {}", region)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedPhone {
+    pub e164: String,
+    pub line_type: PhoneLineType,
+    pub carrier: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CarrierPrefix {
+    country_code: u16,
+    prefix: String,
+    carrier: String,
+}
+
+fn parse_prefixes(data: &str) -> Result<Vec<CarrierPrefix>, csv::Error> {
+    let mut prefixes: Vec<CarrierPrefix> = csv::Reader::from_reader(data.as_bytes())
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    prefixes.sort_by_key(|entry| std::cmp::Reverse(entry.prefix.len()));
+    Ok(prefixes)
+}
+
+fn carrier_prefixes() -> &'static [CarrierPrefix] {
+    static PREFIXES: OnceLock<Vec<CarrierPrefix>> = OnceLock::new();
+    PREFIXES.get_or_init(|| parse_prefixes(CARRIER_DATA).expect("bundled carrier data is valid"))
+}
+
+fn find_carrier(prefixes: &[CarrierPrefix], country_code: u16, national_number: &str) -> Option<String> {
+    prefixes.iter()
+        .find(|entry| entry.country_code == country_code && national_number.starts_with(&entry.prefix))
+        .map(|entry| entry.carrier.clone())
+}
+
+fn carrier_for(country_code: u16, national_number: &str) -> Option<String> {
+    find_carrier(carrier_prefixes(), country_code, national_number)
+}
+
+fn line_type(kind: Type) -> PhoneLineType {
+    match kind {
+        Type::Mobile => PhoneLineType::Mobile,
+        Type::FixedLine => PhoneLineType::FixedLine,
+        Type::FixedLineOrMobile => PhoneLineType::FixedLineOrMobile,
+        Type::TollFree => PhoneLineType::TollFree,
+        Type::PremiumRate => PhoneLineType::PremiumRate,
+        Type::Voip => PhoneLineType::Voip,
+        _ => PhoneLineType::Other,
+    }
+}
+
+/**
+ * Parses a phone number as typed, e.g. "0803 123 4567" or "+234 803 123 4567", into E.164
+ * with its line type and carrier. Numbers without a country code are read in `region`.
+ **/
+pub fn normalize(raw: &str, region: country::Id) -> Result<NormalizedPhone, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err("Phone number is empty".to_string());
+    }
+    let number = phonenumber::parse(Some(region), raw).map_err(|_| format!("{} is not a phone number", raw))?;
+    if !number.is_valid() {
+        return Err(format!("{} is not a valid phone number", raw));
+    }
+
+    let national_number = number.national().to_string();
+    Ok(NormalizedPhone {
+        e164: number.format().mode(Mode::E164).to_string(),
+        line_type: line_type(number.number_type(&phonenumber::metadata::DATABASE)),
+        carrier: carrier_for(number.code().value(), &national_number),
+    })
+}
+
+/**
+ * Fills in the carrier and line type of profiles whose numbers were normalised by migration
+ * and so never went through `normalize`. Runs once at start-up; numbers that fail to parse
+ * are left for the invalid phone report.
+ **/
+pub async fn backfill(pool: PgPool, config: PhoneConfig) {
+    let mut updated = 0;
+    let mut after = Uuid::nil();
+    loop {
+        let batch: Vec<(Uuid, String)> = match sqlx::query_as(
+            r#"
+            SELECT id, "phoneNumber"
+            FROM "Profile"
+            WHERE "phoneNumber" IS NOT NULL AND "phoneLineType" IS NULL AND id > $1
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+            .bind(after)
+            .bind(BACKFILL_BATCH_SIZE)
+            .fetch_all(&pool)
+            .await
+        {
+            Ok(batch) => batch,
+            Err(e) => {
+                error!("Phone metadata backfill query failed: {:?}", e);
+                return;
+            }
+        };
+        let Some((last, _)) = batch.last() else { break };
+        after = *last;
+
+        for (id, phone_number) in batch {
+            let phone = match normalize(&phone_number, config.default_region) {
+                Ok(phone) => phone,
+                Err(e) => {
+                    warn!("Skipping phone metadata for profile {}: {}", id, e);
+                    continue;
+                }
+            };
+            let result = sqlx::query(r#"UPDATE "Profile" SET "phoneCarrier" = $2, "phoneLineType" = $3 WHERE id = $1 AND "phoneNumber" = $4"#)
+                .bind(id)
+                .bind(&phone.carrier)
+                .bind(phone.line_type)
+                .bind(&phone.e164)
+                .execute(&pool)
+                .await;
+            match result {
+                Ok(result) => updated += result.rows_affected(),
+                Err(e) => error!("Phone metadata backfill failed for profile {}: {:?}", id, e),
+            }
+        }
+    }
+
+    if updated > 0 {
+        info!("Filled in phone metadata for {} profiles", updated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_reads_each_written_form_as_the_same_number() {
+        for raw in ["08031234567", "+2348031234567", "234 803 123 4567", " 0803 123 4567 "] {
+            let phone = normalize(raw, country::Id::NG).unwrap();
+            assert_eq!(phone.e164, "+2348031234567", "{}", raw);
+            assert_eq!(phone.line_type, PhoneLineType::Mobile, "{}", raw);
+            assert_eq!(phone.carrier.as_deref(), Some("MTN"), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn normalize_rejects_invalid_numbers() {
+        assert!(normalize("", country::Id::NG).is_err());
+        assert!(normalize("not a number", country::Id::NG).is_err());
+        assert!(normalize("0803 123", country::Id::NG).is_err());
+    }
+
+    #[test]
+    fn carrier_for_prefers_the_longest_prefix() {
+        let prefixes = parse_prefixes("country_code,prefix,carrier\n234,702,Short\n234,7025,Long\n").unwrap();
+        assert_eq!(find_carrier(&prefixes, 234, "7025123456").as_deref(), Some("Long"));
+        assert_eq!(find_carrier(&prefixes, 234, "7021123456").as_deref(), Some("Short"));
+        assert_eq!(find_carrier(&prefixes, 44, "7025123456"), None);
+
+        assert_eq!(carrier_for(234, "7025123456").as_deref(), Some("MTN"));
+        assert_eq!(carrier_for(234, "7020123456").as_deref(), Some("Smile"));
+        assert_eq!(carrier_for(234, "7000123456"), None);
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use chrono::{Datelike, Duration};
+use uuid::Uuid;
+use shared::models::{
+    Planting,
+    CreatePlanting,
+    PlantingFilter
+};
+use tracing::error;
+
+const AREA_EPSILON_HECTARES: f64 = 1e-6;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Planting query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/plantings")
+                    .route("", web::get().to(get_all_plantings))
+                    .route("", web::post().to(create_planting))
+    );
+}
+
+async fn get_all_plantings(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, filter: web::Query<PlantingFilter>) -> Result<HttpResponse, AppError> {
+    let PlantingFilter { season, season_year, crop_id } = filter.into_inner();
+
+    let plantings = sqlx::query_as::<_, Planting>(
+        r#"
+        SELECT *
+        FROM "Planting"
+        WHERE "farmId" = $1
+          AND ($2::varchar IS NULL OR season = $2)
+          AND ($3::int IS NULL OR "seasonYear" = $3)
+          AND ($4::uuid IS NULL OR "cropId" = $4)
+        ORDER BY "plantingDate" DESC
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .bind(season)
+        .bind(season_year)
+        .bind(crop_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(plantings))
+}
+
+// Whether another `planted_area` ha fits in `capacity` ha beside what is already planted
+fn fits(planted_area: f64, capacity: f64, already_planted: f64) -> bool {
+    already_planted + planted_area <= capacity + AREA_EPSILON_HECTARES
+}
+
+/**
+ * Create Planting
+ * Plantings in the same season may not together exceed the farm's available portion
+ **/
+async fn create_planting(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, planting: Json<CreatePlanting>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let planting = planting.into_inner();
+    let planted_area = planting.planted_area.to_hectares();
+    let season_year = planting.season_year.unwrap_or_else(|| planting.planting_date.year());
+
+    if planted_area <= 0.0 {
+        return Err(AppError::GenericError("Planted area must be positive".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query_scalar::<_, Uuid>(r#"SELECT id FROM "Crop" WHERE id = $1"#)
+        .bind(planting.crop_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Crop".to_string()))?;
+
+    let available_portion: Option<Option<f64>> = sqlx::query_scalar(
+        r#"
+        SELECT available_portion
+        FROM "Farm"
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+        .bind(farm_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let available_portion = available_portion
+        .ok_or_else(|| AppError::NotFound("Farm".to_string()))?
+        .unwrap_or(0.0);
+
+    let already_planted: f64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM("plantedArea"), 0)
+        FROM "Planting"
+        WHERE "farmId" = $1 AND season = $2 AND "seasonYear" = $3
+        "#,
+    )
+        .bind(farm_id)
+        .bind(planting.season)
+        .bind(season_year)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if !fits(planted_area, available_portion, already_planted) {
+        return Err(AppError::GenericError(format!(
+            "Planted area of {:.4} ha exceeds the farm's available portion ({:.4} ha, {:.4} ha already planted this season)",
+            planted_area,
+            available_portion,
+            already_planted
+        )));
+    }
+
+    // The farm row lock above also serialises plantings on its plots
+    if let Some(plot_id) = planting.plot_id {
+        let plot_area: f64 = sqlx::query_scalar(r#"SELECT area FROM "Plot" WHERE id = $1 AND "farmId" = $2"#)
+            .bind(plot_id)
+            .bind(farm_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Plot".to_string()))?;
+
+        let already_on_plot: f64 = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM("plantedArea"), 0)
+            FROM "Planting"
+            WHERE "plotId" = $1 AND season = $2 AND "seasonYear" = $3
+            "#,
+        )
+            .bind(plot_id)
+            .bind(planting.season)
+            .bind(season_year)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !fits(planted_area, plot_area, already_on_plot) {
+            return Err(AppError::GenericError(format!(
+                "Planted area of {:.4} ha exceeds the plot area ({:.4} ha, {:.4} ha already planted this season)",
+                planted_area,
+                plot_area,
+                already_on_plot
+            )));
+        }
+    }
+
+    let cycle_days = match planting.variety_id {
+        Some(variety_id) => {
+            let cycle_days: Option<i32> = sqlx::query_scalar(r#"SELECT "cycleDays" FROM "CropVariety" WHERE id = $1 AND "cropId" = $2"#)
+                .bind(variety_id)
+                .bind(planting.crop_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            Some(cycle_days.ok_or_else(|| AppError::NotFound("Crop variety".to_string()))?)
+        }
+        None => None,
+    };
+    let expected_harvest_date = planting.expected_harvest_date
+        .or_else(|| cycle_days.map(|days| planting.planting_date + Duration::days(days as i64)));
+
+    let planting = sqlx::query_as::<_, Planting>(
+        r#"
+        INSERT INTO "Planting" ("farmId", "plotId", "cropId", "varietyId", "plantedArea", "plantingDate", "expectedHarvestDate", season, "seasonYear")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(planting.plot_id)
+        .bind(planting.crop_id)
+        .bind(planting.variety_id)
+        .bind(planted_area)
+        .bind(planting.planting_date)
+        .bind(expected_harvest_date)
+        .bind(planting.season)
+        .bind(season_year)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(planting))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_up_to_the_capacity() {
+        assert!(fits(2.0, 5.0, 3.0));
+        assert!(fits(2.0, 5.0, 3.0 + AREA_EPSILON_HECTARES / 2.0));
+        assert!(fits(5.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn counts_what_is_already_planted() {
+        assert!(fits(3.0, 5.0, 0.0));
+        assert!(!fits(3.0, 5.0, 2.5));
+        assert!(!fits(0.01, 5.0, 5.0));
+    }
+
+    #[test]
+    fn nothing_fits_on_an_empty_capacity() {
+        assert!(!fits(0.5, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction, types::Json as SqlxJson};
+use actix_web::http::StatusCode;
+use uuid::Uuid;
+use shared::geo::Polygon;
+use shared::models::{
+    Plot,
+    CreatePlot,
+    UpdatePlot
+};
+use tracing::error;
+
+// Tolerance for floating point drift when summing plot areas
+const AREA_EPSILON_HECTARES: f64 = 1e-6;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Plot query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+// Registered ahead of the farm scope so `/v0.1/farms/{id}/plots` is not swallowed by it
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/plots")
+                    .route("", web::get().to(get_all_plots))
+                    .route("", web::post().to(create_plot))
+                    .route("/{id}", web::get().to(get_plot))
+                    .route("/{id}", web::put().to(update_plot))
+                    .route("/{id}", web::delete().to(delete_plot))
+    );
+}
+
+/**
+ * Check a plot against its farm: the farm row is locked so concurrent
+ * plot writes cannot together exceed the farm acreage
+ **/
+async fn validate_plot(
+    tx: &mut Transaction<'_, Postgres>,
+    farm_id: Uuid,
+    plot_id: Option<Uuid>,
+    area: f64,
+    boundary: Option<&Polygon>,
+) -> Result<(), AppError> {
+    let farm: Option<(f64, Option<SqlxJson<Polygon>>)> = sqlx::query_as(
+        r#"
+        SELECT acreage, boundary
+        FROM "Farm"
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+        .bind(farm_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    let (acreage, farm_boundary) = farm.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+
+    if let Some(boundary) = boundary {
+        boundary.validate().map_err(AppError::GenericError)?;
+        if let Some(SqlxJson(farm_boundary)) = &farm_boundary {
+            if !farm_boundary.contains_polygon(boundary) {
+                return Err(AppError::GenericError("Plot boundary must lie within the farm boundary".to_string()));
+            }
+        }
+    }
+
+    let allocated: f64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(area), 0)
+        FROM "Plot"
+        WHERE "farmId" = $1
+          AND ($2::uuid IS NULL OR id <> $2)
+        "#,
+    )
+        .bind(farm_id)
+        .bind(plot_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    if allocated + area > acreage + AREA_EPSILON_HECTARES {
+        return Err(AppError::GenericError(format!(
+            "Plot areas would total {:.4} ha, more than the farm's {:.4} ha",
+            allocated + area,
+            acreage
+        )));
+    }
+
+    Ok(())
+}
+
+/**
+ * Why the farm's plots would not fit a farm of `acreage` hectares within `boundary`, if so.
+ * The caller holds the farm row lock.
+ **/
+pub async fn plots_misfit(
+    tx: &mut Transaction<'_, Postgres>,
+    farm_id: Uuid,
+    acreage: f64,
+    boundary: Option<&Polygon>,
+) -> Result<Option<String>, SqlxError> {
+    let plots: Vec<(f64, Option<SqlxJson<Polygon>>)> = sqlx::query_as(
+        r#"
+        SELECT area, boundary
+        FROM "Plot"
+        WHERE "farmId" = $1
+        "#,
+    )
+        .bind(farm_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    let allocated: f64 = plots.iter().map(|(area, _)| area).sum();
+    if allocated > acreage + AREA_EPSILON_HECTARES {
+        return Ok(Some(format!(
+            "Plot areas total {:.4} ha, more than the farm's {:.4} ha",
+            allocated,
+            acreage
+        )));
+    }
+    if let Some(boundary) = boundary {
+        let outside = plots.iter()
+            .filter_map(|(_, plot_boundary)| plot_boundary.as_ref())
+            .any(|SqlxJson(plot_boundary)| !boundary.contains_polygon(plot_boundary));
+        if outside {
+            return Ok(Some("Plot boundaries must lie within the farm boundary".to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+async fn get_all_plots(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let plots = sqlx::query_as::<_, Plot>(
+        r#"
+        SELECT *
+        FROM "Plot"
+        WHERE "farmId" = $1
+        ORDER BY "createdAt"
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(plots))
+}
+
+async fn get_plot(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+
+    let plot = sqlx::query_as::<_, Plot>(
+        r#"
+        SELECT *
+        FROM "Plot"
+        WHERE "farmId" = $1 AND id = $2
+        "#,
+    )
+        .bind(farm_id)
+        .bind(id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match plot {
+        Some(plot) => Ok(HttpResponse::Ok().json(plot)),
+        None => Err(AppError::NotFound("Plot".to_string())),
+    }
+}
+
+/**
+ * Create Plot
+ **/
+async fn create_plot(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, plot: Json<CreatePlot>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let plot = plot.into_inner();
+
+    let area = match (&plot.area, &plot.boundary) {
+        (Some(area), _) => area.to_hectares(),
+        (None, Some(boundary)) => boundary.area_hectares(),
+        (None, None) => return Err(AppError::GenericError("Provide an area or a boundary".to_string())),
+    };
+
+    let mut tx = pool.begin().await?;
+    validate_plot(&mut tx, farm_id, None, area, plot.boundary.as_ref()).await?;
+
+    let plot = sqlx::query_as::<_, Plot>(
+        r#"
+        INSERT INTO "Plot" ("farmId", name, boundary, area, "soilType", is_irrigated, has_drainage_tile)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(plot.name)
+        .bind(plot.boundary.map(SqlxJson))
+        .bind(area)
+        .bind(plot.soil_type)
+        .bind(plot.is_irrigated)
+        .bind(plot.has_drainage_tile)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(plot))
+}
+
+/**
+ * Update Plot
+ **/
+async fn update_plot(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, plot: Json<UpdatePlot>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let update = plot.into_inner();
+
+    let mut tx = pool.begin().await?;
+
+    let existing = sqlx::query_as::<_, Plot>(
+        r#"
+        SELECT *
+        FROM "Plot"
+        WHERE "farmId" = $1 AND id = $2
+        "#,
+    )
+        .bind(farm_id)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Plot".to_string()))?;
+
+    // A new boundary without an explicit area re-derives the area from it
+    let boundary_changed = update.boundary.is_some();
+    let boundary = update.boundary.or(existing.boundary.map(|SqlxJson(boundary)| boundary));
+    let area = match (&update.area, boundary_changed, &boundary) {
+        (Some(area), _, _) => area.to_hectares(),
+        (None, true, Some(boundary)) => boundary.area_hectares(),
+        _ => existing.area,
+    };
+
+    validate_plot(&mut tx, farm_id, Some(id), area, boundary.as_ref()).await?;
+
+    let plot = sqlx::query_as::<_, Plot>(
+        r#"
+        UPDATE "Plot"
+        SET name = $3,
+            boundary = $4,
+            area = $5,
+            "soilType" = $6,
+            is_irrigated = $7,
+            has_drainage_tile = $8,
+            "updatedAt" = current_timestamp
+        WHERE "farmId" = $1 AND id = $2
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(id)
+        .bind(update.name.unwrap_or(existing.name))
+        .bind(boundary.map(SqlxJson))
+        .bind(area)
+        .bind(update.soil_type.or(existing.soil_type))
+        .bind(update.is_irrigated.unwrap_or(existing.is_irrigated))
+        .bind(update.has_drainage_tile.unwrap_or(existing.has_drainage_tile))
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(plot))
+}
+
+/**
+ * Delete Plot
+ **/
+async fn delete_plot(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+
+    let result = sqlx::query(r#"DELETE FROM "Plot" WHERE "farmId" = $1 AND id = $2"#)
+        .bind(farm_id)
+        .bind(id)
+        .execute(pool.get_ref())
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Plot".to_string()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,6 +1,5 @@
 use std::fmt::{Display, Formatter};
 use actix_web::{web::{self, ServiceConfig}, HttpResponse,web::Json};
-use actix_web::error::BlockingError;
 use sqlx::{Error as SqlxError, postgres::PgPool};
 use actix_web::http::StatusCode;
 use actix_web::web::Query;
@@ -9,14 +8,20 @@ use uuid::Uuid;
 use shared::models::{
     Profile,
     CreateProfile,
-    UpdateProfile
+    UpdateProfile,
+    PhoneNumberIssue,
+    DomainEventType
 };
+use chrono::{DateTime, Utc};
 use tracing::error;
+use crate::{history, outbox};
+use crate::phone::{self, NormalizedPhone, PhoneConfig};
 
 #[derive(Debug)]
 pub enum AppError {
     SqlError(SqlxError),
     GenericError(String),
+    NotFound(String),
     OtherError,
 }
 
@@ -25,6 +30,7 @@ impl Display for AppError {
         match self {
             AppError::SqlError(e) => write!(f, "Database error: {}", e),
             AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
             AppError::OtherError => write!(f, "An unknown error occurred"),
         }
     }
@@ -35,6 +41,7 @@ impl actix_web::error::ResponseError for AppError {
         match *self {
             AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::OtherError => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -45,9 +52,18 @@ impl actix_web::error::ResponseError for AppError {
     }
 }
 
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Profile query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
 pub fn service(cfg: &mut ServiceConfig) {
     cfg.service(web::scope("/v0.1/profiles")
+                    .app_data(web::Data::new(PhoneConfig::from_env()))
                     .route("", web::get().to(get_all_profiles))
+                    .route("/phone-issues", web::get().to(get_phone_issues))
                     .route("/profile", web::get().to(get_profile))
                     .route("/profile", web::post().to(create_profile))
                     .route("/profile/{id}", web::put().to(update_profile))
@@ -165,6 +181,8 @@ pub struct SingleProfileFilter {
     bvn: Option<String>,
     identity_number: Option<String>,
     phone_number: Option<String>,
+    // Return the profile as it was at this time
+    as_of: Option<DateTime<Utc>>,
 }
 
 enum FilterValue {
@@ -172,7 +190,7 @@ enum FilterValue {
     StringValue(String),
 }
 
-async fn get_profile(pool: web::Data<PgPool>, filter: Query<SingleProfileFilter>) -> Result<HttpResponse, AppError> {
+async fn get_profile(pool: web::Data<PgPool>, config: web::Data<PhoneConfig>, filter: Query<SingleProfileFilter>) -> Result<HttpResponse, AppError> {
     let mut where_clauses = Vec::new();
     let mut bindings = Vec::<FilterValue>::new();
 
@@ -193,8 +211,10 @@ async fn get_profile(pool: web::Data<PgPool>, filter: Query<SingleProfileFilter>
         bindings.push(FilterValue::StringValue(identity_number.clone()));
     }
     if let Some(phone_number) = &filter.phone_number {
+        // Stored numbers are E.164, so look up the number however it was typed
+        let phone = phone::normalize(phone_number, config.default_region).map_err(AppError::GenericError)?;
         where_clauses.push("\"phoneNumber\" = $?");
-        bindings.push(FilterValue::StringValue(phone_number.clone()));
+        bindings.push(FilterValue::StringValue(phone.e164));
     }
 
     if where_clauses.is_empty() {
@@ -202,11 +222,15 @@ async fn get_profile(pool: web::Data<PgPool>, filter: Query<SingleProfileFilter>
     }
 
     let where_clause = format!("WHERE {}", where_clauses.join(" AND "));
-    let mut query = format!(r#"SELECT * FROM "Profile" {}"#, where_clause);
+    let source = match filter.as_of {
+        Some(_) => history::as_of_source("Profile", bindings.len() + 1, None),
+        None => r#""Profile""#.to_string(),
+    };
+    let mut query = format!(r#"SELECT * FROM {} {}"#, source, where_clause);
 
     for (i, _) in bindings.iter().enumerate() {
         let placeholder = format!("${}", i + 1);
-        query = query.replace("$?", &placeholder);
+        query = query.replacen("$?", &placeholder, 1);
     }
 
     let mut profile_query = sqlx::query_as::<_, Profile>(&query);
@@ -216,18 +240,61 @@ async fn get_profile(pool: web::Data<PgPool>, filter: Query<SingleProfileFilter>
             FilterValue::StringValue(s) => profile_query = profile_query.bind(s),
         }
     }
+    if let Some(as_of) = filter.as_of {
+        profile_query = profile_query.bind(as_of);
+    }
 
-    let profile_result = profile_query.fetch_one(pool.get_ref()).await;
+    let profile_result = profile_query.fetch_optional(pool.get_ref()).await;
 
     match profile_result {
-        Ok(profile) => Ok(HttpResponse::Ok().json(profile)),
+        Ok(Some(profile)) => Ok(HttpResponse::Ok().json(profile)),
+        Ok(None) => Err(AppError::NotFound("Profile".to_string())),
         Err(e) => {
             error!("Failed to get profile: {:?}", e);
             Err(AppError::SqlError(e))
         }
     }
 }
-async fn create_profile(pool: web::Data<PgPool>, profile: Json<CreateProfile>) -> Result<HttpResponse, BlockingError> {
+/**
+ * Normalises a phone number to E.164 and checks no other profile has it
+ **/
+async fn checked_phone(pool: &PgPool, config: &PhoneConfig, raw: Option<&str>, profile_id: Option<Uuid>) -> Result<Option<NormalizedPhone>, AppError> {
+    let Some(raw) = raw.filter(|raw| !raw.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let phone = phone::normalize(raw, config.default_region).map_err(AppError::GenericError)?;
+
+    let taken: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Profile" WHERE "phoneNumber" = $1 AND ($2::uuid IS NULL OR id <> $2))"#)
+        .bind(&phone.e164)
+        .bind(profile_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            error!("Error checking phone number: {:?}", e);
+            AppError::SqlError(e)
+        })?;
+    if taken {
+        return Err(AppError::GenericError(format!("Phone number {} is already registered to another profile", phone.e164)));
+    }
+    Ok(Some(phone))
+}
+
+/**
+ * The 400 for a write that hit a unique index, e.g. a number registered by another
+ * request after `checked_phone` looked
+ **/
+fn unique_violation(e: &SqlxError) -> Option<AppError> {
+    let db_error = e.as_database_error().filter(|db_error| db_error.is_unique_violation())?;
+    match db_error.constraint() {
+        Some("idx_profile_phonenumber") => Some(AppError::GenericError("Phone number is already registered to another profile".to_string())),
+        Some("Profile_userId_key") => Some(AppError::GenericError("User already has a profile".to_string())),
+        _ => None,
+    }
+}
+
+async fn create_profile(pool: web::Data<PgPool>, config: web::Data<PhoneConfig>, profile: Json<CreateProfile>) -> Result<HttpResponse, AppError> {
+    let phone = checked_phone(pool.get_ref(), config.get_ref(), Some(&profile.phone_number), None).await?;
+    let mut tx = pool.begin().await?;
     let result = sqlx::query_as::<_, Profile>(r#"
     INSERT INTO "Profile"
     (
@@ -237,30 +304,44 @@ async fn create_profile(pool: web::Data<PgPool>, profile: Json<CreateProfile>) -
          "gender",
          "identityNumber",
          "phoneNumber",
-         "userId"
+         "userId",
+         "phoneCarrier",
+         "phoneLineType"
     )
-         VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *
     "#)
         .bind(&profile.bio)
         .bind(&profile.account_number)
         .bind(&profile.bvn)
         .bind(&profile.gender)
         .bind(&profile.identity_number)
-        .bind(&profile.phone_number)
-        .bind(&profile.user_id)
-        .fetch_one(pool.get_ref())
+        .bind(phone.as_ref().map(|phone| &phone.e164))
+        .bind(profile.user_id)
+        .bind(phone.as_ref().and_then(|phone| phone.carrier.as_ref()))
+        .bind(phone.as_ref().map(|phone| phone.line_type))
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
-        Ok(profile) => Ok(HttpResponse::Created().json(profile)),
+        Ok(profile) => {
+            outbox::record(&mut tx, DomainEventType::ProfileCreated, profile.id, &profile).await?;
+            tx.commit().await?;
+            Ok(HttpResponse::Created().json(profile))
+        }
         Err(e) => {
+            if let Some(e) = unique_violation(&e) {
+                return Err(e);
+            }
             error!("Error creating profile: {:?}", e);
             Ok(HttpResponse::InternalServerError().json("Internal server error"))
         }
     }
 }
 
-async fn update_profile(pool: web::Data<PgPool>, id: web::Path<Uuid>, profile: Json<UpdateProfile>) -> Result<HttpResponse, BlockingError> {
+async fn update_profile(pool: web::Data<PgPool>, config: web::Data<PhoneConfig>, id: web::Path<Uuid>, profile: Json<UpdateProfile>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let phone = checked_phone(pool.get_ref(), config.get_ref(), profile.phone_number.as_deref(), Some(id)).await?;
+    let mut tx = pool.begin().await?;
     let result = sqlx::query_as::<_, Profile>(r#"
           UPDATE "Profile" SET
           "bio" = $2,
@@ -269,23 +350,34 @@ async fn update_profile(pool: web::Data<PgPool>, id: web::Path<Uuid>, profile: J
           "gender" = $5,
           "identityNumber" = $6,
           "phoneNumber" = $7,
-          "userId" = $8
+          "userId" = $8,
+          "phoneCarrier" = $9,
+          "phoneLineType" = $10
           WHERE id = $1 RETURNING *
       "#)
-        .bind(id.into_inner())
+        .bind(id)
         .bind(&profile.bio)
         .bind(&profile.account_number)
         .bind(&profile.bvn)
         .bind(&profile.gender)
         .bind(&profile.identity_number)
-        .bind(&profile.phone_number)
-        .bind(&profile.user_id)
-        .fetch_one(pool.get_ref())
+        .bind(phone.as_ref().map(|phone| &phone.e164))
+        .bind(profile.user_id)
+        .bind(phone.as_ref().and_then(|phone| phone.carrier.as_ref()))
+        .bind(phone.as_ref().map(|phone| phone.line_type))
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
-        Ok(user) => Ok(HttpResponse::Ok().json(user)),
+        Ok(profile) => {
+            outbox::record(&mut tx, DomainEventType::ProfileUpdated, profile.id, &profile).await?;
+            tx.commit().await?;
+            Ok(HttpResponse::Ok().json(profile))
+        }
         Err(e) => {
+            if let Some(e) = unique_violation(&e) {
+                return Err(e);
+            }
             error!("Error updating profile: {:?}", e);
             Ok(HttpResponse::InternalServerError().json("Internal server error"))
         }
@@ -293,16 +385,40 @@ async fn update_profile(pool: web::Data<PgPool>, id: web::Path<Uuid>, profile: J
 }
 
 async fn delete_profile(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
-    let result = sqlx::query(r#"DELETE FROM "Profile" WHERE id = $1"#)
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query_as::<_, Profile>(r#"DELETE FROM "Profile" WHERE id = $1 RETURNING *"#)
         .bind(id.into_inner())
-        .execute(pool.get_ref())
+        .fetch_optional(&mut *tx)
         .await;
 
     match result {
-        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Ok(deleted) => {
+            if let Some(profile) = deleted {
+                outbox::record(&mut tx, DomainEventType::ProfileDeleted, profile.id, &profile).await?;
+            }
+            tx.commit().await?;
+            Ok(HttpResponse::NoContent().finish())
+        }
         Err(e) => {
             error!("Error deleting profile: {:?}", e);
             Err(AppError::SqlError(e))
         }
     }
-}
\ No newline at end of file
+}
+/**
+ * Get Phone Number Issues
+ * Numbers the E.164 migration could not normalise or found on more than one profile
+ **/
+async fn get_phone_issues(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let issues = sqlx::query_as::<_, PhoneNumberIssue>(r#"SELECT * FROM "PhoneNumberIssue" ORDER BY "createdAt", "profileId""#)
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match issues {
+        Ok(issues) => Ok(HttpResponse::Ok().json(issues)),
+        Err(e) => {
+            error!("Error fetching phone number issues: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
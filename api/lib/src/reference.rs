@@ -0,0 +1,282 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use serde::Deserialize;
+use sqlx::{Error as SqlxError, PgExecutor, postgres::PgPool};
+use actix_web::http::StatusCode;
+use deadpool_redis::Pool as RedisPool;
+use uuid::Uuid;
+use shared::models::{
+    Country,
+    State,
+    Lga,
+    FarmReferenceUnmatched
+};
+use tracing::{error, info};
+use crate::cache;
+
+// Reference data is only changed by migrations and the LGA load, so a day is a safe expiry
+const CACHE_TTL_SECONDS: usize = 60 * 60 * 24;
+// LGAs by country and state code
+const LGA_DATA: &str = include_str!("../data/lgas.csv");
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    OtherError,
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::OtherError => write!(f, "An unknown error occurred"),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::OtherError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LgaRow {
+    country_code: String,
+    state_code: String,
+    name: String,
+}
+
+/**
+ * Adds the bundled LGAs that are missing, then links farms whose locality names one of them.
+ * Runs once at start-up; LGAs added by hand are left alone.
+ **/
+pub async fn load_lgas(pool: PgPool, redis: RedisPool) {
+    let rows: Vec<LgaRow> = csv::Reader::from_reader(LGA_DATA.as_bytes())
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .expect("bundled LGA data is valid");
+    let country_codes: Vec<String> = rows.iter().map(|row| row.country_code.clone()).collect();
+    let state_codes: Vec<String> = rows.iter().map(|row| row.state_code.clone()).collect();
+    let names: Vec<String> = rows.into_iter().map(|row| row.name).collect();
+
+    let inserted: Vec<Uuid> = match sqlx::query_scalar(
+        r#"
+        INSERT INTO "Lga" (name, "stateId")
+        SELECT l.name, s.id
+        FROM UNNEST($1::text[], $2::text[], $3::text[]) AS l(country_code, state_code, name)
+        JOIN "Country" c ON c.code = l.country_code
+        JOIN "State" s ON s."countryId" = c.id AND s.code = l.state_code
+        ON CONFLICT ("stateId", name) DO NOTHING
+        RETURNING "stateId"
+        "#,
+    )
+        .bind(&country_codes)
+        .bind(&state_codes)
+        .bind(&names)
+        .fetch_all(&pool)
+        .await
+    {
+        Ok(inserted) => inserted,
+        Err(e) => {
+            error!("Error loading LGAs: {:?}", e);
+            return;
+        }
+    };
+    if inserted.is_empty() {
+        return;
+    }
+
+    let linked = sqlx::query(
+        r#"
+        UPDATE "Farm" f
+        SET "lgaId" = l.id
+        FROM "Lga" l
+        WHERE f."lgaId" IS NULL
+          AND f."stateId" = l."stateId"
+          AND lower(regexp_replace(trim(f.locality), '\s+(lga|local government( area)?)$', '', 'i')) = lower(l.name)
+        "#,
+    )
+        .execute(&pool)
+        .await;
+    match linked {
+        Ok(linked) => info!("Loaded {} LGAs and linked {} farms to them", inserted.len(), linked.rows_affected()),
+        Err(e) => error!("Error linking farms to LGAs: {:?}", e),
+    }
+
+    let mut state_ids = inserted;
+    state_ids.sort();
+    state_ids.dedup();
+    for state_id in state_ids {
+        cache::delete(&redis, &format!("reference:states:{}:lgas", state_id)).await;
+    }
+}
+
+/**
+ * Why a farm's state is not in its country or its LGA not in its state, if either is so
+ **/
+pub async fn region_mismatch(executor: impl PgExecutor<'_>, country_id: Option<Uuid>, state_id: Option<Uuid>, lga_id: Option<Uuid>) -> Result<Option<String>, SqlxError> {
+    let (state_country_id, lga_state_id): (Option<Uuid>, Option<Uuid>) = sqlx::query_as(
+        r#"
+        SELECT (SELECT "countryId" FROM "State" WHERE id = $1),
+               (SELECT "stateId" FROM "Lga" WHERE id = $2)
+        "#,
+    )
+        .bind(state_id)
+        .bind(lga_id)
+        .fetch_one(executor)
+        .await?;
+
+    if state_id.is_some() {
+        match state_country_id {
+            None => return Ok(Some("stateId is not a known state".to_string())),
+            Some(parent) if country_id.is_some_and(|id| id != parent) => {
+                return Ok(Some("stateId is not a state of countryId".to_string()));
+            }
+            Some(_) => {}
+        }
+    }
+    if lga_id.is_some() {
+        match lga_state_id {
+            None => return Ok(Some("lgaId is not a known LGA".to_string())),
+            Some(parent) if state_id.is_some_and(|id| id != parent) => {
+                return Ok(Some("lgaId is not an LGA of stateId".to_string()));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(None)
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/reference")
+                    .route("/countries", web::get().to(get_countries))
+                    .route("/countries/{id}/states", web::get().to(get_states))
+                    .route("/states/{id}/lgas", web::get().to(get_lgas))
+                    .route("/unmatched-farms", web::get().to(get_unmatched_farms))
+    );
+}
+
+async fn get_countries(pool: web::Data<PgPool>, redis: web::Data<RedisPool>) -> Result<HttpResponse, AppError> {
+    let key = "reference:countries";
+
+    if let Some(countries) = cache::get_json::<Vec<Country>>(redis.get_ref(), key).await {
+        return Ok(HttpResponse::Ok().json(countries));
+    }
+
+    let countries_result = sqlx::query_as::<_, Country>(
+        r#"
+        SELECT *
+        FROM "Country"
+        ORDER BY name
+        "#,
+    )
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match countries_result {
+        Ok(countries) => {
+            cache::set_json(redis.get_ref(), key, &countries, CACHE_TTL_SECONDS).await;
+            Ok(HttpResponse::Ok().json(countries))
+        }
+        Err(e) => {
+            error!("Error getting countries: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+async fn get_states(pool: web::Data<PgPool>, redis: web::Data<RedisPool>, country_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let country_id = country_id.into_inner();
+    let key = format!("reference:countries:{}:states", country_id);
+
+    if let Some(states) = cache::get_json::<Vec<State>>(redis.get_ref(), &key).await {
+        return Ok(HttpResponse::Ok().json(states));
+    }
+
+    let states_result = sqlx::query_as::<_, State>(
+        r#"
+        SELECT *
+        FROM "State"
+        WHERE "countryId" = $1
+        ORDER BY name
+        "#,
+    )
+        .bind(country_id)
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match states_result {
+        Ok(states) => {
+            cache::set_json(redis.get_ref(), &key, &states, CACHE_TTL_SECONDS).await;
+            Ok(HttpResponse::Ok().json(states))
+        }
+        Err(e) => {
+            error!("Error getting states: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+async fn get_lgas(pool: web::Data<PgPool>, redis: web::Data<RedisPool>, state_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let state_id = state_id.into_inner();
+    let key = format!("reference:states:{}:lgas", state_id);
+
+    if let Some(lgas) = cache::get_json::<Vec<Lga>>(redis.get_ref(), &key).await {
+        return Ok(HttpResponse::Ok().json(lgas));
+    }
+
+    let lgas_result = sqlx::query_as::<_, Lga>(
+        r#"
+        SELECT *
+        FROM "Lga"
+        WHERE "stateId" = $1
+        ORDER BY name
+        "#,
+    )
+        .bind(state_id)
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match lgas_result {
+        Ok(lgas) => {
+            cache::set_json(redis.get_ref(), &key, &lgas, CACHE_TTL_SECONDS).await;
+            Ok(HttpResponse::Ok().json(lgas))
+        }
+        Err(e) => {
+            error!("Error getting LGAs: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
+
+/**
+ * Farms whose free-text country, state or locality has no reference match
+ **/
+async fn get_unmatched_farms(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let unmatched_result = sqlx::query_as::<_, FarmReferenceUnmatched>(
+        r#"
+        SELECT *
+        FROM "FarmReferenceUnmatched"
+        ORDER BY field, value
+        "#,
+    )
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match unmatched_result {
+        Ok(unmatched) => Ok(HttpResponse::Ok().json(unmatched)),
+        Err(e) => {
+            error!("Error getting unmatched farm references: {:?}", e);
+            Err(AppError::SqlError(e))
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use shared::models::{
+    SearchEntityType,
+    SearchGroup,
+    SearchHighlight,
+    SearchHit,
+    SearchQuery,
+    SearchResults
+};
+use tracing::error;
+
+const DEFAULT_LIMIT: i64 = 10;
+const MAX_LIMIT: i64 = 50;
+const MAX_TERMS: usize = 8;
+// Same as pg_trgm's default word_similarity_threshold, used by the `<%` operator
+const WORD_SIMILARITY_THRESHOLD: f64 = 0.6;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Search query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/search")
+                    .route("", web::get().to(search))
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    // Lowercased letters and digits only, so it is safe inside a tsquery
+    Word(String),
+    // Phone digits without the country code or trunk zero
+    Digits(String),
+}
+
+#[derive(sqlx::FromRow)]
+struct HitRow {
+    #[sqlx(rename = "entityType")]
+    entity_type: SearchEntityType,
+    total: i64,
+    #[sqlx(flatten)]
+    hit: SearchHit,
+}
+
+// 08035551234, 2348035551234 and 8035551234 all become 8035551234
+fn normalize_digits(digits: &str) -> String {
+    let digits = if digits.len() > 10 { digits.strip_prefix("234").unwrap_or(digits) } else { digits };
+    digits.trim_start_matches('0').to_string()
+}
+
+/**
+ * Splits a query into terms. A query that is only a phone number, spaces and punctuation
+ * included, is a single digits term; otherwise runs of four or more digits are digits terms
+ * and everything else is a word. Single letters are dropped.
+ **/
+fn parse_terms(query: &str) -> Vec<Term> {
+    let compact: String = query.chars().filter(|c| !c.is_whitespace() && !"+-()".contains(*c)).collect();
+    if compact.len() >= 4 && compact.chars().all(|c| c.is_ascii_digit()) {
+        let digits = normalize_digits(&compact);
+        return if digits.is_empty() { Vec::new() } else { vec![Term::Digits(digits)] };
+    }
+
+    let mut terms = Vec::new();
+    for token in query.split(|c: char| !c.is_alphanumeric()) {
+        let token = token.to_lowercase();
+        let term = if token.len() >= 4 && token.chars().all(|c| c.is_ascii_digit()) {
+            Term::Digits(normalize_digits(&token))
+        } else if token.chars().count() >= 2 {
+            Term::Word(token)
+        } else {
+            continue;
+        };
+        if !terms.contains(&term) && !matches!(&term, Term::Digits(digits) if digits.is_empty()) {
+            terms.push(term);
+        }
+    }
+    terms.truncate(MAX_TERMS);
+    terms
+}
+
+fn trigrams(word: &str) -> HashSet<String> {
+    let padded: Vec<char> = format!("  {} ", word).chars().collect();
+    padded.windows(3).map(|window| window.iter().collect()).collect()
+}
+
+// pg_trgm's word_similarity for a single word: the share of the term's trigrams found in the word
+fn word_similarity(term: &str, word: &str) -> f64 {
+    let term_trigrams = trigrams(term);
+    let word_trigrams = trigrams(word);
+    term_trigrams.intersection(&word_trigrams).count() as f64 / term_trigrams.len() as f64
+}
+
+fn segment_matches(segment: &str, terms: &[Term]) -> bool {
+    let lowered = segment.to_lowercase();
+    let digits: String = segment.chars().filter(|c| c.is_ascii_digit()).collect();
+    terms.iter().any(|term| match term {
+        Term::Word(word) => lowered.starts_with(word.as_str()) || word_similarity(word, &lowered) >= WORD_SIMILARITY_THRESHOLD,
+        Term::Digits(wanted) => !digits.is_empty() && normalize_digits(&digits).contains(wanted.as_str()),
+    })
+}
+
+fn push_escaped(fragment: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => fragment.push_str("&amp;"),
+            '<' => fragment.push_str("&lt;"),
+            '>' => fragment.push_str("&gt;"),
+            '"' => fragment.push_str("&quot;"),
+            '\'' => fragment.push_str("&#39;"),
+            _ => fragment.push(c),
+        }
+    }
+}
+
+/**
+ * Wraps the words of `text` that match a term in <mark></mark>. Phone numbers are matched
+ * as a whole, spaces and all. The text is HTML-escaped. Returns None when nothing matched.
+ **/
+fn highlight(text: &str, terms: &[Term]) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut fragment = String::with_capacity(text.len());
+    let mut matched = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        if chars[i].is_ascii_digit() || (chars[i] == '+' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())) {
+            // A phone-like run: digits with the separators people type between them
+            let mut end = i + 1;
+            while end < chars.len() && (chars[end].is_ascii_digit() || " +-()".contains(chars[end])) {
+                end += 1;
+            }
+            while end > start + 1 && !chars[end - 1].is_ascii_digit() {
+                end -= 1;
+            }
+            i = end;
+        } else if chars[i].is_alphanumeric() {
+            while i < chars.len() && chars[i].is_alphanumeric() {
+                i += 1;
+            }
+        } else {
+            push_escaped(&mut fragment, &chars[i].to_string());
+            i += 1;
+            continue;
+        }
+
+        let segment: String = chars[start..i].iter().collect();
+        if segment_matches(&segment, terms) {
+            matched = true;
+            fragment.push_str("<mark>");
+            push_escaped(&mut fragment, &segment);
+            fragment.push_str("</mark>");
+        } else {
+            push_escaped(&mut fragment, &segment);
+        }
+    }
+
+    matched.then_some(fragment)
+}
+
+/**
+ * Search
+ * Finds users and farms by name, phone number, farm name, LGA and state. Every term must
+ * match, either as a word prefix or, to tolerate typos, as a close trigram match; phone
+ * numbers match on their digits in any format. Results are ranked and grouped by entity type,
+ * with matched words highlighted.
+ **/
+async fn search(pool: web::Data<PgPool>, query: web::Query<SearchQuery>) -> Result<HttpResponse, AppError> {
+    let SearchQuery { q, entity_type, limit } = query.into_inner();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::GenericError(format!("Limit must be between 1 and {}", MAX_LIMIT)));
+    }
+    let q = q.trim().to_string();
+    let terms = parse_terms(&q);
+    if terms.is_empty() {
+        return Err(AppError::GenericError("Search needs a word of at least two letters or a number of at least four digits".to_string()));
+    }
+
+    // Any one word is enough to rank; the filter below requires all of them
+    let rank_query = terms.iter()
+        .filter_map(|term| match term {
+            Term::Word(word) => Some(format!("{}:*", word)),
+            Term::Digits(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let conditions = terms.iter()
+        .enumerate()
+        .map(|(i, term)| {
+            let n = i + 5;
+            match term {
+                Term::Word(_) => format!(r#"(d.document @@ to_tsquery('simple', ${} || ':*') OR ${} <% d.content)"#, n, n),
+                Term::Digits(_) => format!("d.content LIKE '%' || ${} || '%'", n),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    let sql = format!(
+        r#"
+        SELECT * FROM (
+            SELECT m.*, row_number() OVER (PARTITION BY m."entityType" ORDER BY m.rank DESC, m.title) AS position
+            FROM (
+                SELECT d."entityType", d."entityId", d.title, d.subtitle,
+                       (coalesce(ts_rank_cd(d.document, to_tsquery('simple', NULLIF($3, ''))), 0)
+                           + word_similarity($2, d.content))::float8 AS rank,
+                       count(*) OVER (PARTITION BY d."entityType") AS total
+                FROM "SearchDocument" d
+                WHERE ($1::varchar IS NULL OR d."entityType" = $1) AND {}
+            ) m
+        ) ranked
+        WHERE position <= $4
+        ORDER BY "entityType", position
+        "#,
+        conditions
+    );
+
+    let mut statement = sqlx::query_as::<_, HitRow>(&sql)
+        .bind(entity_type)
+        .bind(q.to_lowercase())
+        .bind(rank_query)
+        .bind(limit);
+    for term in &terms {
+        statement = match term {
+            Term::Word(word) => statement.bind(word.clone()),
+            Term::Digits(digits) => statement.bind(digits.clone()),
+        };
+    }
+    let rows = statement.fetch_all(pool.get_ref()).await?;
+
+    let mut groups: Vec<SearchGroup> = Vec::new();
+    for HitRow { entity_type, total, mut hit } in rows {
+        hit.highlights = [("title", Some(hit.title.as_str())), ("subtitle", hit.subtitle.as_deref())]
+            .into_iter()
+            .filter_map(|(field, text)| {
+                highlight(text?, &terms).map(|fragment| SearchHighlight { field: field.to_string(), fragment })
+            })
+            .collect();
+        match groups.last_mut() {
+            Some(group) if group.entity_type == entity_type => group.hits.push(hit),
+            _ => groups.push(SearchGroup { entity_type, total, hits: vec![hit] }),
+        }
+    }
+    // Best group first
+    groups.sort_by(|a, b| b.hits[0].rank.total_cmp(&a.hits[0].rank));
+
+    Ok(HttpResponse::Ok().json(SearchResults { query: q, groups }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_marks_matching_words() {
+        let terms = parse_terms("aminu");
+        assert_eq!(highlight("Aminu Bello", &terms).as_deref(), Some("<mark>Aminu</mark> Bello"));
+        assert_eq!(highlight("Musa Bello", &terms), None);
+    }
+
+    #[test]
+    fn highlight_escapes_html() {
+        let terms = parse_terms("farm");
+        assert_eq!(
+            highlight("<b>Farm</b> & \"sons\"", &terms).as_deref(),
+            Some("&lt;b&gt;<mark>Farm</mark>&lt;/b&gt; &amp; &quot;sons&quot;")
+        );
+    }
+}
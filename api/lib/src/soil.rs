@@ -0,0 +1,470 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use uuid::Uuid;
+use shared::models::{
+    SoilSample,
+    CreateSoilSample,
+    SoilNutrient,
+    FertilizerRule,
+    CreateFertilizerRule,
+    FertilizerRecommendation,
+    RecommendationQuery
+};
+use tracing::error;
+
+const NUTRIENTS: [SoilNutrient; 5] = [
+    SoilNutrient::Ph,
+    SoilNutrient::Nitrogen,
+    SoilNutrient::Phosphorus,
+    SoilNutrient::Potassium,
+    SoilNutrient::OrganicMatter,
+];
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Soil query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/soil-samples")
+                    .route("", web::get().to(get_all_soil_samples))
+                    .route("", web::post().to(create_soil_sample))
+                    .route("/{id}/recommendations", web::get().to(get_recommendations))
+    );
+    cfg.service(web::scope("/v0.1/soil/rules")
+                    .route("", web::get().to(get_all_rules))
+                    .route("", web::post().to(create_rule))
+                    .route("/{id}", web::put().to(update_rule))
+                    .route("/{id}", web::delete().to(delete_rule))
+    );
+}
+
+/**
+ * Pick one rule per nutrient for the sample and crop.
+ * If the crop has its own rules for a nutrient they replace the generic ones;
+ * among matching rules the highest priority wins.
+ **/
+pub fn recommend(sample: &SoilSample, rules: &[FertilizerRule], crop_id: Uuid, hectares: f64) -> Vec<FertilizerRecommendation> {
+    NUTRIENTS.iter()
+        .filter_map(|&nutrient| {
+            let value = sample.value(nutrient)?;
+            let for_nutrient: Vec<&FertilizerRule> = rules.iter().filter(|r| r.nutrient == nutrient).collect();
+            let crop_specific = for_nutrient.iter().any(|r| r.crop_id == Some(crop_id));
+
+            let rule = for_nutrient.into_iter()
+                .filter(|r| if crop_specific { r.crop_id == Some(crop_id) } else { r.crop_id.is_none() })
+                .filter(|r| r.matches(value))
+                .max_by_key(|r| r.priority)?;
+
+            Some(FertilizerRecommendation {
+                nutrient,
+                soil_value: value,
+                fertilizer: rule.fertilizer.clone(),
+                rate_kg_per_hectare: rule.rate_kg_per_hectare,
+                total_kg: rule.rate_kg_per_hectare * hectares,
+                rule_id: rule.id,
+                notes: rule.notes.clone(),
+            })
+        })
+        .collect()
+}
+
+async fn get_all_soil_samples(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let samples = sqlx::query_as::<_, SoilSample>(
+        r#"
+        SELECT *
+        FROM "SoilSample"
+        WHERE "farmId" = $1
+        ORDER BY "sampledOn" DESC
+        "#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(samples))
+}
+
+/**
+ * Create Soil Sample
+ **/
+async fn create_soil_sample(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, sample: Json<CreateSoilSample>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let sample = sample.into_inner();
+
+    if sample.ph.is_some_and(|ph| !(0.0..=14.0).contains(&ph)) {
+        return Err(AppError::GenericError("pH must be between 0 and 14".to_string()));
+    }
+    let amounts = [sample.nitrogen, sample.phosphorus, sample.potassium, sample.organic_matter];
+    if amounts.iter().flatten().any(|value| !value.is_finite() || *value < 0.0) {
+        return Err(AppError::GenericError("Nutrient values must not be negative".to_string()));
+    }
+
+    if let Some(plot_id) = sample.plot_id {
+        let plot_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Plot" WHERE id = $1 AND "farmId" = $2)"#)
+            .bind(plot_id)
+            .bind(farm_id)
+            .fetch_one(pool.get_ref())
+            .await?;
+        if !plot_exists {
+            return Err(AppError::NotFound("Plot".to_string()));
+        }
+    }
+
+    let sample = sqlx::query_as::<_, SoilSample>(
+        r#"
+        INSERT INTO "SoilSample" ("farmId", "plotId", "sampledOn", "labReference", ph, nitrogen, phosphorus, potassium, "organicMatter", texture, latitude, longitude)
+        SELECT id, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12
+        FROM "Farm"
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(farm_id)
+        .bind(sample.plot_id)
+        .bind(sample.sampled_on)
+        .bind(sample.lab_reference)
+        .bind(sample.ph)
+        .bind(sample.nitrogen)
+        .bind(sample.phosphorus)
+        .bind(sample.potassium)
+        .bind(sample.organic_matter)
+        .bind(sample.texture)
+        .bind(sample.latitude)
+        .bind(sample.longitude)
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+
+    Ok(HttpResponse::Created().json(sample))
+}
+
+/**
+ * Fertilizer recommendations for a soil sample and planned crop
+ **/
+async fn get_recommendations(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, query: web::Query<RecommendationQuery>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let query = query.into_inner();
+
+    let sample = sqlx::query_as::<_, SoilSample>(r#"SELECT * FROM "SoilSample" WHERE id = $1 AND "farmId" = $2"#)
+        .bind(id)
+        .bind(farm_id)
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Soil sample".to_string()))?;
+
+    let hectares = match query.area {
+        Some(area) if area.is_finite() && area >= 0.0 => query.unit.to_hectares(area),
+        Some(_) => return Err(AppError::GenericError("Area must not be negative".to_string())),
+        None => {
+            sqlx::query_scalar::<_, f64>(
+                r#"
+                SELECT COALESCE(p.area, f.acreage)
+                FROM "Farm" f
+                LEFT JOIN "Plot" p ON p.id = $2
+                WHERE f.id = $1
+                "#,
+            )
+                .bind(farm_id)
+                .bind(sample.plot_id)
+                .fetch_optional(pool.get_ref())
+                .await?
+                .ok_or_else(|| AppError::NotFound("Farm".to_string()))?
+        }
+    };
+
+    let rules = sqlx::query_as::<_, FertilizerRule>(
+        r#"
+        SELECT *
+        FROM "FertilizerRule"
+        WHERE "cropId" IS NULL OR "cropId" = $1
+        "#,
+    )
+        .bind(query.crop_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(recommend(&sample, &rules, query.crop_id, hectares)))
+}
+
+fn validate_rule(rule: &CreateFertilizerRule) -> Result<(), AppError> {
+    if rule.fertilizer.trim().is_empty() {
+        return Err(AppError::GenericError("Fertilizer is required".to_string()));
+    }
+    if !rule.rate_kg_per_hectare.is_finite() || rule.rate_kg_per_hectare < 0.0 {
+        return Err(AppError::GenericError("Rate must not be negative".to_string()));
+    }
+    if let (Some(min), Some(max)) = (rule.min_value, rule.max_value) {
+        if min >= max {
+            return Err(AppError::GenericError("minValue must be below maxValue".to_string()));
+        }
+    }
+    Ok(())
+}
+
+async fn check_crop(pool: &PgPool, crop_id: Option<Uuid>) -> Result<(), AppError> {
+    if let Some(crop_id) = crop_id {
+        sqlx::query_scalar::<_, Uuid>(r#"SELECT id FROM "Crop" WHERE id = $1"#)
+            .bind(crop_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Crop".to_string()))?;
+    }
+    Ok(())
+}
+
+async fn get_all_rules(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let rules = sqlx::query_as::<_, FertilizerRule>(
+        r#"
+        SELECT *
+        FROM "FertilizerRule"
+        ORDER BY nutrient, "cropId" NULLS FIRST, "minValue" NULLS FIRST
+        "#,
+    )
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(rules))
+}
+
+async fn create_rule(pool: web::Data<PgPool>, rule: Json<CreateFertilizerRule>) -> Result<HttpResponse, AppError> {
+    let rule = rule.into_inner();
+    validate_rule(&rule)?;
+    check_crop(pool.get_ref(), rule.crop_id).await?;
+
+    let rule = sqlx::query_as::<_, FertilizerRule>(
+        r#"
+        INSERT INTO "FertilizerRule" ("cropId", nutrient, "minValue", "maxValue", fertilizer, "rateKgPerHectare", priority, notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+        .bind(rule.crop_id)
+        .bind(rule.nutrient)
+        .bind(rule.min_value)
+        .bind(rule.max_value)
+        .bind(rule.fertilizer)
+        .bind(rule.rate_kg_per_hectare)
+        .bind(rule.priority)
+        .bind(rule.notes)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(rule))
+}
+
+async fn update_rule(pool: web::Data<PgPool>, id: web::Path<Uuid>, rule: Json<CreateFertilizerRule>) -> Result<HttpResponse, AppError> {
+    let rule = rule.into_inner();
+    validate_rule(&rule)?;
+    check_crop(pool.get_ref(), rule.crop_id).await?;
+
+    let rule = sqlx::query_as::<_, FertilizerRule>(
+        r#"
+        UPDATE "FertilizerRule"
+        SET "cropId" = $2,
+            nutrient = $3,
+            "minValue" = $4,
+            "maxValue" = $5,
+            fertilizer = $6,
+            "rateKgPerHectare" = $7,
+            priority = $8,
+            notes = $9,
+            "updatedAt" = current_timestamp
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(id.into_inner())
+        .bind(rule.crop_id)
+        .bind(rule.nutrient)
+        .bind(rule.min_value)
+        .bind(rule.max_value)
+        .bind(rule.fertilizer)
+        .bind(rule.rate_kg_per_hectare)
+        .bind(rule.priority)
+        .bind(rule.notes)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match rule {
+        Some(rule) => Ok(HttpResponse::Ok().json(rule)),
+        None => Err(AppError::NotFound("Fertilizer rule".to_string())),
+    }
+}
+
+async fn delete_rule(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let result = sqlx::query(r#"DELETE FROM "FertilizerRule" WHERE id = $1"#)
+        .bind(id.into_inner())
+        .execute(pool.get_ref())
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Fertilizer rule".to_string()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn sample(ph: Option<f64>, nitrogen: Option<f64>, phosphorus: Option<f64>) -> SoilSample {
+        SoilSample {
+            id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            farm_id: Uuid::new_v4(),
+            plot_id: None,
+            sampled_on: NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+            lab_reference: None,
+            ph,
+            nitrogen,
+            phosphorus,
+            potassium: None,
+            organic_matter: None,
+            texture: None,
+            latitude: None,
+            longitude: None,
+        }
+    }
+
+    fn rule(crop_id: Option<Uuid>, nutrient: SoilNutrient, min: Option<f64>, max: Option<f64>, fertilizer: &str, rate: f64, priority: i32) -> FertilizerRule {
+        FertilizerRule {
+            id: Uuid::new_v4(),
+            crop_id,
+            nutrient,
+            min_value: min,
+            max_value: max,
+            fertilizer: fertilizer.to_string(),
+            rate_kg_per_hectare: rate,
+            priority,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn recommends_the_matching_generic_rule_per_nutrient() {
+        let maize = Uuid::new_v4();
+        let rules = vec![
+            rule(None, SoilNutrient::Nitrogen, None, Some(0.1), "Urea", 120.0, 0),
+            rule(None, SoilNutrient::Nitrogen, Some(0.1), None, "Urea", 40.0, 0),
+            rule(None, SoilNutrient::Ph, None, Some(5.5), "Agricultural lime", 500.0, 0),
+        ];
+
+        let recommendations = recommend(&sample(Some(6.2), Some(0.05), None), &rules, maize, 2.0);
+
+        assert_eq!(recommendations.len(), 1);
+        let nitrogen = &recommendations[0];
+        assert_eq!(nitrogen.nutrient, SoilNutrient::Nitrogen);
+        assert_eq!(nitrogen.rule_id, rules[0].id);
+        assert_eq!(nitrogen.soil_value, 0.05);
+        assert_eq!(nitrogen.total_kg, 240.0);
+    }
+
+    #[test]
+    fn range_bounds_include_the_minimum_and_exclude_the_maximum() {
+        let rules = vec![
+            rule(None, SoilNutrient::Nitrogen, None, Some(0.1), "Urea", 120.0, 0),
+            rule(None, SoilNutrient::Nitrogen, Some(0.1), None, "Urea", 40.0, 0),
+        ];
+
+        let recommendations = recommend(&sample(None, Some(0.1), None), &rules, Uuid::new_v4(), 1.0);
+
+        assert_eq!(recommendations.len(), 1);
+        assert_eq!(recommendations[0].rule_id, rules[1].id);
+    }
+
+    #[test]
+    fn crop_specific_rules_replace_the_generic_ones() {
+        let maize = Uuid::new_v4();
+        let rules = vec![
+            rule(None, SoilNutrient::Phosphorus, None, Some(10.0), "SSP", 150.0, 5),
+            rule(Some(maize), SoilNutrient::Phosphorus, None, Some(7.0), "NPK 15-15-15", 200.0, 0),
+            rule(None, SoilNutrient::Nitrogen, None, None, "Urea", 60.0, 0),
+        ];
+
+        let recommendations = recommend(&sample(None, Some(0.2), Some(5.0)), &rules, maize, 1.0);
+        let fertilizer = |nutrient| recommendations.iter().find(|r| r.nutrient == nutrient).map(|r| r.fertilizer.as_str());
+
+        assert_eq!(fertilizer(SoilNutrient::Phosphorus), Some("NPK 15-15-15"));
+        // Nitrogen has no maize rule, so the generic one still applies
+        assert_eq!(fertilizer(SoilNutrient::Nitrogen), Some("Urea"));
+    }
+
+    #[test]
+    fn crop_specific_rules_replace_the_generic_ones_even_when_none_match() {
+        let maize = Uuid::new_v4();
+        let rules = vec![
+            rule(None, SoilNutrient::Phosphorus, None, Some(10.0), "SSP", 150.0, 0),
+            rule(Some(maize), SoilNutrient::Phosphorus, None, Some(7.0), "NPK 15-15-15", 200.0, 0),
+        ];
+
+        let recommendations = recommend(&sample(None, None, Some(8.0)), &rules, maize, 1.0);
+
+        assert!(recommendations.is_empty());
+    }
+
+    #[test]
+    fn rules_for_other_crops_are_ignored() {
+        let (maize, sorghum) = (Uuid::new_v4(), Uuid::new_v4());
+        let rules = vec![
+            rule(None, SoilNutrient::Phosphorus, None, Some(10.0), "SSP", 150.0, 0),
+            rule(Some(sorghum), SoilNutrient::Phosphorus, None, Some(10.0), "NPK 20-10-10", 100.0, 0),
+        ];
+
+        let recommendations = recommend(&sample(None, None, Some(5.0)), &rules, maize, 1.0);
+
+        assert_eq!(recommendations.len(), 1);
+        assert_eq!(recommendations[0].fertilizer, "SSP");
+    }
+
+    #[test]
+    fn the_highest_priority_matching_rule_wins() {
+        let rules = vec![
+            rule(None, SoilNutrient::Ph, None, Some(5.5), "Agricultural lime", 500.0, 0),
+            rule(None, SoilNutrient::Ph, None, Some(6.0), "Dolomite", 300.0, 10),
+        ];
+
+        let recommendations = recommend(&sample(Some(5.0), None, None), &rules, Uuid::new_v4(), 1.0);
+
+        assert_eq!(recommendations.len(), 1);
+        assert_eq!(recommendations[0].fertilizer, "Dolomite");
+    }
+}
@@ -0,0 +1,812 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Acquire, Error as SqlxError, postgres::PgPool, Postgres, Transaction, types::Json as SqlxJson};
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+use shared::models::{
+    DomainEntityType,
+    DomainEventType,
+    SyncOperation,
+    SyncChange,
+    SyncRequest,
+    SyncResultStatus,
+    SyncResult,
+    ConflictRule,
+    ConflictSide,
+    SyncConflict,
+    SyncedRecord,
+    SyncResponse
+};
+use tracing::{error, warn};
+use crate::outbox;
+use crate::phone::{self, PhoneConfig};
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Sync query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+/**
+ * Offline sync settings, read from SYNC_MAX_CHANGES and SYNC_TOKEN_OVERLAP_SECS
+ **/
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    pub max_changes: usize,
+    // Changes are read from this long before the sync token, so a write that committed late
+    // is not missed. Records in the overlap are sent again; clients keep the higher version.
+    pub token_overlap: Duration,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        SyncConfig {
+            max_changes: 500,
+            token_overlap: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+impl SyncConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|value| value.parse().ok())
+        }
+        let default = SyncConfig::default();
+        SyncConfig {
+            max_changes: var("SYNC_MAX_CHANGES").unwrap_or(default.max_changes),
+            token_overlap: var("SYNC_TOKEN_OVERLAP_SECS").map(Duration::from_secs).unwrap_or(default.token_overlap),
+        }
+    }
+}
+
+// A column the app may write, and who wins when it was edited both offline and on the server
+struct Field {
+    name: &'static str,
+    rule: ConflictRule,
+    // Must be given on create and cannot be emptied, as the models read it as set
+    required: bool,
+    // Set on create only; it ties the record to its farmer
+    create_only: bool,
+}
+
+const fn field(name: &'static str, rule: ConflictRule) -> Field {
+    Field { name, rule, required: false, create_only: false }
+}
+
+const fn required(name: &'static str, rule: ConflictRule) -> Field {
+    Field { name, rule, required: true, create_only: false }
+}
+
+const fn create_only(name: &'static str) -> Field {
+    Field { name, rule: ConflictRule::ServerWins, required: true, create_only: true }
+}
+
+// Identity and payment details checked at the office win over the app; what the agent
+// measured on site wins over the office; for the rest the later edit wins
+const USER_FIELDS: &[Field] = &[
+    required("firstName", ConflictRule::LatestWins),
+    required("lastName", ConflictRule::LatestWins),
+    field("middleName", ConflictRule::LatestWins),
+    field("email", ConflictRule::ServerWins),
+];
+
+const PROFILE_FIELDS: &[Field] = &[
+    create_only("userId"),
+    field("bio", ConflictRule::LatestWins),
+    required("gender", ConflictRule::LatestWins),
+    field("nationality", ConflictRule::LatestWins),
+    field("phoneNumber", ConflictRule::ServerWins),
+    field("accountNumber", ConflictRule::ServerWins),
+    required("bvn", ConflictRule::ServerWins),
+    field("identityNumber", ConflictRule::ServerWins),
+];
+
+const FARM_FIELDS: &[Field] = &[
+    create_only("farmerId"),
+    field("farm_name", ConflictRule::LatestWins),
+    required("acreage", ConflictRule::ClientWins),
+    field("available_portion", ConflictRule::ServerWins),
+    required("ownership", ConflictRule::ServerWins),
+    field("land_value", ConflictRule::ServerWins),
+    field("has_drainage_tile", ConflictRule::ClientWins),
+    field("is_irrigated", ConflictRule::ClientWins),
+    required("latitude", ConflictRule::ClientWins),
+    required("longitude", ConflictRule::ClientWins),
+    field("boundary", ConflictRule::ClientWins),
+    field("farm_site", ConflictRule::LatestWins),
+    required("locality", ConflictRule::LatestWins),
+    required("state", ConflictRule::LatestWins),
+    required("country", ConflictRule::LatestWins),
+    field("countryId", ConflictRule::LatestWins),
+    field("stateId", ConflictRule::LatestWins),
+    field("lgaId", ConflictRule::LatestWins),
+];
+
+struct Table {
+    name: &'static str,
+    history: &'static str,
+    // Id column of the history table
+    key: &'static str,
+    // Column holding the id of the farmer the record belongs to
+    farmer: &'static str,
+    fields: &'static [Field],
+    created: DomainEventType,
+    updated: DomainEventType,
+    deleted: DomainEventType,
+}
+
+const USER: Table = Table {
+    name: "User",
+    history: "UserHistory",
+    key: "userId",
+    farmer: "id",
+    fields: USER_FIELDS,
+    created: DomainEventType::UserCreated,
+    updated: DomainEventType::UserUpdated,
+    deleted: DomainEventType::UserDeleted,
+};
+
+const PROFILE: Table = Table {
+    name: "Profile",
+    history: "ProfileHistory",
+    key: "profileId",
+    farmer: "userId",
+    fields: PROFILE_FIELDS,
+    created: DomainEventType::ProfileCreated,
+    updated: DomainEventType::ProfileUpdated,
+    deleted: DomainEventType::ProfileDeleted,
+};
+
+const FARM: Table = Table {
+    name: "Farm",
+    history: "FarmHistory",
+    key: "farmId",
+    farmer: "farmerId",
+    fields: FARM_FIELDS,
+    created: DomainEventType::FarmCreated,
+    updated: DomainEventType::FarmUpdated,
+    deleted: DomainEventType::FarmDeleted,
+};
+
+fn table(entity_type: DomainEntityType) -> &'static Table {
+    match entity_type {
+        DomainEntityType::User => &USER,
+        DomainEntityType::Profile => &PROFILE,
+        DomainEntityType::Farm => &FARM,
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Version {
+    version: i32,
+    operation: String,
+    #[sqlx(rename = "validFrom")]
+    valid_from: DateTime<Utc>,
+    data: SqlxJson<Value>,
+}
+
+impl Version {
+    fn deleted(&self) -> bool {
+        self.operation == "DELETE"
+    }
+
+    fn value(&self, name: &str) -> Value {
+        self.data.get(name).cloned().unwrap_or(Value::Null)
+    }
+
+    fn farmer_id(&self, table: &Table) -> Option<Uuid> {
+        self.data.get(table.farmer)?.as_str()?.parse().ok()
+    }
+}
+
+// Why a change was not applied; database errors other than bad data fail the whole sync
+enum ChangeError {
+    Rejected(String),
+    Sql(SqlxError),
+}
+
+impl From<SqlxError> for ChangeError {
+    fn from(e: SqlxError) -> Self {
+        ChangeError::Sql(e)
+    }
+}
+
+fn rejected<T>(msg: impl Into<String>) -> Result<T, ChangeError> {
+    Err(ChangeError::Rejected(msg.into()))
+}
+
+// JSON numbers from the app and from Postgres may differ only in form, e.g. 2 and 2.00
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| same(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/agents/{agent_id}/sync")
+                    .app_data(web::Data::new(SyncConfig::from_env()))
+                    .app_data(web::Data::new(PhoneConfig::from_env()))
+                    .route("", web::post().to(sync))
+    );
+}
+
+async fn latest_version(tx: &mut Transaction<'_, Postgres>, table: &Table, id: Uuid) -> Result<Option<Version>, SqlxError> {
+    sqlx::query_as::<_, Version>(&format!(
+        r#"SELECT version, operation, "validFrom", data FROM "{}" WHERE "{}" = $1 ORDER BY version DESC LIMIT 1"#,
+        table.history, table.key,
+    ))
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await
+}
+
+async fn versions_since(tx: &mut Transaction<'_, Postgres>, table: &Table, id: Uuid, base_version: i32) -> Result<Vec<Version>, SqlxError> {
+    sqlx::query_as::<_, Version>(&format!(
+        r#"SELECT version, operation, "validFrom", data FROM "{}" WHERE "{}" = $1 AND version >= $2 ORDER BY version"#,
+        table.history, table.key,
+    ))
+        .bind(id)
+        .bind(base_version)
+        .fetch_all(&mut **tx)
+        .await
+}
+
+async fn check_assigned(tx: &mut Transaction<'_, Postgres>, agent_id: Uuid, farmer_id: Option<Uuid>) -> Result<(), ChangeError> {
+    let assigned: bool = sqlx::query_scalar(
+        r#"SELECT EXISTS (SELECT 1 FROM "AgentAssignment" WHERE "agentId" = $1 AND "farmerId" = $2 AND "endedOn" IS NULL)"#,
+    )
+        .bind(agent_id)
+        .bind(farmer_id)
+        .fetch_one(&mut **tx)
+        .await?;
+    if !assigned {
+        return rejected("The farmer is not assigned to this agent");
+    }
+    Ok(())
+}
+
+// Phone numbers are compared and stored in E.164, like profiles written through the API
+fn normalize_fields(table: &Table, fields: &mut Map<String, Value>, phone_config: &PhoneConfig) -> Result<(), ChangeError> {
+    if table.name != PROFILE.name {
+        return Ok(());
+    }
+    if let Some(Value::String(raw)) = fields.get("phoneNumber") {
+        let phone = phone::normalize(raw, phone_config.default_region).map_err(ChangeError::Rejected)?;
+        fields.insert("phoneNumber".to_string(), Value::String(phone.e164));
+    }
+    Ok(())
+}
+
+/**
+ * Writes `fields` to a new or existing row, converting the JSON values with the table's own
+ * row type so they are checked like any other write
+ **/
+async fn write(tx: &mut Transaction<'_, Postgres>, table: &Table, id: Uuid, fields: &Map<String, Value>, phone_config: &PhoneConfig, insert: bool) -> Result<(), ChangeError> {
+    let mut fields = fields.clone();
+    if table.name == PROFILE.name {
+        match fields.get("phoneNumber") {
+            Some(Value::String(e164)) => {
+                let phone = phone::normalize(e164, phone_config.default_region).map_err(ChangeError::Rejected)?;
+                let taken: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Profile" WHERE "phoneNumber" = $1 AND id <> $2)"#)
+                    .bind(&phone.e164)
+                    .bind(id)
+                    .fetch_one(&mut **tx)
+                    .await?;
+                if taken {
+                    return rejected(format!("Phone number {} is already registered to another profile", phone.e164));
+                }
+                fields.insert("phoneCarrier".to_string(), phone.carrier.map(Value::String).unwrap_or(Value::Null));
+                fields.insert("phoneLineType".to_string(), serde_json::to_value(phone.line_type).unwrap_or_default());
+            }
+            Some(_) => {
+                fields.insert("phoneCarrier".to_string(), Value::Null);
+                fields.insert("phoneLineType".to_string(), Value::Null);
+            }
+            None => {}
+        }
+    }
+
+    // Names come from the field lists above, never from the request
+    let columns = fields.keys().map(|name| format!(r#""{}""#, name)).collect::<Vec<_>>();
+    let values = fields.keys().map(|name| format!(r#"r."{}""#, name)).collect::<Vec<_>>();
+    let sql = if insert {
+        format!(
+            r#"INSERT INTO "{table}" (id, {columns}) SELECT $1, {values} FROM jsonb_populate_record(NULL::"{table}", $2) r"#,
+            table = table.name, columns = columns.join(", "), values = values.join(", "),
+        )
+    } else {
+        format!(
+            r#"UPDATE "{table}" SET ({columns}, "updatedAt") = (SELECT {values}, current_timestamp FROM jsonb_populate_record(NULL::"{table}", $2) r) WHERE id = $1"#,
+            table = table.name, columns = columns.join(", "), values = values.join(", "),
+        )
+    };
+    sqlx::query(&sql)
+        .bind(id)
+        .bind(SqlxJson(Value::Object(fields)))
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// When the server last changed a field, among versions after the client's base
+fn server_changed_at(versions: &[Version], name: &str) -> DateTime<Utc> {
+    versions.windows(2)
+        .rev()
+        .find(|pair| !same(&pair[0].value(name), &pair[1].value(name)))
+        .map(|pair| pair[1].valid_from)
+        .unwrap_or_else(|| versions[versions.len() - 1].valid_from)
+}
+
+/**
+ * Three-way merge of the client's fields into the current version, against the version the
+ * client edited (`versions[0]`). A field only one side changed takes that side's value; a
+ * field both changed differently is settled by its rule and reported.
+ **/
+fn merge(table: &Table, change: &SyncChange, versions: &[Version], conflicts: &mut Vec<SyncConflict>) -> Map<String, Value> {
+    let base = &versions[0];
+    let current = &versions[versions.len() - 1];
+    let mut merged = Map::new();
+    for field in table.fields.iter().filter(|field| !field.create_only) {
+        let Some(client_value) = change.fields.get(field.name) else { continue };
+        let server_value = current.value(field.name);
+        let base_value = base.value(field.name);
+        if same(client_value, &server_value) || same(client_value, &base_value) {
+            continue;
+        }
+        if same(&server_value, &base_value) {
+            merged.insert(field.name.to_string(), client_value.clone());
+            continue;
+        }
+
+        let winner = match field.rule {
+            ConflictRule::ClientWins => ConflictSide::Client,
+            ConflictRule::LatestWins => match change.changed_at {
+                Some(changed_at) if changed_at > server_changed_at(versions, field.name) => ConflictSide::Client,
+                _ => ConflictSide::Server,
+            },
+            _ => ConflictSide::Server,
+        };
+        if winner == ConflictSide::Client {
+            merged.insert(field.name.to_string(), client_value.clone());
+        }
+        conflicts.push(SyncConflict {
+            entity_type: change.entity_type,
+            id: change.id,
+            field: Some(field.name.to_string()),
+            base_value,
+            client_value: client_value.clone(),
+            server_value,
+            rule: field.rule,
+            winner,
+        });
+    }
+    merged
+}
+
+async fn create(tx: &mut Transaction<'_, Postgres>, agent_id: Uuid, table: &Table, change: &SyncChange, phone_config: &PhoneConfig) -> Result<(SyncResultStatus, Option<i32>), ChangeError> {
+    if let Some(missing) = table.fields.iter().find(|field| field.required && !change.fields.contains_key(field.name)) {
+        return rejected(format!("{} is required", missing.name));
+    }
+    if table.name == USER.name {
+        if change.id == agent_id {
+            return rejected("An agent cannot create themselves");
+        }
+    } else {
+        let farmer_id = change.fields.get(table.farmer)
+            .and_then(Value::as_str)
+            .and_then(|farmer_id| farmer_id.parse().ok());
+        if farmer_id.is_none() {
+            return rejected(format!("{} must be a farmer id", table.farmer));
+        }
+        check_assigned(tx, agent_id, farmer_id).await?;
+    }
+
+    write(tx, table, change.id, &change.fields, phone_config, true).await?;
+    // Farmers registered in the field join the portfolio of the agent who registered them
+    if table.name == USER.name {
+        sqlx::query(r#"INSERT INTO "AgentAssignment" ("agentId", "farmerId", "startedOn") VALUES ($1, $2, current_date)"#)
+            .bind(agent_id)
+            .bind(change.id)
+            .execute(&mut **tx)
+            .await?;
+    }
+    outbox::record_current(tx, table.created, change.id).await?;
+    let version = latest_version(tx, table, change.id).await?.map(|version| version.version);
+    Ok((SyncResultStatus::Applied, version))
+}
+
+async fn update(tx: &mut Transaction<'_, Postgres>, agent_id: Uuid, table: &Table, change: &SyncChange, latest: Option<Version>, phone_config: &PhoneConfig, conflicts: &mut Vec<SyncConflict>) -> Result<(SyncResultStatus, Option<i32>), ChangeError> {
+    let Some(base_version) = change.base_version else {
+        return rejected("baseVersion is required to update");
+    };
+    let Some(latest) = latest else {
+        return rejected(format!("{} not found", table.name));
+    };
+    check_assigned(tx, agent_id, latest.farmer_id(table)).await?;
+    if base_version < 1 || base_version > latest.version {
+        return rejected(format!("baseVersion must be between 1 and {}", latest.version));
+    }
+    if latest.deleted() {
+        conflicts.push(SyncConflict {
+            entity_type: change.entity_type,
+            id: change.id,
+            field: None,
+            base_value: Value::Null,
+            client_value: Value::Object(change.fields.clone()),
+            server_value: Value::Null,
+            rule: ConflictRule::DeleteWins,
+            winner: ConflictSide::Server,
+        });
+        return Ok((SyncResultStatus::Conflict, None));
+    }
+    for field in table.fields.iter().filter(|field| field.create_only) {
+        if change.fields.get(field.name).is_some_and(|value| !same(value, &latest.value(field.name))) {
+            return rejected(format!("{} cannot be changed", field.name));
+        }
+    }
+
+    let versions = versions_since(tx, table, change.id, base_version).await?;
+    let conflicts_before = conflicts.len();
+    let merged = merge(table, change, &versions, conflicts);
+    let current_version = versions[versions.len() - 1].version;
+    let mut version = current_version;
+    if !merged.is_empty() {
+        write(tx, table, change.id, &merged, phone_config, false).await?;
+        version = latest_version(tx, table, change.id).await?.map_or(current_version, |latest| latest.version);
+        // Values equal to the stored ones once converted make no new version
+        if version > current_version {
+            outbox::record_current(tx, table.updated, change.id).await?;
+        }
+    }
+
+    let status = if conflicts.len() > conflicts_before {
+        SyncResultStatus::Conflict
+    } else if versions.len() > 1 {
+        SyncResultStatus::Merged
+    } else {
+        SyncResultStatus::Applied
+    };
+    Ok((status, Some(version)))
+}
+
+async fn delete(tx: &mut Transaction<'_, Postgres>, agent_id: Uuid, table: &Table, change: &SyncChange, latest: Option<Version>, conflicts: &mut Vec<SyncConflict>) -> Result<(SyncResultStatus, Option<i32>), ChangeError> {
+    // Deleting twice is not an error, so a retried batch goes through
+    let Some(latest) = latest.filter(|latest| !latest.deleted()) else {
+        return Ok((SyncResultStatus::Applied, None));
+    };
+    check_assigned(tx, agent_id, latest.farmer_id(table)).await?;
+    let Some(base_version) = change.base_version else {
+        return rejected("baseVersion is required to delete");
+    };
+    if base_version < 1 || base_version > latest.version {
+        return rejected(format!("baseVersion must be between 1 and {}", latest.version));
+    }
+    if base_version < latest.version {
+        conflicts.push(SyncConflict {
+            entity_type: change.entity_type,
+            id: change.id,
+            field: None,
+            base_value: Value::Null,
+            client_value: Value::Null,
+            server_value: latest.data.0.clone(),
+            rule: ConflictRule::EditWins,
+            winner: ConflictSide::Server,
+        });
+        return Ok((SyncResultStatus::Conflict, Some(latest.version)));
+    }
+
+    outbox::record_current(tx, table.deleted, change.id).await?;
+    sqlx::query(&format!(r#"DELETE FROM "{}" WHERE id = $1"#, table.name))
+        .bind(change.id)
+        .execute(&mut **tx)
+        .await?;
+    Ok((SyncResultStatus::Applied, None))
+}
+
+async fn apply(tx: &mut Transaction<'_, Postgres>, agent_id: Uuid, change: &SyncChange, phone_config: &PhoneConfig, conflicts: &mut Vec<SyncConflict>) -> Result<(SyncResultStatus, Option<i32>), ChangeError> {
+    let table = table(change.entity_type);
+    if let Some(unknown) = change.fields.keys().find(|name| !table.fields.iter().any(|field| field.name == *name)) {
+        return rejected(format!("{} cannot be synced", unknown));
+    }
+    if let Some(emptied) = table.fields.iter().find(|field| field.required && change.fields.get(field.name).is_some_and(Value::is_null)) {
+        return rejected(format!("{} cannot be empty", emptied.name));
+    }
+    let mut change = change.clone();
+    normalize_fields(table, &mut change.fields, phone_config)?;
+
+    // Holds off other writers until the change is settled
+    sqlx::query(&format!(r#"SELECT id FROM "{}" WHERE id = $1 FOR UPDATE"#, table.name))
+        .bind(change.id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    let latest = latest_version(tx, table, change.id).await?;
+
+    match change.operation {
+        SyncOperation::Create => match latest {
+            None => create(tx, agent_id, table, &change, phone_config).await,
+            Some(latest) if latest.deleted() => rejected(format!("{} was deleted", table.name)),
+            // Sent again after a lost response: apply it as an edit of what it created
+            Some(latest) => {
+                change.base_version = Some(1);
+                update(tx, agent_id, table, &change, Some(latest), phone_config, conflicts).await
+            }
+        },
+        SyncOperation::Update => update(tx, agent_id, table, &change, latest, phone_config, conflicts).await,
+        SyncOperation::Delete => delete(tx, agent_id, table, &change, latest, conflicts).await,
+    }
+}
+
+/**
+ * Latest version of every record of the agent's farmers changed after `since`, or of every
+ * record of theirs on a first sync. Farmers assigned after `since` are sent in full.
+ **/
+async fn changes_since(tx: &mut Transaction<'_, Postgres>, agent_id: Uuid, since: Option<DateTime<Utc>>) -> Result<Vec<SyncedRecord>, SqlxError> {
+    sqlx::query_as::<_, SyncedRecord>(
+        r#"
+        WITH farmers AS (
+            SELECT "farmerId" AS id, "createdAt" AS "assignedAt"
+            FROM "AgentAssignment"
+            WHERE "agentId" = $1 AND "endedOn" IS NULL
+        ), changed AS (
+            (
+                SELECT DISTINCT ON (h."userId") 'USER'::varchar AS "entityType", h."userId" AS id, h.version, h.operation, h."validFrom" AS "changedAt", h.data
+                FROM "UserHistory" h
+                JOIN farmers f ON f.id = h."userId"
+                WHERE $2::timestamptz IS NULL OR h."validFrom" > $2 OR f."assignedAt" > $2
+                ORDER BY h."userId", h.version DESC
+            )
+            UNION ALL
+            (
+                SELECT DISTINCT ON (h."profileId") 'PROFILE'::varchar, h."profileId", h.version, h.operation, h."validFrom", h.data
+                FROM "ProfileHistory" h
+                JOIN farmers f ON f.id = (h.data->>'userId')::uuid
+                WHERE $2::timestamptz IS NULL OR h."validFrom" > $2 OR f."assignedAt" > $2
+                ORDER BY h."profileId", h.version DESC
+            )
+            UNION ALL
+            (
+                SELECT DISTINCT ON (h."farmId") 'FARM'::varchar, h."farmId", h.version, h.operation, h."validFrom", h.data
+                FROM "FarmHistory" h
+                JOIN farmers f ON f.id = (h.data->>'farmerId')::uuid
+                WHERE $2::timestamptz IS NULL OR h."validFrom" > $2 OR f."assignedAt" > $2
+                ORDER BY h."farmId", h.version DESC
+            )
+        )
+        SELECT *
+        FROM changed
+        -- A first sync has nothing to delete
+        WHERE $2::timestamptz IS NOT NULL OR operation <> 'DELETE'
+        ORDER BY "changedAt", id
+        "#,
+    )
+        .bind(agent_id)
+        .bind(since)
+        .fetch_all(&mut **tx)
+        .await
+}
+
+/**
+ * Sync an Agent's Device
+ * Applies the changes the app made offline, in order, and returns what changed on the server
+ * since the last sync. Each change is applied or rejected on its own. Updates made to an
+ * older version are merged field by field; fields edited on both sides are settled by fixed
+ * rules and listed in `conflicts`.
+ **/
+async fn sync(pool: web::Data<PgPool>, config: web::Data<SyncConfig>, phone_config: web::Data<PhoneConfig>, agent_id: web::Path<Uuid>, request: Json<SyncRequest>) -> Result<HttpResponse, AppError> {
+    let agent_id = agent_id.into_inner();
+    let request = request.into_inner();
+    if request.changes.len() > config.max_changes {
+        return Err(AppError::GenericError(format!("A sync can carry at most {} changes", config.max_changes)));
+    }
+
+    let mut tx = pool.begin().await?;
+    let agent_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+        .bind(agent_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !agent_exists {
+        return Err(AppError::NotFound("Agent".to_string()));
+    }
+
+    let mut results = Vec::with_capacity(request.changes.len());
+    let mut conflicts = Vec::new();
+    for change in &request.changes {
+        let mut savepoint = (&mut tx).begin().await?;
+        let mut change_conflicts = Vec::new();
+        let outcome = apply(&mut savepoint, agent_id, change, phone_config.get_ref(), &mut change_conflicts).await;
+        let (status, version, error) = match outcome {
+            Ok((status, version)) => {
+                savepoint.commit().await?;
+                conflicts.append(&mut change_conflicts);
+                (status, version, None)
+            }
+            Err(ChangeError::Rejected(reason)) => {
+                savepoint.rollback().await?;
+                (SyncResultStatus::Rejected, None, Some(reason))
+            }
+            // Values the row type or constraints refuse
+            Err(ChangeError::Sql(SqlxError::Database(e))) => {
+                savepoint.rollback().await?;
+                warn!("Sync change to {:?} {} refused: {}", change.entity_type, change.id, e.message());
+                (SyncResultStatus::Rejected, None, Some(e.message().to_string()))
+            }
+            Err(ChangeError::Sql(e)) => return Err(e.into()),
+        };
+        results.push(SyncResult {
+            entity_type: change.entity_type,
+            id: change.id,
+            status,
+            version,
+            error,
+        });
+    }
+    tx.commit().await?;
+
+    let mut tx = pool.begin().await?;
+    // The transaction's start, so anything committed after it is read next time
+    let sync_token: DateTime<Utc> = sqlx::query_scalar("SELECT current_timestamp")
+        .fetch_one(&mut *tx)
+        .await?;
+    let overlap = chrono::Duration::from_std(config.token_overlap).unwrap_or_else(|_| chrono::Duration::zero());
+    let changes = changes_since(&mut tx, agent_id, request.sync_token.map(|token| token - overlap)).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(SyncResponse { sync_token, results, conflicts, changes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2023-11-01T08:00:00Z").unwrap().with_timezone(&Utc) + chrono::Duration::minutes(minutes)
+    }
+
+    fn version(version: i32, minutes: i64, data: Value) -> Version {
+        Version { version, operation: "UPDATE".to_string(), valid_from: at(minutes), data: SqlxJson(data) }
+    }
+
+    fn change(fields: Value, changed_at: Option<DateTime<Utc>>) -> SyncChange {
+        SyncChange {
+            entity_type: DomainEntityType::Farm,
+            id: Uuid::nil(),
+            operation: SyncOperation::Update,
+            base_version: Some(1),
+            fields: fields.as_object().unwrap().clone(),
+            changed_at,
+        }
+    }
+
+    fn farm(farm_name: &str, acreage: Value, available_portion: Value) -> Value {
+        json!({ "farm_name": farm_name, "acreage": acreage, "available_portion": available_portion, "farmerId": Uuid::nil() })
+    }
+
+    #[test]
+    fn a_field_changed_on_one_side_takes_that_sides_value() {
+        let versions = [version(1, 0, farm("Zaria 1", json!(2), json!(1))), version(2, 10, farm("Zaria 1", json!(3), json!(1)))];
+        let mut conflicts = Vec::new();
+        // farm_name changed only by the client; acreage only on the server, the client sends its old value
+        let merged = merge(&FARM, &change(json!({ "farm_name": "Zaria North", "acreage": 2 }), Some(at(5))), &versions, &mut conflicts);
+        assert_eq!(Value::Object(merged), json!({ "farm_name": "Zaria North" }));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn the_same_edit_on_both_sides_is_not_a_conflict() {
+        let versions = [version(1, 0, farm("Zaria 1", json!(2), json!(1))), version(2, 10, farm("Zaria North", json!(2), json!(1)))];
+        let mut conflicts = Vec::new();
+        let merged = merge(&FARM, &change(json!({ "farm_name": "Zaria North" }), Some(at(5))), &versions, &mut conflicts);
+        assert!(merged.is_empty());
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn client_wins_fields_take_the_clients_value() {
+        let versions = [version(1, 0, farm("Zaria 1", json!(2), json!(1))), version(2, 10, farm("Zaria 1", json!(3), json!(1)))];
+        let mut conflicts = Vec::new();
+        let merged = merge(&FARM, &change(json!({ "acreage": 4 }), None), &versions, &mut conflicts);
+        assert_eq!(Value::Object(merged), json!({ "acreage": 4 }));
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.field.as_deref(), Some("acreage"));
+        assert_eq!((conflict.rule, conflict.winner), (ConflictRule::ClientWins, ConflictSide::Client));
+        assert_eq!((&conflict.base_value, &conflict.client_value, &conflict.server_value), (&json!(2), &json!(4), &json!(3)));
+    }
+
+    #[test]
+    fn server_wins_fields_keep_the_servers_value() {
+        let versions = [version(1, 0, farm("Zaria 1", json!(2), json!(1))), version(2, 10, farm("Zaria 1", json!(2), json!(1.5)))];
+        let mut conflicts = Vec::new();
+        let merged = merge(&FARM, &change(json!({ "available_portion": 0.5 }), Some(at(60))), &versions, &mut conflicts);
+        assert!(merged.is_empty());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].rule, conflicts[0].winner), (ConflictRule::ServerWins, ConflictSide::Server));
+    }
+
+    #[test]
+    fn latest_wins_fields_go_to_the_later_edit_of_that_field() {
+        // The server renamed the farm at 10 and changed only its acreage at 30
+        let versions = [
+            version(1, 0, farm("Zaria 1", json!(2), json!(1))),
+            version(2, 10, farm("Zaria South", json!(2), json!(1))),
+            version(3, 30, farm("Zaria South", json!(3), json!(1))),
+        ];
+        let rename = |changed_at| {
+            let mut conflicts = Vec::new();
+            let merged = merge(&FARM, &change(json!({ "farm_name": "Zaria North" }), changed_at), &versions, &mut conflicts);
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].rule, ConflictRule::LatestWins);
+            (merged.get("farm_name").cloned(), conflicts[0].winner)
+        };
+
+        assert_eq!(rename(Some(at(20))), (Some(json!("Zaria North")), ConflictSide::Client));
+        assert_eq!(rename(Some(at(5))), (None, ConflictSide::Server));
+        // Ties and changes without a time go to the server
+        assert_eq!(rename(Some(at(10))), (None, ConflictSide::Server));
+        assert_eq!(rename(None), (None, ConflictSide::Server));
+    }
+
+    #[test]
+    fn numbers_differing_only_in_form_are_the_same() {
+        let stored: Value = serde_json::from_str("2.00").unwrap();
+        let versions = [version(1, 0, farm("Zaria 1", json!(1), json!(1))), version(2, 10, farm("Zaria 1", stored, json!(1)))];
+        let mut conflicts = Vec::new();
+        let merged = merge(&FARM, &change(json!({ "acreage": 2, "available_portion": 1.0 }), Some(at(5))), &versions, &mut conflicts);
+        assert!(merged.is_empty());
+        assert!(conflicts.is_empty());
+        assert!(same(&json!([1, { "a": 2.0 }]), &json!([1.0, { "a": 2 }])));
+        assert!(!same(&json!(2), &json!("2")));
+    }
+
+    #[test]
+    fn create_only_fields_are_not_merged() {
+        let versions = [version(1, 0, farm("Zaria 1", json!(2), json!(1)))];
+        let mut conflicts = Vec::new();
+        let merged = merge(&FARM, &change(json!({ "farmerId": Uuid::new_v4() }), None), &versions, &mut conflicts);
+        assert!(merged.is_empty());
+        assert!(conflicts.is_empty());
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, Postgres, Transaction};
+use actix_web::http::StatusCode;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+use shared::models::{
+    FarmTitle,
+    FarmTransfer,
+    CreateFarmTransfer,
+    TransferDocument,
+    CreateTransferDocument,
+    TransferReason,
+    OwnerQuery,
+    DomainEventType
+};
+use tracing::error;
+use crate::outbox;
+
+const MAX_DOCUMENTS: usize = 20;
+
+const TITLE_QUERY: &str = r#"
+    SELECT t.id, t."farmId", t."ownerId", u."firstName", u."lastName", t."acquiredOn", t."relinquishedOn", t."transferId"
+    FROM "FarmTitle" t
+    JOIN "User" u ON u.id = t."ownerId"
+"#;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Farm title query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/ownership")
+                    .route("/titles", web::get().to(get_all_titles))
+                    .route("/owner", web::get().to(get_owner))
+                    .route("/transfers", web::get().to(get_all_transfers))
+                    .route("/transfers", web::post().to(create_transfer))
+                    .route("/transfers/{id}/documents", web::post().to(add_documents))
+    );
+}
+
+fn validate_documents(documents: &[CreateTransferDocument]) -> Result<(), AppError> {
+    if documents.len() > MAX_DOCUMENTS {
+        return Err(AppError::GenericError(format!("At most {} documents can be attached", MAX_DOCUMENTS)));
+    }
+    if documents.iter().any(|document| !(document.url.starts_with("https://") || document.url.starts_with("http://"))) {
+        return Err(AppError::GenericError("Document URLs must be http(s) links".to_string()));
+    }
+    Ok(())
+}
+
+async fn insert_documents(tx: &mut Transaction<'_, Postgres>, transfer_id: Uuid, documents: Vec<CreateTransferDocument>) -> Result<(), AppError> {
+    for document in documents {
+        sqlx::query(r#"INSERT INTO "TransferDocument" ("transferId", kind, url, description) VALUES ($1, $2, $3, $4)"#)
+            .bind(transfer_id)
+            .bind(document.kind)
+            .bind(document.url)
+            .bind(document.description)
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn attach_documents(pool: &PgPool, transfers: &mut [FarmTransfer]) -> Result<(), AppError> {
+    let ids = transfers.iter().map(|transfer| transfer.id).collect::<Vec<_>>();
+    let documents = sqlx::query_as::<_, TransferDocument>(
+        r#"SELECT * FROM "TransferDocument" WHERE "transferId" = ANY($1) ORDER BY "createdAt""#,
+    )
+        .bind(&ids)
+        .fetch_all(pool)
+        .await?;
+
+    for document in documents {
+        if let Some(transfer) = transfers.iter_mut().find(|transfer| transfer.id == document.transfer_id) {
+            transfer.documents.push(document);
+        }
+    }
+    Ok(())
+}
+
+async fn fetch_transfer(pool: &PgPool, farm_id: Uuid, id: Uuid) -> Result<FarmTransfer, AppError> {
+    let transfer = sqlx::query_as::<_, FarmTransfer>(r#"SELECT * FROM "FarmTransfer" WHERE id = $1 AND "farmId" = $2"#)
+        .bind(id)
+        .bind(farm_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transfer".to_string()))?;
+
+    let mut transfers = [transfer];
+    attach_documents(pool, &mut transfers).await?;
+    let [transfer] = transfers;
+    Ok(transfer)
+}
+
+/**
+ * Get Title History
+ * Every owner of the farm, oldest first
+ **/
+async fn get_all_titles(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let titles = sqlx::query_as::<_, FarmTitle>(&format!(r#"{} WHERE t."farmId" = $1 ORDER BY t."acquiredOn""#, TITLE_QUERY))
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(titles))
+}
+
+/**
+ * Get Owner on a Date
+ * A transfer's effective date belongs to the new owner
+ **/
+async fn get_owner(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, query: web::Query<OwnerQuery>) -> Result<HttpResponse, AppError> {
+    let date = query.date.unwrap_or_else(|| Utc::now().date_naive());
+    let title = sqlx::query_as::<_, FarmTitle>(
+        &format!(
+            r#"{} WHERE t."farmId" = $1 AND t."acquiredOn" <= $2 AND (t."relinquishedOn" IS NULL OR t."relinquishedOn" > $2)"#,
+            TITLE_QUERY
+        ),
+    )
+        .bind(farm_id.into_inner())
+        .bind(date)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    match title {
+        Some(title) => Ok(HttpResponse::Ok().json(title)),
+        None => Err(AppError::NotFound(format!("Owner on {}", date))),
+    }
+}
+
+async fn get_all_transfers(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let mut transfers = sqlx::query_as::<_, FarmTransfer>(
+        r#"SELECT * FROM "FarmTransfer" WHERE "farmId" = $1 ORDER BY "effectiveOn", "createdAt""#,
+    )
+        .bind(farm_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+    attach_documents(pool.get_ref(), &mut transfers).await?;
+
+    Ok(HttpResponse::Ok().json(transfers))
+}
+
+/**
+ * Transfer Ownership
+ * Closes the current title on the effective date and opens one for the new owner. The farm's
+ * owner and ownership type follow the transfer, and active leases move to the new landlord.
+ **/
+async fn create_transfer(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, transfer: Json<CreateFarmTransfer>) -> Result<HttpResponse, AppError> {
+    let farm_id = farm_id.into_inner();
+    let transfer = transfer.into_inner();
+    let effective_on = transfer.effective_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    if effective_on > Utc::now().date_naive() {
+        return Err(AppError::GenericError("effectiveOn must not be in the future".to_string()));
+    }
+    match (&transfer.price, transfer.reason) {
+        (Some(_), reason) if reason != TransferReason::Sale => {
+            return Err(AppError::GenericError("A price is only recorded for sales".to_string()));
+        }
+        (Some(price), _) if price.amount < Decimal::ZERO => {
+            return Err(AppError::GenericError("Price must not be negative".to_string()));
+        }
+        _ => {}
+    }
+    validate_documents(&transfer.documents)?;
+
+    let mut tx = pool.begin().await?;
+
+    let owner_id: Uuid = sqlx::query_scalar(r#"SELECT "farmerId" FROM "Farm" WHERE id = $1 FOR UPDATE"#)
+        .bind(farm_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+    if owner_id == transfer.to_owner_id {
+        return Err(AppError::GenericError("The farm already belongs to this user".to_string()));
+    }
+    let new_owner_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "User" WHERE id = $1)"#)
+        .bind(transfer.to_owner_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !new_owner_exists {
+        return Err(AppError::NotFound("New owner".to_string()));
+    }
+
+    let current: Option<(Uuid, NaiveDate)> = sqlx::query_as(
+        r#"SELECT id, "acquiredOn" FROM "FarmTitle" WHERE "farmId" = $1 AND "relinquishedOn" IS NULL"#,
+    )
+        .bind(farm_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if let Some((_, acquired_on)) = current {
+        if effective_on <= acquired_on {
+            return Err(AppError::GenericError(format!("effectiveOn must be after the current owner acquired the farm on {}", acquired_on)));
+        }
+    }
+
+    let transfer_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO "FarmTransfer" ("farmId", "fromOwnerId", "toOwnerId", reason, "effectiveOn", price, notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id
+        "#,
+    )
+        .bind(farm_id)
+        .bind(owner_id)
+        .bind(transfer.to_owner_id)
+        .bind(transfer.reason)
+        .bind(effective_on)
+        .bind(transfer.price)
+        .bind(transfer.notes)
+        .fetch_one(&mut *tx)
+        .await?;
+    insert_documents(&mut tx, transfer_id, transfer.documents).await?;
+
+    if let Some((title_id, _)) = current {
+        sqlx::query(r#"UPDATE "FarmTitle" SET "relinquishedOn" = $2 WHERE id = $1"#)
+            .bind(title_id)
+            .bind(effective_on)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query(r#"INSERT INTO "FarmTitle" ("farmId", "ownerId", "acquiredOn", "transferId") VALUES ($1, $2, $3, $4)"#)
+        .bind(farm_id)
+        .bind(transfer.to_owner_id)
+        .bind(effective_on)
+        .bind(transfer_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(r#"UPDATE "Farm" SET "farmerId" = $2, ownership = $3, "updatedAt" = current_timestamp WHERE id = $1"#)
+        .bind(farm_id)
+        .bind(transfer.to_owner_id)
+        .bind(transfer.reason.ownership())
+        .execute(&mut *tx)
+        .await?;
+    outbox::record_current(&mut tx, DomainEventType::FarmUpdated, farm_id).await?;
+    sqlx::query(
+        r#"UPDATE "Lease" SET "landlordId" = $2, "updatedAt" = current_timestamp WHERE "farmId" = $1 AND status = 'ACTIVE'"#,
+    )
+        .bind(farm_id)
+        .bind(transfer.to_owner_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    let transfer = fetch_transfer(pool.get_ref(), farm_id, transfer_id).await?;
+    Ok(HttpResponse::Created().json(transfer))
+}
+
+async fn add_documents(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, documents: Json<Vec<CreateTransferDocument>>) -> Result<HttpResponse, AppError> {
+    let (farm_id, id) = path.into_inner();
+    let documents = documents.into_inner();
+
+    if documents.is_empty() {
+        return Err(AppError::GenericError("No documents given".to_string()));
+    }
+    validate_documents(&documents)?;
+
+    let mut tx = pool.begin().await?;
+    let attached: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT (SELECT COUNT(*) FROM "TransferDocument" d WHERE d."transferId" = t.id)
+        FROM "FarmTransfer" t
+        WHERE t.id = $1 AND t."farmId" = $2
+        FOR UPDATE
+        "#,
+    )
+        .bind(id)
+        .bind(farm_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let attached = attached.ok_or_else(|| AppError::NotFound("Transfer".to_string()))?;
+    if attached as usize + documents.len() > MAX_DOCUMENTS {
+        return Err(AppError::GenericError(format!("At most {} documents can be attached", MAX_DOCUMENTS)));
+    }
+
+    insert_documents(&mut tx, id, documents).await?;
+    tx.commit().await?;
+
+    let transfer = fetch_transfer(pool.get_ref(), farm_id, id).await?;
+    Ok(HttpResponse::Created().json(transfer))
+}
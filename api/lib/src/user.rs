@@ -1,18 +1,20 @@
 use std::fmt::{Display, Formatter};
 use actix_web::{web::{self, ServiceConfig}, HttpResponse,web::Json};
-use actix_web::error::BlockingError;
 use sqlx::{Error as SqlxError, postgres::PgPool};
 use actix_web::http::StatusCode;
 use actix_web::web::Query;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use shared::models::{User, Pagination, CreateUser, UpdateUser};
+use shared::models::{User, Pagination, CreateUser, UpdateUser, DomainEventType};
+use chrono::{DateTime, Utc};
 use tracing::error;
+use crate::{history, outbox};
 
 #[derive(Debug)]
 pub enum AppError {
     SqlError(SqlxError),
     GenericError(String),
+    NotFound(String),
     OtherError,
 }
 
@@ -22,6 +24,7 @@ impl Display for AppError {
         match self {
             AppError::SqlError(e) => write!(f, "Database error: {}", e),
             AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
             AppError::OtherError => write!(f, "An unknown error occurred"),
         }
     }
@@ -31,6 +34,7 @@ impl actix_web::error::ResponseError for AppError {
         match *self {
             AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::OtherError => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -41,6 +45,13 @@ impl actix_web::error::ResponseError for AppError {
     }
 }
 
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("User query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
 pub fn service(cfg: &mut ServiceConfig) {
     cfg.service(web::scope("/v0.1/users")
                     .route("", web::get().to(get_all_users))
@@ -117,6 +128,8 @@ async fn get_all_users(pool: web::Data<PgPool>, pagination: Query<Pagination>) -
 pub struct UserFilter {
     id: Option<Uuid>,
     email: Option<String>,
+    // Return the user as they were at this time
+    as_of: Option<DateTime<Utc>>,
 }
 
 async fn get_user(pool: web::Data<PgPool>, filter: Query<UserFilter>) -> Result<HttpResponse, AppError> {
@@ -128,29 +141,37 @@ async fn get_user(pool: web::Data<PgPool>, filter: Query<UserFilter>) -> Result<
 
     tracing::info!("Getting user by {}: {}", filter_field, filter_value);
 
+    let source = match filter.as_of {
+        Some(_) => history::as_of_source("User", 2, None),
+        None => r#""User""#.to_string(),
+    };
     let query = if filter_field == "id" {
         format!(
             r#"
-            SELECT * FROM "User"
+            SELECT * FROM {}
             WHERE id = $1::uuid
-            "#
+            "#,
+            source
         )
     } else {
         format!(
             r#"
-            SELECT * FROM "User"
+            SELECT * FROM {}
             WHERE email = $1
-            "#
+            "#,
+            source
         )
     };
 
-    let result = sqlx::query_as::<_, User>(&query)
-        .bind(filter_value)
-        .fetch_one(pool.get_ref())
-        .await;
+    let mut user_query = sqlx::query_as::<_, User>(&query).bind(filter_value);
+    if let Some(as_of) = filter.as_of {
+        user_query = user_query.bind(as_of);
+    }
+    let result = user_query.fetch_optional(pool.get_ref()).await;
 
     match result {
-        Ok(user) => Ok(HttpResponse::Ok().json(user)),
+        Ok(Some(user)) => Ok(HttpResponse::Ok().json(user)),
+        Ok(None) => Err(AppError::NotFound("User".to_string())),
         Err(e) => {
             error!("Error fetching user: {:?}", e);
             Err(AppError::SqlError(e))
@@ -159,7 +180,8 @@ async fn get_user(pool: web::Data<PgPool>, filter: Query<UserFilter>) -> Result<
 }
 
 
-async fn create_user(pool: web::Data<PgPool>, user: Json<CreateUser>) -> Result<HttpResponse, BlockingError> {
+async fn create_user(pool: web::Data<PgPool>, user: Json<CreateUser>) -> Result<HttpResponse, AppError> {
+    let mut tx = pool.begin().await?;
     let result = sqlx::query_as::<_, User>(r#"
     INSERT INTO "User"
     (
@@ -174,11 +196,15 @@ async fn create_user(pool: web::Data<PgPool>, user: Json<CreateUser>) -> Result<
         .bind(&user.last_name)
         .bind(&user.email)
         .bind(&user.middle_name)
-        .fetch_one(pool.get_ref())
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
-        Ok(user) => Ok(HttpResponse::Created().json(user)),
+        Ok(user) => {
+            outbox::record(&mut tx, DomainEventType::UserCreated, user.id, &user).await?;
+            tx.commit().await?;
+            Ok(HttpResponse::Created().json(user))
+        }
         Err(e) => {
             error!("Error creating user: {:?}", e);
             Ok(HttpResponse::InternalServerError().json("Internal server error"))
@@ -186,18 +212,23 @@ async fn create_user(pool: web::Data<PgPool>, user: Json<CreateUser>) -> Result<
     }
 }
 
-async fn update_user(pool: web::Data<PgPool>, id: web::Path<Uuid>, user: Json<UpdateUser>) -> Result<HttpResponse, BlockingError> {
-    let result = sqlx::query_as::<_, User>("UPDATE \"User\" SET firstName = $2, lastName = $3, email = $4, middleName = $5 WHERE id = $1 RETURNING *")
+async fn update_user(pool: web::Data<PgPool>, id: web::Path<Uuid>, user: Json<UpdateUser>) -> Result<HttpResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query_as::<_, User>(r#"UPDATE "User" SET "firstName" = $2, "lastName" = $3, email = $4, "middleName" = $5, "updatedAt" = current_timestamp WHERE id = $1 RETURNING *"#)
         .bind(id.into_inner())
         .bind(&user.first_name)
         .bind(&user.last_name)
         .bind(&user.email)
         .bind(&user.middle_name)
-        .fetch_one(pool.get_ref())
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
-        Ok(user) => Ok(HttpResponse::Ok().json(user)),
+        Ok(user) => {
+            outbox::record(&mut tx, DomainEventType::UserUpdated, user.id, &user).await?;
+            tx.commit().await?;
+            Ok(HttpResponse::Ok().json(user))
+        }
         Err(e) => {
             error!("Error updating user: {:?}", e);
             Ok(HttpResponse::InternalServerError().json("Internal server error"))
@@ -207,13 +238,20 @@ async fn update_user(pool: web::Data<PgPool>, id: web::Path<Uuid>, user: Json<Up
 
 
 async fn delete_user(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
-    let result = sqlx::query("DELETE FROM \"User\" WHERE id = $1")
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query_as::<_, User>(r#"DELETE FROM "User" WHERE id = $1 RETURNING *"#)
         .bind(id.into_inner())
-        .execute(pool.get_ref())
+        .fetch_optional(&mut *tx)
         .await;
 
     match result {
-        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Ok(deleted) => {
+            if let Some(user) = deleted {
+                outbox::record(&mut tx, DomainEventType::UserDeleted, user.id, &user).await?;
+            }
+            tx.commit().await?;
+            Ok(HttpResponse::NoContent().finish())
+        }
         Err(e) => {
             error!("Error deleting user: {:?}", e);
             Err(AppError::SqlError(e))
@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use actix_web::{web::{self, ServiceConfig}, HttpRequest, HttpResponse};
+use sqlx::{Error as SqlxError, postgres::PgPool};
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDate};
+use uuid::Uuid;
+use shared::geo::distance_metres;
+use shared::models::{
+    WeatherStation,
+    WeatherImport,
+    WeatherImportQuery,
+    FarmWeatherQuery,
+    FarmWeather,
+    NearbyStation,
+    WeatherDay
+};
+use tracing::error;
+use crate::weather_import::{self, ImportFormat};
+
+const MAX_UPLOAD_BYTES: usize = 64 * 1024 * 1024;
+const INSERT_CHUNK_SIZE: usize = 5_000;
+const DEFAULT_BASE_TEMP_C: f64 = 10.0;
+const DEFAULT_STATION_COUNT: usize = 3;
+const DEFAULT_RADIUS_KM: f64 = 50.0;
+const MAX_HISTORY_DAYS: i64 = 366 * 5;
+
+// Daily aggregate for one station
+#[derive(Debug, sqlx::FromRow)]
+struct StationDay {
+    #[sqlx(rename = "stationId")]
+    station_id: Uuid,
+    day: NaiveDate,
+    rainfall_mm: Option<f64>,
+    temp_min_c: Option<f64>,
+    temp_max_c: Option<f64>,
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Weather query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/farms/{farm_id}/weather")
+                    .route("", web::get().to(get_farm_weather))
+    );
+    cfg.service(web::scope("/v0.1/weather")
+                    .app_data(web::PayloadConfig::new(MAX_UPLOAD_BYTES))
+                    .route("/stations", web::get().to(get_all_stations))
+                    .route("/imports", web::get().to(get_all_imports))
+                    .route("/imports", web::post().to(create_import))
+    );
+}
+
+/**
+ * Inverse distance weighting over `(distance in metres, value)` pairs.
+ * A station practically on the farm is used as-is.
+ **/
+pub fn interpolate(samples: &[(f64, f64)]) -> Option<f64> {
+    if let Some((_, value)) = samples.iter().find(|(distance, _)| *distance < 1.0) {
+        return Some(*value);
+    }
+    let (weighted, weights) = samples.iter().fold((0.0, 0.0), |(sum, weights), (distance, value)| {
+        let weight = 1.0 / (distance * distance);
+        (sum + weight * value, weights + weight)
+    });
+    (weights > 0.0).then(|| weighted / weights)
+}
+
+async fn get_all_stations(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let stations = sqlx::query_as::<_, WeatherStation>(r#"SELECT * FROM "WeatherStation" ORDER BY code"#)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(stations))
+}
+
+async fn get_all_imports(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let imports = sqlx::query_as::<_, WeatherImport>(r#"SELECT * FROM "WeatherImport" ORDER BY "createdAt" DESC"#)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(imports))
+}
+
+/**
+ * Upload a CSV or NetCDF-3 file as the raw request body.
+ * Observations already stored for a station and timestamp are overwritten.
+ **/
+async fn create_import(req: HttpRequest, pool: web::Data<PgPool>, query: web::Query<WeatherImportQuery>, body: web::Bytes) -> Result<HttpResponse, AppError> {
+    let query = query.into_inner();
+    let content_type = req.headers().get("Content-Type").and_then(|v| v.to_str().ok());
+    let format = ImportFormat::detect(query.format.as_deref(), content_type, &body)
+        .ok_or_else(|| AppError::GenericError("format must be csv or netcdf".to_string()))?;
+
+    let parsed = weather_import::parse(format, &body).map_err(AppError::GenericError)?;
+    let source = if parsed.gridded { "grid" } else { "station" };
+
+    // Last position reported for a station wins
+    let mut stations: HashMap<&str, (f64, f64)> = HashMap::new();
+    for record in &parsed.records {
+        stations.insert(&record.station, (record.latitude, record.longitude));
+    }
+    let codes: Vec<&str> = stations.keys().copied().collect();
+    let latitudes: Vec<f64> = codes.iter().map(|c| stations[c].0).collect();
+    let longitudes: Vec<f64> = codes.iter().map(|c| stations[c].1).collect();
+
+    let mut tx = pool.begin().await?;
+
+    let station_ids: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        INSERT INTO "WeatherStation" (code, source, latitude, longitude)
+        SELECT code, $4, latitude, longitude
+        FROM UNNEST($1::varchar[], $2::float8[], $3::float8[]) AS s(code, latitude, longitude)
+        ON CONFLICT (code) DO UPDATE
+        SET latitude = EXCLUDED.latitude,
+            longitude = EXCLUDED.longitude,
+            "updatedAt" = current_timestamp
+        RETURNING id, code
+        "#,
+    )
+        .bind(&codes)
+        .bind(&latitudes)
+        .bind(&longitudes)
+        .bind(source)
+        .fetch_all(&mut *tx)
+        .await?;
+    let station_ids: HashMap<String, Uuid> = station_ids.into_iter().map(|(id, code)| (code, id)).collect();
+
+    for chunk in parsed.records.chunks(INSERT_CHUNK_SIZE) {
+        let ids: Vec<Uuid> = chunk.iter().map(|r| station_ids[&r.station]).collect();
+        let observed_at: Vec<_> = chunk.iter().map(|r| r.observed_at).collect();
+        let rainfall: Vec<Option<f64>> = chunk.iter().map(|r| r.rainfall_mm.map(|mm| mm.max(0.0))).collect();
+        let temp_min: Vec<Option<f64>> = chunk.iter().map(|r| r.temp_min_c).collect();
+        let temp_max: Vec<Option<f64>> = chunk.iter().map(|r| r.temp_max_c).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO "WeatherObservation" ("stationId", "observedAt", "rainfallMm", "tempMinC", "tempMaxC")
+            SELECT DISTINCT ON (station_id, observed_at) *
+            FROM UNNEST($1::uuid[], $2::timestamptz[], $3::float8[], $4::float8[], $5::float8[])
+                AS o(station_id, observed_at, rainfall, temp_min, temp_max)
+            ON CONFLICT ("stationId", "observedAt") DO UPDATE
+            SET "rainfallMm" = EXCLUDED."rainfallMm",
+                "tempMinC" = EXCLUDED."tempMinC",
+                "tempMaxC" = EXCLUDED."tempMaxC"
+            "#,
+        )
+            .bind(&ids)
+            .bind(&observed_at)
+            .bind(&rainfall)
+            .bind(&temp_min)
+            .bind(&temp_max)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let import = sqlx::query_as::<_, WeatherImport>(
+        r#"
+        INSERT INTO "WeatherImport" ("fileName", format, stations, "rowsImported", "rowsSkipped")
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+        .bind(query.file_name)
+        .bind(format.as_str())
+        .bind(station_ids.len() as i32)
+        .bind(parsed.records.len() as i32)
+        .bind(parsed.skipped as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(import))
+}
+
+/**
+ * Daily weather at the farm, interpolated from the nearest stations,
+ * with growing degree days and running rainfall totals
+ **/
+async fn get_farm_weather(pool: web::Data<PgPool>, farm_id: web::Path<Uuid>, query: web::Query<FarmWeatherQuery>) -> Result<HttpResponse, AppError> {
+    let weather = farm_weather(pool.get_ref(), farm_id.into_inner(), &query).await?;
+    Ok(HttpResponse::Ok().json(weather))
+}
+
+pub async fn farm_weather(pool: &PgPool, farm_id: Uuid, query: &FarmWeatherQuery) -> Result<FarmWeather, AppError> {
+    let base_temp = query.base_temp.unwrap_or(DEFAULT_BASE_TEMP_C);
+    let station_count = query.stations.unwrap_or(DEFAULT_STATION_COUNT).clamp(1, 10);
+    let radius_km = query.radius_km.unwrap_or(DEFAULT_RADIUS_KM);
+
+    if query.to < query.from {
+        return Err(AppError::GenericError("to must not be before from".to_string()));
+    }
+    if (query.to - query.from).num_days() > MAX_HISTORY_DAYS {
+        return Err(AppError::GenericError(format!("at most {} days can be requested", MAX_HISTORY_DAYS)));
+    }
+    if !(radius_km > 0.0 && radius_km <= 500.0) {
+        return Err(AppError::GenericError("radiusKm must be between 0 and 500".to_string()));
+    }
+
+    let location: Option<(f64, f64)> = sqlx::query_as(r#"SELECT latitude, longitude FROM "Farm" WHERE id = $1"#)
+        .bind(farm_id)
+        .fetch_optional(pool)
+        .await?;
+    let (latitude, longitude) = location.ok_or_else(|| AppError::NotFound("Farm".to_string()))?;
+    let farm = [longitude, latitude];
+
+    // A degree of latitude is ~111 km; widen the longitude window away from the equator
+    let lat_window = radius_km / 111.0;
+    let lon_window = lat_window / latitude.to_radians().cos().max(0.01);
+    let candidates = sqlx::query_as::<_, WeatherStation>(
+        r#"
+        SELECT *
+        FROM "WeatherStation"
+        WHERE latitude BETWEEN $1 - $3 AND $1 + $3
+          AND longitude BETWEEN $2 - $4 AND $2 + $4
+        "#,
+    )
+        .bind(latitude)
+        .bind(longitude)
+        .bind(lat_window)
+        .bind(lon_window)
+        .fetch_all(pool)
+        .await?;
+
+    let mut nearby: Vec<(WeatherStation, f64)> = candidates.into_iter()
+        .map(|station| {
+            let distance = distance_metres(farm, [station.longitude, station.latitude]);
+            (station, distance)
+        })
+        .filter(|(_, distance)| *distance <= radius_km * 1000.0)
+        .collect();
+    nearby.sort_by(|a, b| a.1.total_cmp(&b.1));
+    nearby.truncate(station_count);
+
+    let station_ids: Vec<Uuid> = nearby.iter().map(|(station, _)| station.id).collect();
+    let distances: HashMap<Uuid, f64> = nearby.iter().map(|(station, distance)| (station.id, *distance)).collect();
+
+    let daily = sqlx::query_as::<_, StationDay>(
+        r#"
+        SELECT "stationId",
+               ("observedAt" AT TIME ZONE 'UTC')::date AS day,
+               SUM("rainfallMm") AS rainfall_mm,
+               MIN("tempMinC") AS temp_min_c,
+               MAX("tempMaxC") AS temp_max_c
+        FROM "WeatherObservation"
+        WHERE "stationId" = ANY($1)
+          AND "observedAt" >= $2::date
+          AND "observedAt" < $3::date + 1
+        GROUP BY 1, 2
+        "#,
+    )
+        .bind(&station_ids)
+        .bind(query.from)
+        .bind(query.to)
+        .fetch_all(pool)
+        .await?;
+
+    let mut by_day: BTreeMap<NaiveDate, Vec<StationDay>> = BTreeMap::new();
+    for station_day in daily {
+        by_day.entry(station_day.day).or_default().push(station_day);
+    }
+
+    let mut days = Vec::new();
+    let (mut cumulative_rain, mut cumulative_gdd) = (0.0, 0.0);
+    let mut date = query.from;
+    while date <= query.to {
+        let samples = by_day.get(&date).map(Vec::as_slice).unwrap_or(&[]);
+        let pick = |f: fn(&StationDay) -> Option<f64>| {
+            let values: Vec<(f64, f64)> = samples.iter()
+                .filter_map(|s| f(s).map(|v| (distances[&s.station_id], v)))
+                .collect();
+            interpolate(&values)
+        };
+        let rainfall_mm = pick(|s| s.rainfall_mm);
+        let temp_min_c = pick(|s| s.temp_min_c);
+        let temp_max_c = pick(|s| s.temp_max_c);
+        let growing_degree_days = match (temp_min_c, temp_max_c) {
+            (Some(min), Some(max)) => Some(((min + max) / 2.0 - base_temp).max(0.0)),
+            _ => None,
+        };
+        cumulative_rain += rainfall_mm.unwrap_or(0.0);
+        cumulative_gdd += growing_degree_days.unwrap_or(0.0);
+
+        days.push(WeatherDay {
+            date,
+            rainfall_mm,
+            temp_min_c,
+            temp_max_c,
+            growing_degree_days,
+            cumulative_rainfall_mm: cumulative_rain,
+            cumulative_growing_degree_days: cumulative_gdd,
+        });
+        date += Duration::days(1);
+    }
+
+    Ok(FarmWeather {
+        farm_id,
+        base_temp_c: base_temp,
+        stations: nearby.into_iter()
+            .map(|(station, distance)| NearbyStation { id: station.id, code: station.code, distance_km: distance / 1000.0 })
+            .collect(),
+        days,
+    })
+}
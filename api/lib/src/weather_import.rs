@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use netcdf3::{DataVector, FileReader};
+
+// NetCDF-3 classic and 64-bit offset files start with "CDF\x01" / "CDF\x02"
+const NETCDF_MAGIC: &[u8] = b"CDF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    NetCdf,
+}
+
+impl ImportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "csv",
+            ImportFormat::NetCdf => "netcdf",
+        }
+    }
+
+    /// Explicit format first, then the content type, then the file signature
+    pub fn detect(format: Option<&str>, content_type: Option<&str>, body: &[u8]) -> Option<Self> {
+        match format.map(str::to_ascii_lowercase).as_deref() {
+            Some("csv") => return Some(ImportFormat::Csv),
+            Some("netcdf") | Some("nc") => return Some(ImportFormat::NetCdf),
+            Some(_) => return None,
+            None => {}
+        }
+        match content_type {
+            Some(ct) if ct.starts_with("text/csv") => Some(ImportFormat::Csv),
+            Some(ct) if ct.contains("netcdf") => Some(ImportFormat::NetCdf),
+            _ if body.starts_with(NETCDF_MAGIC) => Some(ImportFormat::NetCdf),
+            _ => Some(ImportFormat::Csv),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObservationRecord {
+    pub station: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub observed_at: DateTime<Utc>,
+    pub rainfall_mm: Option<f64>,
+    pub temp_min_c: Option<f64>,
+    pub temp_max_c: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub records: Vec<ObservationRecord>,
+    pub skipped: usize,
+    // Gridded sources create one pseudo-station per cell
+    pub gridded: bool,
+}
+
+pub fn parse(format: ImportFormat, body: &[u8]) -> Result<ParsedImport, String> {
+    match format {
+        ImportFormat::Csv => parse_csv(body),
+        ImportFormat::NetCdf => parse_netcdf(body),
+    }
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(timestamp) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(timestamp.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|timestamp| timestamp.and_utc())
+}
+
+fn parse_number(value: Option<&str>) -> Result<Option<f64>, ()> {
+    match value.map(str::trim) {
+        None | Some("") | Some("NA") | Some("NaN") => Ok(None),
+        Some(v) => v.parse::<f64>().map(Some).map_err(|_| ()),
+    }
+}
+
+/**
+ * CSV with a header row. Recognised columns (case-insensitive):
+ * station, latitude|lat, longitude|lon, observed_at|date|time,
+ * rainfall_mm|rainfall|precip, temp_min_c|tmin, temp_max_c|tmax
+ **/
+fn parse_csv(body: &[u8]) -> Result<ParsedImport, String> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(body);
+    let headers = reader.headers().map_err(|e| format!("invalid CSV header: {}", e))?.clone();
+
+    let column = |names: &[&str]| {
+        headers.iter().position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+    };
+    let station = column(&["station", "station_code"]).ok_or("missing station column")?;
+    let latitude = column(&["latitude", "lat"]).ok_or("missing latitude column")?;
+    let longitude = column(&["longitude", "lon"]).ok_or("missing longitude column")?;
+    let observed_at = column(&["observed_at", "date", "time"]).ok_or("missing observed_at column")?;
+    let rainfall = column(&["rainfall_mm", "rainfall", "precip"]);
+    let temp_min = column(&["temp_min_c", "tmin"]);
+    let temp_max = column(&["temp_max_c", "tmax"]);
+
+    let mut parsed = ParsedImport::default();
+    for row in reader.records() {
+        let row = match row {
+            Ok(row) => row,
+            Err(_) => {
+                parsed.skipped += 1;
+                continue;
+            }
+        };
+        let record = (|| {
+            let latitude = parse_number(row.get(latitude)).ok()??;
+            let longitude = parse_number(row.get(longitude)).ok()??;
+            if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                return None;
+            }
+            Some(ObservationRecord {
+                station: row.get(station).filter(|s| !s.is_empty())?.to_string(),
+                latitude,
+                longitude,
+                observed_at: parse_timestamp(row.get(observed_at)?)?,
+                rainfall_mm: parse_number(rainfall.and_then(|i| row.get(i))).ok()?,
+                temp_min_c: parse_number(temp_min.and_then(|i| row.get(i))).ok()?,
+                temp_max_c: parse_number(temp_max.and_then(|i| row.get(i))).ok()?,
+            })
+        })();
+        match record {
+            Some(record) => parsed.records.push(record),
+            None => parsed.skipped += 1,
+        }
+    }
+    Ok(parsed)
+}
+
+fn to_f64(data: DataVector) -> Vec<f64> {
+    match data {
+        DataVector::I8(v) => v.into_iter().map(f64::from).collect(),
+        DataVector::U8(v) => v.into_iter().map(f64::from).collect(),
+        DataVector::I16(v) => v.into_iter().map(f64::from).collect(),
+        DataVector::I32(v) => v.into_iter().map(f64::from).collect(),
+        DataVector::F32(v) => v.into_iter().map(f64::from).collect(),
+        DataVector::F64(v) => v,
+    }
+}
+
+// "days since 1990-01-01" / "hours since 1990-01-01 00:00:00"
+fn parse_time_units(units: &str) -> Option<(Duration, DateTime<Utc>)> {
+    let (step, origin) = units.split_once(" since ")?;
+    let step = match step.trim() {
+        "days" | "day" => Duration::days(1),
+        "hours" | "hour" => Duration::hours(1),
+        "minutes" | "minute" => Duration::minutes(1),
+        "seconds" | "second" => Duration::seconds(1),
+        _ => return None,
+    };
+    let origin = origin.trim();
+    let origin = parse_timestamp(origin)
+        .or_else(|| parse_timestamp(origin.get(..10)?))?;
+    Some((step, origin))
+}
+
+struct GridVariable {
+    values: Vec<f64>,
+    fill: Option<f64>,
+    scale: f64,
+    offset: f64,
+    // A rate per second, to be multiplied by the length of the timestep
+    per_second: bool,
+}
+
+impl GridVariable {
+    fn get(&self, index: usize, timestep_seconds: f64) -> Option<f64> {
+        let raw = *self.values.get(index)?;
+        if raw.is_nan() || self.fill.is_some_and(|fill| raw == fill) {
+            return None;
+        }
+        let value = raw * self.scale + self.offset;
+        let value = if self.per_second { value * timestep_seconds } else { value };
+        value.is_finite().then_some(value)
+    }
+}
+
+// None when the offset does not fit in a timestamp, e.g. a corrupt or non-finite time value
+fn timestep_start(origin: DateTime<Utc>, time: f64, step_seconds: f64) -> Option<DateTime<Utc>> {
+    let seconds = (time * step_seconds).round();
+    if !seconds.is_finite() {
+        return None;
+    }
+    origin.checked_add_signed(Duration::try_seconds(seconds as i64)?)
+}
+
+/**
+ * Gridded NetCDF-3 with `time`, `lat`/`latitude` and `lon`/`longitude`
+ * coordinates and any of precipitation (`pr`, `precip`, `tp`),
+ * `tmin`/`tasmin` and `tmax`/`tasmax` laid out as (time, lat, lon)
+ **/
+fn parse_netcdf(body: &[u8]) -> Result<ParsedImport, String> {
+    let mut reader = FileReader::open_seek_read("upload.nc", Box::new(Cursor::new(body.to_vec())))
+        .map_err(|e| format!("invalid NetCDF-3 file: {:?}", e))?;
+
+    let names: Vec<String> = reader.data_set().get_var_names();
+    let find = |candidates: &[&str]| candidates.iter().find(|c| names.iter().any(|n| n == *c)).map(|c| c.to_string());
+
+    let lat_name = find(&["lat", "latitude"]).ok_or("missing latitude variable")?;
+    let lon_name = find(&["lon", "longitude"]).ok_or("missing longitude variable")?;
+    let time_units = reader.data_set().get_var_attr_as_string("time", "units").ok_or("missing time units")?;
+    let (step, origin) = parse_time_units(&time_units).ok_or_else(|| format!("unsupported time units: {}", time_units))?;
+
+    let mut read = |name: &str| reader.read_var(name).map(to_f64).map_err(|e| format!("cannot read {}: {:?}", name, e));
+    let latitudes = read(&lat_name)?;
+    let longitudes = read(&lon_name)?;
+    let times = read("time")?;
+
+    let mut variables: HashMap<&str, GridVariable> = HashMap::new();
+    for (key, candidates) in [("rain", &["pr", "precip", "rainfall", "tp"][..]), ("tmin", &["tmin", "tasmin"][..]), ("tmax", &["tmax", "tasmax"][..])] {
+        let Some(name) = find(candidates) else { continue };
+        let attrs = reader.data_set();
+        let units = attrs.get_var_attr_as_string(&name, "units").unwrap_or_default();
+        let attr_f64 = |attr: &str| {
+            attrs.get_var_attr(&name, attr).and_then(|a| {
+                a.get_f64().map(|v| v[0])
+                    .or_else(|| a.get_f32().map(|v| v[0] as f64))
+                    .or_else(|| a.get_i16().map(|v| v[0] as f64))
+                    .or_else(|| a.get_i32().map(|v| v[0] as f64))
+            })
+        };
+        let fill = attr_f64("_FillValue").or_else(|| attr_f64("missing_value"));
+        let mut scale = attr_f64("scale_factor").unwrap_or(1.0);
+        let mut offset = attr_f64("add_offset").unwrap_or(0.0);
+        let mut per_second = false;
+
+        // Normalise to mm per timestep and °C; a day's rainfall is the sum of its timesteps
+        match (key, units.trim()) {
+            ("rain", "m") => { scale *= 1000.0; offset *= 1000.0; }
+            ("rain", "kg m-2 s-1") => per_second = true,
+            (_, "K") => offset -= 273.15,
+            _ => {}
+        }
+
+        let values = reader.read_var(&name).map(to_f64).map_err(|e| format!("cannot read {}: {:?}", name, e))?;
+        if values.len() != times.len() * latitudes.len() * longitudes.len() {
+            return Err(format!("{} is not laid out as (time, lat, lon)", name));
+        }
+        variables.insert(key, GridVariable { values, fill, scale, offset, per_second });
+    }
+
+    if variables.is_empty() {
+        return Err("no precipitation or temperature variables found".to_string());
+    }
+
+    // Each timestep runs until the next one; the last is as long as the one before it
+    let step_seconds = step.num_seconds() as f64;
+    let timestep_seconds = |t: usize| {
+        let gap = match (times.get(t + 1), t.checked_sub(1).and_then(|previous| times.get(previous))) {
+            (Some(next), _) => next - times[t],
+            (None, Some(previous)) => times[t] - previous,
+            (None, None) => 1.0,
+        };
+        gap * step_seconds
+    };
+
+    let mut parsed = ParsedImport { gridded: true, ..Default::default() };
+    for (t, time) in times.iter().enumerate() {
+        let Some(observed_at) = timestep_start(origin, *time, step_seconds) else {
+            parsed.skipped += latitudes.len() * longitudes.len();
+            continue;
+        };
+        let seconds = timestep_seconds(t);
+        for (y, latitude) in latitudes.iter().enumerate() {
+            for (x, longitude) in longitudes.iter().enumerate() {
+                let index = (t * latitudes.len() + y) * longitudes.len() + x;
+                let value = |key: &str| variables.get(key).and_then(|v| v.get(index, seconds));
+                let record = ObservationRecord {
+                    station: format!("grid:{:.4},{:.4}", latitude, longitude),
+                    latitude: *latitude,
+                    longitude: if *longitude > 180.0 { longitude - 360.0 } else { *longitude },
+                    observed_at,
+                    rainfall_mm: value("rain"),
+                    temp_min_c: value("tmin"),
+                    temp_max_c: value("tmax"),
+                };
+                if record.rainfall_mm.is_none() && record.temp_min_c.is_none() && record.temp_max_c.is_none() {
+                    parsed.skipped += 1;
+                } else {
+                    parsed.records.push(record);
+                }
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use netcdf3::{DataSet, FileWriter, Version};
+
+    const FILL: f64 = -9999.0;
+
+    // One latitude and two longitudes, the second east of the antimeridian
+    fn netcdf(name: &str, times: &[f64], rain_units: &str, rain: &[f64]) -> Vec<u8> {
+        let mut data_set = DataSet::new();
+        data_set.add_fixed_dim("time", times.len()).unwrap();
+        data_set.add_fixed_dim("lat", 1).unwrap();
+        data_set.add_fixed_dim("lon", 2).unwrap();
+        data_set.add_var_f64("time", &["time"]).unwrap();
+        data_set.add_var_attr_string("time", "units", "hours since 2024-01-01 00:00:00").unwrap();
+        data_set.add_var_f32("lat", &["lat"]).unwrap();
+        data_set.add_var_f32("lon", &["lon"]).unwrap();
+        data_set.add_var_f64("pr", &["time", "lat", "lon"]).unwrap();
+        data_set.add_var_attr_string("pr", "units", rain_units).unwrap();
+        data_set.add_var_attr_f64("pr", "_FillValue", vec![FILL]).unwrap();
+
+        let path = std::env::temp_dir().join(format!("planta-{}-{}.nc", name, std::process::id()));
+        let mut writer = FileWriter::open(&path).unwrap();
+        writer.set_def(&data_set, Version::Classic, 0).unwrap();
+        writer.write_var_f64("time", times).unwrap();
+        writer.write_var_f32("lat", &[7.5]).unwrap();
+        writer.write_var_f32("lon", &[3.5, 356.5]).unwrap();
+        writer.write_var_f64("pr", rain).unwrap();
+        writer.close().unwrap();
+
+        let body = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        body
+    }
+
+    fn variable(values: Vec<f64>, per_second: bool) -> GridVariable {
+        GridVariable { values, fill: Some(FILL), scale: 0.1, offset: 1.0, per_second }
+    }
+
+    #[test]
+    fn parses_time_units() {
+        assert_eq!(
+            parse_time_units("days since 1990-01-01"),
+            Some((Duration::days(1), Utc.with_ymd_and_hms(1990, 1, 1, 0, 0, 0).unwrap())),
+        );
+        assert_eq!(
+            parse_time_units("hours since 2024-01-01 06:00:00"),
+            Some((Duration::hours(1), Utc.with_ymd_and_hms(2024, 1, 1, 6, 0, 0).unwrap())),
+        );
+        // Falls back to the date when the origin carries fractional seconds
+        assert_eq!(
+            parse_time_units("seconds since 2024-01-01 00:00:00.0"),
+            Some((Duration::seconds(1), Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())),
+        );
+        assert_eq!(parse_time_units("months since 2024-01-01"), None);
+        assert_eq!(parse_time_units("hours"), None);
+        assert_eq!(parse_time_units("hours since yesterday"), None);
+    }
+
+    #[test]
+    fn grid_variable_applies_fill_scale_and_offset() {
+        let variable = variable(vec![10.0, FILL, f64::NAN], false);
+        assert_eq!(variable.get(0, 3600.0), Some(2.0));
+        assert_eq!(variable.get(1, 3600.0), None);
+        assert_eq!(variable.get(2, 3600.0), None);
+        assert_eq!(variable.get(3, 3600.0), None);
+    }
+
+    #[test]
+    fn grid_variable_scales_rates_by_the_timestep() {
+        let variable = variable(vec![10.0], true);
+        assert_eq!(variable.get(0, 3600.0), Some(7200.0));
+        assert_eq!(variable.get(0, f64::INFINITY), None);
+    }
+
+    #[test]
+    fn timestep_start_rejects_offsets_out_of_range() {
+        let origin = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(timestep_start(origin, 6.0, 3600.0), Some(Utc.with_ymd_and_hms(2024, 1, 1, 6, 0, 0).unwrap()));
+        assert_eq!(timestep_start(origin, 1e300, 3600.0), None);
+        assert_eq!(timestep_start(origin, 1e12, 86400.0), None);
+        assert_eq!(timestep_start(origin, f64::NAN, 3600.0), None);
+        assert_eq!(timestep_start(origin, f64::NEG_INFINITY, 3600.0), None);
+    }
+
+    #[test]
+    fn parses_csv_rows_and_counts_the_invalid_ones() {
+        let body = b"Station,Lat,Lon,Date,Precip,Tmin,Tmax
+ikeja,6.6,3.3,2024-05-01,12.5,23.1,31.0
+ikeja,6.6,3.3,2024-05-02T06:00:00Z,NA,,30.5
+ikeja,96.6,3.3,2024-05-03,1.0,23.0,31.0
+,6.6,3.3,2024-05-04,1.0,23.0,31.0
+ikeja,6.6,3.3,not a date,1.0,23.0,31.0
+ikeja,6.6,3.3,2024-05-06,lots,23.0,31.0
+";
+        let parsed = parse_csv(body).unwrap();
+        assert!(!parsed.gridded);
+        assert_eq!(parsed.skipped, 4);
+        assert_eq!(parsed.records, vec![
+            ObservationRecord {
+                station: "ikeja".to_string(),
+                latitude: 6.6,
+                longitude: 3.3,
+                observed_at: Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap(),
+                rainfall_mm: Some(12.5),
+                temp_min_c: Some(23.1),
+                temp_max_c: Some(31.0),
+            },
+            ObservationRecord {
+                station: "ikeja".to_string(),
+                latitude: 6.6,
+                longitude: 3.3,
+                observed_at: Utc.with_ymd_and_hms(2024, 5, 2, 6, 0, 0).unwrap(),
+                rainfall_mm: None,
+                temp_min_c: None,
+                temp_max_c: Some(30.5),
+            },
+        ]);
+    }
+
+    #[test]
+    fn rejects_csv_without_required_columns() {
+        assert_eq!(parse_csv(b"station,lat,lon\nikeja,6.6,3.3\n").unwrap_err(), "missing observed_at column");
+    }
+
+    #[test]
+    fn parses_netcdf_rates_into_millimetres_per_timestep() {
+        let body = netcdf("rates", &[0.0, 6.0], "kg m-2 s-1", &[1e-4, FILL, 2e-4, 0.0]);
+        assert_eq!(ImportFormat::detect(None, None, &body), Some(ImportFormat::NetCdf));
+
+        let parsed = parse_netcdf(&body).unwrap();
+        assert!(parsed.gridded);
+        assert_eq!(parsed.skipped, 1);
+        let rows: Vec<_> = parsed.records.iter()
+            .map(|r| (r.station.as_str(), r.longitude, r.observed_at, r.rainfall_mm.map(|mm| (mm * 1000.0).round() / 1000.0)))
+            .collect();
+        assert_eq!(rows, vec![
+            ("grid:7.5000,3.5000", 3.5, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(), Some(2.16)),
+            ("grid:7.5000,3.5000", 3.5, Utc.with_ymd_and_hms(2024, 1, 1, 6, 0, 0).unwrap(), Some(4.32)),
+            ("grid:7.5000,356.5000", -3.5, Utc.with_ymd_and_hms(2024, 1, 1, 6, 0, 0).unwrap(), Some(0.0)),
+        ]);
+    }
+
+    #[test]
+    fn skips_netcdf_timesteps_that_overflow() {
+        let body = netcdf("overflow", &[0.0, 6.0, 1e300], "mm", &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let parsed = parse_netcdf(&body).unwrap();
+        assert_eq!(parsed.records.iter().map(|r| r.rainfall_mm).collect::<Vec<_>>(), vec![Some(1.0), Some(2.0), Some(3.0), Some(4.0)]);
+        assert_eq!(parsed.skipped, 2);
+    }
+}
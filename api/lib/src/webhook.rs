@@ -0,0 +1,730 @@
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, web::Json};
+use sqlx::{Error as SqlxError, postgres::PgPool, types::Json as SqlxJson};
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Utc};
+use deadpool_redis::Pool;
+use reqwest::{redirect, Url};
+use ring::hmac;
+use uuid::Uuid;
+use shared::models::{
+    Pagination,
+    DomainEntityType,
+    OutboxEvent,
+    WebhookStatus,
+    WebhookSubscription,
+    CreatedWebhookSubscription,
+    CreateWebhookSubscription,
+    UpdateWebhookSubscription,
+    WebhookDelivery,
+    WebhookDeliveryFilter
+};
+use tracing::{error, info, warn};
+use crate::outbox::{self, OutboxConfig};
+
+// Name of the webhook reader in "StreamCursor"
+const CURSOR_CONSUMER: &str = "webhooks";
+// Keeps instances from queueing the same stream entries at once
+const FAN_OUT_LOCK_KEY: i64 = 0x0B7B_3E0B;
+// The log keeps the start of each endpoint answer
+const RESPONSE_BODY_LIMIT: usize = 2048;
+const MIN_SECRET_LENGTH: usize = 16;
+
+#[derive(Debug)]
+pub enum AppError {
+    SqlError(SqlxError),
+    GenericError(String),
+    NotFound(String),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::SqlError(e) => write!(f, "Database error: {}", e),
+            AppError::GenericError(msg) => write!(f, "Error: {}", msg),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            AppError::SqlError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::GenericError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        HttpResponse::build(status).json(format!("{}", self))
+    }
+}
+
+impl From<SqlxError> for AppError {
+    fn from(e: SqlxError) -> Self {
+        error!("Webhook query failed: {:?}", e);
+        AppError::SqlError(e)
+    }
+}
+
+/**
+ * Webhook delivery settings, read from WEBHOOK_TIMEOUT_SECS, WEBHOOK_MAX_ATTEMPTS,
+ * WEBHOOK_BACKOFF_BASE_SECS, WEBHOOK_BACKOFF_MAX_SECS, WEBHOOK_DISABLE_AFTER,
+ * WEBHOOK_BATCH_SIZE, WEBHOOK_POLL_INTERVAL_MS and WEBHOOK_ALLOW_PRIVATE_HOSTS. Events are
+ * read from OUTBOX_STREAM.
+ **/
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub stream: String,
+    pub timeout: Duration,
+    // A delivery is given up after this many attempts
+    pub max_attempts: i32,
+    // Wait before the second attempt, doubling for each one after up to `backoff_max`
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+    // Failed attempts in a row, across deliveries, before an endpoint is disabled
+    pub disable_after: i32,
+    pub batch_size: i64,
+    pub poll_interval: Duration,
+    // Lets endpoints resolve to loopback, private and link-local addresses, e.g. for local testing
+    pub allow_private_hosts: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            stream: OutboxConfig::default().stream,
+            timeout: Duration::from_secs(10),
+            max_attempts: 10,
+            backoff_base: Duration::from_secs(30),
+            backoff_max: Duration::from_secs(6 * 60 * 60),
+            disable_after: 25,
+            batch_size: 50,
+            poll_interval: Duration::from_secs(1),
+            allow_private_hosts: false,
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|value| value.parse().ok())
+        }
+        let default = WebhookConfig::default();
+        WebhookConfig {
+            stream: OutboxConfig::from_env().stream,
+            timeout: var("WEBHOOK_TIMEOUT_SECS").map(Duration::from_secs).unwrap_or(default.timeout),
+            max_attempts: var("WEBHOOK_MAX_ATTEMPTS").unwrap_or(default.max_attempts),
+            backoff_base: var("WEBHOOK_BACKOFF_BASE_SECS").map(Duration::from_secs).unwrap_or(default.backoff_base),
+            backoff_max: var("WEBHOOK_BACKOFF_MAX_SECS").map(Duration::from_secs).unwrap_or(default.backoff_max),
+            disable_after: var("WEBHOOK_DISABLE_AFTER").unwrap_or(default.disable_after),
+            batch_size: var("WEBHOOK_BATCH_SIZE").unwrap_or(default.batch_size),
+            poll_interval: var("WEBHOOK_POLL_INTERVAL_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.poll_interval),
+            allow_private_hosts: var("WEBHOOK_ALLOW_PRIVATE_HOSTS").unwrap_or(default.allow_private_hosts),
+        }
+    }
+
+    // Wait after the given number of failed attempts
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let doublings = attempts.clamp(1, 31) as u32 - 1;
+        self.backoff_base.saturating_mul(2u32.saturating_pow(doublings)).min(self.backoff_max)
+    }
+}
+
+pub fn service(cfg: &mut ServiceConfig) {
+    cfg.service(web::scope("/v0.1/webhooks")
+                    .app_data(web::Data::new(WebhookConfig::from_env()))
+                    .route("", web::get().to(get_all_subscriptions))
+                    .route("", web::post().to(create_subscription))
+                    .route("/{id}", web::get().to(get_subscription))
+                    .route("/{id}", web::put().to(update_subscription))
+                    .route("/{id}", web::delete().to(delete_subscription))
+                    .route("/{id}/deliveries", web::get().to(get_deliveries))
+                    .route("/{id}/deliveries/{delivery_id}", web::get().to(get_delivery))
+                    .route("/{id}/deliveries/{delivery_id}/replay", web::post().to(replay_delivery))
+    );
+}
+
+/**
+ * Value of the X-Planta-Signature header: `t=<unix seconds>,v1=<hex HMAC-SHA256>` over
+ * `"<t>.<body>"` keyed with the subscription secret. Receivers should recompute it and
+ * reject stale timestamps so captured requests cannot be replayed.
+ **/
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, format!("{}.{}", timestamp, body).as_bytes());
+    format!("t={},v1={}", timestamp, hex::encode(tag.as_ref()))
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast()),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public(IpAddr::V4(mapped)),
+            // fc00::/7 is unique local and fe80::/10 link-local
+            None => !(ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xfe00) == 0xfc00 || (ip.segments()[0] & 0xffc0) == 0xfe80),
+        },
+    }
+}
+
+/**
+ * The addresses an endpoint resolves to. Endpoints on loopback, private or link-local
+ * addresses are refused unless `allow_private_hosts` is set, so subscriptions cannot be
+ * used to reach services inside the network.
+ **/
+async fn resolve_endpoint(url: &Url, config: &WebhookConfig) -> Result<Vec<SocketAddr>, String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("URL must be an http or https address".to_string());
+    }
+    let host = url.host_str().ok_or("URL must be an http or https address")?;
+    let port = url.port_or_known_default().ok_or("URL has no port")?;
+    // IPv6 hosts are written in brackets
+    let name = host.trim_start_matches('[').trim_end_matches(']');
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((name, port))
+        .await
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .collect();
+    if addresses.is_empty() {
+        return Err(format!("Could not resolve {}", host));
+    }
+    if !config.allow_private_hosts {
+        if let Some(address) = addresses.iter().find(|address| !is_public(address.ip())) {
+            return Err(match name.parse::<IpAddr>() {
+                Ok(_) => format!("{} is not a public address", host),
+                Err(_) => format!("{} resolves to {}, which is not a public address", host, address.ip()),
+            });
+        }
+    }
+    Ok(addresses)
+}
+
+async fn validate_url(url: &str, config: &WebhookConfig) -> Result<(), AppError> {
+    let parsed = Url::parse(url).map_err(|e| AppError::GenericError(format!("Invalid URL: {}", e)))?;
+    resolve_endpoint(&parsed, config).await.map_err(AppError::GenericError)?;
+    Ok(())
+}
+
+async fn fetch_subscription(pool: &PgPool, id: Uuid) -> Result<WebhookSubscription, AppError> {
+    sqlx::query_as::<_, WebhookSubscription>(r#"SELECT * FROM "WebhookSubscription" WHERE id = $1"#)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook subscription".to_string()))
+}
+
+async fn get_all_subscriptions(pool: web::Data<PgPool>, pagination: web::Query<Pagination>) -> Result<HttpResponse, AppError> {
+    let Pagination { limit, offset } = pagination.into_inner();
+    let subscriptions = sqlx::query_as::<_, WebhookSubscription>(
+        r#"SELECT * FROM "WebhookSubscription" ORDER BY "createdAt" LIMIT $1 OFFSET $2"#,
+    )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(subscriptions))
+}
+
+/**
+ * Create a Webhook Subscription
+ * The endpoint receives the listed event types from the time it is created. The signing
+ * secret is generated unless given and is only shown in this response.
+ **/
+async fn create_subscription(pool: web::Data<PgPool>, config: web::Data<WebhookConfig>, subscription: Json<CreateWebhookSubscription>) -> Result<HttpResponse, AppError> {
+    let subscription = subscription.into_inner();
+    validate_url(&subscription.url, config.get_ref()).await?;
+    if subscription.event_types.is_empty() {
+        return Err(AppError::GenericError("eventTypes must list at least one event type".to_string()));
+    }
+    let secret = match subscription.secret {
+        Some(secret) if secret.len() < MIN_SECRET_LENGTH => {
+            return Err(AppError::GenericError(format!("Secret must be at least {} characters", MIN_SECRET_LENGTH)));
+        }
+        Some(secret) => secret,
+        None => format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+    };
+    if let Some(cooperative_id) = subscription.cooperative_id {
+        let exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Cooperative" WHERE id = $1)"#)
+            .bind(cooperative_id)
+            .fetch_one(pool.get_ref())
+            .await?;
+        if !exists {
+            return Err(AppError::NotFound("Cooperative".to_string()));
+        }
+    }
+
+    let created = sqlx::query_as::<_, WebhookSubscription>(
+        r#"
+        INSERT INTO "WebhookSubscription" (url, "eventTypes", secret, description, "cooperativeId")
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+        .bind(subscription.url)
+        .bind(SqlxJson(subscription.event_types))
+        .bind(&secret)
+        .bind(subscription.description)
+        .bind(subscription.cooperative_id)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Created().json(CreatedWebhookSubscription { subscription: created, secret }))
+}
+
+async fn get_subscription(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let subscription = fetch_subscription(pool.get_ref(), id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(subscription))
+}
+
+/**
+ * Update a Webhook Subscription
+ * Setting the status back to ACTIVE re-enables an endpoint that was disabled for failing
+ **/
+async fn update_subscription(pool: web::Data<PgPool>, config: web::Data<WebhookConfig>, id: web::Path<Uuid>, subscription: Json<UpdateWebhookSubscription>) -> Result<HttpResponse, AppError> {
+    let subscription = subscription.into_inner();
+    if let Some(url) = &subscription.url {
+        validate_url(url, config.get_ref()).await?;
+    }
+    if subscription.event_types.as_ref().is_some_and(Vec::is_empty) {
+        return Err(AppError::GenericError("eventTypes must list at least one event type".to_string()));
+    }
+
+    let updated = sqlx::query_as::<_, WebhookSubscription>(
+        r#"
+        UPDATE "WebhookSubscription"
+        SET url = COALESCE($2, url),
+            "eventTypes" = COALESCE($3, "eventTypes"),
+            description = COALESCE($4, description),
+            status = COALESCE($5, status),
+            "disabledReason" = CASE $5
+                WHEN 'ACTIVE' THEN NULL
+                WHEN 'DISABLED' THEN 'Disabled by request'
+                ELSE "disabledReason"
+            END,
+            "consecutiveFailures" = CASE WHEN $5 = 'ACTIVE' THEN 0 ELSE "consecutiveFailures" END,
+            "updatedAt" = current_timestamp
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(id.into_inner())
+        .bind(subscription.url)
+        .bind(subscription.event_types.map(SqlxJson))
+        .bind(subscription.description)
+        .bind(subscription.status)
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook subscription".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+async fn delete_subscription(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
+    let result = sqlx::query(r#"DELETE FROM "WebhookSubscription" WHERE id = $1"#)
+        .bind(id.into_inner())
+        .execute(pool.get_ref())
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Webhook subscription".to_string()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/**
+ * Get Webhook Deliveries
+ * The subscription's delivery log, newest first
+ **/
+async fn get_deliveries(pool: web::Data<PgPool>, id: web::Path<Uuid>, pagination: web::Query<Pagination>, filter: web::Query<WebhookDeliveryFilter>) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let Pagination { limit, offset } = pagination.into_inner();
+    fetch_subscription(pool.get_ref(), id).await?;
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT *
+        FROM "WebhookDelivery"
+        WHERE "subscriptionId" = $1 AND ($2::varchar IS NULL OR status = $2)
+        ORDER BY "createdAt" DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+        .bind(id)
+        .bind(filter.into_inner().status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(deliveries))
+}
+
+async fn get_delivery(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (id, delivery_id) = path.into_inner();
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(r#"SELECT * FROM "WebhookDelivery" WHERE id = $1 AND "subscriptionId" = $2"#)
+        .bind(delivery_id)
+        .bind(id)
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook delivery".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(delivery))
+}
+
+/**
+ * Replay a Webhook Delivery
+ * Sends the same event again as a new delivery with its own attempts. The event id is
+ * unchanged so receivers can tell it is a repeat.
+ **/
+async fn replay_delivery(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>) -> Result<HttpResponse, AppError> {
+    let (id, delivery_id) = path.into_inner();
+    let subscription = fetch_subscription(pool.get_ref(), id).await?;
+    if subscription.status == WebhookStatus::Disabled {
+        return Err(AppError::GenericError("The subscription is disabled; set it ACTIVE before replaying".to_string()));
+    }
+
+    let replay = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        INSERT INTO "WebhookDelivery" ("subscriptionId", "eventId", "eventType", payload, "nextAttemptAt", "replayOf")
+        SELECT "subscriptionId", "eventId", "eventType", payload, current_timestamp, id
+        FROM "WebhookDelivery"
+        WHERE id = $1 AND "subscriptionId" = $2
+        RETURNING *
+        "#,
+    )
+        .bind(delivery_id)
+        .bind(id)
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook delivery".to_string()))?;
+
+    Ok(HttpResponse::Created().json(replay))
+}
+
+pub async fn run(pool: PgPool, redis: Pool, config: WebhookConfig) {
+    let mut ticker = actix_web::rt::time::interval(config.poll_interval);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = fan_out(&pool, &redis, &config).await {
+            error!("Webhook fan-out failed: {:?}", e);
+        }
+        if let Err(e) = deliver_due(&pool, &config).await {
+            error!("Webhook delivery failed: {:?}", e);
+        }
+    }
+}
+
+// The farmer an event is about, for cooperative subscriptions
+fn event_user_id(event: &OutboxEvent) -> Option<Uuid> {
+    let field = match event.entity_type {
+        DomainEntityType::User => return Some(event.entity_id),
+        DomainEntityType::Profile => "user_id",
+        DomainEntityType::Farm => "farmer_id",
+    };
+    event.payload.get(field).and_then(|id| id.as_str()).and_then(|id| id.parse().ok())
+}
+
+/**
+ * Queue a delivery for each subscription that wants each new event on the stream. The
+ * stream position is saved with the deliveries, so every event is queued once.
+ **/
+async fn fan_out(pool: &PgPool, redis: &Pool, config: &WebhookConfig) -> Result<usize, SqlxError> {
+    let mut tx = pool.begin().await?;
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+        .bind(FAN_OUT_LOCK_KEY)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !locked {
+        return Ok(0);
+    }
+    let last_id: String = sqlx::query_scalar(r#"SELECT "lastId" FROM "StreamCursor" WHERE consumer = $1"#)
+        .bind(CURSOR_CONSUMER)
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or_else(|| "0-0".to_string());
+
+    let mut conn = match redis.get().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            warn!("Redis unavailable, webhook events not read: {:?}", e);
+            return Ok(0);
+        }
+    };
+    let entries = match outbox::read_stream(&mut conn, &config.stream, &last_id, config.batch_size, None).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Error reading {} for webhooks: {:?}", config.stream, e);
+            return Ok(0);
+        }
+    };
+    let Some(newest) = entries.last().map(|entry| entry.id.clone()) else { return Ok(0) };
+
+    for entry in &entries {
+        let Some(event) = &entry.event else {
+            warn!("Skipping unreadable stream entry {}", entry.id);
+            continue;
+        };
+        let envelope = serde_json::to_value(event).unwrap_or_default();
+        sqlx::query(
+            r#"
+            INSERT INTO "WebhookDelivery" ("subscriptionId", "eventId", "eventType", payload, "nextAttemptAt")
+            SELECT s.id, $1, $2, $3, current_timestamp
+            FROM "WebhookSubscription" s
+            WHERE s.status = 'ACTIVE' AND s."eventTypes" ? $2 AND s."createdAt" <= $4
+              AND (
+                  s."cooperativeId" IS NULL
+                  OR EXISTS (
+                      SELECT 1 FROM "CooperativeMembership" m
+                      WHERE m."cooperativeId" = s."cooperativeId" AND m."userId" = $5 AND m."leftOn" IS NULL
+                  )
+              )
+            ON CONFLICT ("subscriptionId", "eventId") WHERE "replayOf" IS NULL DO NOTHING
+            "#,
+        )
+            .bind(event.id)
+            .bind(event.event_type)
+            .bind(SqlxJson(envelope))
+            .bind(event.created_at)
+            .bind(event_user_id(event))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO "StreamCursor" (consumer, "lastId") VALUES ($1, $2)
+        ON CONFLICT (consumer) DO UPDATE SET "lastId" = EXCLUDED."lastId", "updatedAt" = current_timestamp
+        "#,
+    )
+        .bind(CURSOR_CONSUMER)
+        .bind(newest)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(entries.len())
+}
+
+#[derive(sqlx::FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    #[sqlx(rename = "subscriptionId")]
+    subscription_id: Uuid,
+    #[sqlx(rename = "eventId")]
+    event_id: Uuid,
+    #[sqlx(rename = "eventType")]
+    event_type: String,
+    attempts: i32,
+    payload: SqlxJson<serde_json::Value>,
+    url: String,
+    secret: String,
+}
+
+/**
+ * Send the deliveries that are due, concurrently. Claimed deliveries are pushed back by
+ * twice the timeout first, so one lost with its instance is retried rather than dropped.
+ **/
+pub async fn deliver_due(pool: &PgPool, config: &WebhookConfig) -> Result<(), SqlxError> {
+    let due = sqlx::query_as::<_, DueDelivery>(
+        r#"
+        UPDATE "WebhookDelivery" d
+        SET "nextAttemptAt" = current_timestamp + make_interval(secs => $2)
+        FROM "WebhookSubscription" s
+        WHERE s.id = d."subscriptionId"
+          AND d.id IN (
+              SELECT due.id
+              FROM "WebhookDelivery" due
+              JOIN "WebhookSubscription" ds ON ds.id = due."subscriptionId"
+              WHERE due.status = 'PENDING' AND due."nextAttemptAt" <= current_timestamp AND ds.status = 'ACTIVE'
+              ORDER BY due."nextAttemptAt"
+              LIMIT $1
+              FOR UPDATE OF due SKIP LOCKED
+          )
+        RETURNING d.id, d."subscriptionId", d."eventId", d."eventType", d.attempts, d.payload, s.url, s.secret
+        "#,
+    )
+        .bind(config.batch_size)
+        .bind(config.timeout.as_secs_f64() * 2.0)
+        .fetch_all(pool)
+        .await?;
+
+    let sends = due.into_iter()
+        .map(|delivery| {
+            let config = config.clone();
+            actix_web::rt::spawn(async move {
+                let body = delivery.payload.0.to_string();
+                let headers = vec![
+                    ("X-Planta-Event".to_string(), delivery.event_type.clone()),
+                    ("X-Planta-Event-Id".to_string(), delivery.event_id.to_string()),
+                    ("X-Planta-Delivery".to_string(), delivery.id.to_string()),
+                    ("X-Planta-Signature".to_string(), signature(&delivery.secret, Utc::now().timestamp(), &body)),
+                ];
+                let started = Instant::now();
+                let outcome = post(&delivery.url, &headers, body, &config).await;
+                (delivery, outcome, started.elapsed())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for send in sends {
+        let Ok((delivery, outcome, elapsed)) = send.await else { continue };
+        record_attempt(pool, config, &delivery, outcome, elapsed).await?;
+    }
+    Ok(())
+}
+
+async fn record_attempt(
+    pool: &PgPool,
+    config: &WebhookConfig,
+    delivery: &DueDelivery,
+    outcome: Result<EndpointResponse, String>,
+    elapsed: Duration,
+) -> Result<(), SqlxError> {
+    let attempts = delivery.attempts + 1;
+    let (response_status, response_body, error) = match outcome {
+        Ok(response) if (200..300).contains(&response.status) => (Some(response.status as i32), Some(response.body), None),
+        Ok(response) => (Some(response.status as i32), Some(response.body), Some(format!("Endpoint answered {}", response.status))),
+        Err(e) => (None, None, Some(e)),
+    };
+    let status = match &error {
+        None => "SUCCEEDED",
+        Some(_) if attempts >= config.max_attempts => "FAILED",
+        Some(_) => "PENDING",
+    };
+    let next_attempt_at: Option<DateTime<Utc>> = (status == "PENDING").then(|| {
+        Utc::now() + chrono::Duration::from_std(config.backoff(attempts)).unwrap_or_else(|_| chrono::Duration::hours(6))
+    });
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"
+        UPDATE "WebhookDelivery"
+        SET status = $2, attempts = $3, "nextAttemptAt" = $4, "lastAttemptAt" = current_timestamp,
+            "responseStatus" = $5, "responseBody" = $6, "lastError" = $7, "durationMs" = $8
+        WHERE id = $1
+        "#,
+    )
+        .bind(delivery.id)
+        .bind(status)
+        .bind(attempts)
+        .bind(next_attempt_at)
+        .bind(response_status)
+        .bind(response_body)
+        .bind(&error)
+        .bind(elapsed.as_millis().min(i32::MAX as u128) as i32)
+        .execute(&mut *tx)
+        .await?;
+
+    if error.is_none() {
+        sqlx::query(r#"UPDATE "WebhookSubscription" SET "consecutiveFailures" = 0 WHERE id = $1"#)
+            .bind(delivery.subscription_id)
+            .execute(&mut *tx)
+            .await?;
+    } else {
+        let disabled: Option<bool> = sqlx::query_scalar(
+            r#"
+            UPDATE "WebhookSubscription"
+            SET "consecutiveFailures" = "consecutiveFailures" + 1,
+                status = CASE WHEN "consecutiveFailures" + 1 >= $2 THEN 'DISABLED' ELSE status END,
+                "disabledReason" = CASE WHEN "consecutiveFailures" + 1 >= $2 AND status = 'ACTIVE' THEN $3 ELSE "disabledReason" END,
+                "updatedAt" = current_timestamp
+            WHERE id = $1
+            RETURNING status = 'DISABLED'
+            "#,
+        )
+            .bind(delivery.subscription_id)
+            .bind(config.disable_after)
+            .bind(format!("Disabled after {} failed attempts in a row", config.disable_after))
+            .fetch_optional(&mut *tx)
+            .await?;
+        if disabled == Some(true) {
+            warn!("Webhook subscription {} is disabled after repeated failures", delivery.subscription_id);
+        }
+    }
+    tx.commit().await?;
+
+    if status == "FAILED" {
+        info!("Gave up on webhook delivery {} after {} attempts", delivery.id, attempts);
+    }
+    Ok(())
+}
+
+struct EndpointResponse {
+    status: u16,
+    body: String,
+}
+
+/**
+ * POST `body` as JSON and read the start of the answer. The endpoint is resolved and
+ * checked again here and the connection is made to the checked addresses, so a name that
+ * has since moved to a private address is not followed. Redirects are not followed either.
+ **/
+async fn post(url: &str, headers: &[(String, String)], body: String, config: &WebhookConfig) -> Result<EndpointResponse, String> {
+    let url = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    let addresses = resolve_endpoint(&url, config).await?;
+    let host = url.host_str().unwrap_or_default().to_string();
+    let client = reqwest::Client::builder()
+        .user_agent("planta-webhooks/0.1")
+        .redirect(redirect::Policy::none())
+        .timeout(config.timeout)
+        .resolve_to_addrs(&host, &addresses)
+        .build()
+        .map_err(|e| format!("Could not build HTTP client: {}", e))?;
+
+    let mut request = client.post(url).header(reqwest::header::CONTENT_TYPE, "application/json");
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let mut response = request.body(body).send().await.map_err(|e| format!("Error sending request: {}", e))?;
+
+    let status = response.status().as_u16();
+    let mut body = Vec::new();
+    // An answer cut short after the status line still counts; the body is only logged
+    while body.len() < RESPONSE_BODY_LIMIT {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) | Err(_) => break,
+        }
+    }
+    body.truncate(RESPONSE_BODY_LIMIT);
+    Ok(EndpointResponse {
+        status,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_public_refuses_internal_addresses() {
+        for internal in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public(internal.parse().unwrap()), "{}", internal);
+        }
+        for public in ["8.8.8.8", "102.89.1.1", "2001:4860:4860::8888", "::ffff:8.8.8.8"] {
+            assert!(is_public(public.parse().unwrap()), "{}", public);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn resolve_endpoint_refuses_private_hosts_unless_allowed() {
+        let config = WebhookConfig::default();
+        for url in ["http://127.0.0.1:8080/hook", "https://[::1]/hook", "http://169.254.169.254/latest/meta-data", "http://localhost/hook"] {
+            assert!(resolve_endpoint(&Url::parse(url).unwrap(), &config).await.is_err(), "{}", url);
+        }
+        assert!(resolve_endpoint(&Url::parse("ftp://8.8.8.8/hook").unwrap(), &config).await.is_err());
+
+        let addresses = resolve_endpoint(&Url::parse("https://8.8.8.8/hook").unwrap(), &config).await.unwrap();
+        assert_eq!(addresses, vec!["8.8.8.8:443".parse().unwrap()]);
+
+        let config = WebhookConfig { allow_private_hosts: true, ..WebhookConfig::default() };
+        let addresses = resolve_endpoint(&Url::parse("http://127.0.0.1:8080/hook").unwrap(), &config).await.unwrap();
+        assert_eq!(addresses, vec!["127.0.0.1:8080".parse().unwrap()]);
+    }
+}
@@ -0,0 +1,112 @@
+use actix_web::{test, web, App};
+use serde_json::json;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+// Runs against the database in DATABASE_URL and is skipped when it is unset
+async fn pool() -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    Some(PgPool::connect(&url).await.expect("could not connect to DATABASE_URL"))
+}
+
+async fn insert_user(pool: &PgPool, id: Uuid, first_name: &str) {
+    sqlx::query(r#"INSERT INTO "User" (id, "firstName", "lastName") VALUES ($1, $2, 'Merge Test')"#)
+        .bind(id)
+        .bind(first_name)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn merge_moves_the_merged_users_phone_to_the_survivor() {
+    let Some(pool) = pool().await else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+
+    let mut ids = [Uuid::new_v4(), Uuid::new_v4()];
+    ids.sort();
+    let (survivor_id, merged_id) = (ids[0], ids[1]);
+    let reviewer_id = Uuid::new_v4();
+    let phone = format!("+23480{:08}", merged_id.as_u128() % 100_000_000);
+
+    insert_user(&pool, survivor_id, "Survivor").await;
+    insert_user(&pool, merged_id, "Merged").await;
+    insert_user(&pool, reviewer_id, "Reviewer").await;
+    sqlx::query(r#"INSERT INTO "Profile" (id, "userId", bvn, gender) VALUES ($1, $2, '22345678902', 'MALE')"#)
+        .bind(Uuid::new_v4())
+        .bind(survivor_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO "Profile" (id, "userId", bvn, gender, "phoneNumber", "phoneCarrier", "phoneLineType")
+        VALUES ($1, $2, '22345678902', 'MALE', $3, 'MTN', 'MOBILE')
+        "#,
+    )
+        .bind(Uuid::new_v4())
+        .bind(merged_id)
+        .bind(&phone)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let candidate_id: Uuid = sqlx::query_scalar(
+        r#"INSERT INTO "DuplicateCandidate" ("userId", "otherUserId", score) VALUES ($1, $2, 0.9) RETURNING id"#,
+    )
+        .bind(survivor_id)
+        .bind(merged_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .configure(api_lib::duplicate::service),
+    )
+        .await;
+    let request = test::TestRequest::post()
+        .uri(&format!("/v0.1/duplicates/{}/merge", candidate_id))
+        .set_json(json!({ "survivorId": survivor_id, "reviewerId": reviewer_id }))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    let status = response.status();
+
+    let survivor: Option<(Option<String>, Option<String>, Option<String>)> = sqlx::query_as(
+        r#"SELECT "phoneNumber", "phoneCarrier", "phoneLineType" FROM "Profile" WHERE "userId" = $1"#,
+    )
+        .bind(survivor_id)
+        .fetch_optional(&pool)
+        .await
+        .unwrap();
+    let merged_left: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "User" WHERE id = $1"#)
+        .bind(merged_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    sqlx::query(r#"DELETE FROM "UserMerge" WHERE "survivorId" = $1"#)
+        .bind(survivor_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(r#"DELETE FROM "Profile" WHERE "userId" = ANY($1)"#)
+        .bind(vec![survivor_id, merged_id])
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(r#"DELETE FROM "User" WHERE id = ANY($1)"#)
+        .bind(vec![survivor_id, merged_id, reviewer_id])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(status, actix_web::http::StatusCode::CREATED);
+    assert_eq!(merged_left, 0);
+    assert_eq!(
+        survivor,
+        Some((Some(phone), Some("MTN".to_string()), Some("MOBILE".to_string()))),
+    );
+}
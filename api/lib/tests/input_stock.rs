@@ -0,0 +1,98 @@
+use actix_web::{test, web, App};
+use serde_json::{json, Value};
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+// Runs against the database in DATABASE_URL and is skipped when it is unset
+async fn pool() -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    Some(PgPool::connect(&url).await.expect("could not connect to DATABASE_URL"))
+}
+
+#[actix_web::test]
+async fn applications_draw_down_stock_and_cannot_exceed_it() {
+    let Some(pool) = pool().await else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+
+    let farmer_id = Uuid::new_v4();
+    let farm_id = Uuid::new_v4();
+    sqlx::query(r#"INSERT INTO "User" (id, "firstName", "lastName") VALUES ($1, 'Stock', 'Test')"#)
+        .bind(farmer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(r#"INSERT INTO "Farm" (id, "farmerId", acreage) VALUES ($1, $2, 10)"#)
+        .bind(farm_id)
+        .bind(farmer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let product_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO "InputProduct" (name, category, unit, "recommendedRatePerHectare")
+        VALUES ($1, 'FERTILIZER', 'KG', 100)
+        RETURNING id
+        "#,
+    )
+        .bind(format!("Stock test {}", farm_id))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .configure(api_lib::input::service),
+    )
+        .await;
+    let mut statuses = Vec::new();
+    for (kind, quantity) in [("PURCHASE", 150.0), ("APPLICATION", 100.0), ("APPLICATION", 60.0)] {
+        let mut body = json!({
+            "productId": product_id,
+            "kind": kind,
+            "quantity": quantity,
+            "transactionDate": "2024-05-01",
+        });
+        if kind == "APPLICATION" {
+            body["appliedArea"] = json!({ "value": 1.0, "unit": "hectare" });
+        }
+        let request = test::TestRequest::post()
+            .uri(&format!("/v0.1/farms/{}/inputs", farm_id))
+            .set_json(body)
+            .to_request();
+        statuses.push(test::call_service(&app, request).await.status().as_u16());
+    }
+    let request = test::TestRequest::get()
+        .uri(&format!("/v0.1/farms/{}/inputs/balance", farm_id))
+        .to_request();
+    let balances: Value = test::call_and_read_body_json(&app, request).await;
+
+    sqlx::query(r#"DELETE FROM "InputTransaction" WHERE "farmId" = $1"#)
+        .bind(farm_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(r#"DELETE FROM "InputProduct" WHERE id = $1"#)
+        .bind(product_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(r#"DELETE FROM "Farm" WHERE id = $1"#)
+        .bind(farm_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(r#"DELETE FROM "User" WHERE id = $1"#)
+        .bind(farmer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // The second application needs 60 kg with only 50 kg left
+    assert_eq!(statuses, vec![201, 201, 400]);
+    assert_eq!(balances[0]["received"], json!(150.0));
+    assert_eq!(balances[0]["applied"], json!(100.0));
+    assert_eq!(balances[0]["balance"], json!(50.0));
+}
@@ -0,0 +1,300 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use actix_web::{dev::ServerHandle, test, web, App, HttpRequest, HttpResponse, HttpServer};
+use api_lib::webhook::{self, WebhookConfig};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+// Runs against the database in DATABASE_URL and is skipped when it is unset. Delivery picks up
+// every due delivery, so no API instance should be delivering from the same database.
+const SECRET: &str = "whsec_integration_test_secret";
+
+#[derive(Clone)]
+struct Received {
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+// Answers with the queued statuses in turn, then 200, and keeps every request it gets
+#[derive(Clone, Default)]
+struct Receiver {
+    statuses: Arc<Mutex<VecDeque<u16>>>,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl Receiver {
+    fn answer_with(&self, statuses: &[u16]) {
+        self.statuses.lock().unwrap().extend(statuses);
+    }
+
+    fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn receive(receiver: web::Data<Receiver>, request: HttpRequest, body: String) -> HttpResponse {
+    let headers = request.headers().iter()
+        .map(|(name, value)| (name.as_str().to_string(), value.to_str().unwrap_or_default().to_string()))
+        .collect();
+    receiver.received.lock().unwrap().push(Received { headers, body });
+    let status = receiver.statuses.lock().unwrap().pop_front().unwrap_or(200);
+    HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap()).body("received")
+}
+
+fn start_receiver() -> (Receiver, String, ServerHandle) {
+    let receiver = Receiver::default();
+    let state = receiver.clone();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .default_service(web::to(receive))
+    })
+        .workers(1)
+        .listen(listener)
+        .unwrap()
+        .run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+    (receiver, url, handle)
+}
+
+fn config() -> WebhookConfig {
+    WebhookConfig {
+        timeout: Duration::from_secs(5),
+        max_attempts: 5,
+        backoff_base: Duration::from_secs(60),
+        disable_after: 3,
+        allow_private_hosts: true,
+        ..WebhookConfig::default()
+    }
+}
+
+// Tests share the database and each delivers everything due, so they run one at a time
+async fn exclusive() -> tokio::sync::MutexGuard<'static, ()> {
+    static LOCK: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| tokio::sync::Mutex::new(())).lock().await
+}
+
+async fn pool() -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    // The receiver listens on loopback
+    std::env::set_var("WEBHOOK_ALLOW_PRIVATE_HOSTS", "true");
+    Some(PgPool::connect(&url).await.expect("could not connect to DATABASE_URL"))
+}
+
+macro_rules! app {
+    ($pool:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($pool.clone()))
+                .configure(webhook::service),
+        )
+            .await
+    };
+}
+
+fn create_subscription(url: &str) -> test::TestRequest {
+    test::TestRequest::post()
+        .uri("/v0.1/webhooks")
+        .set_json(json!({ "url": url, "eventTypes": ["UserCreated"], "secret": SECRET }))
+}
+
+fn id(created: &Value) -> Uuid {
+    created["id"].as_str().unwrap().parse().unwrap()
+}
+
+async fn queue_delivery(pool: &PgPool, subscription_id: Uuid) -> Uuid {
+    let event_id = Uuid::new_v4();
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO "WebhookDelivery" ("subscriptionId", "eventId", "eventType", payload, "nextAttemptAt")
+        VALUES ($1, $2, 'UserCreated', $3, current_timestamp)
+        RETURNING id
+        "#,
+    )
+        .bind(subscription_id)
+        .bind(event_id)
+        .bind(json!({ "id": event_id, "type": "UserCreated", "data": { "firstName": "Amina" } }))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn make_due(pool: &PgPool, delivery_id: Uuid) {
+    sqlx::query(r#"UPDATE "WebhookDelivery" SET "nextAttemptAt" = current_timestamp WHERE id = $1"#)
+        .bind(delivery_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+async fn delivery_state(pool: &PgPool, delivery_id: Uuid) -> (String, i32, Option<i32>, Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+    sqlx::query_as(r#"SELECT status, attempts, "responseStatus", "nextAttemptAt", "lastAttemptAt" FROM "WebhookDelivery" WHERE id = $1"#)
+        .bind(delivery_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn delete_subscription(pool: &PgPool, subscription_id: Uuid) {
+    sqlx::query(r#"DELETE FROM "WebhookSubscription" WHERE id = $1"#)
+        .bind(subscription_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn deliveries_are_signed_with_the_subscription_secret() {
+    let Some(pool) = pool().await else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let _guard = exclusive().await;
+    let (receiver, url, server) = start_receiver();
+    let app = app!(pool);
+    let subscription_id = id(&test::call_and_read_body_json(&app, create_subscription(&url).to_request()).await);
+    let delivery_id = queue_delivery(&pool, subscription_id).await;
+
+    webhook::deliver_due(&pool, &config()).await.unwrap();
+    let received = receiver.received();
+    let (status, attempts, response_status, _, _) = delivery_state(&pool, delivery_id).await;
+    delete_subscription(&pool, subscription_id).await;
+    server.stop(false).await;
+
+    assert_eq!(received.len(), 1);
+    let request = &received[0];
+    assert_eq!(request.headers["x-planta-event"], "UserCreated");
+    assert_eq!(request.headers["x-planta-delivery"], delivery_id.to_string());
+    assert_eq!(request.headers["content-type"], "application/json");
+    let header = &request.headers["x-planta-signature"];
+    let timestamp: i64 = header.strip_prefix("t=").and_then(|rest| rest.split(',').next()).unwrap().parse().unwrap();
+    assert!((Utc::now().timestamp() - timestamp).abs() < 60);
+    assert_eq!(header, &webhook::signature(SECRET, timestamp, &request.body));
+    assert_ne!(header, &webhook::signature("some other secret", timestamp, &request.body));
+    assert_eq!(serde_json::from_str::<Value>(&request.body).unwrap()["data"]["firstName"], "Amina");
+    assert_eq!((status.as_str(), attempts, response_status), ("SUCCEEDED", 1, Some(200)));
+}
+
+#[actix_web::test]
+async fn failed_deliveries_are_retried_after_the_backoff() {
+    let Some(pool) = pool().await else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let _guard = exclusive().await;
+    let (receiver, url, server) = start_receiver();
+    receiver.answer_with(&[500]);
+    let app = app!(pool);
+    let subscription_id = id(&test::call_and_read_body_json(&app, create_subscription(&url).to_request()).await);
+    let delivery_id = queue_delivery(&pool, subscription_id).await;
+    let config = config();
+
+    webhook::deliver_due(&pool, &config).await.unwrap();
+    let after_failure = delivery_state(&pool, delivery_id).await;
+    // Not due yet, so nothing is sent
+    webhook::deliver_due(&pool, &config).await.unwrap();
+    let sent_before_due = receiver.received().len();
+    make_due(&pool, delivery_id).await;
+    webhook::deliver_due(&pool, &config).await.unwrap();
+    let after_retry = delivery_state(&pool, delivery_id).await;
+    let failures: i32 = sqlx::query_scalar(r#"SELECT "consecutiveFailures" FROM "WebhookSubscription" WHERE id = $1"#)
+        .bind(subscription_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let received = receiver.received();
+    delete_subscription(&pool, subscription_id).await;
+    server.stop(false).await;
+
+    let (status, attempts, response_status, next_attempt_at, last_attempt_at) = after_failure;
+    assert_eq!((status.as_str(), attempts, response_status), ("PENDING", 1, Some(500)));
+    let wait = next_attempt_at.unwrap() - last_attempt_at.unwrap();
+    assert!((wait - chrono::Duration::seconds(60)).num_seconds().abs() <= 5, "waited {}", wait);
+    assert_eq!(sent_before_due, 1);
+
+    let (status, attempts, response_status, next_attempt_at, _) = after_retry;
+    assert_eq!((status.as_str(), attempts, response_status, next_attempt_at), ("SUCCEEDED", 2, Some(200), None));
+    assert_eq!(failures, 0);
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].headers["x-planta-delivery"], received[1].headers["x-planta-delivery"]);
+}
+
+#[actix_web::test]
+async fn endpoints_are_disabled_after_repeated_failures() {
+    let Some(pool) = pool().await else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let _guard = exclusive().await;
+    let (receiver, url, server) = start_receiver();
+    receiver.answer_with(&[500, 503, 500, 500]);
+    let app = app!(pool);
+    let subscription_id = id(&test::call_and_read_body_json(&app, create_subscription(&url).to_request()).await);
+    let delivery_id = queue_delivery(&pool, subscription_id).await;
+    let config = config();
+
+    for _ in 0..config.disable_after {
+        make_due(&pool, delivery_id).await;
+        webhook::deliver_due(&pool, &config).await.unwrap();
+    }
+    make_due(&pool, delivery_id).await;
+    webhook::deliver_due(&pool, &config).await.unwrap();
+    let (status, reason): (String, Option<String>) =
+        sqlx::query_as(r#"SELECT status, "disabledReason" FROM "WebhookSubscription" WHERE id = $1"#)
+            .bind(subscription_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let (_, attempts, _, _, _) = delivery_state(&pool, delivery_id).await;
+    let sent = receiver.received().len();
+    delete_subscription(&pool, subscription_id).await;
+    server.stop(false).await;
+
+    assert_eq!(status, "DISABLED");
+    assert_eq!(reason.as_deref(), Some("Disabled after 3 failed attempts in a row"));
+    // Nothing more is sent once the endpoint is disabled
+    assert_eq!(attempts, config.disable_after);
+    assert_eq!(sent, config.disable_after as usize);
+}
+
+#[actix_web::test]
+async fn replayed_deliveries_are_sent_again_with_the_same_event_id() {
+    let Some(pool) = pool().await else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let _guard = exclusive().await;
+    let (receiver, url, server) = start_receiver();
+    let app = app!(pool);
+    let subscription_id = id(&test::call_and_read_body_json(&app, create_subscription(&url).to_request()).await);
+    let delivery_id = queue_delivery(&pool, subscription_id).await;
+    let config = config();
+
+    webhook::deliver_due(&pool, &config).await.unwrap();
+    let request = test::TestRequest::post()
+        .uri(&format!("/v0.1/webhooks/{}/deliveries/{}/replay", subscription_id, delivery_id))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    let replay_status = response.status();
+    let replay: Value = test::read_body_json(response).await;
+    let replay_id = id(&replay);
+    webhook::deliver_due(&pool, &config).await.unwrap();
+    let (status, attempts, _, _, _) = delivery_state(&pool, replay_id).await;
+    let received = receiver.received();
+    delete_subscription(&pool, subscription_id).await;
+    server.stop(false).await;
+
+    assert_eq!(replay_status, actix_web::http::StatusCode::CREATED);
+    assert_eq!(replay["replayOf"], delivery_id.to_string());
+    assert_eq!((status.as_str(), attempts), ("SUCCEEDED", 1));
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].headers["x-planta-event-id"], received[1].headers["x-planta-event-id"]);
+    assert_eq!(received[1].headers["x-planta-delivery"], replay_id.to_string());
+    assert_eq!(received[0].body, received[1].body);
+}
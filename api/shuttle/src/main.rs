@@ -1,7 +1,6 @@
 use actix_web::{web, App, HttpServer, middleware};
 use sqlx::postgres::PgPoolOptions;
-use redis::aio::Connection;
-use std::sync::Arc; // Import Arc for shared references
+use deadpool_redis::{Config as RedisConfig, Runtime};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -16,22 +15,81 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to create pool.");
 
-    let redis_client = redis::Client::open(redis_url).expect("Failed to create Redis client");
-    let redis_connection: Arc<Connection> = Arc::new(
-        redis_client
-            .get_tokio_connection()
-            .await
-            .expect("Failed to connect to Redis"),
-    );
+    let redis_pool = RedisConfig::from_url(redis_url)
+        .create_pool(Some(Runtime::Tokio1))
+        .expect("Failed to create Redis pool");
+
+    actix_web::rt::spawn(api_lib::outbreak::run(
+        pool.clone(),
+        redis_pool.clone(),
+        api_lib::outbreak::OutbreakConfig::from_env(),
+    ));
+
+    actix_web::rt::spawn(api_lib::outbox::run(
+        pool.clone(),
+        redis_pool.clone(),
+        api_lib::outbox::OutboxConfig::from_env(),
+    ));
+
+    actix_web::rt::spawn(api_lib::webhook::run(
+        pool.clone(),
+        redis_pool.clone(),
+        api_lib::webhook::WebhookConfig::from_env(),
+    ));
+
+    let event_hub = api_lib::events::EventHub::new(api_lib::events::EventsConfig::from_env());
+    actix_web::rt::spawn(api_lib::events::run(redis_pool.clone(), event_hub.clone()));
+
+    actix_web::rt::spawn(api_lib::phone::backfill(
+        pool.clone(),
+        api_lib::phone::PhoneConfig::from_env(),
+    ));
+
+    actix_web::rt::spawn(api_lib::reference::load_lgas(
+        pool.clone(),
+        redis_pool.clone(),
+    ));
+
+    let identity_provider = api_lib::identity::provider_from_env();
 
     HttpServer::new(move || {
         App::new()
             .wrap(middleware::Compress::default())
             .app_data(web::Data::new(pool.clone()))
-            .app_data(web::Data::new(redis_connection.clone()))
+            .app_data(web::Data::new(redis_pool.clone()))
+            .app_data(web::Data::from(identity_provider.clone()))
+            .app_data(web::Data::new(event_hub.clone()))
             .configure(api_lib::user::service)
             .configure(api_lib::profile::service)
+            // Nested farm scopes must be registered before the farm scope
+            .configure(api_lib::plot::service)
+            .configure(api_lib::planting::service)
+            .configure(api_lib::harvest::service)
+            .configure(api_lib::soil::service)
+            .configure(api_lib::weather::service)
+            .configure(api_lib::irrigation::service)
+            .configure(api_lib::incident::service)
+            .configure(api_lib::input::service)
+            .configure(api_lib::livestock::service)
+            // Nested agent scopes must be registered before the agent scope
+            .configure(api_lib::sync::service)
+            .configure(api_lib::agent::service)
+            .configure(api_lib::lease::service)
+            .configure(api_lib::title::service)
+            .configure(api_lib::history::service)
             .configure(api_lib::farm::service)
+            .configure(api_lib::reference::service)
+            .configure(api_lib::money::service)
+            .configure(api_lib::crop::service)
+            .configure(api_lib::analytics::service)
+            .configure(api_lib::outbreak::service)
+            .configure(api_lib::cooperative::service)
+            .configure(api_lib::kyc::service)
+            .configure(api_lib::duplicate::service)
+            .configure(api_lib::search::service)
+            .configure(api_lib::webhook::service)
+            .configure(api_lib::api_key::service)
+            .configure(api_lib::events::service)
     })
         .bind("0.0.0.0:8080")?
         .run()
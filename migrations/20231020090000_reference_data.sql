@@ -0,0 +1,119 @@
+CREATE TABLE "Country" (
+                           "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                           "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                           "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                           "code" CHAR(2) NOT NULL,
+                           "code3" CHAR(3) NOT NULL,
+                           "name" VARCHAR(191) NOT NULL,
+                           UNIQUE ("code"),
+                           UNIQUE ("code3")
+);
+
+CREATE TABLE "State" (
+                         "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                         "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                         "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                         "code" VARCHAR(6) NOT NULL,
+                         "name" VARCHAR(191) NOT NULL,
+                         "countryId" UUID NOT NULL,
+                         UNIQUE ("countryId", "code"),
+                         UNIQUE ("countryId", "name"),
+                         FOREIGN KEY ("countryId") REFERENCES "Country" ("id")
+);
+
+CREATE TABLE "Lga" (
+                       "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                       "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                       "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                       "name" VARCHAR(191) NOT NULL,
+                       "stateId" UUID NOT NULL,
+                       UNIQUE ("stateId", "name"),
+                       FOREIGN KEY ("stateId") REFERENCES "State" ("id")
+);
+
+CREATE INDEX idx_state_countryId ON "State" ("countryId");
+CREATE INDEX idx_lga_stateId ON "Lga" ("stateId");
+
+INSERT INTO "Country" ("code", "code3", "name") VALUES
+    ('NG', 'NGA', 'Nigeria'),
+    ('GH', 'GHA', 'Ghana'),
+    ('BJ', 'BEN', 'Benin'),
+    ('NE', 'NER', 'Niger'),
+    ('CM', 'CMR', 'Cameroon'),
+    ('TD', 'TCD', 'Chad');
+
+INSERT INTO "State" ("code", "name", "countryId")
+SELECT s.code, s.name, c.id
+FROM (VALUES
+    ('AB', 'Abia'), ('AD', 'Adamawa'), ('AK', 'Akwa Ibom'), ('AN', 'Anambra'),
+    ('BA', 'Bauchi'), ('BY', 'Bayelsa'), ('BE', 'Benue'), ('BO', 'Borno'),
+    ('CR', 'Cross River'), ('DE', 'Delta'), ('EB', 'Ebonyi'), ('ED', 'Edo'),
+    ('EK', 'Ekiti'), ('EN', 'Enugu'), ('FC', 'Federal Capital Territory'), ('GO', 'Gombe'),
+    ('IM', 'Imo'), ('JI', 'Jigawa'), ('KD', 'Kaduna'), ('KN', 'Kano'),
+    ('KT', 'Katsina'), ('KE', 'Kebbi'), ('KO', 'Kogi'), ('KW', 'Kwara'),
+    ('LA', 'Lagos'), ('NA', 'Nasarawa'), ('NI', 'Niger'), ('OG', 'Ogun'),
+    ('ON', 'Ondo'), ('OS', 'Osun'), ('OY', 'Oyo'), ('PL', 'Plateau'),
+    ('RI', 'Rivers'), ('SO', 'Sokoto'), ('TA', 'Taraba'), ('YO', 'Yobe'),
+    ('ZA', 'Zamfara')
+) AS s(code, name)
+CROSS JOIN "Country" c
+WHERE c.code = 'NG';
+
+-- LGAs are not seeded here. Load them per state before relying on "lgaId";
+-- the normalisation below only links localities that already have a match.
+
+ALTER TABLE "Farm"
+    ADD COLUMN "countryId" UUID REFERENCES "Country" ("id"),
+    ADD COLUMN "stateId" UUID REFERENCES "State" ("id"),
+    ADD COLUMN "lgaId" UUID REFERENCES "Lga" ("id");
+
+CREATE INDEX idx_farm_countryId ON "Farm" ("countryId");
+CREATE INDEX idx_farm_stateId ON "Farm" ("stateId");
+CREATE INDEX idx_farm_lgaId ON "Farm" ("lgaId");
+
+-- Normalise the existing free-text values onto the reference tables
+UPDATE "Farm" f
+SET "countryId" = c.id
+FROM "Country" c
+WHERE lower(trim(f.country)) IN (lower(c.name), lower(c.code), lower(c.code3))
+   OR (c.code = 'NG' AND lower(trim(f.country)) = 'nigerian');
+
+UPDATE "Farm" f
+SET "stateId" = s.id,
+    "countryId" = COALESCE(f."countryId", s."countryId")
+FROM "State" s
+WHERE (f."countryId" IS NULL OR f."countryId" = s."countryId")
+  AND (
+        lower(regexp_replace(trim(f.state), '\s+state$', '', 'i')) = lower(s.name)
+     OR upper(trim(f.state)) = s.code
+     OR (s.code = 'FC' AND lower(trim(f.state)) IN ('fct', 'abuja', 'fct abuja', 'abuja fct'))
+  );
+
+UPDATE "Farm" f
+SET "lgaId" = l.id
+FROM "Lga" l
+WHERE f."stateId" = l."stateId"
+  AND lower(regexp_replace(trim(f.locality), '\s+(lga|local government( area)?)$', '', 'i')) = lower(l.name);
+
+-- Rows whose free-text value could not be linked to a reference row
+CREATE VIEW "FarmReferenceUnmatched" AS
+SELECT f.id AS "farmId", 'country' AS "field", f.country AS "value"
+FROM "Farm" f
+WHERE f."countryId" IS NULL AND f.country IS NOT NULL AND trim(f.country) <> ''
+UNION ALL
+SELECT f.id, 'state', f.state
+FROM "Farm" f
+WHERE f."stateId" IS NULL AND f.state IS NOT NULL AND trim(f.state) <> ''
+UNION ALL
+SELECT f.id, 'locality', f.locality
+FROM "Farm" f
+WHERE f."lgaId" IS NULL AND f.locality IS NOT NULL AND trim(f.locality) <> '';
+
+DO $$
+DECLARE
+    r RECORD;
+BEGIN
+    FOR r IN SELECT "field", COUNT(*) AS total FROM "FarmReferenceUnmatched" GROUP BY "field" LOOP
+        RAISE NOTICE 'Farm reference normalisation: % unmatched % value(s)', r.total, r.field;
+    END LOOP;
+END $$;
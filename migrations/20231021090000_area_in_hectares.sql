@@ -0,0 +1,7 @@
+-- Areas were captured in acres (hence "acreage"); store them in hectares from now on
+UPDATE "Farm"
+SET "acreage" = "acreage" * 0.40468564224,
+    "available_portion" = "available_portion" * 0.40468564224;
+
+COMMENT ON COLUMN "Farm"."acreage" IS 'Farm area in hectares';
+COMMENT ON COLUMN "Farm"."available_portion" IS 'Unused farm area in hectares';
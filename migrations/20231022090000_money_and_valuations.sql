@@ -0,0 +1,54 @@
+CREATE TYPE money_value AS (
+    "amount" NUMERIC(20, 2),
+    "currency" VARCHAR(3)
+);
+
+-- Existing land values were whole naira
+ALTER TABLE "Farm"
+    ALTER COLUMN "land_value" TYPE money_value
+        USING CASE
+            WHEN "land_value" IS NULL THEN NULL
+            ELSE ROW("land_value"::NUMERIC(20, 2), 'NGN')::money_value
+        END;
+
+CREATE TABLE "ExchangeRate" (
+                                "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                "baseCurrency" VARCHAR(3) NOT NULL,
+                                "quoteCurrency" VARCHAR(3) NOT NULL,
+                                "rate" NUMERIC(20, 10) NOT NULL CHECK ("rate" > 0),
+                                "effectiveDate" DATE NOT NULL,
+                                UNIQUE ("baseCurrency", "quoteCurrency", "effectiveDate")
+);
+
+CREATE INDEX idx_exchange_rate_pair ON "ExchangeRate" ("baseCurrency", "quoteCurrency", "effectiveDate" DESC);
+
+CREATE TABLE "FarmValuation" (
+                                 "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                 "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                 "farmId" UUID NOT NULL,
+                                 "value" money_value NOT NULL,
+                                 FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_farm_valuation_farmId ON "FarmValuation" ("farmId", "createdAt");
+
+INSERT INTO "FarmValuation" ("farmId", "value", "createdAt")
+SELECT "id", "land_value", "updatedAt"
+FROM "Farm"
+WHERE "land_value" IS NOT NULL;
+
+-- Every change to a farm's land value is kept in "FarmValuation"
+CREATE FUNCTION record_farm_valuation() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW."land_value" IS NOT NULL
+        AND (TG_OP = 'INSERT' OR NEW."land_value" IS DISTINCT FROM OLD."land_value") THEN
+        INSERT INTO "FarmValuation" ("farmId", "value") VALUES (NEW."id", NEW."land_value");
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_farm_valuation
+    AFTER INSERT OR UPDATE OF "land_value" ON "Farm"
+    FOR EACH ROW EXECUTE FUNCTION record_farm_valuation();
@@ -0,0 +1,21 @@
+-- GeoJSON polygon of the farm, used to check plot geometry
+ALTER TABLE "Farm" ADD COLUMN "boundary" JSONB;
+
+CREATE TABLE "Plot" (
+                        "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                        "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                        "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                        "farmId" UUID NOT NULL,
+                        "name" VARCHAR(191) NOT NULL,
+                        "boundary" JSONB,
+                        "area" DOUBLE PRECISION NOT NULL CHECK ("area" >= 0),
+                        "soilType" VARCHAR(191),
+                        "is_irrigated" BOOLEAN NOT NULL DEFAULT FALSE,
+                        "has_drainage_tile" BOOLEAN NOT NULL DEFAULT FALSE,
+                        UNIQUE ("farmId", "name"),
+                        FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_plot_farmId ON "Plot" ("farmId");
+
+COMMENT ON COLUMN "Plot"."area" IS 'Plot area in hectares';
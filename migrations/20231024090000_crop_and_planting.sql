@@ -0,0 +1,47 @@
+CREATE TABLE "Crop" (
+                        "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                        "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                        "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                        "name" VARCHAR(191) NOT NULL,
+                        "scientificName" VARCHAR(191),
+                        UNIQUE ("name")
+);
+
+CREATE TABLE "CropVariety" (
+                               "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                               "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                               "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                               "cropId" UUID NOT NULL,
+                               "name" VARCHAR(191) NOT NULL,
+                               "cycleDays" INT NOT NULL CHECK ("cycleDays" > 0),
+                               "expectedYieldKgPerHectare" DOUBLE PRECISION NOT NULL CHECK ("expectedYieldKgPerHectare" >= 0),
+                               UNIQUE ("cropId", "name"),
+                               FOREIGN KEY ("cropId") REFERENCES "Crop" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_crop_variety_cropId ON "CropVariety" ("cropId");
+
+CREATE TABLE "Planting" (
+                            "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                            "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                            "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                            "farmId" UUID NOT NULL,
+                            "plotId" UUID,
+                            "cropId" UUID NOT NULL,
+                            "varietyId" UUID,
+                            "plantedArea" DOUBLE PRECISION NOT NULL CHECK ("plantedArea" > 0),
+                            "plantingDate" DATE NOT NULL,
+                            "expectedHarvestDate" DATE,
+                            "season" VARCHAR(3) NOT NULL CHECK ("season" IN ('WET', 'DRY')),
+                            "seasonYear" INT NOT NULL,
+                            FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                            FOREIGN KEY ("plotId") REFERENCES "Plot" ("id") ON DELETE SET NULL,
+                            FOREIGN KEY ("cropId") REFERENCES "Crop" ("id"),
+                            FOREIGN KEY ("varietyId") REFERENCES "CropVariety" ("id")
+);
+
+CREATE INDEX idx_planting_farmId ON "Planting" ("farmId");
+CREATE INDEX idx_planting_cropId ON "Planting" ("cropId");
+CREATE INDEX idx_planting_season ON "Planting" ("seasonYear", "season");
+
+COMMENT ON COLUMN "Planting"."plantedArea" IS 'Planted area in hectares';
@@ -0,0 +1,19 @@
+CREATE TABLE "Harvest" (
+                           "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                           "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                           "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                           "farmId" UUID NOT NULL,
+                           "plantingId" UUID NOT NULL,
+                           "quantity" DOUBLE PRECISION NOT NULL CHECK ("quantity" >= 0),
+                           "unit" VARCHAR(16) NOT NULL,
+                           "quantityKg" DOUBLE PRECISION NOT NULL CHECK ("quantityKg" >= 0),
+                           "postHarvestLossKg" DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK ("postHarvestLossKg" >= 0),
+                           "qualityGrade" VARCHAR(16),
+                           "harvestDate" DATE NOT NULL,
+                           CHECK ("postHarvestLossKg" <= "quantityKg"),
+                           FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                           FOREIGN KEY ("plantingId") REFERENCES "Planting" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_harvest_farmId ON "Harvest" ("farmId");
+CREATE INDEX idx_harvest_plantingId ON "Harvest" ("plantingId");
@@ -0,0 +1,57 @@
+CREATE TABLE "SoilSample" (
+                              "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                              "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                              "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                              "farmId" UUID NOT NULL,
+                              "plotId" UUID,
+                              "sampledOn" DATE NOT NULL,
+                              "labReference" VARCHAR(191),
+                              "ph" DOUBLE PRECISION CHECK ("ph" BETWEEN 0 AND 14),
+                              "nitrogen" DOUBLE PRECISION CHECK ("nitrogen" >= 0),
+                              "phosphorus" DOUBLE PRECISION CHECK ("phosphorus" >= 0),
+                              "potassium" DOUBLE PRECISION CHECK ("potassium" >= 0),
+                              "organicMatter" DOUBLE PRECISION CHECK ("organicMatter" >= 0),
+                              "texture" VARCHAR(32),
+                              "latitude" DOUBLE PRECISION,
+                              "longitude" DOUBLE PRECISION,
+                              FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                              FOREIGN KEY ("plotId") REFERENCES "Plot" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_soil_sample_farmId ON "SoilSample" ("farmId", "sampledOn");
+
+COMMENT ON COLUMN "SoilSample"."nitrogen" IS 'Total nitrogen, %';
+COMMENT ON COLUMN "SoilSample"."phosphorus" IS 'Available phosphorus (Bray-1), mg/kg';
+COMMENT ON COLUMN "SoilSample"."potassium" IS 'Exchangeable potassium, cmol/kg';
+COMMENT ON COLUMN "SoilSample"."organicMatter" IS 'Organic matter, %';
+
+-- A rule applies when the sample's value for "nutrient" is in ["minValue", "maxValue").
+-- Crop-specific rules take precedence over rules with no crop.
+CREATE TABLE "FertilizerRule" (
+                                  "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                  "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                  "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                  "cropId" UUID,
+                                  "nutrient" VARCHAR(16) NOT NULL CHECK ("nutrient" IN ('ph', 'nitrogen', 'phosphorus', 'potassium', 'organic_matter')),
+                                  "minValue" DOUBLE PRECISION,
+                                  "maxValue" DOUBLE PRECISION,
+                                  "fertilizer" VARCHAR(191) NOT NULL,
+                                  "rateKgPerHectare" DOUBLE PRECISION NOT NULL CHECK ("rateKgPerHectare" >= 0),
+                                  "priority" INT NOT NULL DEFAULT 0,
+                                  "notes" TEXT,
+                                  CHECK ("minValue" IS NULL OR "maxValue" IS NULL OR "minValue" < "maxValue"),
+                                  FOREIGN KEY ("cropId") REFERENCES "Crop" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_fertilizer_rule_lookup ON "FertilizerRule" ("nutrient", "cropId");
+
+-- Generic starting points for savanna soils, to be tuned by agronomists
+INSERT INTO "FertilizerRule" ("nutrient", "minValue", "maxValue", "fertilizer", "rateKgPerHectare", "notes") VALUES
+    ('ph', NULL, 5.5, 'Agricultural lime', 1000, 'Acidic soil'),
+    ('nitrogen', NULL, 0.1, 'Urea (46% N)', 130, 'Low nitrogen'),
+    ('nitrogen', 0.1, 0.2, 'Urea (46% N)', 65, 'Medium nitrogen'),
+    ('phosphorus', NULL, 10, 'Single superphosphate (18% P2O5)', 250, 'Low phosphorus'),
+    ('phosphorus', 10, 20, 'Single superphosphate (18% P2O5)', 125, 'Medium phosphorus'),
+    ('potassium', NULL, 0.2, 'Muriate of potash (60% K2O)', 100, 'Low potassium'),
+    ('potassium', 0.2, 0.4, 'Muriate of potash (60% K2O)', 50, 'Medium potassium'),
+    ('organic_matter', NULL, 1.5, 'Compost or farmyard manure', 5000, 'Low organic matter');
@@ -0,0 +1,35 @@
+CREATE TABLE "WeatherStation" (
+                                  "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                  "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                  "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                  "code" VARCHAR(191) NOT NULL,
+                                  "name" VARCHAR(191),
+                                  "source" VARCHAR(8) NOT NULL CHECK ("source" IN ('station', 'grid')),
+                                  "latitude" DOUBLE PRECISION NOT NULL,
+                                  "longitude" DOUBLE PRECISION NOT NULL,
+                                  UNIQUE ("code")
+);
+
+CREATE INDEX idx_weather_station_location ON "WeatherStation" ("latitude", "longitude");
+
+CREATE TABLE "WeatherObservation" (
+                                      "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                      "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                      "stationId" UUID NOT NULL,
+                                      "observedAt" TIMESTAMPTZ NOT NULL,
+                                      "rainfallMm" DOUBLE PRECISION CHECK ("rainfallMm" >= 0),
+                                      "tempMinC" DOUBLE PRECISION,
+                                      "tempMaxC" DOUBLE PRECISION,
+                                      UNIQUE ("stationId", "observedAt"),
+                                      FOREIGN KEY ("stationId") REFERENCES "WeatherStation" ("id") ON DELETE CASCADE
+);
+
+CREATE TABLE "WeatherImport" (
+                                 "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                 "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                 "fileName" VARCHAR(191),
+                                 "format" VARCHAR(8) NOT NULL,
+                                 "stations" INT NOT NULL,
+                                 "rowsImported" INT NOT NULL,
+                                 "rowsSkipped" INT NOT NULL
+);
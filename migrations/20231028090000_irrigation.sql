@@ -0,0 +1,41 @@
+-- Peak daily crop water use (ETc); used by the irrigation water balance
+ALTER TABLE "Crop" ADD COLUMN "waterRequirementMmPerDay" DOUBLE PRECISION CHECK ("waterRequirementMmPerDay" >= 0);
+
+CREATE TABLE "IrrigationSystem" (
+                                    "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                    "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                    "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                    "farmId" UUID NOT NULL,
+                                    "name" VARCHAR(191),
+                                    "systemType" VARCHAR(16) NOT NULL CHECK ("systemType" IN ('DRIP', 'SPRINKLER', 'CENTER_PIVOT', 'FURROW', 'FLOOD', 'MANUAL')),
+                                    "waterSource" VARCHAR(16) NOT NULL CHECK ("waterSource" IN ('BOREHOLE', 'WELL', 'RIVER', 'DAM', 'RESERVOIR', 'MUNICIPAL', 'RAINWATER')),
+                                    "capacityLitresPerHour" DOUBLE PRECISION NOT NULL CHECK ("capacityLitresPerHour" > 0),
+                                    "efficiency" DOUBLE PRECISION NOT NULL CHECK ("efficiency" > 0 AND "efficiency" <= 1),
+                                    "installedOn" DATE,
+                                    FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_irrigation_system_farmId ON "IrrigationSystem" ("farmId");
+
+CREATE TABLE "IrrigationEvent" (
+                                   "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                   "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                   "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                   "farmId" UUID NOT NULL,
+                                   "systemId" UUID,
+                                   "plotId" UUID,
+                                   "status" VARCHAR(9) NOT NULL CHECK ("status" IN ('SCHEDULED', 'COMPLETED', 'CANCELLED')),
+                                   "scheduledDate" DATE,
+                                   "scheduledVolumeLitres" DOUBLE PRECISION CHECK ("scheduledVolumeLitres" >= 0),
+                                   "actualDate" DATE,
+                                   "actualVolumeLitres" DOUBLE PRECISION CHECK ("actualVolumeLitres" >= 0),
+                                   "durationMinutes" INT CHECK ("durationMinutes" >= 0),
+                                   "notes" TEXT,
+                                   CHECK ("status" <> 'COMPLETED' OR ("actualDate" IS NOT NULL AND "actualVolumeLitres" IS NOT NULL)),
+                                   CHECK ("status" <> 'SCHEDULED' OR "scheduledDate" IS NOT NULL),
+                                   FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                                   FOREIGN KEY ("systemId") REFERENCES "IrrigationSystem" ("id") ON DELETE SET NULL,
+                                   FOREIGN KEY ("plotId") REFERENCES "Plot" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_irrigation_event_farmId ON "IrrigationEvent" ("farmId", "status");
@@ -0,0 +1,50 @@
+CREATE TABLE "OutbreakAlert" (
+                                 "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                 "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                 "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                 "category" VARCHAR(7) NOT NULL CHECK ("category" IN ('PEST', 'DISEASE')),
+                                 "name" VARCHAR(191) NOT NULL,
+                                 "status" VARCHAR(8) NOT NULL DEFAULT 'ACTIVE' CHECK ("status" IN ('ACTIVE', 'RESOLVED')),
+                                 "severity" VARCHAR(8) NOT NULL CHECK ("severity" IN ('LOW', 'MODERATE', 'HIGH', 'SEVERE')),
+                                 "latitude" DOUBLE PRECISION NOT NULL,
+                                 "longitude" DOUBLE PRECISION NOT NULL,
+                                 "radiusKm" DOUBLE PRECISION NOT NULL,
+                                 "incidentCount" INT NOT NULL,
+                                 "farmCount" INT NOT NULL,
+                                 "firstObservedOn" DATE NOT NULL,
+                                 "lastObservedOn" DATE NOT NULL,
+                                 "notifiedAt" TIMESTAMPTZ,
+                                 "resolvedAt" TIMESTAMPTZ
+);
+
+CREATE INDEX idx_outbreak_alert_status ON "OutbreakAlert" ("status", "lastObservedOn");
+
+CREATE TABLE "PestIncident" (
+                                "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                "farmId" UUID NOT NULL,
+                                "plotId" UUID,
+                                "reportedBy" UUID,
+                                "category" VARCHAR(7) NOT NULL CHECK ("category" IN ('PEST', 'DISEASE')),
+                                -- Stored lower-case so reports of the same issue cluster together
+                                "name" VARCHAR(191) NOT NULL,
+                                "severity" VARCHAR(8) NOT NULL CHECK ("severity" IN ('LOW', 'MODERATE', 'HIGH', 'SEVERE')),
+                                "affectedArea" DOUBLE PRECISION CHECK ("affectedArea" >= 0),
+                                "observedOn" DATE NOT NULL,
+                                "latitude" DOUBLE PRECISION NOT NULL,
+                                "longitude" DOUBLE PRECISION NOT NULL,
+                                "photoUrls" TEXT[] NOT NULL DEFAULT '{}',
+                                "notes" TEXT,
+                                "outbreakId" UUID,
+                                FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                                FOREIGN KEY ("plotId") REFERENCES "Plot" ("id") ON DELETE SET NULL,
+                                FOREIGN KEY ("reportedBy") REFERENCES "User" ("id") ON DELETE SET NULL,
+                                FOREIGN KEY ("outbreakId") REFERENCES "OutbreakAlert" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_pest_incident_farmId ON "PestIncident" ("farmId");
+CREATE INDEX idx_pest_incident_observedOn ON "PestIncident" ("observedOn", "category", "name");
+CREATE INDEX idx_pest_incident_outbreakId ON "PestIncident" ("outbreakId");
+
+COMMENT ON COLUMN "PestIncident"."affectedArea" IS 'Affected area in hectares';
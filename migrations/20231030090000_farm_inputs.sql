@@ -0,0 +1,43 @@
+CREATE TABLE "InputProduct" (
+                                "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                "name" VARCHAR(191) NOT NULL,
+                                "category" VARCHAR(11) NOT NULL CHECK ("category" IN ('SEED', 'FERTILIZER', 'HERBICIDE', 'INSECTICIDE', 'FUNGICIDE', 'OTHER')),
+                                "unit" VARCHAR(5) NOT NULL CHECK ("unit" IN ('KG', 'LITRE', 'PIECE')),
+                                "manufacturer" VARCHAR(191),
+                                "cropId" UUID,
+                                "recommendedRatePerHectare" DOUBLE PRECISION CHECK ("recommendedRatePerHectare" > 0),
+                                "maxRatePerHectare" DOUBLE PRECISION CHECK ("maxRatePerHectare" > 0),
+                                UNIQUE ("name"),
+                                CHECK ("maxRatePerHectare" IS NULL OR "recommendedRatePerHectare" IS NULL OR "maxRatePerHectare" >= "recommendedRatePerHectare"),
+                                FOREIGN KEY ("cropId") REFERENCES "Crop" ("id") ON DELETE SET NULL
+);
+
+-- Allocations and purchases add to the farmer's stock, applications draw it down
+CREATE TABLE "InputTransaction" (
+                                    "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                    "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                    "farmId" UUID NOT NULL,
+                                    "farmerId" UUID NOT NULL,
+                                    "productId" UUID NOT NULL,
+                                    "plotId" UUID,
+                                    "kind" VARCHAR(11) NOT NULL CHECK ("kind" IN ('ALLOCATION', 'PURCHASE', 'APPLICATION')),
+                                    "quantity" DOUBLE PRECISION NOT NULL CHECK ("quantity" > 0),
+                                    "unitCost" money_value,
+                                    "appliedArea" DOUBLE PRECISION CHECK ("appliedArea" > 0),
+                                    "transactionDate" DATE NOT NULL,
+                                    "supplier" VARCHAR(191),
+                                    "notes" TEXT,
+                                    CHECK ("kind" = 'APPLICATION' OR "appliedArea" IS NULL),
+                                    FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                                    FOREIGN KEY ("farmerId") REFERENCES "User" ("id") ON DELETE CASCADE,
+                                    FOREIGN KEY ("productId") REFERENCES "InputProduct" ("id"),
+                                    FOREIGN KEY ("plotId") REFERENCES "Plot" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_input_transaction_farmId ON "InputTransaction" ("farmId", "transactionDate");
+CREATE INDEX idx_input_transaction_farmerId ON "InputTransaction" ("farmerId", "productId");
+
+COMMENT ON COLUMN "InputTransaction"."quantity" IS 'Quantity in the product unit';
+COMMENT ON COLUMN "InputTransaction"."appliedArea" IS 'Area treated, in hectares';
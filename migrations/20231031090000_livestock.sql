@@ -0,0 +1,60 @@
+CREATE TABLE "Herd" (
+                        "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                        "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                        "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                        "farmId" UUID NOT NULL,
+                        "name" VARCHAR(191) NOT NULL,
+                        "species" VARCHAR(7) NOT NULL CHECK ("species" IN ('CATTLE', 'GOAT', 'SHEEP', 'PIG', 'POULTRY', 'RABBIT', 'FISH', 'OTHER')),
+                        "breed" VARCHAR(191),
+                        "startedOn" DATE NOT NULL,
+                        UNIQUE ("farmId", "name"),
+                        FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_herd_farmId ON "Herd" ("farmId");
+
+CREATE TABLE "Animal" (
+                          "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                          "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                          "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                          "herdId" UUID NOT NULL,
+                          "tagId" VARCHAR(64) NOT NULL,
+                          "sex" VARCHAR(6) NOT NULL CHECK ("sex" IN ('MALE', 'FEMALE')),
+                          "breed" VARCHAR(191),
+                          "birthDate" DATE,
+                          "damId" UUID,
+                          "sireId" UUID,
+                          "status" VARCHAR(5) NOT NULL DEFAULT 'ALIVE' CHECK ("status" IN ('ALIVE', 'DEAD', 'SOLD')),
+                          "notes" TEXT,
+                          UNIQUE ("tagId"),
+                          FOREIGN KEY ("herdId") REFERENCES "Herd" ("id") ON DELETE CASCADE,
+                          FOREIGN KEY ("damId") REFERENCES "Animal" ("id") ON DELETE SET NULL,
+                          FOREIGN KEY ("sireId") REFERENCES "Animal" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_animal_herdId ON "Animal" ("herdId", "status");
+
+-- Headcount is the running sum of OPENING, PURCHASE and BIRTH minus DEATH and SALE;
+-- vaccinations and treatments leave it unchanged
+CREATE TABLE "LivestockEvent" (
+                                  "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                  "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                  "herdId" UUID NOT NULL,
+                                  "animalId" UUID,
+                                  "kind" VARCHAR(11) NOT NULL CHECK ("kind" IN ('OPENING', 'PURCHASE', 'BIRTH', 'DEATH', 'SALE', 'VACCINATION', 'TREATMENT')),
+                                  "count" INT NOT NULL CHECK ("count" > 0),
+                                  "eventDate" DATE NOT NULL,
+                                  "salePrice" money_value,
+                                  "cause" VARCHAR(191),
+                                  "product" VARCHAR(191),
+                                  "dose" VARCHAR(64),
+                                  "administeredBy" VARCHAR(191),
+                                  "notes" TEXT,
+                                  CHECK ("kind" = 'SALE' OR "salePrice" IS NULL),
+                                  CHECK ("kind" NOT IN ('VACCINATION', 'TREATMENT') OR "product" IS NOT NULL),
+                                  FOREIGN KEY ("herdId") REFERENCES "Herd" ("id") ON DELETE CASCADE,
+                                  FOREIGN KEY ("animalId") REFERENCES "Animal" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_livestock_event_herdId ON "LivestockEvent" ("herdId", "eventDate");
+CREATE INDEX idx_livestock_event_animalId ON "LivestockEvent" ("animalId");
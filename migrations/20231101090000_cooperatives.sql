@@ -0,0 +1,34 @@
+CREATE TABLE "Cooperative" (
+                               "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                               "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                               "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                               "name" VARCHAR(191) NOT NULL,
+                               "kind" VARCHAR(11) NOT NULL CHECK ("kind" IN ('COOPERATIVE', 'CLUSTER')),
+                               "registrationNumber" VARCHAR(64),
+                               "stateId" UUID,
+                               "lgaId" UUID,
+                               "description" TEXT,
+                               UNIQUE ("name"),
+                               UNIQUE ("registrationNumber"),
+                               FOREIGN KEY ("stateId") REFERENCES "State" ("id"),
+                               FOREIGN KEY ("lgaId") REFERENCES "Lga" ("id")
+);
+
+-- A role change closes the current row and opens a new one, so the table is the membership history
+CREATE TABLE "CooperativeMembership" (
+                                         "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                         "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                         "cooperativeId" UUID NOT NULL,
+                                         "userId" UUID NOT NULL,
+                                         "role" VARCHAR(9) NOT NULL CHECK ("role" IN ('CHAIR', 'SECRETARY', 'MEMBER')),
+                                         "joinedOn" DATE NOT NULL,
+                                         "leftOn" DATE,
+                                         CHECK ("leftOn" IS NULL OR "leftOn" >= "joinedOn"),
+                                         FOREIGN KEY ("cooperativeId") REFERENCES "Cooperative" ("id") ON DELETE CASCADE,
+                                         FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_membership_current ON "CooperativeMembership" ("cooperativeId", "userId") WHERE "leftOn" IS NULL;
+-- One sitting chair and secretary per group
+CREATE UNIQUE INDEX idx_membership_officer ON "CooperativeMembership" ("cooperativeId", "role") WHERE "leftOn" IS NULL AND "role" <> 'MEMBER';
+CREATE INDEX idx_membership_userId ON "CooperativeMembership" ("userId");
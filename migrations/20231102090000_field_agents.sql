@@ -0,0 +1,40 @@
+-- Agent portfolios; ending an assignment keeps the row as history
+CREATE TABLE "AgentAssignment" (
+                                   "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                   "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                   "agentId" UUID NOT NULL,
+                                   "farmerId" UUID NOT NULL,
+                                   "startedOn" DATE NOT NULL,
+                                   "endedOn" DATE,
+                                   CHECK ("agentId" <> "farmerId"),
+                                   CHECK ("endedOn" IS NULL OR "endedOn" >= "startedOn"),
+                                   FOREIGN KEY ("agentId") REFERENCES "User" ("id") ON DELETE CASCADE,
+                                   FOREIGN KEY ("farmerId") REFERENCES "User" ("id") ON DELETE CASCADE
+);
+
+-- A farmer has one current agent
+CREATE UNIQUE INDEX idx_assignment_current ON "AgentAssignment" ("farmerId") WHERE "endedOn" IS NULL;
+CREATE INDEX idx_assignment_agentId ON "AgentAssignment" ("agentId");
+
+CREATE TABLE "FarmVisit" (
+                             "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                             "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                             "farmId" UUID NOT NULL,
+                             "agentId" UUID NOT NULL,
+                             "visitedAt" TIMESTAMPTZ NOT NULL,
+                             "latitude" DOUBLE PRECISION NOT NULL,
+                             "longitude" DOUBLE PRECISION NOT NULL,
+                             "accuracyMetres" DOUBLE PRECISION CHECK ("accuracyMetres" >= 0),
+                             "distanceMetres" DOUBLE PRECISION NOT NULL,
+                             "locationVerified" BOOLEAN NOT NULL,
+                             "notes" TEXT,
+                             "photoUrls" TEXT[] NOT NULL DEFAULT '{}',
+                             "checklist" JSONB NOT NULL DEFAULT '[]',
+                             FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                             FOREIGN KEY ("agentId") REFERENCES "User" ("id") ON DELETE CASCADE
+);
+
+COMMENT ON COLUMN "FarmVisit"."distanceMetres" IS 'Distance of the check-in from the farm''s recorded location';
+
+CREATE INDEX idx_visit_farmId ON "FarmVisit" ("farmId", "visitedAt");
+CREATE INDEX idx_visit_agentId ON "FarmVisit" ("agentId", "visitedAt");
@@ -0,0 +1,77 @@
+-- Land offered for lease out of a farm's available portion
+CREATE TABLE "LandListing" (
+                               "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                               "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                               "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                               "farmId" UUID NOT NULL,
+                               "plotId" UUID,
+                               "area" DOUBLE PRECISION NOT NULL CHECK ("area" >= 0),
+                               "rentPerHectare" money_value NOT NULL,
+                               "availableFrom" DATE NOT NULL,
+                               "minTermMonths" INT NOT NULL CHECK ("minTermMonths" > 0),
+                               "maxTermMonths" INT NOT NULL,
+                               "description" TEXT,
+                               "status" VARCHAR(9) NOT NULL DEFAULT 'OPEN' CHECK ("status" IN ('OPEN', 'LEASED', 'WITHDRAWN')),
+                               CHECK ("maxTermMonths" >= "minTermMonths"),
+                               FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                               FOREIGN KEY ("plotId") REFERENCES "Plot" ("id") ON DELETE SET NULL
+);
+
+COMMENT ON COLUMN "LandListing"."area" IS 'Area still on offer in hectares';
+COMMENT ON COLUMN "LandListing"."rentPerHectare" IS 'Yearly rent per hectare';
+
+CREATE INDEX idx_listing_farmId ON "LandListing" ("farmId");
+CREATE INDEX idx_listing_status ON "LandListing" ("status");
+
+CREATE TABLE "LeaseApplication" (
+                                    "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                    "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                    "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                    "listingId" UUID NOT NULL,
+                                    "applicantId" UUID NOT NULL,
+                                    "area" DOUBLE PRECISION NOT NULL CHECK ("area" > 0),
+                                    "startDate" DATE NOT NULL,
+                                    "termMonths" INT NOT NULL CHECK ("termMonths" > 0),
+                                    "message" TEXT,
+                                    "status" VARCHAR(9) NOT NULL DEFAULT 'PENDING' CHECK ("status" IN ('PENDING', 'ACCEPTED', 'REJECTED', 'WITHDRAWN')),
+                                    "decisionReason" TEXT,
+                                    FOREIGN KEY ("listingId") REFERENCES "LandListing" ("id") ON DELETE CASCADE,
+                                    FOREIGN KEY ("applicantId") REFERENCES "User" ("id") ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_application_pending ON "LeaseApplication" ("listingId", "applicantId") WHERE "status" = 'PENDING';
+CREATE INDEX idx_application_applicantId ON "LeaseApplication" ("applicantId");
+
+CREATE TABLE "Lease" (
+                         "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                         "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                         "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                         "farmId" UUID NOT NULL,
+                         "plotId" UUID,
+                         "listingId" UUID NOT NULL,
+                         "applicationId" UUID NOT NULL,
+                         "landlordId" UUID NOT NULL,
+                         "tenantId" UUID NOT NULL,
+                         "area" DOUBLE PRECISION NOT NULL CHECK ("area" > 0),
+                         "startDate" DATE NOT NULL,
+                         "endDate" DATE NOT NULL,
+                         "rent" money_value NOT NULL,
+                         "status" VARCHAR(6) NOT NULL DEFAULT 'ACTIVE' CHECK ("status" IN ('ACTIVE', 'ENDED')),
+                         "endedOn" DATE,
+                         UNIQUE ("applicationId"),
+                         CHECK ("endDate" > "startDate"),
+                         CHECK (("status" = 'ENDED') = ("endedOn" IS NOT NULL)),
+                         FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                         FOREIGN KEY ("plotId") REFERENCES "Plot" ("id") ON DELETE SET NULL,
+                         FOREIGN KEY ("listingId") REFERENCES "LandListing" ("id"),
+                         FOREIGN KEY ("applicationId") REFERENCES "LeaseApplication" ("id"),
+                         FOREIGN KEY ("landlordId") REFERENCES "User" ("id"),
+                         FOREIGN KEY ("tenantId") REFERENCES "User" ("id")
+);
+
+COMMENT ON COLUMN "Lease"."area" IS 'Leased area in hectares, taken out of the farm''s available portion';
+COMMENT ON COLUMN "Lease"."rent" IS 'Total rent for the term';
+
+CREATE INDEX idx_lease_farmId ON "Lease" ("farmId");
+CREATE INDEX idx_lease_tenantId ON "Lease" ("tenantId");
+CREATE INDEX idx_lease_landlordId ON "Lease" ("landlordId");
@@ -0,0 +1,70 @@
+-- VARCHAR(6) was too short for the INHERIT value its check constraint allows
+ALTER TABLE "Farm" ALTER COLUMN "ownership" TYPE VARCHAR(7);
+
+CREATE TABLE "FarmTransfer" (
+                                "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                "farmId" UUID NOT NULL,
+                                "fromOwnerId" UUID NOT NULL,
+                                "toOwnerId" UUID NOT NULL,
+                                "reason" VARCHAR(11) NOT NULL CHECK ("reason" IN ('SALE', 'INHERITANCE', 'LEASE_END')),
+                                "effectiveOn" DATE NOT NULL,
+                                "price" money_value,
+                                "notes" TEXT,
+                                CHECK ("fromOwnerId" <> "toOwnerId"),
+                                CHECK ("reason" = 'SALE' OR "price" IS NULL),
+                                FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                                FOREIGN KEY ("fromOwnerId") REFERENCES "User" ("id"),
+                                FOREIGN KEY ("toOwnerId") REFERENCES "User" ("id")
+);
+
+CREATE INDEX idx_transfer_farmId ON "FarmTransfer" ("farmId", "effectiveOn");
+
+CREATE TABLE "TransferDocument" (
+                                    "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                    "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                    "transferId" UUID NOT NULL,
+                                    "kind" VARCHAR(24) NOT NULL CHECK ("kind" IN ('DEED', 'CERTIFICATE_OF_OCCUPANCY', 'SALE_AGREEMENT', 'WILL', 'LETTER_OF_ADMINISTRATION', 'LEASE_AGREEMENT', 'OTHER')),
+                                    "url" TEXT NOT NULL,
+                                    "description" TEXT,
+                                    FOREIGN KEY ("transferId") REFERENCES "FarmTransfer" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_transfer_document_transferId ON "TransferDocument" ("transferId");
+
+-- One row per owner; the transfer that ends a title opens the next, on the same day
+CREATE TABLE "FarmTitle" (
+                             "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                             "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                             "farmId" UUID NOT NULL,
+                             "ownerId" UUID NOT NULL,
+                             "acquiredOn" DATE NOT NULL,
+                             "relinquishedOn" DATE,
+                             "transferId" UUID,
+                             CHECK ("relinquishedOn" IS NULL OR "relinquishedOn" > "acquiredOn"),
+                             FOREIGN KEY ("farmId") REFERENCES "Farm" ("id") ON DELETE CASCADE,
+                             FOREIGN KEY ("ownerId") REFERENCES "User" ("id"),
+                             FOREIGN KEY ("transferId") REFERENCES "FarmTransfer" ("id")
+);
+
+CREATE UNIQUE INDEX idx_title_current ON "FarmTitle" ("farmId") WHERE "relinquishedOn" IS NULL;
+CREATE INDEX idx_title_farmId ON "FarmTitle" ("farmId", "acquiredOn");
+CREATE INDEX idx_title_ownerId ON "FarmTitle" ("ownerId");
+
+COMMENT ON COLUMN "FarmTitle"."transferId" IS 'Transfer that opened the title; NULL for the original owner';
+
+INSERT INTO "FarmTitle" ("farmId", "ownerId", "acquiredOn")
+SELECT "id", "farmerId", "createdAt"::date
+FROM "Farm";
+
+-- New farms open their first title; later changes of owner go through a transfer
+CREATE FUNCTION open_farm_title() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO "FarmTitle" ("farmId", "ownerId", "acquiredOn") VALUES (NEW."id", NEW."farmerId", NEW."createdAt"::date);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_farm_title
+    AFTER INSERT ON "Farm"
+    FOR EACH ROW EXECUTE FUNCTION open_farm_title();
@@ -0,0 +1,43 @@
+ALTER TABLE "Profile"
+    ADD COLUMN "kycStatus" VARCHAR(10) NOT NULL DEFAULT 'UNVERIFIED' CHECK ("kycStatus" IN ('UNVERIFIED', 'PENDING', 'VERIFIED', 'REJECTED')),
+    ADD COLUMN "kycReason" TEXT;
+
+COMMENT ON COLUMN "Profile"."kycReason" IS 'Why the last KYC check was rejected';
+
+CREATE INDEX idx_profile_kycStatus ON "Profile" ("kycStatus");
+
+-- Every KYC status change, with the identity provider's answer on submissions
+CREATE TABLE "KycEvent" (
+                            "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                            "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                            "profileId" UUID NOT NULL,
+                            "fromStatus" VARCHAR(10) NOT NULL,
+                            "toStatus" VARCHAR(10) NOT NULL,
+                            "actorId" UUID,
+                            "reason" TEXT,
+                            "provider" VARCHAR(64),
+                            "providerReference" VARCHAR(191),
+                            "providerOutcome" VARCHAR(8) CHECK ("providerOutcome" IN ('MATCH', 'MISMATCH', 'INVALID')),
+                            FOREIGN KEY ("profileId") REFERENCES "Profile" ("id") ON DELETE CASCADE,
+                            FOREIGN KEY ("actorId") REFERENCES "User" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_kyc_event_profileId ON "KycEvent" ("profileId", "createdAt");
+
+-- Changing the identity details on file voids any earlier verification
+CREATE FUNCTION reset_profile_kyc() RETURNS TRIGGER AS $$
+BEGIN
+    IF (NEW."bvn" IS DISTINCT FROM OLD."bvn" OR NEW."identityNumber" IS DISTINCT FROM OLD."identityNumber")
+        AND OLD."kycStatus" <> 'UNVERIFIED' THEN
+        NEW."kycStatus" := 'UNVERIFIED';
+        NEW."kycReason" := NULL;
+        INSERT INTO "KycEvent" ("profileId", "fromStatus", "toStatus", "reason")
+        VALUES (NEW."id", OLD."kycStatus", 'UNVERIFIED', 'Identity details changed');
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_profile_kyc
+    BEFORE UPDATE OF "bvn", "identityNumber" ON "Profile"
+    FOR EACH ROW EXECUTE FUNCTION reset_profile_kyc();
@@ -0,0 +1,46 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Suspected duplicate farmers; each pair is stored once with the lower id first
+CREATE TABLE "DuplicateCandidate" (
+                                      "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                      "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                      "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                      "userId" UUID NOT NULL,
+                                      "otherUserId" UUID NOT NULL,
+                                      "score" DOUBLE PRECISION NOT NULL CHECK ("score" BETWEEN 0 AND 1),
+                                      "signals" JSONB NOT NULL DEFAULT '[]',
+                                      "status" VARCHAR(9) NOT NULL DEFAULT 'PENDING' CHECK ("status" IN ('PENDING', 'DISMISSED')),
+                                      "reviewerId" UUID,
+                                      "reviewedAt" TIMESTAMPTZ,
+                                      "reason" TEXT,
+                                      UNIQUE ("userId", "otherUserId"),
+                                      CHECK ("userId" < "otherUserId"),
+                                      FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE,
+                                      FOREIGN KEY ("otherUserId") REFERENCES "User" ("id") ON DELETE CASCADE,
+                                      FOREIGN KEY ("reviewerId") REFERENCES "User" ("id") ON DELETE SET NULL
+);
+
+COMMENT ON COLUMN "DuplicateCandidate"."signals" IS 'Matching signals and the weight each contributed to the score';
+
+CREATE INDEX idx_duplicate_status_score ON "DuplicateCandidate" ("status", "score" DESC);
+CREATE INDEX idx_duplicate_otherUserId ON "DuplicateCandidate" ("otherUserId");
+
+-- Audit trail of merges; the merged user is deleted, so its id and name are copied here
+CREATE TABLE "UserMerge" (
+                             "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                             "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                             "survivorId" UUID NOT NULL,
+                             "mergedUserId" UUID NOT NULL,
+                             "mergedFirstName" VARCHAR(191) NOT NULL,
+                             "mergedLastName" VARCHAR(191) NOT NULL,
+                             "score" DOUBLE PRECISION NOT NULL,
+                             "signals" JSONB NOT NULL DEFAULT '[]',
+                             "moved" JSONB NOT NULL DEFAULT '{}',
+                             "reviewerId" UUID,
+                             FOREIGN KEY ("survivorId") REFERENCES "User" ("id") ON DELETE CASCADE,
+                             FOREIGN KEY ("reviewerId") REFERENCES "User" ("id") ON DELETE SET NULL
+);
+
+COMMENT ON COLUMN "UserMerge"."moved" IS 'Rows moved onto the survivor, by table';
+
+CREATE INDEX idx_user_merge_survivorId ON "UserMerge" ("survivorId");
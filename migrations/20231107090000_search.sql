@@ -0,0 +1,130 @@
+-- One row per searchable user or farm, kept current by the triggers below. Users carry
+-- the places they farm in and farms carry their farmer's name, so "Aminu Bello Zaria"
+-- matches either.
+CREATE TABLE "SearchDocument" (
+                                  "entityType" VARCHAR(4) NOT NULL CHECK ("entityType" IN ('USER', 'FARM')),
+                                  "entityId" UUID NOT NULL,
+                                  "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                  "title" TEXT NOT NULL,
+                                  "subtitle" TEXT,
+                                  "content" TEXT NOT NULL,
+                                  "document" TSVECTOR NOT NULL,
+                                  PRIMARY KEY ("entityType", "entityId")
+);
+
+COMMENT ON COLUMN "SearchDocument"."content" IS 'Lowercased searchable text, including phone digits, for trigram matching';
+
+CREATE INDEX idx_search_document ON "SearchDocument" USING GIN ("document");
+CREATE INDEX idx_search_content ON "SearchDocument" USING GIN ("content" gin_trgm_ops);
+
+CREATE FUNCTION refresh_user_search(target UUID) RETURNS void AS $$
+BEGIN
+    DELETE FROM "SearchDocument" WHERE "entityType" = 'USER' AND "entityId" = target;
+
+    INSERT INTO "SearchDocument" ("entityType", "entityId", "title", "subtitle", "content", "document")
+    SELECT 'USER', u.id, d.name, NULLIF(concat_ws(' · ', p."phoneNumber", d.places), ''),
+           lower(concat_ws(' ', d.name, d.places, d.digits, right(d.digits, 10))),
+           setweight(to_tsvector('simple', d.name), 'A')
+               || setweight(to_tsvector('simple', coalesce(d.places, '')), 'B')
+               || setweight(to_tsvector('simple', concat_ws(' ', d.digits, right(d.digits, 10))), 'C')
+    FROM "User" u
+    LEFT JOIN "Profile" p ON p."userId" = u.id
+    CROSS JOIN LATERAL (
+        SELECT concat_ws(' ', u."firstName", u."middleName", u."lastName") AS name,
+               NULLIF(regexp_replace(coalesce(p."phoneNumber", ''), '\D', '', 'g'), '') AS digits,
+               (SELECT string_agg(DISTINCT concat_ws(', ', l.name, s.name), '; ')
+                FROM "Farm" f
+                LEFT JOIN "Lga" l ON l.id = f."lgaId"
+                LEFT JOIN "State" s ON s.id = f."stateId"
+                WHERE f."farmerId" = u.id AND (l.id IS NOT NULL OR s.id IS NOT NULL)) AS places
+    ) d
+    WHERE u.id = target;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION refresh_farm_search(target UUID) RETURNS void AS $$
+BEGIN
+    DELETE FROM "SearchDocument" WHERE "entityType" = 'FARM' AND "entityId" = target;
+
+    INSERT INTO "SearchDocument" ("entityType", "entityId", "title", "subtitle", "content", "document")
+    SELECT 'FARM', f.id, coalesce(f.farm_name, 'Unnamed farm'), NULLIF(concat_ws(' · ', d.farmer, d.place), ''),
+           lower(concat_ws(' ', f.farm_name, d.farmer, d.place)),
+           setweight(to_tsvector('simple', coalesce(f.farm_name, '')), 'A')
+               || setweight(to_tsvector('simple', coalesce(d.place, '')), 'B')
+               || setweight(to_tsvector('simple', coalesce(d.farmer, '')), 'C')
+    FROM "Farm" f
+    JOIN "User" u ON u.id = f."farmerId"
+    LEFT JOIN "Lga" l ON l.id = f."lgaId"
+    LEFT JOIN "State" s ON s.id = f."stateId"
+    CROSS JOIN LATERAL (
+        SELECT concat_ws(' ', u."firstName", u."middleName", u."lastName") AS farmer,
+               NULLIF(concat_ws(', ', l.name, s.name), '') AS place
+    ) d
+    WHERE f.id = target;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION sync_user_search() RETURNS TRIGGER AS $$
+DECLARE
+    farm UUID;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM refresh_user_search(OLD."id");
+        RETURN OLD;
+    END IF;
+    PERFORM refresh_user_search(NEW."id");
+    -- Farms show their farmer's name
+    IF TG_OP = 'UPDATE' THEN
+        FOR farm IN SELECT "id" FROM "Farm" WHERE "farmerId" = NEW."id" LOOP
+            PERFORM refresh_farm_search(farm);
+        END LOOP;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION sync_profile_search() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP <> 'INSERT' THEN
+        PERFORM refresh_user_search(OLD."userId");
+    END IF;
+    IF TG_OP <> 'DELETE' THEN
+        PERFORM refresh_user_search(NEW."userId");
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION sync_farm_search() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM refresh_farm_search(OLD."id");
+    ELSE
+        PERFORM refresh_farm_search(NEW."id");
+    END IF;
+    -- Users show the places they farm in
+    IF TG_OP <> 'INSERT' THEN
+        PERFORM refresh_user_search(OLD."farmerId");
+    END IF;
+    IF TG_OP <> 'DELETE' AND (TG_OP = 'INSERT' OR NEW."farmerId" IS DISTINCT FROM OLD."farmerId") THEN
+        PERFORM refresh_user_search(NEW."farmerId");
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_user_search
+    AFTER INSERT OR UPDATE OF "firstName", "middleName", "lastName" OR DELETE ON "User"
+    FOR EACH ROW EXECUTE FUNCTION sync_user_search();
+
+CREATE TRIGGER trg_profile_search
+    AFTER INSERT OR UPDATE OF "phoneNumber", "userId" OR DELETE ON "Profile"
+    FOR EACH ROW EXECUTE FUNCTION sync_profile_search();
+
+CREATE TRIGGER trg_farm_search
+    AFTER INSERT OR UPDATE OF farm_name, "farmerId", "stateId", "lgaId" OR DELETE ON "Farm"
+    FOR EACH ROW EXECUTE FUNCTION sync_farm_search();
+
+-- State and LGA names are reference data; renaming one needs a re-run of the refresh functions
+SELECT refresh_user_search("id") FROM "User";
+SELECT refresh_farm_search("id") FROM "Farm";
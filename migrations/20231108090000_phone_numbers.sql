@@ -0,0 +1,73 @@
+ALTER TABLE "Profile"
+    ADD COLUMN "phoneCarrier" VARCHAR(64),
+    ADD COLUMN "phoneLineType" VARCHAR(20) CHECK ("phoneLineType" IN ('MOBILE', 'FIXED_LINE', 'FIXED_LINE_OR_MOBILE', 'TOLL_FREE', 'PREMIUM_RATE', 'VOIP', 'OTHER'));
+
+COMMENT ON COLUMN "Profile"."phoneNumber" IS 'E.164, e.g. +2348031234567';
+
+-- Numbers this migration could not keep, with the value as it was
+CREATE TABLE "PhoneNumberIssue" (
+                                    "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                    "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                    "profileId" UUID NOT NULL,
+                                    "phoneNumber" VARCHAR(191) NOT NULL,
+                                    "reason" VARCHAR(9) NOT NULL CHECK ("reason" IN ('INVALID', 'DUPLICATE')),
+                                    "duplicateOfProfileId" UUID,
+                                    FOREIGN KEY ("profileId") REFERENCES "Profile" ("id") ON DELETE CASCADE,
+                                    FOREIGN KEY ("duplicateOfProfileId") REFERENCES "Profile" ("id") ON DELETE SET NULL
+);
+
+CREATE INDEX idx_phone_issue_profileId ON "PhoneNumberIssue" ("profileId");
+
+-- Existing numbers were all captured in Nigeria. Mobile numbers in any of the usual forms are
+-- rewritten to E.164 and numbers that already carry another country code are kept; anything
+-- else is reported and cleared so it can be re-entered through the API, which validates fully.
+CREATE TEMPORARY TABLE phone_normalization AS
+SELECT p."id",
+       p."phoneNumber" AS original,
+       n.e164,
+       row_number() OVER (PARTITION BY n.e164 ORDER BY p."createdAt", p."id") AS position,
+       first_value(p."id") OVER (PARTITION BY n.e164 ORDER BY p."createdAt", p."id") AS first_id
+FROM "Profile" p
+CROSS JOIN LATERAL (SELECT regexp_replace(p."phoneNumber", '[^0-9+]', '', 'g') AS compact) c
+CROSS JOIN LATERAL (
+    SELECT CASE
+               WHEN c.compact ~ '^\+234[789][01][0-9]{8}$' THEN c.compact
+               WHEN c.compact ~ '^234[789][01][0-9]{8}$' THEN '+' || c.compact
+               WHEN c.compact ~ '^0[789][01][0-9]{8}$' THEN '+234' || substr(c.compact, 2)
+               WHEN c.compact ~ '^[789][01][0-9]{8}$' THEN '+234' || c.compact
+               WHEN c.compact ~ '^\+[1-9][0-9]{7,14}$' AND c.compact !~ '^\+234' THEN c.compact
+           END AS e164
+) n
+WHERE trim(p."phoneNumber") <> '';
+
+INSERT INTO "PhoneNumberIssue" ("profileId", "phoneNumber", "reason")
+SELECT "id", original, 'INVALID' FROM phone_normalization WHERE e164 IS NULL;
+
+-- The oldest profile keeps a shared number
+INSERT INTO "PhoneNumberIssue" ("profileId", "phoneNumber", "reason", "duplicateOfProfileId")
+SELECT "id", original, 'DUPLICATE', first_id FROM phone_normalization WHERE e164 IS NOT NULL AND position > 1;
+
+UPDATE "Profile" p
+SET "phoneNumber" = CASE WHEN n.position = 1 THEN n.e164 END
+FROM phone_normalization n
+WHERE n."id" = p."id" AND n.original IS DISTINCT FROM CASE WHEN n.position = 1 THEN n.e164 END;
+
+UPDATE "Profile" SET "phoneNumber" = NULL WHERE trim("phoneNumber") = '';
+
+DO $$
+DECLARE
+    invalid INT;
+    duplicate INT;
+BEGIN
+    SELECT count(*) FILTER (WHERE e164 IS NULL), count(*) FILTER (WHERE e164 IS NOT NULL AND position > 1)
+    INTO invalid, duplicate
+    FROM phone_normalization;
+    IF invalid + duplicate > 0 THEN
+        RAISE NOTICE 'Cleared % invalid and % duplicate phone numbers; see "PhoneNumberIssue"', invalid, duplicate;
+    END IF;
+END;
+$$;
+
+DROP TABLE phone_normalization;
+
+CREATE UNIQUE INDEX idx_profile_phoneNumber ON "Profile" ("phoneNumber");
@@ -0,0 +1,100 @@
+-- Every version of a user, profile and farm row. A version is current from "validFrom" until
+-- "validTo"; deleting a row closes its last version and records a zero-length DELETE version.
+-- History has no foreign keys so it outlives the rows it describes.
+CREATE TABLE "UserHistory" (
+                               "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                               "userId" UUID NOT NULL,
+                               "version" INT NOT NULL,
+                               "operation" VARCHAR(6) NOT NULL CHECK ("operation" IN ('INSERT', 'UPDATE', 'DELETE')),
+                               "validFrom" TIMESTAMPTZ NOT NULL,
+                               "validTo" TIMESTAMPTZ,
+                               "data" JSONB NOT NULL,
+                               UNIQUE ("userId", "version")
+);
+
+CREATE UNIQUE INDEX idx_user_history_current ON "UserHistory" ("userId") WHERE "validTo" IS NULL;
+CREATE INDEX idx_user_history_validFrom ON "UserHistory" ("userId", "validFrom");
+
+CREATE TABLE "ProfileHistory" (
+                                  "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                  "profileId" UUID NOT NULL,
+                                  "version" INT NOT NULL,
+                                  "operation" VARCHAR(6) NOT NULL CHECK ("operation" IN ('INSERT', 'UPDATE', 'DELETE')),
+                                  "validFrom" TIMESTAMPTZ NOT NULL,
+                                  "validTo" TIMESTAMPTZ,
+                                  "data" JSONB NOT NULL,
+                                  UNIQUE ("profileId", "version")
+);
+
+CREATE UNIQUE INDEX idx_profile_history_current ON "ProfileHistory" ("profileId") WHERE "validTo" IS NULL;
+CREATE INDEX idx_profile_history_validFrom ON "ProfileHistory" ("profileId", "validFrom");
+
+CREATE TABLE "FarmHistory" (
+                               "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                               "farmId" UUID NOT NULL,
+                               "version" INT NOT NULL,
+                               "operation" VARCHAR(6) NOT NULL CHECK ("operation" IN ('INSERT', 'UPDATE', 'DELETE')),
+                               "validFrom" TIMESTAMPTZ NOT NULL,
+                               "validTo" TIMESTAMPTZ,
+                               "data" JSONB NOT NULL,
+                               UNIQUE ("farmId", "version")
+);
+
+CREATE UNIQUE INDEX idx_farm_history_current ON "FarmHistory" ("farmId") WHERE "validTo" IS NULL;
+CREATE INDEX idx_farm_history_validFrom ON "FarmHistory" ("farmId", "validFrom");
+
+-- Writes to "<table>History"; the trigger argument names its id column
+CREATE FUNCTION record_version() RETURNS TRIGGER AS $$
+DECLARE
+    history TEXT := TG_TABLE_NAME || 'History';
+    key TEXT := TG_ARGV[0];
+    row_id UUID;
+    next_version INT;
+BEGIN
+    -- Touching "updatedAt" alone is not a new version
+    IF TG_OP = 'UPDATE' AND to_jsonb(NEW) - 'updatedAt' = to_jsonb(OLD) - 'updatedAt' THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'DELETE' THEN
+        row_id := OLD."id";
+    ELSE
+        row_id := NEW."id";
+    END IF;
+
+    EXECUTE format('UPDATE %I SET "validTo" = current_timestamp WHERE %I = $1 AND "validTo" IS NULL', history, key)
+        USING row_id;
+    EXECUTE format('SELECT coalesce(max("version"), 0) + 1 FROM %I WHERE %I = $1', history, key)
+        INTO next_version
+        USING row_id;
+    EXECUTE format('INSERT INTO %I (%I, "version", "operation", "validFrom", "validTo", "data") VALUES ($1, $2, $3, current_timestamp, $4, $5)', history, key)
+        USING row_id,
+              next_version,
+              TG_OP,
+              CASE WHEN TG_OP = 'DELETE' THEN current_timestamp END,
+              CASE WHEN TG_OP = 'DELETE' THEN to_jsonb(OLD) ELSE to_jsonb(NEW) END;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_user_history
+    AFTER INSERT OR UPDATE OR DELETE ON "User"
+    FOR EACH ROW EXECUTE FUNCTION record_version('userId');
+
+CREATE TRIGGER trg_profile_history
+    AFTER INSERT OR UPDATE OR DELETE ON "Profile"
+    FOR EACH ROW EXECUTE FUNCTION record_version('profileId');
+
+CREATE TRIGGER trg_farm_history
+    AFTER INSERT OR UPDATE OR DELETE ON "Farm"
+    FOR EACH ROW EXECUTE FUNCTION record_version('farmId');
+
+-- Earlier changes were not kept, so existing rows start with their current state as of creation
+INSERT INTO "UserHistory" ("userId", "version", "operation", "validFrom", "data")
+SELECT "id", 1, 'INSERT', "createdAt", to_jsonb(u) FROM "User" u;
+
+INSERT INTO "ProfileHistory" ("profileId", "version", "operation", "validFrom", "data")
+SELECT "id", 1, 'INSERT', "createdAt", to_jsonb(p) FROM "Profile" p;
+
+INSERT INTO "FarmHistory" ("farmId", "version", "operation", "validFrom", "data")
+SELECT "id", 1, 'INSERT', "createdAt", to_jsonb(f) FROM "Farm" f;
@@ -0,0 +1,19 @@
+-- Domain events written in the same transaction as the change they describe, relayed to Redis
+-- Streams in "sequence" order. Changes to one row are serialised by its row lock, so an entity's
+-- events always get increasing sequence numbers.
+CREATE TABLE "OutboxEvent" (
+                               "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                               "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                               "sequence" BIGSERIAL NOT NULL UNIQUE,
+                               "eventType" VARCHAR(32) NOT NULL,
+                               "entityType" VARCHAR(7) NOT NULL CHECK ("entityType" IN ('USER', 'PROFILE', 'FARM')),
+                               "entityId" UUID NOT NULL,
+                               "payload" JSONB NOT NULL,
+                               "publishedAt" TIMESTAMPTZ,
+                               "attempts" INT NOT NULL DEFAULT 0,
+                               "lastError" TEXT
+);
+
+CREATE INDEX idx_outbox_event_pending ON "OutboxEvent" ("sequence") WHERE "publishedAt" IS NULL;
+CREATE INDEX idx_outbox_event_publishedAt ON "OutboxEvent" ("publishedAt");
+CREATE INDEX idx_outbox_event_entity ON "OutboxEvent" ("entityType", "entityId", "sequence");
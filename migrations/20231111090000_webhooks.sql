@@ -0,0 +1,51 @@
+-- Partner endpoints notified of domain events. A cooperative's subscription only hears about
+-- its current members and their profiles and farms.
+CREATE TABLE "WebhookSubscription" (
+                                       "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                       "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                       "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                       "url" VARCHAR(2048) NOT NULL,
+                                       "eventTypes" JSONB NOT NULL CHECK (jsonb_typeof("eventTypes") = 'array'),
+                                       "secret" VARCHAR(191) NOT NULL,
+                                       "description" VARCHAR(191),
+                                       "cooperativeId" UUID,
+                                       "status" VARCHAR(8) NOT NULL DEFAULT 'ACTIVE' CHECK ("status" IN ('ACTIVE', 'DISABLED')),
+                                       "disabledReason" TEXT,
+                                       "consecutiveFailures" INT NOT NULL DEFAULT 0,
+                                       FOREIGN KEY ("cooperativeId") REFERENCES "Cooperative" ("id") ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_subscription_eventTypes ON "WebhookSubscription" USING GIN ("eventTypes");
+
+-- One row per attempt series; replays are new rows pointing at the delivery they repeat
+CREATE TABLE "WebhookDelivery" (
+                                   "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                                   "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                                   "subscriptionId" UUID NOT NULL,
+                                   "eventId" UUID NOT NULL,
+                                   "eventType" VARCHAR(32) NOT NULL,
+                                   "payload" JSONB NOT NULL,
+                                   "status" VARCHAR(9) NOT NULL DEFAULT 'PENDING' CHECK ("status" IN ('PENDING', 'SUCCEEDED', 'FAILED')),
+                                   "attempts" INT NOT NULL DEFAULT 0,
+                                   "nextAttemptAt" TIMESTAMPTZ,
+                                   "lastAttemptAt" TIMESTAMPTZ,
+                                   "responseStatus" INT,
+                                   "responseBody" TEXT,
+                                   "lastError" TEXT,
+                                   "durationMs" INT,
+                                   "replayOf" UUID,
+                                   FOREIGN KEY ("subscriptionId") REFERENCES "WebhookSubscription" ("id") ON DELETE CASCADE,
+                                   FOREIGN KEY ("replayOf") REFERENCES "WebhookDelivery" ("id") ON DELETE SET NULL
+);
+
+-- Events read twice from the stream are only delivered once
+CREATE UNIQUE INDEX idx_webhook_delivery_event ON "WebhookDelivery" ("subscriptionId", "eventId") WHERE "replayOf" IS NULL;
+CREATE INDEX idx_webhook_delivery_due ON "WebhookDelivery" ("nextAttemptAt") WHERE "status" = 'PENDING';
+CREATE INDEX idx_webhook_delivery_subscription ON "WebhookDelivery" ("subscriptionId", "createdAt");
+
+-- Position of each reader of the domain event stream
+CREATE TABLE "StreamCursor" (
+                                "consumer" VARCHAR(64) PRIMARY KEY,
+                                "lastId" VARCHAR(64) NOT NULL,
+                                "updatedAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp
+);
@@ -0,0 +1,18 @@
+-- States an event concerns: the farm's state, or the states of the user's farms. Lets stream
+-- readers filter by region without looking the entity up.
+ALTER TABLE "OutboxEvent" ADD COLUMN "stateIds" UUID[] NOT NULL DEFAULT '{}';
+
+-- Credentials for clients of the live event stream. Only a hash of the key is kept.
+CREATE TABLE "ApiKey" (
+                          "id" UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+                          "createdAt" TIMESTAMPTZ NOT NULL DEFAULT current_timestamp,
+                          "name" VARCHAR(191) NOT NULL,
+                          "keyPrefix" VARCHAR(16) NOT NULL,
+                          "keyHash" VARCHAR(64) NOT NULL UNIQUE,
+                          -- Entity types the key may see
+                          "entityTypes" JSONB NOT NULL CHECK (jsonb_typeof("entityTypes") = 'array'),
+                          -- States the key may see; empty for every state
+                          "stateIds" UUID[] NOT NULL DEFAULT '{}',
+                          "lastUsedAt" TIMESTAMPTZ,
+                          "revokedAt" TIMESTAMPTZ
+);
@@ -0,0 +1,37 @@
+-- Room for three-digit minor units (KWD, BHD, ...). Amounts are rounded per currency by the API.
+CREATE TYPE money_value_wide AS (
+    "amount" NUMERIC(20, 3),
+    "currency" VARCHAR(3)
+);
+
+-- The column list of the valuation trigger pins "land_value"'s type
+DROP TRIGGER trg_farm_valuation ON "Farm";
+
+ALTER TABLE "Farm"
+    ALTER COLUMN "land_value" TYPE money_value_wide
+        USING ROW(("land_value")."amount", ("land_value")."currency")::money_value_wide;
+ALTER TABLE "FarmValuation"
+    ALTER COLUMN "value" TYPE money_value_wide
+        USING ROW(("value")."amount", ("value")."currency")::money_value_wide;
+ALTER TABLE "InputTransaction"
+    ALTER COLUMN "unitCost" TYPE money_value_wide
+        USING ROW(("unitCost")."amount", ("unitCost")."currency")::money_value_wide;
+ALTER TABLE "LivestockEvent"
+    ALTER COLUMN "salePrice" TYPE money_value_wide
+        USING ROW(("salePrice")."amount", ("salePrice")."currency")::money_value_wide;
+ALTER TABLE "LandListing"
+    ALTER COLUMN "rentPerHectare" TYPE money_value_wide
+        USING ROW(("rentPerHectare")."amount", ("rentPerHectare")."currency")::money_value_wide;
+ALTER TABLE "Lease"
+    ALTER COLUMN "rent" TYPE money_value_wide
+        USING ROW(("rent")."amount", ("rent")."currency")::money_value_wide;
+ALTER TABLE "FarmTransfer"
+    ALTER COLUMN "price" TYPE money_value_wide
+        USING ROW(("price")."amount", ("price")."currency")::money_value_wide;
+
+DROP TYPE money_value;
+ALTER TYPE money_value_wide RENAME TO money_value;
+
+CREATE TRIGGER trg_farm_valuation
+    AFTER INSERT OR UPDATE OF "land_value" ON "Farm"
+    FOR EACH ROW EXECUTE FUNCTION record_farm_valuation();
@@ -0,0 +1,65 @@
+-- Search farms and farmers by the state and locality as written on the farm as well, so
+-- farms whose free text has no reference match (see "FarmReferenceUnmatched") can be found
+
+-- `place` names the farm's LGA and state, falling back to the free text; `written` is the free text
+CREATE FUNCTION farm_place(locality TEXT, state TEXT, lga_id UUID, state_id UUID)
+    RETURNS TABLE (place TEXT, written TEXT) AS $$
+    SELECT coalesce(NULLIF(concat_ws(', ', l.name, s.name), ''), w.written), w.written
+    FROM (SELECT NULLIF(concat_ws(', ', NULLIF(trim(locality), ''), NULLIF(trim(state), '')), '') AS written) w
+    LEFT JOIN "Lga" l ON l.id = lga_id
+    LEFT JOIN "State" s ON s.id = state_id;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION refresh_user_search(target UUID) RETURNS void AS $$
+BEGIN
+    DELETE FROM "SearchDocument" WHERE "entityType" = 'USER' AND "entityId" = target;
+
+    INSERT INTO "SearchDocument" ("entityType", "entityId", "title", "subtitle", "content", "document")
+    SELECT 'USER', u.id, d.name, NULLIF(concat_ws(' · ', p."phoneNumber", d.places), ''),
+           lower(concat_ws(' ', d.name, d.places, d.written, d.digits, right(d.digits, 10))),
+           setweight(to_tsvector('simple', d.name), 'A')
+               || setweight(to_tsvector('simple', concat_ws(' ', d.places, d.written)), 'B')
+               || setweight(to_tsvector('simple', concat_ws(' ', d.digits, right(d.digits, 10))), 'C')
+    FROM "User" u
+    LEFT JOIN "Profile" p ON p."userId" = u.id
+    CROSS JOIN LATERAL (
+        SELECT concat_ws(' ', u."firstName", u."middleName", u."lastName") AS name,
+               NULLIF(regexp_replace(coalesce(p."phoneNumber", ''), '\D', '', 'g'), '') AS digits,
+               string_agg(DISTINCT fp.place, '; ') AS places,
+               string_agg(DISTINCT fp.written, '; ') AS written
+        FROM "Farm" f
+        CROSS JOIN LATERAL farm_place(f.locality, f.state, f."lgaId", f."stateId") fp
+        WHERE f."farmerId" = u.id
+    ) d
+    WHERE u.id = target;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION refresh_farm_search(target UUID) RETURNS void AS $$
+BEGIN
+    DELETE FROM "SearchDocument" WHERE "entityType" = 'FARM' AND "entityId" = target;
+
+    INSERT INTO "SearchDocument" ("entityType", "entityId", "title", "subtitle", "content", "document")
+    SELECT 'FARM', f.id, coalesce(f.farm_name, 'Unnamed farm'), NULLIF(concat_ws(' · ', d.farmer, fp.place), ''),
+           lower(concat_ws(' ', f.farm_name, d.farmer, fp.place, fp.written)),
+           setweight(to_tsvector('simple', coalesce(f.farm_name, '')), 'A')
+               || setweight(to_tsvector('simple', concat_ws(' ', fp.place, fp.written)), 'B')
+               || setweight(to_tsvector('simple', coalesce(d.farmer, '')), 'C')
+    FROM "Farm" f
+    JOIN "User" u ON u.id = f."farmerId"
+    CROSS JOIN LATERAL farm_place(f.locality, f.state, f."lgaId", f."stateId") fp
+    CROSS JOIN LATERAL (
+        SELECT concat_ws(' ', u."firstName", u."middleName", u."lastName") AS farmer
+    ) d
+    WHERE f.id = target;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER trg_farm_search ON "Farm";
+
+CREATE TRIGGER trg_farm_search
+    AFTER INSERT OR UPDATE OF farm_name, "farmerId", state, locality, "stateId", "lgaId" OR DELETE ON "Farm"
+    FOR EACH ROW EXECUTE FUNCTION sync_farm_search();
+
+SELECT refresh_user_search("id") FROM "User";
+SELECT refresh_farm_search("id") FROM "Farm";
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+const EARTH_RADIUS_METRES: f64 = 6_371_008.8;
+
+/// A `[longitude, latitude]` pair, as in GeoJSON
+pub type Position = [f64; 2];
+
+// ------** Polygon **------//
+// GeoJSON polygon: the first ring is the exterior, any further rings are holes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Polygon {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub coordinates: Vec<Vec<Position>>,
+}
+
+impl Polygon {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kind != "Polygon" {
+            return Err(format!("expected a Polygon geometry, got {}", self.kind));
+        }
+        if self.coordinates.is_empty() {
+            return Err("polygon has no rings".to_string());
+        }
+        for ring in &self.coordinates {
+            if ring.len() < 4 {
+                return Err("polygon rings need at least four positions".to_string());
+            }
+            if ring.first() != ring.last() {
+                return Err("polygon rings must be closed".to_string());
+            }
+            if let Some(p) = ring.iter().find(|p| !valid_position(p)) {
+                return Err(format!("invalid position [{}, {}]", p[0], p[1]));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn exterior(&self) -> &[Position] {
+        self.coordinates.first().map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn holes(&self) -> &[Vec<Position>] {
+        self.coordinates.get(1..).unwrap_or(&[])
+    }
+
+    /// Points on the boundary count as inside
+    pub fn contains_point(&self, point: Position) -> bool {
+        let exterior = self.exterior();
+        if on_ring(exterior, point) {
+            return true;
+        }
+        if !ring_contains(exterior, point) {
+            return false;
+        }
+        !self.holes().iter().any(|hole| ring_contains(hole, point) && !on_ring(hole, point))
+    }
+
+    /// `other` lies within this polygon when all of its vertices are inside,
+    /// none of its edges cross one of ours and none of our holes lies within it
+    pub fn contains_polygon(&self, other: &Polygon) -> bool {
+        if !other.exterior().iter().all(|p| self.contains_point(*p)) {
+            return false;
+        }
+        let strictly_inside_other = |p: &Position| {
+            other.contains_point(*p) && !other.coordinates.iter().any(|ring| on_ring(ring, *p))
+        };
+        if self.holes().iter().flatten().any(strictly_inside_other) {
+            return false;
+        }
+        let edges = |rings: &[Vec<Position>]| -> Vec<(Position, Position)> {
+            rings.iter()
+                .flat_map(|ring| ring.windows(2).map(|w| (w[0], w[1])))
+                .collect()
+        };
+        let ours = edges(&self.coordinates);
+        let theirs = edges(other.coordinates.get(..1).unwrap_or(&[]));
+        !theirs.iter().any(|a| ours.iter().any(|b| segments_cross(*a, *b)))
+    }
+
+    pub fn centroid(&self) -> Position {
+        let ring = self.exterior();
+        let n = ring.len().saturating_sub(1).max(1) as f64;
+        let (lon, lat) = ring.iter().take(ring.len().saturating_sub(1))
+            .fold((0.0, 0.0), |(x, y), p| (x + p[0], y + p[1]));
+        [lon / n, lat / n]
+    }
+
+    /// Approximate area using a local equirectangular projection, which is
+    /// accurate enough at farm scale
+    pub fn area_hectares(&self) -> f64 {
+        let origin = self.centroid();
+        let exterior = projected_ring_area(self.exterior(), origin);
+        let holes: f64 = self.holes().iter().map(|hole| projected_ring_area(hole, origin)).sum();
+        (exterior - holes).max(0.0) / 10_000.0
+    }
+}
+
+/// Great-circle distance in metres
+pub fn distance_metres(a: Position, b: Position) -> f64 {
+    let (lat1, lat2) = (a[1].to_radians(), b[1].to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (b[0] - a[0]).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METRES * h.sqrt().asin()
+}
+
+fn valid_position(p: &Position) -> bool {
+    (-180.0..=180.0).contains(&p[0]) && (-90.0..=90.0).contains(&p[1])
+}
+
+fn projected_ring_area(ring: &[Position], origin: Position) -> f64 {
+    let scale_x = EARTH_RADIUS_METRES * origin[1].to_radians().cos();
+    let project = |p: &Position| {
+        ((p[0] - origin[0]).to_radians() * scale_x, (p[1] - origin[1]).to_radians() * EARTH_RADIUS_METRES)
+    };
+    let twice_area: f64 = ring.windows(2)
+        .map(|w| {
+            let (x1, y1) = project(&w[0]);
+            let (x2, y2) = project(&w[1]);
+            x1 * y2 - x2 * y1
+        })
+        .sum();
+    twice_area.abs() / 2.0
+}
+
+// Even-odd ray casting
+fn ring_contains(ring: &[Position], point: Position) -> bool {
+    let mut inside = false;
+    for w in ring.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        if (a[1] > point[1]) != (b[1] > point[1]) {
+            let x = a[0] + (point[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+            if point[0] < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn on_ring(ring: &[Position], point: Position) -> bool {
+    ring.windows(2).any(|w| orientation(w[0], w[1], point) == 0.0 && within_box(w[0], w[1], point))
+}
+
+fn orientation(a: Position, b: Position, c: Position) -> f64 {
+    let value = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+    if value.abs() < 1e-12 { 0.0 } else { value.signum() }
+}
+
+fn within_box(a: Position, b: Position, p: Position) -> bool {
+    p[0] >= a[0].min(b[0]) && p[0] <= a[0].max(b[0]) && p[1] >= a[1].min(b[1]) && p[1] <= a[1].max(b[1])
+}
+
+// Proper crossings only; shared vertices and touching edges are allowed
+fn segments_cross(a: (Position, Position), b: (Position, Position)) -> bool {
+    let o1 = orientation(a.0, a.1, b.0);
+    let o2 = orientation(a.0, a.1, b.1);
+    let o3 = orientation(b.0, b.1, a.0);
+    let o4 = orientation(b.0, b.1, a.1);
+    o1 * o2 < 0.0 && o3 * o4 < 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Metres spanned by one degree of latitude
+    const METRES_PER_DEGREE: f64 = EARTH_RADIUS_METRES * std::f64::consts::PI / 180.0;
+
+    fn polygon(rings: &[&[Position]]) -> Polygon {
+        Polygon {
+            kind: "Polygon".to_string(),
+            coordinates: rings.iter().map(|ring| ring.to_vec()).collect(),
+        }
+    }
+
+    fn square(min: f64, max: f64) -> Vec<Position> {
+        vec![[min, min], [max, min], [max, max], [min, max], [min, min]]
+    }
+
+    // A U opening upwards, with the notch between x = 1 and x = 2 above y = 1
+    fn u_shape() -> Polygon {
+        polygon(&[&[[0.0, 0.0], [3.0, 0.0], [3.0, 3.0], [2.0, 3.0], [2.0, 1.0], [1.0, 1.0], [1.0, 3.0], [0.0, 3.0], [0.0, 0.0]]])
+    }
+
+    #[test]
+    fn contains_point_inside_and_outside() {
+        let square = polygon(&[&square(0.0, 2.0)]);
+        assert!(square.contains_point([1.0, 1.0]));
+        assert!(!square.contains_point([3.0, 1.0]));
+        assert!(!square.contains_point([1.0, -0.5]));
+    }
+
+    #[test]
+    fn contains_point_counts_the_boundary_as_inside() {
+        let square = polygon(&[&square(0.0, 2.0)]);
+        assert!(square.contains_point([2.0, 1.0]));
+        assert!(square.contains_point([1.0, 0.0]));
+        assert!(square.contains_point([0.0, 0.0]));
+    }
+
+    #[test]
+    fn contains_point_in_a_concave_polygon() {
+        let u = u_shape();
+        assert!(u.contains_point([0.5, 2.5]));
+        assert!(u.contains_point([2.5, 2.5]));
+        assert!(u.contains_point([1.5, 0.5]));
+        assert!(!u.contains_point([1.5, 2.0]));
+        assert!(u.contains_point([1.5, 1.0]));
+    }
+
+    #[test]
+    fn contains_point_excludes_holes_but_not_their_edges() {
+        let outer = square(0.0, 4.0);
+        let hole = square(1.0, 2.0);
+        let with_hole = polygon(&[&outer, &hole]);
+        assert!(!with_hole.contains_point([1.5, 1.5]));
+        assert!(with_hole.contains_point([1.0, 1.5]));
+        assert!(with_hole.contains_point([3.0, 3.0]));
+    }
+
+    #[test]
+    fn contains_polygon_inside_and_sharing_edges() {
+        let farm = polygon(&[&square(0.0, 4.0)]);
+        assert!(farm.contains_polygon(&polygon(&[&square(1.0, 2.0)])));
+        assert!(farm.contains_polygon(&polygon(&[&square(0.0, 2.0)])));
+        assert!(farm.contains_polygon(&farm));
+        assert!(!farm.contains_polygon(&polygon(&[&square(3.0, 5.0)])));
+    }
+
+    #[test]
+    fn contains_polygon_rejects_plots_enclosing_a_hole() {
+        let farm = polygon(&[&square(0.0, 4.0), &square(1.0, 2.0)]);
+        // No vertex or edge of the plot touches the hole, which lies wholly inside it
+        assert!(!farm.contains_polygon(&polygon(&[&square(0.5, 2.5)])));
+        // Sharing an edge with the hole is fine as long as the hole stays outside
+        assert!(farm.contains_polygon(&polygon(&[&[[2.0, 1.0], [3.0, 1.0], [3.0, 2.0], [2.0, 2.0], [2.0, 1.0]]])));
+        assert!(farm.contains_polygon(&polygon(&[&square(2.5, 3.5)])));
+        // A plot with the same hole cut out leaves it outside
+        assert!(farm.contains_polygon(&polygon(&[&square(0.5, 2.5), &square(1.0, 2.0)])));
+    }
+
+    #[test]
+    fn contains_polygon_rejects_edges_crossing_a_concave_notch() {
+        let u = u_shape();
+        // Every vertex lies in the U, but the top edge spans the notch
+        let bridge = polygon(&[&[[0.5, 2.0], [2.5, 2.0], [2.5, 2.5], [0.5, 2.5], [0.5, 2.0]]]);
+        assert!(bridge.exterior().iter().all(|p| u.contains_point(*p)));
+        assert!(!u.contains_polygon(&bridge));
+
+        let arm = polygon(&[&[[0.0, 1.0], [1.0, 1.0], [1.0, 3.0], [0.0, 3.0], [0.0, 1.0]]]);
+        assert!(u.contains_polygon(&arm));
+    }
+
+    #[test]
+    fn area_hectares_of_a_hectare_square() {
+        let side = 100.0 / METRES_PER_DEGREE;
+        let hectare = polygon(&[&square(0.0, side)]);
+        assert!((hectare.area_hectares() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn area_hectares_subtracts_holes() {
+        let side = 100.0 / METRES_PER_DEGREE;
+        let hole = square(0.0, side / 2.0);
+        let with_hole = polygon(&[&square(0.0, side), &hole]);
+        assert!((with_hole.area_hectares() - 0.75).abs() < 1e-3);
+    }
+
+    #[test]
+    fn area_hectares_of_a_concave_polygon() {
+        let degree = 100.0 / METRES_PER_DEGREE;
+        let u = u_shape();
+        let scaled = polygon(&[&u.exterior().iter().map(|p| [p[0] * degree, p[1] * degree]).collect::<Vec<_>>()]);
+        // Nine squares of a hectare, less the two in the notch
+        assert!((scaled.area_hectares() - 7.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn distance_metres_along_a_meridian_and_the_equator() {
+        assert_eq!(distance_metres([7.49, 9.06], [7.49, 9.06]), 0.0);
+        assert!((distance_metres([3.0, 6.0], [3.0, 7.0]) - METRES_PER_DEGREE).abs() < 1e-6);
+        assert!((distance_metres([0.0, 0.0], [1.0, 0.0]) - METRES_PER_DEGREE).abs() < 1e-6);
+    }
+
+    #[test]
+    fn distance_metres_shrinks_with_latitude() {
+        let at_sixty = distance_metres([0.0, 60.0], [1.0, 60.0]);
+        assert!((at_sixty - METRES_PER_DEGREE / 2.0).abs() < 1.0);
+    }
+}
@@ -1 +1,2 @@
-pub mod models;
\ No newline at end of file
+pub mod models;
+pub mod geo;
\ No newline at end of file
@@ -1,6 +1,14 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef, Postgres};
+use sqlx::postgres::types::{Oid, PgRecordDecoder, PgRecordEncoder};
+use sqlx::types::Json;
 use uuid::Uuid;
+use crate::geo::Polygon;
 
 // ------** Pagination Model **------//
 // PAGINATION
@@ -11,6 +19,288 @@ pub struct Pagination {
 }
 
 
+// ------** Measurement Model **------//
+// AREA UNIT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AreaUnit {
+    #[default]
+    Hectare,
+    Acre,
+    SquareMetre,
+    // A standard Nigerian plot of 50 x 100 feet
+    Plot,
+}
+
+impl AreaUnit {
+    const SQUARE_METRES_PER_HECTARE: f64 = 10_000.0;
+
+    pub fn square_metres(self) -> f64 {
+        match self {
+            AreaUnit::Hectare => Self::SQUARE_METRES_PER_HECTARE,
+            AreaUnit::Acre => 4_046.856_422_4,
+            AreaUnit::SquareMetre => 1.0,
+            AreaUnit::Plot => 464.515_2,
+        }
+    }
+
+    pub fn to_hectares(self, value: f64) -> f64 {
+        value * self.square_metres() / Self::SQUARE_METRES_PER_HECTARE
+    }
+
+    pub fn from_hectares(self, hectares: f64) -> f64 {
+        hectares * Self::SQUARE_METRES_PER_HECTARE / self.square_metres()
+    }
+}
+
+// AREA
+// Accepts either a bare number or `{ "value": 2.5, "unit": "hectare" }`. Bare numbers are
+// acres, as areas were captured before units were introduced.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "AreaInput")]
+pub struct Area {
+    pub value: f64,
+    pub unit: AreaUnit,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AreaInput {
+    Acres(f64),
+    Measured { value: f64, unit: AreaUnit },
+}
+
+impl TryFrom<AreaInput> for Area {
+    type Error = String;
+
+    fn try_from(input: AreaInput) -> Result<Self, Self::Error> {
+        let area = match input {
+            AreaInput::Acres(value) => Area { value, unit: AreaUnit::Acre },
+            AreaInput::Measured { value, unit } => Area { value, unit },
+        };
+        if !area.value.is_finite() || area.value < 0.0 {
+            return Err(format!("invalid area value: {}", area.value));
+        }
+        Ok(area)
+    }
+}
+
+impl Area {
+    pub fn hectares(value: f64) -> Self {
+        Area { value, unit: AreaUnit::Hectare }
+    }
+
+    /// Canonical storage value
+    pub fn to_hectares(&self) -> f64 {
+        self.unit.to_hectares(self.value)
+    }
+
+    pub fn convert(&self, unit: AreaUnit) -> Self {
+        Area { value: unit.from_hectares(self.to_hectares()), unit }
+    }
+}
+
+// MASS UNIT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MassUnit {
+    Kilogram,
+    Tonne,
+    // Standard produce bags
+    Bag50kg,
+    Bag100kg,
+}
+
+impl MassUnit {
+    pub fn to_kilograms(self, value: f64) -> f64 {
+        match self {
+            MassUnit::Kilogram => value,
+            MassUnit::Tonne => value * 1_000.0,
+            MassUnit::Bag50kg => value * 50.0,
+            MassUnit::Bag100kg => value * 100.0,
+        }
+    }
+}
+
+// AREA UNIT QUERY
+#[derive(Debug, Deserialize)]
+pub struct AreaUnitQuery {
+    pub unit: Option<AreaUnit>,
+}
+
+
+// ------** Money Model **------//
+// ISO 4217 currencies in circulation and the digits of their minor unit, sorted by code.
+// Amounts are stored with three decimals, so the four-digit units of account (CLF, UYW)
+// are left out.
+const CURRENCIES: &[(&str, u32)] = &[
+    ("AED", 2), ("AFN", 2), ("ALL", 2), ("AMD", 2), ("AOA", 2), ("ARS", 2), ("AUD", 2), ("AWG", 2),
+    ("AZN", 2), ("BAM", 2), ("BBD", 2), ("BDT", 2), ("BGN", 2), ("BHD", 3), ("BIF", 0), ("BMD", 2),
+    ("BND", 2), ("BOB", 2), ("BOV", 2), ("BRL", 2), ("BSD", 2), ("BTN", 2), ("BWP", 2), ("BYN", 2),
+    ("BZD", 2), ("CAD", 2), ("CDF", 2), ("CHE", 2), ("CHF", 2), ("CHW", 2), ("CLP", 0),
+    ("CNY", 2), ("COP", 2), ("COU", 2), ("CRC", 2), ("CUP", 2), ("CVE", 2), ("CZK", 2), ("DJF", 0),
+    ("DKK", 2), ("DOP", 2), ("DZD", 2), ("EGP", 2), ("ERN", 2), ("ETB", 2), ("EUR", 2), ("FJD", 2),
+    ("FKP", 2), ("GBP", 2), ("GEL", 2), ("GHS", 2), ("GIP", 2), ("GMD", 2), ("GNF", 0), ("GTQ", 2),
+    ("GYD", 2), ("HKD", 2), ("HNL", 2), ("HTG", 2), ("HUF", 2), ("IDR", 2), ("ILS", 2), ("INR", 2),
+    ("IQD", 3), ("IRR", 2), ("ISK", 0), ("JMD", 2), ("JOD", 3), ("JPY", 0), ("KES", 2), ("KGS", 2),
+    ("KHR", 2), ("KMF", 0), ("KPW", 2), ("KRW", 0), ("KWD", 3), ("KYD", 2), ("KZT", 2), ("LAK", 2),
+    ("LBP", 2), ("LKR", 2), ("LRD", 2), ("LSL", 2), ("LYD", 3), ("MAD", 2), ("MDL", 2), ("MGA", 2),
+    ("MKD", 2), ("MMK", 2), ("MNT", 2), ("MOP", 2), ("MRU", 2), ("MUR", 2), ("MVR", 2), ("MWK", 2),
+    ("MXN", 2), ("MXV", 2), ("MYR", 2), ("MZN", 2), ("NAD", 2), ("NGN", 2), ("NIO", 2), ("NOK", 2),
+    ("NPR", 2), ("NZD", 2), ("OMR", 3), ("PAB", 2), ("PEN", 2), ("PGK", 2), ("PHP", 2), ("PKR", 2),
+    ("PLN", 2), ("PYG", 0), ("QAR", 2), ("RON", 2), ("RSD", 2), ("RUB", 2), ("RWF", 0), ("SAR", 2),
+    ("SBD", 2), ("SCR", 2), ("SDG", 2), ("SEK", 2), ("SGD", 2), ("SHP", 2), ("SLE", 2), ("SOS", 2),
+    ("SRD", 2), ("SSP", 2), ("STN", 2), ("SVC", 2), ("SYP", 2), ("SZL", 2), ("THB", 2), ("TJS", 2),
+    ("TMT", 2), ("TND", 3), ("TOP", 2), ("TRY", 2), ("TTD", 2), ("TWD", 2), ("TZS", 2), ("UAH", 2),
+    ("UGX", 0), ("USD", 2), ("USN", 2), ("UYI", 0), ("UYU", 2), ("UZS", 2), ("VED", 2),
+    ("VES", 2), ("VND", 0), ("VUV", 0), ("WST", 2), ("XAF", 0), ("XCD", 2), ("XCG", 2), ("XOF", 0),
+    ("XPF", 0), ("YER", 2), ("ZAR", 2), ("ZMW", 2), ("ZWG", 2),
+];
+
+// MONEY
+// Stored as the `money_value` composite type: NUMERIC amount plus ISO 4217 code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "MoneyInput")]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: String,
+}
+
+impl sqlx::Type<Postgres> for Money {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("money_value")
+    }
+}
+
+// Encoded by hand: Postgres rejects a TEXT field for the VARCHAR `currency` attribute
+impl sqlx::Encode<'_, Postgres> for Money {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        let mut encoder = PgRecordEncoder::new(buf);
+        encoder.encode(self.amount);
+        encoder.encode(Varchar(&self.currency));
+        encoder.finish();
+        IsNull::No
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for Money {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let mut decoder = PgRecordDecoder::new(value)?;
+        let amount = decoder.try_decode::<Decimal>()?;
+        let currency = decoder.try_decode::<String>()?;
+        // Binary record input skips the NUMERIC typmod, so normalise the scale here
+        let digits = Money::minor_unit_digits(&currency).unwrap_or(amount.scale());
+        Ok(Money { amount: Money::round(amount, digits), currency })
+    }
+}
+
+struct Varchar<'a>(&'a str);
+
+impl sqlx::Type<Postgres> for Varchar<'_> {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_oid(Oid(1043))
+    }
+}
+
+impl sqlx::Encode<'_, Postgres> for Varchar<'_> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <&str as sqlx::Encode<Postgres>>::encode(self.0, buf)
+    }
+}
+
+#[derive(Deserialize)]
+struct MoneyInput {
+    amount: Decimal,
+    currency: String,
+}
+
+impl TryFrom<MoneyInput> for Money {
+    type Error = String;
+
+    fn try_from(input: MoneyInput) -> Result<Self, Self::Error> {
+        Money::new(input.amount, &input.currency)
+    }
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: &str) -> Result<Self, String> {
+        let currency = currency.trim().to_ascii_uppercase();
+        let digits = Self::minor_unit_digits(&currency)
+            .ok_or_else(|| format!("invalid ISO 4217 currency code: {}", currency))?;
+        Ok(Money {
+            amount: Self::round(amount, digits),
+            currency,
+        })
+    }
+
+    // Halves round away from zero, as on receipts and bank statements
+    fn round(amount: Decimal, digits: u32) -> Decimal {
+        amount.round_dp_with_strategy(digits, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    /// Digits after the decimal point for `currency`, e.g. 0 for JPY and 3 for KWD
+    pub fn minor_unit_digits(currency: &str) -> Option<u32> {
+        CURRENCIES.binary_search_by_key(&currency, |&(code, _)| code)
+            .ok()
+            .map(|index| CURRENCIES[index].1)
+    }
+}
+
+// EXCHANGE RATE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "baseCurrency")]
+    #[serde(rename = "baseCurrency")]
+    pub base_currency: String,
+    #[sqlx(rename = "quoteCurrency")]
+    #[serde(rename = "quoteCurrency")]
+    pub quote_currency: String,
+    // Units of quote currency per one unit of base currency
+    pub rate: Decimal,
+    #[sqlx(rename = "effectiveDate")]
+    #[serde(rename = "effectiveDate")]
+    pub effective_date: NaiveDate,
+}
+
+// CREATE EXCHANGE RATE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateExchangeRate {
+    #[serde(rename = "baseCurrency")]
+    pub base_currency: String,
+    #[serde(rename = "quoteCurrency")]
+    pub quote_currency: String,
+    pub rate: Decimal,
+    #[serde(rename = "effectiveDate")]
+    pub effective_date: NaiveDate,
+}
+
+// CONVERT MONEY
+#[derive(Debug, Deserialize)]
+pub struct ConvertMoney {
+    pub amount: Decimal,
+    pub from: String,
+    pub to: String,
+    pub date: Option<NaiveDate>,
+}
+
+// FARM VALUATION
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct FarmValuation {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    pub value: Money,
+}
+
+
 // ------** User Model **------//
 // GET USER
 #[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
@@ -80,6 +370,58 @@ pub struct Profile {
     pub phone_number: Option<String>,
     #[sqlx(rename = "userId")]
     pub user_id: Uuid,
+    #[sqlx(rename = "kycStatus")]
+    pub kyc_status: KycStatus,
+    #[sqlx(rename = "kycReason")]
+    pub kyc_reason: Option<String>,
+    #[sqlx(rename = "phoneCarrier")]
+    pub phone_carrier: Option<String>,
+    #[sqlx(rename = "phoneLineType")]
+    pub phone_line_type: Option<PhoneLineType>,
+}
+
+// PHONE LINE TYPE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PhoneLineType {
+    Mobile,
+    FixedLine,
+    // Regions such as the US do not tell the two apart
+    FixedLineOrMobile,
+    TollFree,
+    PremiumRate,
+    Voip,
+    Other,
+}
+
+// PHONE NUMBER ISSUE REASON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PhoneIssueReason {
+    Invalid,
+    // Another profile already had the number
+    Duplicate,
+}
+
+// GET PHONE NUMBER ISSUE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct PhoneNumberIssue {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "profileId")]
+    #[serde(rename = "profileId")]
+    pub profile_id: Uuid,
+    // As it was before normalisation
+    #[sqlx(rename = "phoneNumber")]
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    pub reason: PhoneIssueReason,
+    #[sqlx(rename = "duplicateOfProfileId")]
+    #[serde(rename = "duplicateOfProfileId")]
+    pub duplicate_of_profile_id: Option<Uuid>,
 }
 
 // CREATE PROFILE
@@ -135,7 +477,7 @@ pub struct Farm {
     pub state: String,
     pub locality: String,
     pub has_drainage_tile: Option<bool>,
-    pub land_value: Option<i32>,
+    pub land_value: Option<Money>,
     pub is_irrigated: Option<bool>,
     pub ownership: String,
     pub available_portion: Option<f64>,
@@ -145,6 +487,28 @@ pub struct Farm {
     pub latitude: f64,
     pub longitude: f64,
     pub farm_site: Option<String>,
+    #[sqlx(rename = "countryId")]
+    pub country_id: Option<Uuid>,
+    #[sqlx(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    #[sqlx(rename = "lgaId")]
+    pub lga_id: Option<Uuid>,
+    pub boundary: Option<Json<Polygon>>,
+    // Unit of `acreage` and `available_portion`; rows are stored in hectares
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub area_unit: AreaUnit,
+}
+
+impl Farm {
+    pub fn in_unit(mut self, unit: AreaUnit) -> Self {
+        let current = self.area_unit;
+        self.acreage = unit.from_hectares(current.to_hectares(self.acreage));
+        self.available_portion = self.available_portion
+            .map(|portion| unit.from_hectares(current.to_hectares(portion)));
+        self.area_unit = unit;
+        self
+    }
 }
 
 // CREATE FARM
@@ -152,20 +516,27 @@ pub struct Farm {
 pub struct CreateFarm {
     #[serde(rename = "farmName")]
     pub farm_name: String,
-    pub acreage: f64,
+    pub acreage: Area,
     pub state: String,
     pub locality: String,
     pub has_drainage_tile: bool,
-    pub land_value: i32,
+    pub land_value: Money,
     pub is_irrigated: bool,
     pub ownership: String,
-    pub available_portion: f64,
+    pub available_portion: Area,
     pub country: String,
     #[serde(rename = "farmerId")]
     pub farmer_id: Uuid,
     pub latitude: f64,
     pub longitude: f64,
     pub farm_site: String,
+    #[serde(rename = "countryId")]
+    pub country_id: Option<Uuid>,
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    #[serde(rename = "lgaId")]
+    pub lga_id: Option<Uuid>,
+    pub boundary: Option<Polygon>,
 }
 
 // UPDATE FARM
@@ -173,24 +544,3003 @@ pub struct CreateFarm {
 pub struct UpdateFarm {
     pub id: Uuid,
     pub farm_name: Option<String>,
-    pub acreage: Option<f64>,
+    pub acreage: Option<Area>,
     pub state: Option<String>,
     pub locality: Option<String>,
     pub has_drainage_tile: Option<bool>,
-    pub land_value: Option<i32>,
+    pub land_value: Option<Money>,
     pub is_irrigated: Option<bool>,
     pub ownership: Option<String>,
-    pub available_portion: Option<f64>,
+    pub available_portion: Option<Area>,
     pub country: Option<String>,
-    #[serde(rename = "farmerId")]
-    pub farmer_id: Option<Uuid>,
     pub latitude: Option<f64>,
     pub longitude: Option<f64>,
     pub farm_site: Option<String>,
+    #[serde(rename = "countryId")]
+    pub country_id: Option<Uuid>,
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    #[serde(rename = "lgaId")]
+    pub lga_id: Option<Uuid>,
+    pub boundary: Option<Polygon>,
 }
 
 // DELETE FARM
 #[derive(Debug, Deserialize, Serialize)]
 pub struct DeleteFarm {
     pub id: Uuid,
-}
\ No newline at end of file
+}
+
+// FARM FILTER
+#[derive(Debug, Deserialize)]
+pub struct FarmFilter {
+    #[serde(rename = "countryId")]
+    pub country_id: Option<Uuid>,
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    #[serde(rename = "lgaId")]
+    pub lga_id: Option<Uuid>,
+}
+
+
+// ------** Reference Data Model **------//
+// GET COUNTRY
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Country {
+    pub id: Uuid,
+    pub code: String,
+    pub code3: String,
+    pub name: String,
+}
+
+// GET STATE
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct State {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    #[sqlx(rename = "countryId")]
+    #[serde(rename = "countryId")]
+    pub country_id: Uuid,
+}
+
+// GET LGA
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Lga {
+    pub id: Uuid,
+    pub name: String,
+    #[sqlx(rename = "stateId")]
+    #[serde(rename = "stateId")]
+    pub state_id: Uuid,
+}
+
+// UNMATCHED FARM REFERENCE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct FarmReferenceUnmatched {
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    pub field: String,
+    pub value: String,
+}
+
+
+// ------** Plot Model **------//
+// GET PLOT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Plot {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    pub name: String,
+    pub boundary: Option<Json<Polygon>>,
+    // Hectares
+    pub area: f64,
+    #[sqlx(rename = "soilType")]
+    #[serde(rename = "soilType")]
+    pub soil_type: Option<String>,
+    pub is_irrigated: bool,
+    pub has_drainage_tile: bool,
+}
+
+// CREATE PLOT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePlot {
+    pub name: String,
+    pub boundary: Option<Polygon>,
+    // Derived from the boundary when omitted
+    pub area: Option<Area>,
+    #[serde(rename = "soilType")]
+    pub soil_type: Option<String>,
+    #[serde(default)]
+    pub is_irrigated: bool,
+    #[serde(default)]
+    pub has_drainage_tile: bool,
+}
+
+// UPDATE PLOT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdatePlot {
+    pub name: Option<String>,
+    pub boundary: Option<Polygon>,
+    pub area: Option<Area>,
+    #[serde(rename = "soilType")]
+    pub soil_type: Option<String>,
+    pub is_irrigated: Option<bool>,
+    pub has_drainage_tile: Option<bool>,
+}
+
+
+// ------** Crop Model **------//
+// GET CROP
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Crop {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    pub name: String,
+    #[sqlx(rename = "scientificName")]
+    #[serde(rename = "scientificName")]
+    pub scientific_name: Option<String>,
+    #[sqlx(rename = "waterRequirementMmPerDay")]
+    #[serde(rename = "waterRequirementMmPerDay")]
+    pub water_requirement_mm_per_day: Option<f64>,
+}
+
+// CREATE CROP
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateCrop {
+    pub name: String,
+    #[serde(rename = "scientificName")]
+    pub scientific_name: Option<String>,
+    #[serde(rename = "waterRequirementMmPerDay")]
+    pub water_requirement_mm_per_day: Option<f64>,
+}
+
+// GET CROP VARIETY
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct CropVariety {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "cropId")]
+    #[serde(rename = "cropId")]
+    pub crop_id: Uuid,
+    pub name: String,
+    #[sqlx(rename = "cycleDays")]
+    #[serde(rename = "cycleDays")]
+    pub cycle_days: i32,
+    #[sqlx(rename = "expectedYieldKgPerHectare")]
+    #[serde(rename = "expectedYieldKgPerHectare")]
+    pub expected_yield_kg_per_hectare: f64,
+}
+
+// CREATE CROP VARIETY
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateCropVariety {
+    pub name: String,
+    #[serde(rename = "cycleDays")]
+    pub cycle_days: i32,
+    #[serde(rename = "expectedYieldKgPerHectare")]
+    pub expected_yield_kg_per_hectare: f64,
+}
+
+
+// ------** Planting Model **------//
+// SEASON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Season {
+    Wet,
+    Dry,
+}
+
+// GET PLANTING
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Planting {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "plotId")]
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[sqlx(rename = "cropId")]
+    #[serde(rename = "cropId")]
+    pub crop_id: Uuid,
+    #[sqlx(rename = "varietyId")]
+    #[serde(rename = "varietyId")]
+    pub variety_id: Option<Uuid>,
+    // Hectares
+    #[sqlx(rename = "plantedArea")]
+    #[serde(rename = "plantedArea")]
+    pub planted_area: f64,
+    #[sqlx(rename = "plantingDate")]
+    #[serde(rename = "plantingDate")]
+    pub planting_date: NaiveDate,
+    #[sqlx(rename = "expectedHarvestDate")]
+    #[serde(rename = "expectedHarvestDate")]
+    pub expected_harvest_date: Option<NaiveDate>,
+    pub season: Season,
+    #[sqlx(rename = "seasonYear")]
+    #[serde(rename = "seasonYear")]
+    pub season_year: i32,
+}
+
+// CREATE PLANTING
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePlanting {
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[serde(rename = "cropId")]
+    pub crop_id: Uuid,
+    #[serde(rename = "varietyId")]
+    pub variety_id: Option<Uuid>,
+    #[serde(rename = "plantedArea")]
+    pub planted_area: Area,
+    #[serde(rename = "plantingDate")]
+    pub planting_date: NaiveDate,
+    // Defaults to the planting date plus the variety's cycle length
+    #[serde(rename = "expectedHarvestDate")]
+    pub expected_harvest_date: Option<NaiveDate>,
+    pub season: Season,
+    // Defaults to the year of the planting date
+    #[serde(rename = "seasonYear")]
+    pub season_year: Option<i32>,
+}
+
+// PLANTING FILTER
+#[derive(Debug, Deserialize)]
+pub struct PlantingFilter {
+    pub season: Option<Season>,
+    #[serde(rename = "seasonYear")]
+    pub season_year: Option<i32>,
+    #[serde(rename = "cropId")]
+    pub crop_id: Option<Uuid>,
+}
+
+
+// ------** Harvest Model **------//
+// GET HARVEST
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Harvest {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "plantingId")]
+    #[serde(rename = "plantingId")]
+    pub planting_id: Uuid,
+    pub quantity: f64,
+    pub unit: MassUnit,
+    #[sqlx(rename = "quantityKg")]
+    #[serde(rename = "quantityKg")]
+    pub quantity_kg: f64,
+    #[sqlx(rename = "postHarvestLossKg")]
+    #[serde(rename = "postHarvestLossKg")]
+    pub post_harvest_loss_kg: f64,
+    #[sqlx(rename = "qualityGrade")]
+    #[serde(rename = "qualityGrade")]
+    pub quality_grade: Option<String>,
+    #[sqlx(rename = "harvestDate")]
+    #[serde(rename = "harvestDate")]
+    pub harvest_date: NaiveDate,
+}
+
+// CREATE HARVEST
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateHarvest {
+    #[serde(rename = "plantingId")]
+    pub planting_id: Uuid,
+    pub quantity: f64,
+    pub unit: MassUnit,
+    // In the same unit as `quantity`
+    #[serde(rename = "postHarvestLoss", default)]
+    pub post_harvest_loss: f64,
+    #[serde(rename = "qualityGrade")]
+    pub quality_grade: Option<String>,
+    #[serde(rename = "harvestDate")]
+    pub harvest_date: NaiveDate,
+}
+
+// YIELD GROUPING
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YieldGroupBy {
+    #[default]
+    Farm,
+    Crop,
+    State,
+    Season,
+}
+
+// YIELD FILTER
+#[derive(Debug, Deserialize)]
+pub struct YieldFilter {
+    #[serde(rename = "groupBy", default)]
+    pub group_by: YieldGroupBy,
+    #[serde(rename = "cropId")]
+    pub crop_id: Option<Uuid>,
+    pub season: Option<Season>,
+    #[serde(rename = "seasonYear")]
+    pub season_year: Option<i32>,
+    // Share of the catalogue benchmark below which a farm is flagged
+    pub threshold: Option<f64>,
+}
+
+// YIELD SUMMARY
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct YieldSummary {
+    pub key: String,
+    pub label: Option<String>,
+    #[sqlx(rename = "farmCount")]
+    #[serde(rename = "farmCount")]
+    pub farm_count: i64,
+    #[sqlx(rename = "harvestedKg")]
+    #[serde(rename = "harvestedKg")]
+    pub harvested_kg: f64,
+    pub hectares: f64,
+    #[sqlx(rename = "yieldKgPerHectare")]
+    #[serde(rename = "yieldKgPerHectare")]
+    pub yield_kg_per_hectare: Option<f64>,
+    #[sqlx(rename = "benchmarkKgPerHectare")]
+    #[serde(rename = "benchmarkKgPerHectare")]
+    pub benchmark_kg_per_hectare: Option<f64>,
+    // Actual yield as a share of the benchmark
+    #[sqlx(rename = "performanceRatio")]
+    #[serde(rename = "performanceRatio")]
+    pub performance_ratio: Option<f64>,
+}
+
+// UNDERPERFORMING FARM
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct UnderperformingFarm {
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "farmName")]
+    #[serde(rename = "farmName")]
+    pub farm_name: Option<String>,
+    #[sqlx(rename = "cropId")]
+    #[serde(rename = "cropId")]
+    pub crop_id: Uuid,
+    pub crop: String,
+    pub season: Season,
+    #[sqlx(rename = "seasonYear")]
+    #[serde(rename = "seasonYear")]
+    pub season_year: i32,
+    #[sqlx(rename = "yieldKgPerHectare")]
+    #[serde(rename = "yieldKgPerHectare")]
+    pub yield_kg_per_hectare: f64,
+    #[sqlx(rename = "benchmarkKgPerHectare")]
+    #[serde(rename = "benchmarkKgPerHectare")]
+    pub benchmark_kg_per_hectare: f64,
+    #[sqlx(rename = "performanceRatio")]
+    #[serde(rename = "performanceRatio")]
+    pub performance_ratio: f64,
+}
+
+
+// ------** Soil Model **------//
+// GET SOIL SAMPLE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct SoilSample {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "plotId")]
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[sqlx(rename = "sampledOn")]
+    #[serde(rename = "sampledOn")]
+    pub sampled_on: NaiveDate,
+    #[sqlx(rename = "labReference")]
+    #[serde(rename = "labReference")]
+    pub lab_reference: Option<String>,
+    pub ph: Option<f64>,
+    // Total nitrogen, %
+    pub nitrogen: Option<f64>,
+    // Available phosphorus (Bray-1), mg/kg
+    pub phosphorus: Option<f64>,
+    // Exchangeable potassium, cmol/kg
+    pub potassium: Option<f64>,
+    // %
+    #[sqlx(rename = "organicMatter")]
+    #[serde(rename = "organicMatter")]
+    pub organic_matter: Option<f64>,
+    pub texture: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+// CREATE SOIL SAMPLE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateSoilSample {
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[serde(rename = "sampledOn")]
+    pub sampled_on: NaiveDate,
+    #[serde(rename = "labReference")]
+    pub lab_reference: Option<String>,
+    pub ph: Option<f64>,
+    pub nitrogen: Option<f64>,
+    pub phosphorus: Option<f64>,
+    pub potassium: Option<f64>,
+    #[serde(rename = "organicMatter")]
+    pub organic_matter: Option<f64>,
+    pub texture: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+// SOIL NUTRIENT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SoilNutrient {
+    Ph,
+    Nitrogen,
+    Phosphorus,
+    Potassium,
+    OrganicMatter,
+}
+
+impl SoilSample {
+    pub fn value(&self, nutrient: SoilNutrient) -> Option<f64> {
+        match nutrient {
+            SoilNutrient::Ph => self.ph,
+            SoilNutrient::Nitrogen => self.nitrogen,
+            SoilNutrient::Phosphorus => self.phosphorus,
+            SoilNutrient::Potassium => self.potassium,
+            SoilNutrient::OrganicMatter => self.organic_matter,
+        }
+    }
+}
+
+// GET FERTILIZER RULE
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct FertilizerRule {
+    pub id: Uuid,
+    #[sqlx(rename = "cropId")]
+    #[serde(rename = "cropId")]
+    pub crop_id: Option<Uuid>,
+    pub nutrient: SoilNutrient,
+    #[sqlx(rename = "minValue")]
+    #[serde(rename = "minValue")]
+    pub min_value: Option<f64>,
+    #[sqlx(rename = "maxValue")]
+    #[serde(rename = "maxValue")]
+    pub max_value: Option<f64>,
+    pub fertilizer: String,
+    #[sqlx(rename = "rateKgPerHectare")]
+    #[serde(rename = "rateKgPerHectare")]
+    pub rate_kg_per_hectare: f64,
+    pub priority: i32,
+    pub notes: Option<String>,
+}
+
+impl FertilizerRule {
+    pub fn matches(&self, value: f64) -> bool {
+        self.min_value.is_none_or(|min| value >= min) && self.max_value.is_none_or(|max| value < max)
+    }
+}
+
+// CREATE FERTILIZER RULE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateFertilizerRule {
+    #[serde(rename = "cropId")]
+    pub crop_id: Option<Uuid>,
+    pub nutrient: SoilNutrient,
+    #[serde(rename = "minValue")]
+    pub min_value: Option<f64>,
+    #[serde(rename = "maxValue")]
+    pub max_value: Option<f64>,
+    pub fertilizer: String,
+    #[serde(rename = "rateKgPerHectare")]
+    pub rate_kg_per_hectare: f64,
+    #[serde(default)]
+    pub priority: i32,
+    pub notes: Option<String>,
+}
+
+// RECOMMENDATION QUERY
+#[derive(Debug, Deserialize)]
+pub struct RecommendationQuery {
+    #[serde(rename = "cropId")]
+    pub crop_id: Uuid,
+    // Planned area; defaults to the plot or farm area
+    pub area: Option<f64>,
+    #[serde(default)]
+    pub unit: AreaUnit,
+}
+
+// FERTILIZER RECOMMENDATION
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FertilizerRecommendation {
+    pub nutrient: SoilNutrient,
+    #[serde(rename = "soilValue")]
+    pub soil_value: f64,
+    pub fertilizer: String,
+    #[serde(rename = "rateKgPerHectare")]
+    pub rate_kg_per_hectare: f64,
+    #[serde(rename = "totalKg")]
+    pub total_kg: f64,
+    #[serde(rename = "ruleId")]
+    pub rule_id: Uuid,
+    pub notes: Option<String>,
+}
+
+
+// ------** Weather Model **------//
+// GET WEATHER STATION
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct WeatherStation {
+    pub id: Uuid,
+    pub code: String,
+    pub name: Option<String>,
+    pub source: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+// GET WEATHER IMPORT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct WeatherImport {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "fileName")]
+    #[serde(rename = "fileName")]
+    pub file_name: Option<String>,
+    pub format: String,
+    pub stations: i32,
+    #[sqlx(rename = "rowsImported")]
+    #[serde(rename = "rowsImported")]
+    pub rows_imported: i32,
+    #[sqlx(rename = "rowsSkipped")]
+    #[serde(rename = "rowsSkipped")]
+    pub rows_skipped: i32,
+}
+
+// WEATHER IMPORT QUERY
+#[derive(Debug, Deserialize)]
+pub struct WeatherImportQuery {
+    pub format: Option<String>,
+    #[serde(rename = "fileName")]
+    pub file_name: Option<String>,
+}
+
+// FARM WEATHER QUERY
+#[derive(Debug, Deserialize)]
+pub struct FarmWeatherQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    // Base temperature for growing degree days, °C
+    #[serde(rename = "baseTemp")]
+    pub base_temp: Option<f64>,
+    // Number of nearest stations to interpolate from
+    pub stations: Option<usize>,
+    #[serde(rename = "radiusKm")]
+    pub radius_km: Option<f64>,
+}
+
+// NEARBY STATION
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NearbyStation {
+    pub id: Uuid,
+    pub code: String,
+    #[serde(rename = "distanceKm")]
+    pub distance_km: f64,
+}
+
+// WEATHER DAY
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeatherDay {
+    pub date: NaiveDate,
+    #[serde(rename = "rainfallMm")]
+    pub rainfall_mm: Option<f64>,
+    #[serde(rename = "tempMinC")]
+    pub temp_min_c: Option<f64>,
+    #[serde(rename = "tempMaxC")]
+    pub temp_max_c: Option<f64>,
+    #[serde(rename = "growingDegreeDays")]
+    pub growing_degree_days: Option<f64>,
+    #[serde(rename = "cumulativeRainfallMm")]
+    pub cumulative_rainfall_mm: f64,
+    #[serde(rename = "cumulativeGrowingDegreeDays")]
+    pub cumulative_growing_degree_days: f64,
+}
+
+// FARM WEATHER
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FarmWeather {
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[serde(rename = "baseTempC")]
+    pub base_temp_c: f64,
+    pub stations: Vec<NearbyStation>,
+    pub days: Vec<WeatherDay>,
+}
+
+
+// ------** Irrigation Model **------//
+// IRRIGATION SYSTEM TYPE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IrrigationSystemType {
+    Drip,
+    Sprinkler,
+    CenterPivot,
+    Furrow,
+    Flood,
+    Manual,
+}
+
+impl IrrigationSystemType {
+    // Typical application efficiency, used when none is given
+    pub fn default_efficiency(self) -> f64 {
+        match self {
+            IrrigationSystemType::Drip => 0.9,
+            IrrigationSystemType::CenterPivot => 0.85,
+            IrrigationSystemType::Sprinkler => 0.75,
+            IrrigationSystemType::Manual => 0.7,
+            IrrigationSystemType::Furrow => 0.6,
+            IrrigationSystemType::Flood => 0.5,
+        }
+    }
+}
+
+// WATER SOURCE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WaterSource {
+    Borehole,
+    Well,
+    River,
+    Dam,
+    Reservoir,
+    Municipal,
+    Rainwater,
+}
+
+// GET IRRIGATION SYSTEM
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct IrrigationSystem {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    pub name: Option<String>,
+    #[sqlx(rename = "systemType")]
+    #[serde(rename = "systemType")]
+    pub system_type: IrrigationSystemType,
+    #[sqlx(rename = "waterSource")]
+    #[serde(rename = "waterSource")]
+    pub water_source: WaterSource,
+    #[sqlx(rename = "capacityLitresPerHour")]
+    #[serde(rename = "capacityLitresPerHour")]
+    pub capacity_litres_per_hour: f64,
+    pub efficiency: f64,
+    #[sqlx(rename = "installedOn")]
+    #[serde(rename = "installedOn")]
+    pub installed_on: Option<NaiveDate>,
+}
+
+// CREATE IRRIGATION SYSTEM
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateIrrigationSystem {
+    pub name: Option<String>,
+    #[serde(rename = "systemType")]
+    pub system_type: IrrigationSystemType,
+    #[serde(rename = "waterSource")]
+    pub water_source: WaterSource,
+    #[serde(rename = "capacityLitresPerHour")]
+    pub capacity_litres_per_hour: f64,
+    pub efficiency: Option<f64>,
+    #[serde(rename = "installedOn")]
+    pub installed_on: Option<NaiveDate>,
+}
+
+// IRRIGATION EVENT STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum IrrigationStatus {
+    Scheduled,
+    Completed,
+    Cancelled,
+}
+
+// GET IRRIGATION EVENT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct IrrigationEvent {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "systemId")]
+    #[serde(rename = "systemId")]
+    pub system_id: Option<Uuid>,
+    #[sqlx(rename = "plotId")]
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    pub status: IrrigationStatus,
+    #[sqlx(rename = "scheduledDate")]
+    #[serde(rename = "scheduledDate")]
+    pub scheduled_date: Option<NaiveDate>,
+    #[sqlx(rename = "scheduledVolumeLitres")]
+    #[serde(rename = "scheduledVolumeLitres")]
+    pub scheduled_volume_litres: Option<f64>,
+    #[sqlx(rename = "actualDate")]
+    #[serde(rename = "actualDate")]
+    pub actual_date: Option<NaiveDate>,
+    #[sqlx(rename = "actualVolumeLitres")]
+    #[serde(rename = "actualVolumeLitres")]
+    pub actual_volume_litres: Option<f64>,
+    #[sqlx(rename = "durationMinutes")]
+    #[serde(rename = "durationMinutes")]
+    pub duration_minutes: Option<i32>,
+    pub notes: Option<String>,
+}
+
+// CREATE IRRIGATION EVENT
+// Without `actualDate` and `actualVolumeLitres` the event is scheduled
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateIrrigationEvent {
+    #[serde(rename = "systemId")]
+    pub system_id: Option<Uuid>,
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[serde(rename = "scheduledDate")]
+    pub scheduled_date: Option<NaiveDate>,
+    #[serde(rename = "scheduledVolumeLitres")]
+    pub scheduled_volume_litres: Option<f64>,
+    #[serde(rename = "actualDate")]
+    pub actual_date: Option<NaiveDate>,
+    #[serde(rename = "actualVolumeLitres")]
+    pub actual_volume_litres: Option<f64>,
+    #[serde(rename = "durationMinutes")]
+    pub duration_minutes: Option<i32>,
+    pub notes: Option<String>,
+}
+
+// COMPLETE IRRIGATION EVENT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CompleteIrrigationEvent {
+    #[serde(rename = "actualDate")]
+    pub actual_date: NaiveDate,
+    #[serde(rename = "actualVolumeLitres")]
+    pub actual_volume_litres: f64,
+    #[serde(rename = "durationMinutes")]
+    pub duration_minutes: Option<i32>,
+    pub notes: Option<String>,
+}
+
+// IRRIGATION EVENT FILTER
+#[derive(Debug, Deserialize)]
+pub struct IrrigationEventFilter {
+    pub status: Option<IrrigationStatus>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+// WATER BALANCE QUERY
+#[derive(Debug, Deserialize)]
+pub struct WaterBalanceQuery {
+    // Defaults to today
+    pub date: Option<NaiveDate>,
+    #[serde(rename = "systemId")]
+    pub system_id: Option<Uuid>,
+    // Days of rainfall and irrigation history to replay
+    #[serde(rename = "windowDays")]
+    pub window_days: Option<i64>,
+    // Soil water deficit at which irrigation is due
+    #[serde(rename = "allowableDepletionMm")]
+    pub allowable_depletion_mm: Option<f64>,
+}
+
+// WATER BALANCE
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WaterBalance {
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    pub date: NaiveDate,
+    #[serde(rename = "systemId")]
+    pub system_id: Option<Uuid>,
+    #[serde(rename = "plantedHectares")]
+    pub planted_hectares: f64,
+    #[serde(rename = "cropDemandMmPerDay")]
+    pub crop_demand_mm_per_day: f64,
+    #[serde(rename = "rainfallMm")]
+    pub rainfall_mm: f64,
+    #[serde(rename = "effectiveRainfallMm")]
+    pub effective_rainfall_mm: f64,
+    #[serde(rename = "irrigationAppliedMm")]
+    pub irrigation_applied_mm: f64,
+    #[serde(rename = "soilWaterDeficitMm")]
+    pub soil_water_deficit_mm: f64,
+    #[serde(rename = "allowableDepletionMm")]
+    pub allowable_depletion_mm: f64,
+    #[serde(rename = "nextIrrigationDate")]
+    pub next_irrigation_date: Option<NaiveDate>,
+    #[serde(rename = "recommendedVolumeLitres")]
+    pub recommended_volume_litres: Option<f64>,
+    #[serde(rename = "estimatedDurationMinutes")]
+    pub estimated_duration_minutes: Option<f64>,
+}
+
+
+// ------** Pest Incident Model **------//
+// PEST CATEGORY
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PestCategory {
+    Pest,
+    Disease,
+}
+
+// SEVERITY, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Severity {
+    Low,
+    Moderate,
+    High,
+    Severe,
+}
+
+// GET PEST INCIDENT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct PestIncident {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "plotId")]
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[sqlx(rename = "reportedBy")]
+    #[serde(rename = "reportedBy")]
+    pub reported_by: Option<Uuid>,
+    pub category: PestCategory,
+    pub name: String,
+    pub severity: Severity,
+    // Hectares
+    #[sqlx(rename = "affectedArea")]
+    #[serde(rename = "affectedArea")]
+    pub affected_area: Option<f64>,
+    #[sqlx(rename = "observedOn")]
+    #[serde(rename = "observedOn")]
+    pub observed_on: NaiveDate,
+    pub latitude: f64,
+    pub longitude: f64,
+    #[sqlx(rename = "photoUrls")]
+    #[serde(rename = "photoUrls")]
+    pub photo_urls: Vec<String>,
+    pub notes: Option<String>,
+    #[sqlx(rename = "outbreakId")]
+    #[serde(rename = "outbreakId")]
+    pub outbreak_id: Option<Uuid>,
+}
+
+// CREATE PEST INCIDENT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePestIncident {
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[serde(rename = "reportedBy")]
+    pub reported_by: Option<Uuid>,
+    pub category: PestCategory,
+    pub name: String,
+    pub severity: Severity,
+    #[serde(rename = "affectedArea")]
+    pub affected_area: Option<Area>,
+    #[serde(rename = "observedOn")]
+    pub observed_on: NaiveDate,
+    // Default to the farm's location
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    #[serde(rename = "photoUrls", default)]
+    pub photo_urls: Vec<String>,
+    pub notes: Option<String>,
+}
+
+// OUTBREAK STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OutbreakStatus {
+    Active,
+    Resolved,
+}
+
+// GET OUTBREAK ALERT
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct OutbreakAlert {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    pub category: PestCategory,
+    pub name: String,
+    pub status: OutbreakStatus,
+    pub severity: Severity,
+    // Centroid of the clustered incidents
+    pub latitude: f64,
+    pub longitude: f64,
+    #[sqlx(rename = "radiusKm")]
+    #[serde(rename = "radiusKm")]
+    pub radius_km: f64,
+    #[sqlx(rename = "incidentCount")]
+    #[serde(rename = "incidentCount")]
+    pub incident_count: i32,
+    #[sqlx(rename = "farmCount")]
+    #[serde(rename = "farmCount")]
+    pub farm_count: i32,
+    #[sqlx(rename = "firstObservedOn")]
+    #[serde(rename = "firstObservedOn")]
+    pub first_observed_on: NaiveDate,
+    #[sqlx(rename = "lastObservedOn")]
+    #[serde(rename = "lastObservedOn")]
+    pub last_observed_on: NaiveDate,
+    #[sqlx(rename = "notifiedAt")]
+    #[serde(rename = "notifiedAt")]
+    pub notified_at: Option<DateTime<Utc>>,
+    #[sqlx(rename = "resolvedAt")]
+    #[serde(rename = "resolvedAt")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+// OUTBREAK FILTER
+#[derive(Debug, Deserialize)]
+pub struct OutbreakFilter {
+    pub status: Option<OutbreakStatus>,
+    pub category: Option<PestCategory>,
+    pub name: Option<String>,
+}
+
+// OUTBREAK SCAN SUMMARY
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OutbreakScan {
+    pub created: usize,
+    pub updated: usize,
+    pub resolved: u64,
+    pub notified: usize,
+}
+
+
+// ------** Farm Input Model **------//
+// INPUT CATEGORY
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum InputCategory {
+    Seed,
+    Fertilizer,
+    Herbicide,
+    Insecticide,
+    Fungicide,
+    Other,
+}
+
+// INPUT UNIT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum InputUnit {
+    Kg,
+    Litre,
+    Piece,
+}
+
+// GET INPUT PRODUCT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct InputProduct {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    pub name: String,
+    pub category: InputCategory,
+    pub unit: InputUnit,
+    pub manufacturer: Option<String>,
+    #[sqlx(rename = "cropId")]
+    #[serde(rename = "cropId")]
+    pub crop_id: Option<Uuid>,
+    // Product units per hectare
+    #[sqlx(rename = "recommendedRatePerHectare")]
+    #[serde(rename = "recommendedRatePerHectare")]
+    pub recommended_rate_per_hectare: Option<f64>,
+    #[sqlx(rename = "maxRatePerHectare")]
+    #[serde(rename = "maxRatePerHectare")]
+    pub max_rate_per_hectare: Option<f64>,
+}
+
+// CREATE INPUT PRODUCT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateInputProduct {
+    pub name: String,
+    pub category: InputCategory,
+    pub unit: InputUnit,
+    pub manufacturer: Option<String>,
+    #[serde(rename = "cropId")]
+    pub crop_id: Option<Uuid>,
+    #[serde(rename = "recommendedRatePerHectare")]
+    pub recommended_rate_per_hectare: Option<f64>,
+    #[serde(rename = "maxRatePerHectare")]
+    pub max_rate_per_hectare: Option<f64>,
+}
+
+// INPUT PRODUCT FILTER
+#[derive(Debug, Deserialize)]
+pub struct InputProductFilter {
+    pub category: Option<InputCategory>,
+}
+
+// INPUT TRANSACTION KIND
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum InputTransactionKind {
+    Allocation,
+    Purchase,
+    Application,
+}
+
+// GET INPUT TRANSACTION
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct InputTransaction {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "farmerId")]
+    #[serde(rename = "farmerId")]
+    pub farmer_id: Uuid,
+    #[sqlx(rename = "productId")]
+    #[serde(rename = "productId")]
+    pub product_id: Uuid,
+    #[sqlx(rename = "plotId")]
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    pub kind: InputTransactionKind,
+    pub quantity: f64,
+    #[sqlx(rename = "unitCost")]
+    #[serde(rename = "unitCost")]
+    pub unit_cost: Option<Money>,
+    // Hectares
+    #[sqlx(rename = "appliedArea")]
+    #[serde(rename = "appliedArea")]
+    pub applied_area: Option<f64>,
+    #[sqlx(rename = "transactionDate")]
+    #[serde(rename = "transactionDate")]
+    pub transaction_date: NaiveDate,
+    pub supplier: Option<String>,
+    pub notes: Option<String>,
+}
+
+// CREATE INPUT TRANSACTION
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateInputTransaction {
+    #[serde(rename = "productId")]
+    pub product_id: Uuid,
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    pub kind: InputTransactionKind,
+    pub quantity: f64,
+    #[serde(rename = "unitCost")]
+    pub unit_cost: Option<Money>,
+    // Applications only; defaults to the plot's area, or the farm's
+    #[serde(rename = "appliedArea")]
+    pub applied_area: Option<Area>,
+    #[serde(rename = "transactionDate")]
+    pub transaction_date: NaiveDate,
+    pub supplier: Option<String>,
+    pub notes: Option<String>,
+}
+
+// INPUT TRANSACTION FILTER
+#[derive(Debug, Deserialize)]
+pub struct InputTransactionFilter {
+    #[serde(rename = "productId")]
+    pub product_id: Option<Uuid>,
+    pub kind: Option<InputTransactionKind>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+// INPUT STOCK BALANCE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct InputBalance {
+    #[sqlx(rename = "productId")]
+    #[serde(rename = "productId")]
+    pub product_id: Uuid,
+    pub product: String,
+    pub unit: InputUnit,
+    pub received: f64,
+    pub applied: f64,
+    pub balance: f64,
+}
+
+
+// ------** Livestock Model **------//
+// SPECIES
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Species {
+    Cattle,
+    Goat,
+    Sheep,
+    Pig,
+    Poultry,
+    Rabbit,
+    Fish,
+    Other,
+}
+
+// GET HERD
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Herd {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    pub name: String,
+    pub species: Species,
+    pub breed: Option<String>,
+    #[sqlx(rename = "startedOn")]
+    #[serde(rename = "startedOn")]
+    pub started_on: NaiveDate,
+    // Current number of animals
+    pub headcount: i64,
+}
+
+// CREATE HERD
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateHerd {
+    pub name: String,
+    pub species: Species,
+    pub breed: Option<String>,
+    #[serde(rename = "startedOn")]
+    pub started_on: NaiveDate,
+    // Animals already on hand when the herd is registered
+    #[serde(rename = "openingCount", default)]
+    pub opening_count: i32,
+}
+
+// UPDATE HERD
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateHerd {
+    pub name: Option<String>,
+    pub breed: Option<String>,
+}
+
+// ANIMAL SEX
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+// ANIMAL STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AnimalStatus {
+    Alive,
+    Dead,
+    Sold,
+}
+
+// GET ANIMAL
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Animal {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "herdId")]
+    #[serde(rename = "herdId")]
+    pub herd_id: Uuid,
+    #[sqlx(rename = "tagId")]
+    #[serde(rename = "tagId")]
+    pub tag_id: String,
+    pub sex: Sex,
+    pub breed: Option<String>,
+    #[sqlx(rename = "birthDate")]
+    #[serde(rename = "birthDate")]
+    pub birth_date: Option<NaiveDate>,
+    #[sqlx(rename = "damId")]
+    #[serde(rename = "damId")]
+    pub dam_id: Option<Uuid>,
+    #[sqlx(rename = "sireId")]
+    #[serde(rename = "sireId")]
+    pub sire_id: Option<Uuid>,
+    pub status: AnimalStatus,
+    pub notes: Option<String>,
+}
+
+// CREATE ANIMAL
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateAnimal {
+    #[serde(rename = "tagId")]
+    pub tag_id: String,
+    pub sex: Sex,
+    pub breed: Option<String>,
+    #[serde(rename = "birthDate")]
+    pub birth_date: Option<NaiveDate>,
+    #[serde(rename = "damId")]
+    pub dam_id: Option<Uuid>,
+    #[serde(rename = "sireId")]
+    pub sire_id: Option<Uuid>,
+    // BIRTH or PURCHASE adds the animal to the headcount;
+    // leave empty when tagging an animal that is already counted
+    pub acquisition: Option<LivestockEventKind>,
+    #[serde(rename = "acquiredOn")]
+    pub acquired_on: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+// LIVESTOCK EVENT KIND
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LivestockEventKind {
+    Opening,
+    Purchase,
+    Birth,
+    Death,
+    Sale,
+    Vaccination,
+    Treatment,
+}
+
+impl LivestockEventKind {
+    // Effect of one animal on the headcount
+    pub fn headcount_sign(self) -> i32 {
+        match self {
+            LivestockEventKind::Opening | LivestockEventKind::Purchase | LivestockEventKind::Birth => 1,
+            LivestockEventKind::Death | LivestockEventKind::Sale => -1,
+            LivestockEventKind::Vaccination | LivestockEventKind::Treatment => 0,
+        }
+    }
+}
+
+// GET LIVESTOCK EVENT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct LivestockEvent {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "herdId")]
+    #[serde(rename = "herdId")]
+    pub herd_id: Uuid,
+    #[sqlx(rename = "animalId")]
+    #[serde(rename = "animalId")]
+    pub animal_id: Option<Uuid>,
+    pub kind: LivestockEventKind,
+    pub count: i32,
+    #[sqlx(rename = "eventDate")]
+    #[serde(rename = "eventDate")]
+    pub event_date: NaiveDate,
+    #[sqlx(rename = "salePrice")]
+    #[serde(rename = "salePrice")]
+    pub sale_price: Option<Money>,
+    pub cause: Option<String>,
+    pub product: Option<String>,
+    pub dose: Option<String>,
+    #[sqlx(rename = "administeredBy")]
+    #[serde(rename = "administeredBy")]
+    pub administered_by: Option<String>,
+    pub notes: Option<String>,
+}
+
+// CREATE LIVESTOCK EVENT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateLivestockEvent {
+    #[serde(rename = "animalId")]
+    pub animal_id: Option<Uuid>,
+    pub kind: LivestockEventKind,
+    // Number of animals; 1 when `animalId` is given
+    pub count: Option<i32>,
+    #[serde(rename = "eventDate")]
+    pub event_date: NaiveDate,
+    #[serde(rename = "salePrice")]
+    pub sale_price: Option<Money>,
+    pub cause: Option<String>,
+    pub product: Option<String>,
+    pub dose: Option<String>,
+    #[serde(rename = "administeredBy")]
+    pub administered_by: Option<String>,
+    pub notes: Option<String>,
+}
+
+// LIVESTOCK EVENT FILTER
+#[derive(Debug, Deserialize)]
+pub struct LivestockEventFilter {
+    pub kind: Option<LivestockEventKind>,
+    #[serde(rename = "animalId")]
+    pub animal_id: Option<Uuid>,
+}
+
+// HERD SIZE INTERVAL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HerdSizeInterval {
+    Day,
+    Week,
+    #[default]
+    Month,
+}
+
+// HERD SIZE QUERY
+#[derive(Debug, Deserialize)]
+pub struct HerdSizeQuery {
+    #[serde(rename = "herdId")]
+    pub herd_id: Option<Uuid>,
+    pub species: Option<Species>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default)]
+    pub interval: HerdSizeInterval,
+}
+
+// HERD SIZE POINT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct HerdSizePoint {
+    pub date: NaiveDate,
+    pub headcount: i64,
+}
+
+// LIVESTOCK SUMMARY QUERY
+#[derive(Debug, Deserialize)]
+pub struct LivestockSummaryQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+// LIVESTOCK SUMMARY, one row per herd
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct LivestockSummary {
+    #[sqlx(rename = "herdId")]
+    #[serde(rename = "herdId")]
+    pub herd_id: Uuid,
+    pub name: String,
+    pub species: Species,
+    #[sqlx(rename = "openingHeadcount")]
+    #[serde(rename = "openingHeadcount")]
+    pub opening_headcount: i64,
+    pub purchases: i64,
+    pub births: i64,
+    pub deaths: i64,
+    pub sales: i64,
+    #[sqlx(rename = "closingHeadcount")]
+    #[serde(rename = "closingHeadcount")]
+    pub closing_headcount: i64,
+    pub vaccinations: i64,
+    pub treatments: i64,
+}
+
+
+// ------** Cooperative Model **------//
+// COOPERATIVE KIND
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CooperativeKind {
+    Cooperative,
+    Cluster,
+}
+
+// GET COOPERATIVE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Cooperative {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    pub name: String,
+    pub kind: CooperativeKind,
+    #[sqlx(rename = "registrationNumber")]
+    #[serde(rename = "registrationNumber")]
+    pub registration_number: Option<String>,
+    #[sqlx(rename = "stateId")]
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    #[sqlx(rename = "lgaId")]
+    #[serde(rename = "lgaId")]
+    pub lga_id: Option<Uuid>,
+    pub description: Option<String>,
+}
+
+// CREATE COOPERATIVE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateCooperative {
+    pub name: String,
+    pub kind: CooperativeKind,
+    #[serde(rename = "registrationNumber")]
+    pub registration_number: Option<String>,
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    #[serde(rename = "lgaId")]
+    pub lga_id: Option<Uuid>,
+    pub description: Option<String>,
+}
+
+// COOPERATIVE FILTER
+#[derive(Debug, Deserialize)]
+pub struct CooperativeFilter {
+    pub kind: Option<CooperativeKind>,
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    // Groups the user currently belongs to
+    #[serde(rename = "userId")]
+    pub user_id: Option<Uuid>,
+}
+
+// MEMBER ROLE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MemberRole {
+    Chair,
+    Secretary,
+    #[default]
+    Member,
+}
+
+// GET COOPERATIVE MEMBER
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct CooperativeMember {
+    #[sqlx(rename = "membershipId")]
+    #[serde(rename = "membershipId")]
+    pub membership_id: Uuid,
+    #[sqlx(rename = "userId")]
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    #[sqlx(rename = "firstName")]
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    #[sqlx(rename = "lastName")]
+    #[serde(rename = "lastName")]
+    pub last_name: String,
+    pub role: MemberRole,
+    #[sqlx(rename = "joinedOn")]
+    #[serde(rename = "joinedOn")]
+    pub joined_on: NaiveDate,
+    #[sqlx(rename = "leftOn")]
+    #[serde(rename = "leftOn")]
+    pub left_on: Option<NaiveDate>,
+}
+
+// ADD COOPERATIVE MEMBER
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AddCooperativeMember {
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    #[serde(default)]
+    pub role: MemberRole,
+    // Defaults to today
+    #[serde(rename = "joinedOn")]
+    pub joined_on: Option<NaiveDate>,
+}
+
+// CHANGE MEMBER ROLE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChangeMemberRole {
+    pub role: MemberRole,
+    // Defaults to today
+    #[serde(rename = "effectiveOn")]
+    pub effective_on: Option<NaiveDate>,
+}
+
+// MEMBER LIST QUERY
+#[derive(Debug, Deserialize)]
+pub struct MemberListQuery {
+    // Include past memberships and roles
+    #[serde(default)]
+    pub history: bool,
+}
+
+// END MEMBERSHIP QUERY
+#[derive(Debug, Deserialize)]
+pub struct EndMembershipQuery {
+    #[serde(rename = "leftOn")]
+    pub left_on: Option<NaiveDate>,
+}
+
+// COOPERATIVE SUMMARY QUERY
+#[derive(Debug, Deserialize)]
+pub struct CooperativeSummaryQuery {
+    pub unit: Option<AreaUnit>,
+    pub season: Option<Season>,
+    #[serde(rename = "seasonYear")]
+    pub season_year: Option<i32>,
+}
+
+// CROP SHARE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct CropShare {
+    #[sqlx(rename = "cropId")]
+    #[serde(rename = "cropId")]
+    pub crop_id: Uuid,
+    pub crop: String,
+    #[sqlx(rename = "plantingCount")]
+    #[serde(rename = "plantingCount")]
+    pub planting_count: i64,
+    #[sqlx(rename = "plantedArea")]
+    #[serde(rename = "plantedArea")]
+    pub planted_area: f64,
+    // Fraction of the group's planted area
+    pub share: f64,
+}
+
+// COOPERATIVE SUMMARY
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CooperativeSummary {
+    #[serde(rename = "cooperativeId")]
+    pub cooperative_id: Uuid,
+    #[serde(rename = "memberCount")]
+    pub member_count: i64,
+    #[serde(rename = "farmCount")]
+    pub farm_count: i64,
+    #[serde(rename = "totalAcreage")]
+    pub total_acreage: f64,
+    #[serde(rename = "areaUnit")]
+    pub area_unit: AreaUnit,
+    #[serde(rename = "cropMix")]
+    pub crop_mix: Vec<CropShare>,
+}
+
+
+// ------** Field Agent Model **------//
+// GET AGENT ASSIGNMENT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct AgentAssignment {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "agentId")]
+    #[serde(rename = "agentId")]
+    pub agent_id: Uuid,
+    #[sqlx(rename = "farmerId")]
+    #[serde(rename = "farmerId")]
+    pub farmer_id: Uuid,
+    #[sqlx(rename = "firstName")]
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    #[sqlx(rename = "lastName")]
+    #[serde(rename = "lastName")]
+    pub last_name: String,
+    #[sqlx(rename = "startedOn")]
+    #[serde(rename = "startedOn")]
+    pub started_on: NaiveDate,
+    #[sqlx(rename = "endedOn")]
+    #[serde(rename = "endedOn")]
+    pub ended_on: Option<NaiveDate>,
+}
+
+// CREATE AGENT ASSIGNMENT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateAgentAssignment {
+    #[serde(rename = "farmerId")]
+    pub farmer_id: Uuid,
+    // Defaults to today
+    #[serde(rename = "startedOn")]
+    pub started_on: Option<NaiveDate>,
+}
+
+// ASSIGNMENT LIST QUERY
+#[derive(Debug, Deserialize)]
+pub struct AssignmentListQuery {
+    // Include ended assignments
+    #[serde(default)]
+    pub history: bool,
+}
+
+// END ASSIGNMENT QUERY
+#[derive(Debug, Deserialize)]
+pub struct EndAssignmentQuery {
+    #[serde(rename = "endedOn")]
+    pub ended_on: Option<NaiveDate>,
+}
+
+// CHECKLIST ANSWER
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistAnswer {
+    pub question: String,
+    pub answer: serde_json::Value,
+}
+
+// GET FARM VISIT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct FarmVisit {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "agentId")]
+    #[serde(rename = "agentId")]
+    pub agent_id: Uuid,
+    #[sqlx(rename = "visitedAt")]
+    #[serde(rename = "visitedAt")]
+    pub visited_at: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    #[sqlx(rename = "accuracyMetres")]
+    #[serde(rename = "accuracyMetres")]
+    pub accuracy_metres: Option<f64>,
+    // Distance of the check-in from the farm's recorded location
+    #[sqlx(rename = "distanceMetres")]
+    #[serde(rename = "distanceMetres")]
+    pub distance_metres: f64,
+    #[sqlx(rename = "locationVerified")]
+    #[serde(rename = "locationVerified")]
+    pub location_verified: bool,
+    pub notes: Option<String>,
+    #[sqlx(rename = "photoUrls")]
+    #[serde(rename = "photoUrls")]
+    pub photo_urls: Vec<String>,
+    pub checklist: Json<Vec<ChecklistAnswer>>,
+}
+
+// CREATE FARM VISIT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateFarmVisit {
+    #[serde(rename = "agentId")]
+    pub agent_id: Uuid,
+    // Defaults to now
+    #[serde(rename = "visitedAt")]
+    pub visited_at: Option<DateTime<Utc>>,
+    // GPS check-in
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(rename = "accuracyMetres")]
+    pub accuracy_metres: Option<f64>,
+    pub notes: Option<String>,
+    #[serde(rename = "photoUrls", default)]
+    pub photo_urls: Vec<String>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistAnswer>,
+}
+
+// VISIT FILTER
+#[derive(Debug, Deserialize)]
+pub struct VisitFilter {
+    #[serde(rename = "agentId")]
+    pub agent_id: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+// COVERAGE QUERY
+#[derive(Debug, Deserialize)]
+pub struct CoverageQuery {
+    // Defaults to the visit interval ending today
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+// AGENT COVERAGE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct AgentCoverage {
+    #[sqlx(rename = "agentId")]
+    #[serde(rename = "agentId")]
+    pub agent_id: Uuid,
+    #[sqlx(rename = "firstName")]
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    #[sqlx(rename = "lastName")]
+    #[serde(rename = "lastName")]
+    pub last_name: String,
+    #[sqlx(rename = "farmerCount")]
+    #[serde(rename = "farmerCount")]
+    pub farmer_count: i64,
+    #[sqlx(rename = "farmCount")]
+    #[serde(rename = "farmCount")]
+    pub farm_count: i64,
+    // Farms with at least one verified visit in the period
+    #[sqlx(rename = "visitedFarmCount")]
+    #[serde(rename = "visitedFarmCount")]
+    pub visited_farm_count: i64,
+    #[sqlx(rename = "visitCount")]
+    #[serde(rename = "visitCount")]
+    pub visit_count: i64,
+    #[sqlx(rename = "unverifiedVisitCount")]
+    #[serde(rename = "unverifiedVisitCount")]
+    pub unverified_visit_count: i64,
+    // visitedFarmCount / farmCount
+    pub coverage: f64,
+}
+
+// OVERDUE QUERY
+#[derive(Debug, Deserialize)]
+pub struct OverdueQuery {
+    #[serde(rename = "agentId")]
+    pub agent_id: Option<Uuid>,
+    // Defaults to the configured visit interval
+    #[serde(rename = "intervalDays")]
+    pub interval_days: Option<i64>,
+}
+
+// OVERDUE FARM
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct OverdueFarm {
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "farmName")]
+    #[serde(rename = "farmName")]
+    pub farm_name: Option<String>,
+    #[sqlx(rename = "farmerId")]
+    #[serde(rename = "farmerId")]
+    pub farmer_id: Uuid,
+    #[sqlx(rename = "agentId")]
+    #[serde(rename = "agentId")]
+    pub agent_id: Uuid,
+    // Last verified visit; None if never visited
+    #[sqlx(rename = "lastVisitedAt")]
+    #[serde(rename = "lastVisitedAt")]
+    pub last_visited_at: Option<DateTime<Utc>>,
+    #[sqlx(rename = "daysSinceVisit")]
+    #[serde(rename = "daysSinceVisit")]
+    pub days_since_visit: Option<i32>,
+}
+
+
+// ------** Land Lease Model **------//
+// LISTING STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ListingStatus {
+    Open,
+    Leased,
+    Withdrawn,
+}
+
+// GET LAND LISTING
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct LandListing {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "plotId")]
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    // Hectares still on offer
+    pub area: f64,
+    // Yearly rent per hectare
+    #[sqlx(rename = "rentPerHectare")]
+    #[serde(rename = "rentPerHectare")]
+    pub rent_per_hectare: Money,
+    #[sqlx(rename = "availableFrom")]
+    #[serde(rename = "availableFrom")]
+    pub available_from: NaiveDate,
+    #[sqlx(rename = "minTermMonths")]
+    #[serde(rename = "minTermMonths")]
+    pub min_term_months: i32,
+    #[sqlx(rename = "maxTermMonths")]
+    #[serde(rename = "maxTermMonths")]
+    pub max_term_months: i32,
+    pub description: Option<String>,
+    pub status: ListingStatus,
+}
+
+// CREATE LAND LISTING
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateLandListing {
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    pub area: Area,
+    #[serde(rename = "rentPerHectare")]
+    pub rent_per_hectare: Money,
+    #[serde(rename = "availableFrom")]
+    pub available_from: NaiveDate,
+    #[serde(rename = "minTermMonths")]
+    pub min_term_months: i32,
+    #[serde(rename = "maxTermMonths")]
+    pub max_term_months: i32,
+    pub description: Option<String>,
+}
+
+// LISTING FILTER
+#[derive(Debug, Deserialize)]
+pub struct ListingFilter {
+    // Defaults to open listings
+    pub status: Option<ListingStatus>,
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    #[serde(rename = "lgaId")]
+    pub lga_id: Option<Uuid>,
+    // Hectares
+    #[serde(rename = "minArea")]
+    pub min_area: Option<f64>,
+}
+
+// APPLICATION STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ApplicationStatus {
+    Pending,
+    Accepted,
+    Rejected,
+    Withdrawn,
+}
+
+// GET LEASE APPLICATION
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct LeaseApplication {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "listingId")]
+    #[serde(rename = "listingId")]
+    pub listing_id: Uuid,
+    #[sqlx(rename = "applicantId")]
+    #[serde(rename = "applicantId")]
+    pub applicant_id: Uuid,
+    // Hectares
+    pub area: f64,
+    #[sqlx(rename = "startDate")]
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    #[sqlx(rename = "termMonths")]
+    #[serde(rename = "termMonths")]
+    pub term_months: i32,
+    pub message: Option<String>,
+    pub status: ApplicationStatus,
+    #[sqlx(rename = "decisionReason")]
+    #[serde(rename = "decisionReason")]
+    pub decision_reason: Option<String>,
+}
+
+// CREATE LEASE APPLICATION
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateLeaseApplication {
+    #[serde(rename = "applicantId")]
+    pub applicant_id: Uuid,
+    // Defaults to the whole listing
+    pub area: Option<Area>,
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    #[serde(rename = "termMonths")]
+    pub term_months: i32,
+    pub message: Option<String>,
+}
+
+// APPLICATION DECISION
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ApplicationDecision {
+    pub reason: Option<String>,
+}
+
+// LEASE STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LeaseStatus {
+    Active,
+    Ended,
+}
+
+// GET LEASE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Lease {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "plotId")]
+    #[serde(rename = "plotId")]
+    pub plot_id: Option<Uuid>,
+    #[sqlx(rename = "listingId")]
+    #[serde(rename = "listingId")]
+    pub listing_id: Uuid,
+    #[sqlx(rename = "applicationId")]
+    #[serde(rename = "applicationId")]
+    pub application_id: Uuid,
+    #[sqlx(rename = "landlordId")]
+    #[serde(rename = "landlordId")]
+    pub landlord_id: Uuid,
+    #[sqlx(rename = "tenantId")]
+    #[serde(rename = "tenantId")]
+    pub tenant_id: Uuid,
+    // Hectares
+    pub area: f64,
+    #[sqlx(rename = "startDate")]
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    #[sqlx(rename = "endDate")]
+    #[serde(rename = "endDate")]
+    pub end_date: NaiveDate,
+    // Total rent for the term
+    pub rent: Money,
+    pub status: LeaseStatus,
+    #[sqlx(rename = "endedOn")]
+    #[serde(rename = "endedOn")]
+    pub ended_on: Option<NaiveDate>,
+}
+
+// LEASE FILTER
+#[derive(Debug, Deserialize)]
+pub struct LeaseFilter {
+    #[serde(rename = "farmId")]
+    pub farm_id: Option<Uuid>,
+    #[serde(rename = "landlordId")]
+    pub landlord_id: Option<Uuid>,
+    #[serde(rename = "tenantId")]
+    pub tenant_id: Option<Uuid>,
+    pub status: Option<LeaseStatus>,
+}
+
+// END LEASE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EndLease {
+    // Defaults to today
+    #[serde(rename = "endedOn")]
+    pub ended_on: Option<NaiveDate>,
+}
+
+
+// ------** Farm Title Model **------//
+// TRANSFER REASON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransferReason {
+    Sale,
+    Inheritance,
+    LeaseEnd,
+}
+
+impl TransferReason {
+    // Value of `Farm.ownership` once the transfer takes effect
+    pub fn ownership(self) -> &'static str {
+        match self {
+            TransferReason::Sale | TransferReason::LeaseEnd => "OWNER",
+            TransferReason::Inheritance => "INHERIT",
+        }
+    }
+}
+
+// DOCUMENT KIND
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DocumentKind {
+    Deed,
+    CertificateOfOccupancy,
+    SaleAgreement,
+    Will,
+    LetterOfAdministration,
+    LeaseAgreement,
+    Other,
+}
+
+// GET TRANSFER DOCUMENT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct TransferDocument {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "transferId")]
+    #[serde(rename = "transferId")]
+    pub transfer_id: Uuid,
+    pub kind: DocumentKind,
+    pub url: String,
+    pub description: Option<String>,
+}
+
+// CREATE TRANSFER DOCUMENT
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateTransferDocument {
+    pub kind: DocumentKind,
+    pub url: String,
+    pub description: Option<String>,
+}
+
+// GET FARM TRANSFER
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct FarmTransfer {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "fromOwnerId")]
+    #[serde(rename = "fromOwnerId")]
+    pub from_owner_id: Uuid,
+    #[sqlx(rename = "toOwnerId")]
+    #[serde(rename = "toOwnerId")]
+    pub to_owner_id: Uuid,
+    pub reason: TransferReason,
+    #[sqlx(rename = "effectiveOn")]
+    #[serde(rename = "effectiveOn")]
+    pub effective_on: NaiveDate,
+    pub price: Option<Money>,
+    pub notes: Option<String>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub documents: Vec<TransferDocument>,
+}
+
+// CREATE FARM TRANSFER
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateFarmTransfer {
+    #[serde(rename = "toOwnerId")]
+    pub to_owner_id: Uuid,
+    pub reason: TransferReason,
+    // Defaults to today
+    #[serde(rename = "effectiveOn")]
+    pub effective_on: Option<NaiveDate>,
+    // Sales only
+    pub price: Option<Money>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub documents: Vec<CreateTransferDocument>,
+}
+
+// GET FARM TITLE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct FarmTitle {
+    pub id: Uuid,
+    #[sqlx(rename = "farmId")]
+    #[serde(rename = "farmId")]
+    pub farm_id: Uuid,
+    #[sqlx(rename = "ownerId")]
+    #[serde(rename = "ownerId")]
+    pub owner_id: Uuid,
+    #[sqlx(rename = "firstName")]
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    #[sqlx(rename = "lastName")]
+    #[serde(rename = "lastName")]
+    pub last_name: String,
+    #[sqlx(rename = "acquiredOn")]
+    #[serde(rename = "acquiredOn")]
+    pub acquired_on: NaiveDate,
+    // None while the title is current
+    #[sqlx(rename = "relinquishedOn")]
+    #[serde(rename = "relinquishedOn")]
+    pub relinquished_on: Option<NaiveDate>,
+    #[sqlx(rename = "transferId")]
+    #[serde(rename = "transferId")]
+    pub transfer_id: Option<Uuid>,
+}
+
+// OWNER QUERY
+#[derive(Debug, Deserialize)]
+pub struct OwnerQuery {
+    // Defaults to today
+    pub date: Option<NaiveDate>,
+}
+
+
+// ------** KYC Model **------//
+// KYC STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum KycStatus {
+    Unverified,
+    Pending,
+    Verified,
+    Rejected,
+}
+
+impl KycStatus {
+    // unverified -> pending -> verified | rejected; rejected profiles can be resubmitted
+    pub fn can_become(self, next: KycStatus) -> bool {
+        matches!(
+            (self, next),
+            (KycStatus::Unverified, KycStatus::Pending)
+                | (KycStatus::Rejected, KycStatus::Pending)
+                | (KycStatus::Pending, KycStatus::Verified)
+                | (KycStatus::Pending, KycStatus::Rejected)
+        )
+    }
+}
+
+// IDENTITY OUTCOME
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum IdentityOutcome {
+    // The identity exists and matches the profile
+    Match,
+    // The identity exists but belongs to someone else
+    Mismatch,
+    // The number is malformed or unknown
+    Invalid,
+}
+
+// GET KYC EVENT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct KycEvent {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "profileId")]
+    #[serde(rename = "profileId")]
+    pub profile_id: Uuid,
+    #[sqlx(rename = "fromStatus")]
+    #[serde(rename = "fromStatus")]
+    pub from_status: KycStatus,
+    #[sqlx(rename = "toStatus")]
+    #[serde(rename = "toStatus")]
+    pub to_status: KycStatus,
+    // None for automatic changes
+    #[sqlx(rename = "actorId")]
+    #[serde(rename = "actorId")]
+    pub actor_id: Option<Uuid>,
+    pub reason: Option<String>,
+    pub provider: Option<String>,
+    #[sqlx(rename = "providerReference")]
+    #[serde(rename = "providerReference")]
+    pub provider_reference: Option<String>,
+    #[sqlx(rename = "providerOutcome")]
+    #[serde(rename = "providerOutcome")]
+    pub provider_outcome: Option<IdentityOutcome>,
+}
+
+// GET KYC PROFILE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct KycProfile {
+    #[sqlx(rename = "profileId")]
+    #[serde(rename = "profileId")]
+    pub profile_id: Uuid,
+    #[sqlx(rename = "userId")]
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    #[sqlx(rename = "firstName")]
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    #[sqlx(rename = "lastName")]
+    #[serde(rename = "lastName")]
+    pub last_name: String,
+    pub status: KycStatus,
+    pub reason: Option<String>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub events: Vec<KycEvent>,
+}
+
+// KYC QUEUE FILTER
+#[derive(Debug, Deserialize)]
+pub struct KycFilter {
+    // Defaults to profiles awaiting review
+    pub status: Option<KycStatus>,
+}
+
+// SUBMIT KYC
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SubmitKyc {
+    // Agent submitting the profile
+    #[serde(rename = "submittedBy")]
+    pub submitted_by: Uuid,
+}
+
+// REVIEW KYC
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReviewKyc {
+    #[serde(rename = "reviewerId")]
+    pub reviewer_id: Uuid,
+    // Required when rejecting
+    pub reason: Option<String>,
+}
+
+
+// ------** Duplicate Farmer Model **------//
+// DUPLICATE SIGNAL KIND
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DuplicateSignalKind {
+    Name,
+    Phone,
+    Bvn,
+    FarmProximity,
+}
+
+// DUPLICATE SIGNAL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateSignal {
+    pub signal: DuplicateSignalKind,
+    // Contribution to the pair's score
+    pub weight: f64,
+    pub detail: String,
+}
+
+// DUPLICATE STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DuplicateStatus {
+    Pending,
+    Dismissed,
+}
+
+// GET DUPLICATE CANDIDATE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct DuplicateCandidate {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(rename = "userId")]
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    #[sqlx(rename = "firstName")]
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    #[sqlx(rename = "lastName")]
+    #[serde(rename = "lastName")]
+    pub last_name: String,
+    #[sqlx(rename = "otherUserId")]
+    #[serde(rename = "otherUserId")]
+    pub other_user_id: Uuid,
+    #[sqlx(rename = "otherFirstName")]
+    #[serde(rename = "otherFirstName")]
+    pub other_first_name: String,
+    #[sqlx(rename = "otherLastName")]
+    #[serde(rename = "otherLastName")]
+    pub other_last_name: String,
+    pub score: f64,
+    pub signals: Json<Vec<DuplicateSignal>>,
+    pub status: DuplicateStatus,
+    #[sqlx(rename = "reviewerId")]
+    #[serde(rename = "reviewerId")]
+    pub reviewer_id: Option<Uuid>,
+    #[sqlx(rename = "reviewedAt")]
+    #[serde(rename = "reviewedAt")]
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+// DUPLICATE QUEUE FILTER
+#[derive(Debug, Deserialize)]
+pub struct DuplicateFilter {
+    // Defaults to pairs awaiting review
+    pub status: Option<DuplicateStatus>,
+    #[serde(rename = "minScore")]
+    pub min_score: Option<f64>,
+    // Pairs involving this user
+    #[serde(rename = "userId")]
+    pub user_id: Option<Uuid>,
+}
+
+// SCAN DUPLICATES
+#[derive(Debug, Deserialize)]
+pub struct DuplicateScanQuery {
+    // Only score pairs involving this user; all users when absent
+    #[serde(rename = "userId")]
+    pub user_id: Option<Uuid>,
+}
+
+// DUPLICATE SCAN SUMMARY
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateScan {
+    #[serde(rename = "pairsScored")]
+    pub pairs_scored: usize,
+    // Pairs at or above the threshold, new or rescored
+    pub candidates: usize,
+}
+
+// DISMISS DUPLICATE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DismissDuplicate {
+    #[serde(rename = "reviewerId")]
+    pub reviewer_id: Uuid,
+    pub reason: Option<String>,
+}
+
+// MERGE DUPLICATE
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MergeDuplicate {
+    // Either user of the pair; the other is merged into it and deleted
+    #[serde(rename = "survivorId")]
+    pub survivor_id: Uuid,
+    #[serde(rename = "reviewerId")]
+    pub reviewer_id: Uuid,
+}
+
+// GET USER MERGE
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct UserMerge {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "survivorId")]
+    #[serde(rename = "survivorId")]
+    pub survivor_id: Uuid,
+    #[sqlx(rename = "mergedUserId")]
+    #[serde(rename = "mergedUserId")]
+    pub merged_user_id: Uuid,
+    #[sqlx(rename = "mergedFirstName")]
+    #[serde(rename = "mergedFirstName")]
+    pub merged_first_name: String,
+    #[sqlx(rename = "mergedLastName")]
+    #[serde(rename = "mergedLastName")]
+    pub merged_last_name: String,
+    pub score: f64,
+    pub signals: Json<Vec<DuplicateSignal>>,
+    // Rows moved onto the survivor, by table
+    pub moved: Json<BTreeMap<String, i64>>,
+    #[sqlx(rename = "reviewerId")]
+    #[serde(rename = "reviewerId")]
+    pub reviewer_id: Option<Uuid>,
+}
+
+// USER MERGE FILTER
+#[derive(Debug, Deserialize)]
+pub struct UserMergeFilter {
+    #[serde(rename = "survivorId")]
+    pub survivor_id: Option<Uuid>,
+}
+
+
+// ------** Search Model **------//
+// SEARCH ENTITY TYPE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SearchEntityType {
+    User,
+    Farm,
+}
+
+// SEARCH QUERY
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    // Only search one kind of record
+    #[serde(rename = "type")]
+    pub entity_type: Option<SearchEntityType>,
+    // Hits per group
+    pub limit: Option<i64>,
+}
+
+// SEARCH HIGHLIGHT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHighlight {
+    pub field: String,
+    // The field with matched words wrapped in <mark></mark>
+    pub fragment: String,
+}
+
+// SEARCH HIT
+#[derive(Debug, sqlx::FromRow, Serialize, Deserialize)]
+pub struct SearchHit {
+    #[sqlx(rename = "entityId")]
+    pub id: Uuid,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub rank: f64,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub highlights: Vec<SearchHighlight>,
+}
+
+// SEARCH GROUP
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchGroup {
+    #[serde(rename = "entityType")]
+    pub entity_type: SearchEntityType,
+    // Matches in this group, including those past the limit
+    pub total: i64,
+    pub hits: Vec<SearchHit>,
+}
+
+// SEARCH RESULTS
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub query: String,
+    pub groups: Vec<SearchGroup>,
+}
+
+
+// ------** History Model **------//
+// AS OF QUERY
+#[derive(Debug, Deserialize)]
+pub struct AsOfQuery {
+    // Return the record as it was at this time
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+// VERSION OPERATION
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum VersionOperation {
+    Insert,
+    Update,
+    Delete,
+}
+
+// FIELD CHANGE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+// GET FARM VERSION
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FarmVersion {
+    pub version: i32,
+    pub operation: VersionOperation,
+    #[serde(rename = "validFrom")]
+    pub valid_from: DateTime<Utc>,
+    // None for the current version
+    #[serde(rename = "validTo")]
+    pub valid_to: Option<DateTime<Utc>>,
+    // Fields that differ from the previous version
+    pub changes: Vec<FieldChange>,
+}
+
+
+// ------** Domain Event Model **------//
+// DOMAIN ENTITY TYPE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DomainEntityType {
+    User,
+    Profile,
+    Farm,
+}
+
+// DOMAIN EVENT TYPE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR")]
+pub enum DomainEventType {
+    UserCreated,
+    UserUpdated,
+    UserDeleted,
+    ProfileCreated,
+    ProfileUpdated,
+    ProfileDeleted,
+    FarmCreated,
+    FarmUpdated,
+    FarmDeleted,
+}
+
+impl DomainEventType {
+    pub fn entity_type(self) -> DomainEntityType {
+        match self {
+            DomainEventType::UserCreated | DomainEventType::UserUpdated | DomainEventType::UserDeleted => DomainEntityType::User,
+            DomainEventType::ProfileCreated | DomainEventType::ProfileUpdated | DomainEventType::ProfileDeleted => DomainEntityType::Profile,
+            DomainEventType::FarmCreated | DomainEventType::FarmUpdated | DomainEventType::FarmDeleted => DomainEntityType::Farm,
+        }
+    }
+}
+
+// GET OUTBOX EVENT
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OutboxEvent {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    #[serde(rename = "occurredAt")]
+    pub created_at: DateTime<Utc>,
+    pub sequence: i64,
+    #[sqlx(rename = "eventType")]
+    #[serde(rename = "eventType")]
+    pub event_type: DomainEventType,
+    #[sqlx(rename = "entityType")]
+    #[serde(rename = "entityType")]
+    pub entity_type: DomainEntityType,
+    #[sqlx(rename = "entityId")]
+    #[serde(rename = "entityId")]
+    pub entity_id: Uuid,
+    // The entity after the change, or as it was before it was deleted
+    pub payload: Json<serde_json::Value>,
+    // States the event concerns, for region filters
+    #[sqlx(rename = "stateIds")]
+    #[serde(rename = "stateIds", default)]
+    pub state_ids: Vec<Uuid>,
+}
+
+
+// ------** Webhook Model **------//
+// WEBHOOK STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WebhookStatus {
+    Active,
+    Disabled,
+}
+
+// GET WEBHOOK SUBSCRIPTION
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    pub url: String,
+    #[sqlx(rename = "eventTypes")]
+    #[serde(rename = "eventTypes")]
+    pub event_types: Json<Vec<DomainEventType>>,
+    pub description: Option<String>,
+    #[sqlx(rename = "cooperativeId")]
+    #[serde(rename = "cooperativeId")]
+    pub cooperative_id: Option<Uuid>,
+    pub status: WebhookStatus,
+    #[sqlx(rename = "disabledReason")]
+    #[serde(rename = "disabledReason")]
+    pub disabled_reason: Option<String>,
+    #[sqlx(rename = "consecutiveFailures")]
+    #[serde(rename = "consecutiveFailures")]
+    pub consecutive_failures: i32,
+}
+
+// CREATED WEBHOOK SUBSCRIPTION
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedWebhookSubscription {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    // Only returned when the subscription is created
+    pub secret: String,
+}
+
+// CREATE WEBHOOK SUBSCRIPTION
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookSubscription {
+    pub url: String,
+    #[serde(rename = "eventTypes")]
+    pub event_types: Vec<DomainEventType>,
+    pub description: Option<String>,
+    #[serde(rename = "cooperativeId")]
+    pub cooperative_id: Option<Uuid>,
+    // Generated when not given
+    pub secret: Option<String>,
+}
+
+// UPDATE WEBHOOK SUBSCRIPTION
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookSubscription {
+    pub url: Option<String>,
+    #[serde(rename = "eventTypes")]
+    pub event_types: Option<Vec<DomainEventType>>,
+    pub description: Option<String>,
+    // Setting ACTIVE re-enables a disabled endpoint and resumes its pending deliveries
+    pub status: Option<WebhookStatus>,
+}
+
+// WEBHOOK DELIVERY STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+// GET WEBHOOK DELIVERY
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[sqlx(rename = "subscriptionId")]
+    #[serde(rename = "subscriptionId")]
+    pub subscription_id: Uuid,
+    #[sqlx(rename = "eventId")]
+    #[serde(rename = "eventId")]
+    pub event_id: Uuid,
+    #[sqlx(rename = "eventType")]
+    #[serde(rename = "eventType")]
+    pub event_type: DomainEventType,
+    pub payload: Json<serde_json::Value>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    #[sqlx(rename = "nextAttemptAt")]
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    #[sqlx(rename = "lastAttemptAt")]
+    #[serde(rename = "lastAttemptAt")]
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    #[sqlx(rename = "responseStatus")]
+    #[serde(rename = "responseStatus")]
+    pub response_status: Option<i32>,
+    // First few kilobytes of the endpoint's answer
+    #[sqlx(rename = "responseBody")]
+    #[serde(rename = "responseBody")]
+    pub response_body: Option<String>,
+    #[sqlx(rename = "lastError")]
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[sqlx(rename = "durationMs")]
+    #[serde(rename = "durationMs")]
+    pub duration_ms: Option<i32>,
+    #[sqlx(rename = "replayOf")]
+    #[serde(rename = "replayOf")]
+    pub replay_of: Option<Uuid>,
+}
+
+// WEBHOOK DELIVERY FILTER
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveryFilter {
+    pub status: Option<WebhookDeliveryStatus>,
+}
+
+
+// ------** Api Key Model **------//
+// GET API KEY
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    #[sqlx(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    pub name: String,
+    // First characters of the key, to tell keys apart
+    #[sqlx(rename = "keyPrefix")]
+    #[serde(rename = "keyPrefix")]
+    pub key_prefix: String,
+    #[sqlx(rename = "entityTypes")]
+    #[serde(rename = "entityTypes")]
+    pub entity_types: Json<Vec<DomainEntityType>>,
+    // Empty for every state
+    #[sqlx(rename = "stateIds")]
+    #[serde(rename = "stateIds")]
+    pub state_ids: Vec<Uuid>,
+    #[sqlx(rename = "lastUsedAt")]
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[sqlx(rename = "revokedAt")]
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+// CREATED API KEY
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    // Only returned when the key is created
+    pub key: String,
+}
+
+// CREATE API KEY
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKey {
+    pub name: String,
+    #[serde(rename = "entityTypes")]
+    pub entity_types: Vec<DomainEntityType>,
+    #[serde(rename = "stateIds", default)]
+    pub state_ids: Vec<Uuid>,
+}
+
+
+// ------** Event Stream Model **------//
+// EVENT STREAM FILTER
+#[derive(Debug, Deserialize)]
+pub struct EventStreamFilter {
+    // Comma separated entity types, e.g. `FARM,PROFILE`
+    #[serde(rename = "entityTypes")]
+    pub entity_types: Option<String>,
+    // Region filter
+    #[serde(rename = "stateId")]
+    pub state_id: Option<Uuid>,
+    // For clients that cannot send the Last-Event-ID header
+    #[serde(rename = "lastEventId")]
+    pub last_event_id: Option<String>,
+    // For clients that cannot send an Authorization header, such as browser EventSource
+    pub access_token: Option<String>,
+}
+
+
+// ------** Sync Model **------//
+// SYNC OPERATION
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SyncOperation {
+    Create,
+    Update,
+    Delete,
+}
+
+// SYNC CHANGE
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncChange {
+    #[serde(rename = "entityType")]
+    pub entity_type: DomainEntityType,
+    // Generated by the client when it creates the record
+    pub id: Uuid,
+    pub operation: SyncOperation,
+    // Version the client last saw; required to update or delete
+    #[serde(rename = "baseVersion")]
+    pub base_version: Option<i32>,
+    // Changed columns and their new values, keyed as in `SyncedRecord.data`
+    #[serde(default)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    // When the change was made on the device
+    #[serde(rename = "changedAt")]
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+// SYNC REQUEST
+#[derive(Debug, Deserialize)]
+pub struct SyncRequest {
+    // Token from the previous sync; none for the first
+    #[serde(rename = "syncToken")]
+    pub sync_token: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub changes: Vec<SyncChange>,
+}
+
+// SYNC RESULT STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SyncResultStatus {
+    // Applied to the version the client edited
+    Applied,
+    // Merged with server changes to other fields
+    Merged,
+    // Merged, with fields or the whole change settled by the conflict rules
+    Conflict,
+    // Not applied; see `error`
+    Rejected,
+}
+
+// SYNC RESULT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncResult {
+    #[serde(rename = "entityType")]
+    pub entity_type: DomainEntityType,
+    pub id: Uuid,
+    pub status: SyncResultStatus,
+    // Version after the change; none when the record no longer exists
+    pub version: Option<i32>,
+    pub error: Option<String>,
+}
+
+// CONFLICT RULE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConflictRule {
+    ServerWins,
+    ClientWins,
+    // The later edit wins; the server's on a tie or when the client gave no `changedAt`
+    LatestWins,
+    // The record was deleted on the server; the client's edit is dropped
+    DeleteWins,
+    // The record was edited on the server; the client's delete is dropped
+    EditWins,
+}
+
+// CONFLICT SIDE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ConflictSide {
+    Server,
+    Client,
+}
+
+// SYNC CONFLICT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    #[serde(rename = "entityType")]
+    pub entity_type: DomainEntityType,
+    pub id: Uuid,
+    // None when the conflict is about the whole record
+    pub field: Option<String>,
+    #[serde(rename = "baseValue")]
+    pub base_value: serde_json::Value,
+    #[serde(rename = "clientValue")]
+    pub client_value: serde_json::Value,
+    #[serde(rename = "serverValue")]
+    pub server_value: serde_json::Value,
+    pub rule: ConflictRule,
+    pub winner: ConflictSide,
+}
+
+// SYNCED RECORD
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SyncedRecord {
+    #[sqlx(rename = "entityType")]
+    #[serde(rename = "entityType")]
+    pub entity_type: DomainEntityType,
+    pub id: Uuid,
+    pub version: i32,
+    // INSERT, UPDATE or DELETE
+    pub operation: String,
+    #[sqlx(rename = "changedAt")]
+    #[serde(rename = "changedAt")]
+    pub changed_at: DateTime<Utc>,
+    // The record's columns; for a delete, as they were before it
+    pub data: Json<serde_json::Value>,
+}
+
+// SYNC RESPONSE
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncResponse {
+    // Send with the next sync
+    #[serde(rename = "syncToken")]
+    pub sync_token: DateTime<Utc>,
+    // One per change, in the order sent
+    pub results: Vec<SyncResult>,
+    pub conflicts: Vec<SyncConflict>,
+    // Latest version of each record of the agent's farmers changed since the token
+    pub changes: Vec<SyncedRecord>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(json: &str) -> Result<Area, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    fn decimal(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn currencies_are_sorted_and_fit_the_stored_scale() {
+        assert!(CURRENCIES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(CURRENCIES.iter().all(|&(_, digits)| digits <= 3));
+    }
+
+    #[test]
+    fn minor_unit_digits_follow_iso_4217() {
+        assert_eq!(Money::minor_unit_digits("NGN"), Some(2));
+        assert_eq!(Money::minor_unit_digits("JPY"), Some(0));
+        assert_eq!(Money::minor_unit_digits("KWD"), Some(3));
+        assert_eq!(Money::minor_unit_digits("AED"), Some(2));
+        assert_eq!(Money::minor_unit_digits("ZWG"), Some(2));
+        assert_eq!(Money::minor_unit_digits("CLF"), None);
+        assert_eq!(Money::minor_unit_digits("ngn"), None);
+        assert_eq!(Money::minor_unit_digits("XYZ"), None);
+    }
+
+    #[test]
+    fn money_rounds_halves_away_from_zero_to_the_minor_unit() {
+        let amount = |value: &str, currency: &str| Money::new(decimal(value), currency).unwrap().amount;
+        assert_eq!(amount("2.345", "USD"), decimal("2.35"));
+        assert_eq!(amount("2.355", "USD"), decimal("2.36"));
+        assert_eq!(amount("-2.345", "USD"), decimal("-2.35"));
+        assert_eq!(amount("2.5", "JPY"), decimal("3"));
+        assert_eq!(amount("1.0005", "KWD"), decimal("1.001"));
+        assert_eq!(amount("1500", "NGN"), decimal("1500"));
+    }
+
+    #[test]
+    fn money_validates_the_currency() {
+        assert_eq!(Money::new(decimal("1"), " ngn ").unwrap().currency, "NGN");
+        assert!(Money::new(decimal("1"), "XYZ").is_err());
+        assert!(Money::new(decimal("1"), "CLF").is_err());
+        assert!(Money::new(decimal("1"), "").is_err());
+        assert_eq!(
+            serde_json::from_str::<Money>(r#"{ "amount": "12.345", "currency": "ngn" }"#).unwrap(),
+            Money { amount: decimal("12.35"), currency: "NGN".to_string() },
+        );
+        assert!(serde_json::from_str::<Money>(r#"{ "amount": 1, "currency": "XXZ" }"#).is_err());
+    }
+
+    #[test]
+    fn area_reads_bare_numbers_as_acres() {
+        assert_eq!(area("10").unwrap(), Area { value: 10.0, unit: AreaUnit::Acre });
+        assert_eq!(area("2.5").unwrap(), Area { value: 2.5, unit: AreaUnit::Acre });
+        assert_eq!(area(r#"{ "value": 2.5, "unit": "hectare" }"#).unwrap(), Area::hectares(2.5));
+        assert_eq!(area(r#"{ "value": 3, "unit": "plot" }"#).unwrap(), Area { value: 3.0, unit: AreaUnit::Plot });
+    }
+
+    #[test]
+    fn area_rejects_invalid_input() {
+        for invalid in ["-1", r#""10""#, "null", r#"{ "value": 1 }"#, r#"{ "value": 1, "unit": "furlong" }"#, r#"{ "value": -2, "unit": "acre" }"#] {
+            assert!(area(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn area_converts_through_hectares() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(AreaUnit::Hectare.to_hectares(2.5), 2.5));
+        assert!(close(AreaUnit::Hectare.from_hectares(2.5), 2.5));
+        assert!(close(AreaUnit::Acre.to_hectares(10.0), 4.046_856_422_4));
+        assert!(close(AreaUnit::Acre.from_hectares(4.046_856_422_4), 10.0));
+        assert!(close(AreaUnit::SquareMetre.from_hectares(1.0), 10_000.0));
+        assert!(close(AreaUnit::Plot.to_hectares(1.0), 0.046_451_52));
+
+        // A bare number sent in comes back unchanged when read in acres
+        let sent = area("10").unwrap();
+        assert!(close(sent.to_hectares(), 4.046_856_422_4));
+        assert!(close(sent.convert(AreaUnit::Acre).value, 10.0));
+        assert_eq!(sent.convert(AreaUnit::Hectare).unit, AreaUnit::Hectare);
+    }
+
+    #[test]
+    fn kyc_status_transitions() {
+        use KycStatus::*;
+        let statuses = [Unverified, Pending, Verified, Rejected];
+        let allowed = [(Unverified, Pending), (Rejected, Pending), (Pending, Verified), (Pending, Rejected)];
+
+        for from in statuses {
+            for to in statuses {
+                assert_eq!(
+                    from.can_become(to),
+                    allowed.contains(&(from, to)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+}